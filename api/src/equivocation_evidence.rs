@@ -0,0 +1,14 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_consensus_types::equivocation_evidence::{
+    EquivocationEvidence, EQUIVOCATION_EVIDENCE, MAX_EVIDENCE_PER_RESPONSE,
+};
+use poem::{handler, web::Json};
+
+/// Returns the most recent evidence of equivocating validators collected by consensus on this
+/// node, ordered by epoch and round.
+#[handler]
+pub fn equivocation_evidence_poem() -> Json<Vec<EquivocationEvidence>> {
+    Json(EQUIVOCATION_EVIDENCE.get_latest(MAX_EVIDENCE_PER_RESPONSE))
+}
@@ -4,9 +4,9 @@
 
 use crate::{
     accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
-    context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
-    view_function::ViewFunctionApi,
+    context::Context, equivocation_evidence, error_converter::convert_error, events::EventsApi,
+    index::IndexApi, log::middleware_log, set_failpoints, state::StateApi,
+    transactions::TransactionsApi, view_function::ViewFunctionApi,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::X_APTOS_CLIENT;
@@ -189,6 +189,10 @@ pub fn attach_poem_to_runtime(
                     .at(
                         "/set_failpoint",
                         poem::get(set_failpoints::set_failpoint_poem).data(context.clone()),
+                    )
+                    .at(
+                        "/equivocation_evidence",
+                        poem::get(equivocation_evidence::equivocation_evidence_poem),
                     ),
             )
             .with(cors)
@@ -2,6 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{format_err, Result};
+use aptos_block_executor::{
+    txn_commit_hook::NoOpTransactionCommitHook, txn_last_input_output::TxnConflictStats,
+};
 use aptos_gas_meter::{StandardGasAlgebra, StandardGasMeter};
 use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
 use aptos_gas_schedule::{MiscGasParameters, NativeGasParameters, LATEST_GAS_FEATURE_VERSION};
@@ -13,18 +16,21 @@ use aptos_types::{
     account_address::AccountAddress,
     chain_id::ChainId,
     on_chain_config::{Features, OnChainConfig, TimedFeatures},
+    state_store::state_key::StateKey,
     transaction::{
         SignedTransaction, Transaction, TransactionInfo, TransactionOutput, TransactionPayload,
         Version,
     },
-    vm_status::VMStatus,
+    vm_status::{StatusCode, VMStatus},
 };
 use aptos_validator_interface::{
     AptosValidatorInterface, DBDebuggerInterface, DebuggerStateView, RestDebuggerInterface,
 };
 use aptos_vm::{
+    aptos_vm::RAYON_EXEC_POOL,
+    block_executor::{AptosTransactionOutput, BlockAptosVM},
     data_cache::StorageAdapter,
-    move_vm_ext::{MoveVmExt, SessionExt, SessionId},
+    move_vm_ext::{ExecutionTrace, ExecutionTracer, MoveVmExt, SessionExt, SessionId},
     AptosVM, VMExecutor,
 };
 use aptos_vm_logging::log_schema::AdapterLogSchema;
@@ -61,6 +67,29 @@ impl AptosDebugger {
             .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))
     }
 
+    /// Executes the transactions like execute_transactions_at_version, also returning the
+    /// conflict statistics of each transaction in the parallel execution.
+    pub fn execute_transactions_at_version_with_conflict_stats(
+        &self,
+        version: Version,
+        txns: Vec<Transaction>,
+    ) -> Result<Vec<(TransactionOutput, TxnConflictStats<StateKey>)>> {
+        let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let (outputs, conflict_stats) = BlockAptosVM::execute_block_with_conflict_stats::<
+            _,
+            NoOpTransactionCommitHook<AptosTransactionOutput, VMStatus>,
+        >(
+            Arc::clone(&RAYON_EXEC_POOL),
+            txns,
+            &state_view,
+            AptosVM::get_concurrency_level(),
+            None,
+            None,
+        )
+        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+        Ok(outputs.into_iter().zip(conflict_stats).collect())
+    }
+
     pub fn execute_transaction_at_version_with_gas_profiler(
         &self,
         version: Version,
@@ -103,6 +132,53 @@ impl AptosDebugger {
         Ok((status, output, gas_profiler.finish()))
     }
 
+    pub fn execute_transaction_at_version_with_tracer(
+        &self,
+        version: Version,
+        txn: SignedTransaction,
+    ) -> Result<(VMStatus, VMOutput, ExecutionTrace)> {
+        let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let txn = txn
+            .check_signature()
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+
+        let (status, output, tracer) = AptosVM::execute_user_transaction_with_custom_gas_meter(
+            &state_view,
+            &txn,
+            &log_context,
+            |gas_feature_version, gas_params, storage_gas_params, balance| {
+                let gas_meter =
+                    MemoryTrackedGasMeter::new(StandardGasMeter::new(StandardGasAlgebra::new(
+                        gas_feature_version,
+                        gas_params,
+                        storage_gas_params,
+                        balance,
+                    )));
+                let tracer = match txn.payload() {
+                    TransactionPayload::Script(_) => ExecutionTracer::new_script(gas_meter),
+                    TransactionPayload::EntryFunction(entry_func) => ExecutionTracer::new_function(
+                        gas_meter,
+                        entry_func.module(),
+                        entry_func.function().to_string(),
+                        entry_func.ty_args().to_vec(),
+                        entry_func.args().to_vec(),
+                    ),
+                    TransactionPayload::ModuleBundle(..) => unreachable!("not supported"),
+                    TransactionPayload::Multisig(..) => {
+                        return Err(VMStatus::error(
+                            StatusCode::FEATURE_UNDER_GATING,
+                            Some("Tracing multisig transactions is not supported yet".to_string()),
+                        ))
+                    },
+                };
+                Ok(tracer)
+            },
+        )?;
+
+        Ok((status, output, tracer.finish()))
+    }
+
     pub async fn execute_past_transactions(
         &self,
         mut begin: Version,
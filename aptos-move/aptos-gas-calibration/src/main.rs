@@ -5,14 +5,16 @@ mod math;
 mod math_interface;
 mod measurements;
 mod measurements_helpers;
+mod proposal;
 mod solve;
 use aptos_abstract_gas_usage::{aggregate_terms, expand_terms};
 use aptos_gas_algebra::DynamicExpression;
 use clap::Parser;
 use math_interface::{convert_to_matrix_format, total_num_of_cols, total_num_rows};
 use measurements::compile_and_run;
+use proposal::{propose_gas_schedule, report_gas_schedule_diff, write_gas_schedule_proposal};
 use solve::{build_coefficient_matrix, build_constant_matrix, least_squares};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, path::PathBuf};
 
 /// Automated Gas Calibration to calibrate Move bytecode and Native Functions
 #[derive(Parser, Debug)]
@@ -24,6 +26,20 @@ struct Args {
     /// Number of iterations to run each Calibration Function
     #[clap(short, long, default_value_t = 20)]
     iterations: u64,
+
+    /// Directory to write a governance proposal script updating the gas schedule with the
+    /// calibrated gas parameters to. No proposal is generated if not provided.
+    #[clap(long)]
+    proposal_dir: Option<PathBuf>,
+
+    /// Internal gas per microsecond of running time to convert the calibrated gas parameters
+    /// with. Defaults to the median rate of the calibrated parameters in the current schedule.
+    #[clap(long)]
+    internal_gas_per_microsecond: Option<f64>,
+
+    /// Generate the governance proposal script for testnet
+    #[clap(long)]
+    testnet: bool,
 }
 
 fn main() {
@@ -71,10 +87,20 @@ fn main() {
     let mut const_matrix = build_constant_matrix(measurements.regular_meter, nrows, vec_col);
 
     // Solve the system of linear equations
-    least_squares(
+    let costs = least_squares(
         mappings,
         &mut coeff_matrix,
         &mut const_matrix,
         measurements.equation_names,
     );
+
+    // Propose the gas schedule update
+    if let Some(proposal_dir) = &args.proposal_dir {
+        let costs = costs.expect("Failed: gas parameters could not be calibrated.");
+        let proposal = propose_gas_schedule(&costs, args.internal_gas_per_microsecond)
+            .expect("Failed: should propose a gas schedule.");
+        report_gas_schedule_diff(&proposal);
+        write_gas_schedule_proposal(&proposal, proposal_dir, args.testnet)
+            .expect("Failed: should write the governance proposal.");
+    }
 }
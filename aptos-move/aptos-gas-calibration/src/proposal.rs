@@ -0,0 +1,355 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, Result};
+use aptos_gas_schedule::{
+    AptosGasParameters, InitialGasSchedule, OnChainGasScheduleKeys, ToOnChainGasSchedule,
+    LATEST_GAS_FEATURE_VERSION,
+};
+use aptos_release_builder::components::gas::generate_gas_upgrade_proposal;
+use aptos_types::on_chain_config::GasScheduleV2;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// a gas parameter whose value in the current gas schedule changes in the proposal
+pub struct GasParameterUpdate {
+    pub key: String,
+    pub current: u64,
+    pub proposed: u64,
+}
+
+/// the gas schedule proposed from the calibrated costs, along with the
+/// parameters that changed and the ones that could not be calibrated
+pub struct ProposedGasSchedule {
+    pub gas_schedule: GasScheduleV2,
+    pub updates: Vec<GasParameterUpdate>,
+    pub skipped: Vec<String>,
+    pub internal_gas_per_microsecond: f64,
+}
+
+/// propose a new gas schedule by converting the calibrated costs into internal
+/// gas and replacing the respective entries of the current gas schedule
+///
+/// ### Arguments
+///
+/// * `costs` - Calibrated costs of the gas parameters (microseconds per unit)
+/// * `internal_gas_per_microsecond` - Conversion rate from running time to internal gas.
+///   If not provided, the median rate of the current gas schedule is used, so that the
+///   proposal keeps the overall level of the gas costs and only rebalances them.
+pub fn propose_gas_schedule(
+    costs: &BTreeMap<String, f64>,
+    internal_gas_per_microsecond: Option<f64>,
+) -> Result<ProposedGasSchedule> {
+    let feature_version = LATEST_GAS_FEATURE_VERSION;
+    let mut entries = AptosGasParameters::initial().to_on_chain_gas_schedule(feature_version);
+    let keys: BTreeMap<&str, String> =
+        AptosGasParameters::on_chain_gas_schedule_keys(feature_version)
+            .into_iter()
+            .collect();
+    let current: BTreeMap<String, u64> = entries.iter().cloned().collect();
+
+    // Non-positive costs are artifacts of the least squares solution (e.g. parameters that
+    // are too cheap to be measured), and parameters outside the gas schedule cannot be updated.
+    let mut calibrated = vec![];
+    let mut skipped = vec![];
+    for (name, cost) in costs {
+        match keys.get(name.as_str()) {
+            Some(key) if *cost > 0.0 => calibrated.push((key.clone(), *cost)),
+            _ => skipped.push(name.clone()),
+        }
+    }
+    if calibrated.is_empty() {
+        bail!("No gas parameter of the gas schedule was calibrated");
+    }
+
+    let internal_gas_per_microsecond = match internal_gas_per_microsecond {
+        Some(rate) => rate,
+        None => median_rate(&calibrated, &current)
+            .ok_or_else(|| anyhow!("No calibrated gas parameter has a non-zero value"))?,
+    };
+
+    let proposed: BTreeMap<String, u64> = calibrated
+        .into_iter()
+        .map(|(key, cost)| {
+            let value = (cost * internal_gas_per_microsecond).round().max(1.0) as u64;
+            (key, value)
+        })
+        .collect();
+
+    let mut updates = vec![];
+    for (key, value) in entries.iter_mut() {
+        if let Some(proposed) = proposed.get(key) {
+            if proposed != value {
+                updates.push(GasParameterUpdate {
+                    key: key.clone(),
+                    current: *value,
+                    proposed: *proposed,
+                });
+                *value = *proposed;
+            }
+        }
+    }
+
+    Ok(ProposedGasSchedule {
+        gas_schedule: GasScheduleV2 {
+            feature_version,
+            entries,
+        },
+        updates,
+        skipped,
+        internal_gas_per_microsecond,
+    })
+}
+
+/// median of the internal gas per microsecond implied by the current values
+/// of the calibrated gas parameters
+///
+/// ### Arguments
+///
+/// * `calibrated` - Calibrated costs, by on-chain key
+/// * `current` - Current gas schedule
+fn median_rate(calibrated: &[(String, f64)], current: &BTreeMap<String, u64>) -> Option<f64> {
+    let mut rates: Vec<f64> = calibrated
+        .iter()
+        .filter_map(|(key, cost)| match current.get(key) {
+            Some(value) if *value > 0 => Some(*value as f64 / cost),
+            _ => None,
+        })
+        .collect();
+    if rates.is_empty() {
+        return None;
+    }
+    rates.sort_by(|a, b| a.total_cmp(b));
+    Some(rates[rates.len() / 2])
+}
+
+/// display the differences between the current and proposed gas schedule
+///
+/// ### Arguments
+///
+/// * `proposal` - The proposed gas schedule
+pub fn report_gas_schedule_diff(proposal: &ProposedGasSchedule) {
+    println!(
+        "\nProposed gas schedule (at {:.3} internal gas per µs):\n",
+        proposal.internal_gas_per_microsecond
+    );
+    if proposal.updates.is_empty() {
+        println!("- no changes to the current gas schedule\n");
+    }
+    for update in &proposal.updates {
+        println!(
+            "- {} | Current {} vs. Proposed {} | Change {}\n",
+            update.key,
+            update.current,
+            update.proposed,
+            format_args!(
+                "{:+.1}%",
+                (update.proposed as f64 - update.current as f64) / update.current.max(1) as f64
+                    * 100.0
+            )
+        );
+    }
+
+    if !proposal.skipped.is_empty() {
+        println!("\nGas parameters that were not updated (non-positive cost or not on-chain):\n");
+        for name in &proposal.skipped {
+            println!("- gas parameter: {}\n", name);
+        }
+    }
+}
+
+/// write the governance proposal scripts updating the gas schedule
+///
+/// ### Arguments
+///
+/// * `proposal` - The proposed gas schedule
+/// * `output_dir` - Directory to write the scripts to
+/// * `is_testnet` - Whether to generate the scripts for testnet
+pub fn write_gas_schedule_proposal(
+    proposal: &ProposedGasSchedule,
+    output_dir: &Path,
+    is_testnet: bool,
+) -> Result<()> {
+    fs::create_dir_all(output_dir)?;
+    for (name, script) in generate_gas_upgrade_proposal(&proposal.gas_schedule, is_testnet, vec![])?
+    {
+        let path = output_dir.join(format!("{}.move", name));
+        fs::write(&path, script)?;
+        println!("Governance proposal script written to {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    /// the first `n` gas parameters of the current gas schedule with a value above 1,
+    /// as (name, on-chain key, current value)
+    fn gas_parameters(n: usize) -> Vec<(String, String, u64)> {
+        let current: BTreeMap<String, u64> = AptosGasParameters::initial()
+            .to_on_chain_gas_schedule(LATEST_GAS_FEATURE_VERSION)
+            .into_iter()
+            .collect();
+        let parameters: Vec<_> =
+            AptosGasParameters::on_chain_gas_schedule_keys(LATEST_GAS_FEATURE_VERSION)
+                .into_iter()
+                .filter_map(|(name, key)| {
+                    let value = current[&key];
+                    (value > 1).then(|| (name.to_string(), key, value))
+                })
+                .take(n)
+                .collect();
+        assert_eq!(parameters.len(), n);
+        parameters
+    }
+
+    fn value_of(proposal: &ProposedGasSchedule, key: &str) -> u64 {
+        proposal
+            .gas_schedule
+            .entries
+            .iter()
+            .find(|(entry, _)| entry == key)
+            .map(|(_, value)| *value)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_median_rate() {
+        let current: BTreeMap<String, u64> = [("a", 10), ("b", 10), ("c", 10), ("zero", 0)]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        let calibrated = |costs: &[(&str, f64)]| -> Vec<(String, f64)> {
+            costs
+                .iter()
+                .map(|(key, cost)| (key.to_string(), *cost))
+                .collect()
+        };
+
+        // rates of 10, 5 and 2.5, parameters with a zero or no current value are ignored
+        let rate = median_rate(
+            &calibrated(&[
+                ("a", 1.0),
+                ("b", 2.0),
+                ("c", 4.0),
+                ("zero", 1.0),
+                ("missing", 1.0),
+            ]),
+            &current,
+        )
+        .unwrap();
+        assert!(approx_eq!(f64, rate, 5.0, ulps = 2));
+
+        // the upper one of the two middle rates
+        let rate = median_rate(&calibrated(&[("a", 1.0), ("b", 2.0)]), &current).unwrap();
+        assert!(approx_eq!(f64, rate, 10.0, ulps = 2));
+
+        assert!(median_rate(&calibrated(&[("zero", 1.0), ("missing", 1.0)]), &current).is_none());
+    }
+
+    #[test]
+    fn test_propose_gas_schedule_with_rate() {
+        let parameters = gas_parameters(3);
+        let costs: BTreeMap<String, f64> = parameters
+            .iter()
+            .map(|(name, _, _)| name.clone())
+            .zip([2.4, 0.26, 0.01])
+            .collect();
+
+        let proposal = propose_gas_schedule(&costs, Some(10.0)).unwrap();
+        assert!(approx_eq!(
+            f64,
+            proposal.internal_gas_per_microsecond,
+            10.0,
+            ulps = 2
+        ));
+        assert!(proposal.skipped.is_empty());
+        assert_eq!(
+            proposal.gas_schedule.feature_version,
+            LATEST_GAS_FEATURE_VERSION
+        );
+
+        // rounded to the nearest integer, and at least 1
+        let expected = [24, 3, 1];
+        for ((_, key, _), expected) in parameters.iter().zip(expected) {
+            assert_eq!(value_of(&proposal, key), expected);
+        }
+
+        // only the parameters whose value changed are reported
+        let updated: Vec<_> = proposal
+            .updates
+            .iter()
+            .map(|update| (update.key.clone(), update.current, update.proposed))
+            .collect();
+        let expected_updates: Vec<_> = parameters
+            .iter()
+            .zip(expected)
+            .filter(|((_, _, current), proposed)| current != proposed)
+            .map(|((_, key, current), proposed)| (key.clone(), *current, proposed))
+            .collect();
+        assert_eq!(updated, expected_updates);
+
+        // the rest of the gas schedule is unchanged
+        let current =
+            AptosGasParameters::initial().to_on_chain_gas_schedule(LATEST_GAS_FEATURE_VERSION);
+        assert_eq!(proposal.gas_schedule.entries.len(), current.len());
+        for (key, value) in current {
+            if !parameters
+                .iter()
+                .any(|(_, calibrated, _)| *calibrated == key)
+            {
+                assert_eq!(value_of(&proposal, &key), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_propose_gas_schedule_with_median_rate() {
+        // costs implying the same rate of 4 for every parameter keep the gas schedule
+        let parameters = gas_parameters(3);
+        let costs: BTreeMap<String, f64> = parameters
+            .iter()
+            .map(|(name, _, value)| (name.clone(), *value as f64 / 4.0))
+            .collect();
+
+        let proposal = propose_gas_schedule(&costs, None).unwrap();
+        assert!(approx_eq!(
+            f64,
+            proposal.internal_gas_per_microsecond,
+            4.0,
+            ulps = 2
+        ));
+        assert!(proposal.updates.is_empty());
+        for (_, key, value) in &parameters {
+            assert_eq!(value_of(&proposal, key), *value);
+        }
+    }
+
+    #[test]
+    fn test_propose_gas_schedule_skips_parameters() {
+        let parameters = gas_parameters(3);
+        let mut costs: BTreeMap<String, f64> = parameters
+            .iter()
+            .map(|(name, _, _)| name.clone())
+            .zip([1.0, 0.0, -1.0])
+            .collect();
+        costs.insert("NOT_ON_CHAIN".to_string(), 1.0);
+
+        let proposal = propose_gas_schedule(&costs, Some(100.0)).unwrap();
+        let mut expected_skipped = vec![
+            parameters[1].0.clone(),
+            parameters[2].0.clone(),
+            "NOT_ON_CHAIN".to_string(),
+        ];
+        expected_skipped.sort();
+        assert_eq!(proposal.skipped, expected_skipped);
+        assert_eq!(value_of(&proposal, &parameters[0].1), 100);
+        assert_eq!(value_of(&proposal, &parameters[1].1), parameters[1].2);
+        assert_eq!(value_of(&proposal, &parameters[2].1), parameters[2].2);
+
+        // nothing left to calibrate
+        costs.remove(&parameters[0].0);
+        assert!(propose_gas_schedule(&costs, Some(100.0)).is_err());
+    }
+}
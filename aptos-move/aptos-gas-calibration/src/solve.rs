@@ -42,7 +42,8 @@ pub fn build_constant_matrix(input: Vec<u128>, nrows: usize, ncols: usize) -> DM
     const_matrix
 }
 
-/// compute the least squares solution
+/// compute the least squares solution, returning the value of each gas parameter
+/// (in microseconds per unit) if the system could be solved
 ///
 /// ### Arguments
 ///
@@ -54,7 +55,7 @@ pub fn least_squares(
     coeff_matrix: &mut DMatrix<f64>,
     const_matrix: &mut DMatrix<f64>,
     equation_names: Vec<String>,
-) {
+) -> Option<BTreeMap<String, f64>> {
     let lss = compute_least_square_solutions(coeff_matrix, const_matrix);
     if let Ok(answer) = lss {
         let mut x_hat = answer;
@@ -79,8 +80,16 @@ pub fn least_squares(
         report_computed_times(&equation_names, &computed_time_and_outliers);
 
         report_outliers(&equation_names, &computed_time_and_outliers);
+
+        Some(
+            keys.into_iter()
+                .enumerate()
+                .map(|(i, key)| (key, x_hat[(i, 0)]))
+                .collect(),
+        )
     } else {
         report_undetermined_gas_params(input, coeff_matrix, const_matrix);
+        None
     }
 }
 
@@ -60,6 +60,23 @@ macro_rules! define_gas_parameters {
             }
         }
 
+        impl $crate::traits::OnChainGasScheduleKeys for $params_name {
+            #[allow(unused)]
+            fn on_chain_gas_schedule_keys(feature_version: u64) -> Vec<(&'static str, String)> {
+                let mut output = vec![];
+
+                $(
+                    if let Some(key) = $crate::gas_schedule::macros::define_gas_parameters_extract_key_at_version!($key_bindings, feature_version) {
+                        paste::paste! {
+                            output.push((stringify!([<$name:upper>]), format!("{}.{}", $prefix, key)))
+                        }
+                    }
+                )*
+
+                output
+            }
+        }
+
         impl $params_name {
             pub fn zeros() -> Self {
                 Self {
@@ -172,6 +189,24 @@ macro_rules! define_gas_parameters {
                 )*
             }
         }
+
+        #[test]
+        fn on_chain_gas_schedule_keys_should_match_entries() {
+            use $crate::traits::{OnChainGasScheduleKeys, ToOnChainGasSchedule};
+
+            for ver in 0..=$crate::LATEST_GAS_FEATURE_VERSION {
+                let keys = $params_name::on_chain_gas_schedule_keys(ver)
+                    .into_iter()
+                    .map(|(_, key)| key)
+                    .collect::<Vec<_>>();
+                let entries = $params_name::zeros()
+                    .to_on_chain_gas_schedule(ver)
+                    .into_iter()
+                    .map(|(key, _)| key)
+                    .collect::<Vec<_>>();
+                assert_eq!(keys, entries);
+            }
+        }
     };
 }
 
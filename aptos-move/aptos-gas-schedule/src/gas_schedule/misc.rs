@@ -6,7 +6,9 @@
 
 use crate::{
     gas_schedule::VMGasParameters,
-    traits::{FromOnChainGasSchedule, InitialGasSchedule, ToOnChainGasSchedule},
+    traits::{
+        FromOnChainGasSchedule, InitialGasSchedule, OnChainGasScheduleKeys, ToOnChainGasSchedule,
+    },
 };
 use aptos_gas_algebra::{AbstractValueSize, AbstractValueSizePerArg};
 use move_core_types::{account_address::AccountAddress, gas_algebra::NumArgs, u256::U256};
@@ -601,6 +603,12 @@ impl ToOnChainGasSchedule for MiscGasParameters {
     }
 }
 
+impl OnChainGasScheduleKeys for MiscGasParameters {
+    fn on_chain_gas_schedule_keys(feature_version: u64) -> Vec<(&'static str, String)> {
+        AbstractValueSizeGasParameters::on_chain_gas_schedule_keys(feature_version)
+    }
+}
+
 impl MiscGasParameters {
     pub fn zeros() -> Self {
         Self {
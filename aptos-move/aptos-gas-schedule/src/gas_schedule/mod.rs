@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::traits::{FromOnChainGasSchedule, InitialGasSchedule, ToOnChainGasSchedule};
+use crate::traits::{
+    FromOnChainGasSchedule, InitialGasSchedule, OnChainGasScheduleKeys, ToOnChainGasSchedule,
+};
 use std::collections::BTreeMap;
 
 mod aptos_framework;
@@ -64,6 +66,16 @@ impl ToOnChainGasSchedule for AptosGasParameters {
     }
 }
 
+impl OnChainGasScheduleKeys for AptosGasParameters {
+    fn on_chain_gas_schedule_keys(feature_version: u64) -> Vec<(&'static str, String)> {
+        let mut keys = VMGasParameters::on_chain_gas_schedule_keys(feature_version);
+        keys.extend(NativeGasParameters::on_chain_gas_schedule_keys(
+            feature_version,
+        ));
+        keys
+    }
+}
+
 impl AptosGasParameters {
     pub fn zeros() -> Self {
         Self {
@@ -117,6 +129,19 @@ impl ToOnChainGasSchedule for VMGasParameters {
     }
 }
 
+impl OnChainGasScheduleKeys for VMGasParameters {
+    fn on_chain_gas_schedule_keys(feature_version: u64) -> Vec<(&'static str, String)> {
+        let mut keys = InstructionGasParameters::on_chain_gas_schedule_keys(feature_version);
+        keys.extend(TransactionGasParameters::on_chain_gas_schedule_keys(
+            feature_version,
+        ));
+        keys.extend(MiscGasParameters::on_chain_gas_schedule_keys(
+            feature_version,
+        ));
+        keys
+    }
+}
+
 impl VMGasParameters {
     pub fn zeros() -> Self {
         Self {
@@ -178,6 +203,19 @@ impl ToOnChainGasSchedule for NativeGasParameters {
     }
 }
 
+impl OnChainGasScheduleKeys for NativeGasParameters {
+    fn on_chain_gas_schedule_keys(feature_version: u64) -> Vec<(&'static str, String)> {
+        let mut keys = MoveStdlibGasParameters::on_chain_gas_schedule_keys(feature_version);
+        keys.extend(TableGasParameters::on_chain_gas_schedule_keys(
+            feature_version,
+        ));
+        keys.extend(AptosFrameworkGasParameters::on_chain_gas_schedule_keys(
+            feature_version,
+        ));
+        keys
+    }
+}
+
 impl NativeGasParameters {
     pub fn zeros() -> Self {
         Self {
@@ -31,5 +31,7 @@ mod traits;
 mod ver;
 
 pub use gas_schedule::*;
-pub use traits::{FromOnChainGasSchedule, InitialGasSchedule, ToOnChainGasSchedule};
+pub use traits::{
+    FromOnChainGasSchedule, InitialGasSchedule, OnChainGasScheduleKeys, ToOnChainGasSchedule,
+};
 pub use ver::LATEST_GAS_FEATURE_VERSION;
@@ -22,6 +22,14 @@ pub trait ToOnChainGasSchedule {
     fn to_on_chain_gas_schedule(&self, feature_version: u64) -> Vec<(String, u64)>;
 }
 
+/// A trait for mapping the gas parameters, by the names they have in abstract gas expressions
+/// (e.g. `LD_U8`), to their keys in the on-chain gas schedule (e.g. `instr.ld_u8`).
+pub trait OnChainGasScheduleKeys {
+    /// Returns the name and key of each parameter that is part of the on-chain gas schedule at
+    /// the given feature version.
+    fn on_chain_gas_schedule_keys(feature_version: u64) -> Vec<(&'static str, String)>;
+}
+
 /// A trait for defining an initial value to be used in the genesis.
 pub trait InitialGasSchedule: Sized {
     /// Returns the initial value of this type, which is used in the genesis.
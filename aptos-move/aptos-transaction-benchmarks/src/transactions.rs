@@ -254,7 +254,7 @@ where
             (None, None)
         } else {
             let client =
-                LocalExecutorService::setup_local_executor_shards(num_executor_shards, None);
+                LocalExecutorService::setup_local_executor_shards(num_executor_shards, None, false);
             let parallel_block_executor = Arc::new(ShardedBlockExecutor::new(client));
             (
                 Some(parallel_block_executor),
@@ -358,6 +358,7 @@ where
             1,
             maybe_block_gas_limit,
             None,
+            None,
         )
         .expect("VM should not fail to start");
         let exec_time = timer.elapsed().as_millis();
@@ -401,6 +402,7 @@ where
                 concurrency_level_per_shard,
                 maybe_block_gas_limit,
                 None,
+                None,
             )
             .expect("VM should not fail to start")
         };
@@ -87,7 +87,9 @@ static NUM_EXECUTION_SHARD: OnceCell<usize> = OnceCell::new();
 static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
+static PREFETCH_STATE_KEYS: OnceCell<bool> = OnceCell::new();
 static TIMED_FEATURE_OVERRIDE: OnceCell<TimedFeatureOverride> = OnceCell::new();
+static USE_ESTIMATED_WRITE_SETS: OnceCell<bool> = OnceCell::new();
 
 pub static RAYON_EXEC_POOL: Lazy<Arc<rayon::ThreadPool>> = Lazy::new(|| {
     Arc::new(
@@ -162,6 +164,21 @@ impl AptosVM {
         }
     }
 
+    /// Sets whether the sharded block executor passes the write-sets estimated by the block
+    /// partitioner to the block executor, to delay likely-conflicting transactions.
+    pub fn set_use_estimated_write_sets_once(enable: bool) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        USE_ESTIMATED_WRITE_SETS.set(enable).ok();
+    }
+
+    /// Whether estimated write-sets are used, false by default.
+    pub fn get_use_estimated_write_sets() -> bool {
+        match USE_ESTIMATED_WRITE_SETS.get() {
+            Some(enable) => *enable,
+            None => false,
+        }
+    }
+
     /// Sets runtime config when invoked the first time.
     pub fn set_paranoid_type_checks(enable: bool) {
         // Only the first call succeeds, due to OnceCell semantics.
@@ -216,6 +233,21 @@ impl AptosVM {
         }
     }
 
+    /// Sets whether the executor prefetches the state keys that a block is likely to read
+    /// (see state_key_prefetch) before executing it, when invoked the first time.
+    pub fn set_prefetch_state_keys_once(enable: bool) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        PREFETCH_STATE_KEYS.set(enable).ok();
+    }
+
+    /// Get whether state keys are prefetched if already set, otherwise return default false.
+    pub fn get_prefetch_state_keys() -> bool {
+        match PREFETCH_STATE_KEYS.get() {
+            Some(enable) => *enable,
+            None => false,
+        }
+    }
+
     pub fn internals(&self) -> AptosVMInternals {
         AptosVMInternals::new(&self.0)
     }
@@ -1503,6 +1535,7 @@ impl VMExecutor for AptosVM {
             Self::get_concurrency_level(),
             maybe_block_gas_limit,
             None,
+            None,
         );
         if ret.is_ok() {
             // Record the histogram count for transactions per block.
@@ -22,6 +22,7 @@ use aptos_block_executor::{
         TransactionOutput as BlockExecutorTransactionOutput,
     },
     txn_commit_hook::TransactionCommitHook,
+    txn_last_input_output::TxnConflictStats,
 };
 use aptos_infallible::Mutex;
 use aptos_state_view::{StateView, StateViewId};
@@ -165,6 +166,8 @@ impl BlockAptosVM {
             .collect()
     }
 
+    /// Executes the block. The estimated write-sets, if provided, must have one entry per
+    /// transaction, and are used to delay the transactions likely to conflict with earlier ones.
     pub fn execute_block<
         S: StateView + Sync,
         L: TransactionCommitHook<Output = AptosTransactionOutput>,
@@ -175,7 +178,71 @@ impl BlockAptosVM {
         concurrency_level: usize,
         maybe_block_gas_limit: Option<u64>,
         transaction_commit_listener: Option<L>,
+        maybe_estimated_write_sets: Option<Vec<Vec<StateKey>>>,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        Self::execute_block_impl(
+            executor_thread_pool,
+            transactions,
+            state_view,
+            concurrency_level,
+            maybe_block_gas_limit,
+            transaction_commit_listener,
+            maybe_estimated_write_sets,
+            false,
+        )
+        .map(|(output_vec, _)| output_vec)
+    }
+
+    /// Executes the block like execute_block, and also returns the conflict statistics of each
+    /// transaction, i.e. how many times it was executed, and due to which conflicting reads.
+    pub fn execute_block_with_conflict_stats<
+        S: StateView + Sync,
+        L: TransactionCommitHook<Output = AptosTransactionOutput>,
+    >(
+        executor_thread_pool: Arc<ThreadPool>,
+        transactions: Vec<Transaction>,
+        state_view: &S,
+        concurrency_level: usize,
+        maybe_block_gas_limit: Option<u64>,
+        transaction_commit_listener: Option<L>,
+    ) -> Result<(Vec<TransactionOutput>, Vec<TxnConflictStats<StateKey>>), VMStatus> {
+        Self::execute_block_impl(
+            executor_thread_pool,
+            transactions,
+            state_view,
+            concurrency_level,
+            maybe_block_gas_limit,
+            transaction_commit_listener,
+            None,
+            true,
+        )
+        .map(|(output_vec, maybe_conflict_stats)| {
+            (
+                output_vec,
+                maybe_conflict_stats.expect("Conflict stats must be collected"),
+            )
+        })
+    }
+
+    fn execute_block_impl<
+        S: StateView + Sync,
+        L: TransactionCommitHook<Output = AptosTransactionOutput>,
+    >(
+        executor_thread_pool: Arc<ThreadPool>,
+        transactions: Vec<Transaction>,
+        state_view: &S,
+        concurrency_level: usize,
+        maybe_block_gas_limit: Option<u64>,
+        transaction_commit_listener: Option<L>,
+        maybe_estimated_write_sets: Option<Vec<Vec<StateKey>>>,
+        collect_conflict_stats: bool,
+    ) -> Result<
+        (
+            Vec<TransactionOutput>,
+            Option<Vec<TxnConflictStats<StateKey>>>,
+        ),
+        VMStatus,
+    > {
         let _timer = BLOCK_EXECUTOR_EXECUTE_BLOCK_SECONDS.start_timer();
         // Verify the signatures of all the transactions in parallel.
         // This is time consuming so don't wait and do the checking
@@ -195,7 +262,7 @@ impl BlockAptosVM {
         }
 
         BLOCK_EXECUTOR_CONCURRENCY.set(concurrency_level as i64);
-        let executor = BlockExecutor::<
+        let mut executor = BlockExecutor::<
             PreprocessedTransaction,
             AptosExecutorTask<S>,
             S,
@@ -207,8 +274,16 @@ impl BlockAptosVM {
             maybe_block_gas_limit,
             transaction_commit_listener,
         );
+        if collect_conflict_stats {
+            executor = executor.with_conflict_stats();
+        }
 
-        let ret = executor.execute_block(state_view, signature_verified_block, state_view);
+        let ret = executor.execute_block(
+            state_view,
+            signature_verified_block,
+            state_view,
+            maybe_estimated_write_sets,
+        );
         match ret {
             Ok(outputs) => {
                 let output_vec: Vec<TransactionOutput> = outputs
@@ -225,7 +300,7 @@ impl BlockAptosVM {
                     flush_speculative_logs(pos);
                 }
 
-                Ok(output_vec)
+                Ok((output_vec, executor.take_conflict_stats()))
             },
             Err(Error::ModulePathReadWrite) => {
                 unreachable!("[Execution]: Must be handled by sequential fallback")
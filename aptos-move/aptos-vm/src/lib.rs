@@ -117,6 +117,7 @@ mod errors;
 pub mod move_vm_ext;
 pub mod natives;
 pub mod sharded_block_executor;
+pub mod state_key_prefetch;
 pub mod system_module_names;
 pub mod transaction_metadata;
 mod transaction_validation;
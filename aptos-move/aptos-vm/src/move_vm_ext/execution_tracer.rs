@@ -0,0 +1,649 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An opt-in tracer of Move execution in sessions, implemented as a gas meter adapter so that it
+//! can be passed wherever a session takes a gas meter (e.g. via
+//! AptosVM::execute_user_transaction_with_custom_gas_meter).
+//!
+//! The trace is a tree of call frames, each recording its arguments, the resource accesses and
+//! events it performed, and the calls it made. Frames that did not return are the ones on the
+//! call stack when execution aborted. Note that return values are only observable (and recorded)
+//! for native functions.
+
+use aptos_gas_algebra::{Fee, FeePerGasUnit, InternalGas, NumArgs, NumBytes};
+use aptos_gas_meter::AptosGasMeter;
+use aptos_types::{
+    contract_event::ContractEvent, state_store::state_key::StateKey, write_set::WriteOp,
+};
+use move_binary_format::{
+    errors::{PartialVMResult, VMResult},
+    file_format::CodeOffset,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, TypeTag},
+    u256::U256,
+};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView, ValueVisitor},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TracedFrame {
+    /// The module of the function, or None for a script.
+    pub module_id: Option<String>,
+    pub function: String,
+    pub ty_args: Vec<String>,
+    pub args: Vec<String>,
+    /// Return values, only recorded for native functions.
+    pub return_values: Option<Vec<String>>,
+    /// Whether the function returned, i.e. false for the frames on the call stack of an abort.
+    pub returned: bool,
+    pub events: Vec<TraceEvent>,
+}
+
+impl TracedFrame {
+    fn new(
+        module_id: Option<&ModuleId>,
+        function: String,
+        ty_args: Vec<TypeTag>,
+        args: Vec<String>,
+    ) -> Self {
+        Self {
+            module_id: module_id.map(|module_id| module_id.short_str_lossless()),
+            function,
+            ty_args: ty_args.iter().map(|ty| ty.to_string()).collect(),
+            args,
+            return_values: None,
+            returned: false,
+            events: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TraceEvent {
+    Call(TracedFrame),
+    /// A resource loaded from storage (at most once per resource in a transaction).
+    LoadResource {
+        address: AccountAddress,
+        ty: String,
+        value: Option<String>,
+    },
+    BorrowGlobal {
+        ty: String,
+        is_mut: bool,
+        success: bool,
+    },
+    Exists {
+        ty: String,
+        exists: bool,
+    },
+    MoveFrom {
+        ty: String,
+        value: Option<String>,
+    },
+    MoveTo {
+        ty: String,
+        value: String,
+        success: bool,
+    },
+    EmitEvent {
+        ty: String,
+        value: String,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TracedWrite {
+    pub key: String,
+    pub op: String,
+}
+
+/// The execution trace of a transaction, serializable to JSON.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExecutionTrace {
+    pub call_graph: TracedFrame,
+    /// The writes of the transaction output, in the order they were charged.
+    pub write_set: Vec<TracedWrite>,
+}
+
+impl ExecutionTrace {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Renders a value in a Move-like syntax, e.g. `{ 0x1, [1, 2], true }` for a struct.
+#[derive(Default)]
+struct ValueRenderer {
+    out: String,
+    // The depth, closing delimiter and whether any element was rendered, for each open
+    // struct, vector or reference.
+    open: Vec<(usize, &'static str, bool)>,
+}
+
+impl ValueRenderer {
+    fn render(val: impl ValueView) -> String {
+        let mut renderer = Self::default();
+        val.visit(&mut renderer);
+        renderer.close_until(0);
+        renderer.out
+    }
+
+    fn close_until(&mut self, depth: usize) {
+        while let Some((open_depth, close, _)) = self.open.last() {
+            if *open_depth < depth {
+                break;
+            }
+            self.out.push_str(close);
+            self.open.pop();
+        }
+    }
+
+    fn begin(&mut self, depth: usize) {
+        self.close_until(depth);
+        if let Some((_, _, non_empty)) = self.open.last_mut() {
+            if *non_empty {
+                self.out.push_str(", ");
+            }
+            *non_empty = true;
+        }
+    }
+
+    fn leaf(&mut self, depth: usize, val: impl ToString) {
+        self.begin(depth);
+        self.out.push_str(&val.to_string());
+    }
+
+    fn container(&mut self, depth: usize, open: &str, close: &'static str) -> bool {
+        self.begin(depth);
+        self.out.push_str(open);
+        self.open.push((depth, close, false));
+        true
+    }
+}
+
+impl ValueVisitor for ValueRenderer {
+    fn visit_u8(&mut self, depth: usize, val: u8) {
+        self.leaf(depth, val)
+    }
+
+    fn visit_u16(&mut self, depth: usize, val: u16) {
+        self.leaf(depth, val)
+    }
+
+    fn visit_u32(&mut self, depth: usize, val: u32) {
+        self.leaf(depth, val)
+    }
+
+    fn visit_u64(&mut self, depth: usize, val: u64) {
+        self.leaf(depth, val)
+    }
+
+    fn visit_u128(&mut self, depth: usize, val: u128) {
+        self.leaf(depth, val)
+    }
+
+    fn visit_u256(&mut self, depth: usize, val: U256) {
+        self.leaf(depth, val)
+    }
+
+    fn visit_bool(&mut self, depth: usize, val: bool) {
+        self.leaf(depth, val)
+    }
+
+    fn visit_address(&mut self, depth: usize, val: AccountAddress) {
+        self.leaf(depth, val.to_hex_literal())
+    }
+
+    fn visit_struct(&mut self, depth: usize, _len: usize) -> bool {
+        self.container(depth, "{ ", " }")
+    }
+
+    fn visit_vec(&mut self, depth: usize, _len: usize) -> bool {
+        self.container(depth, "[", "]")
+    }
+
+    fn visit_ref(&mut self, depth: usize, _is_global: bool) -> bool {
+        self.container(depth, "&", "")
+    }
+
+    fn visit_vec_u8(&mut self, depth: usize, vals: &[u8]) {
+        self.leaf(depth, format!("0x{}", hex::encode(vals)))
+    }
+}
+
+/// A gas meter adapter that records the execution trace, delegating all charges to the base gas
+/// meter.
+pub struct ExecutionTracer<G> {
+    base: G,
+    frames: Vec<TracedFrame>,
+    write_set: Vec<TracedWrite>,
+}
+
+macro_rules! delegate {
+    ($(
+        fn $fn: ident $(<$($lt: lifetime),*>)? (&self $(, $arg: ident : $ty: ty)* $(,)?) -> $ret_ty: ty;
+    )*) => {
+        $(fn $fn $(<$($lt)*>)? (&self, $($arg: $ty),*) -> $ret_ty {
+            self.base.$fn($($arg),*)
+        })*
+    };
+}
+
+macro_rules! delegate_mut {
+    ($(
+        fn $fn: ident $(<$($lt: lifetime),*>)? (&mut self $(, $arg: ident : $ty: ty)* $(,)?) -> $ret_ty: ty;
+    )*) => {
+        $(fn $fn $(<$($lt)*>)? (&mut self, $($arg: $ty),*) -> $ret_ty {
+            self.base.$fn($($arg),*)
+        })*
+    };
+}
+
+impl<G> ExecutionTracer<G> {
+    pub fn new_script(base: G) -> Self {
+        Self::new(
+            base,
+            TracedFrame::new(None, "main".to_string(), vec![], vec![]),
+            vec![],
+        )
+    }
+
+    /// Creates a tracer for an entry function, given its (serialized) arguments.
+    pub fn new_function(
+        base: G,
+        module_id: &ModuleId,
+        function: String,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+    ) -> Self {
+        Self::new(
+            base,
+            TracedFrame::new(Some(module_id), function, ty_args, vec![]),
+            args,
+        )
+    }
+
+    fn new(base: G, mut entry_frame: TracedFrame, args: Vec<Vec<u8>>) -> Self {
+        entry_frame.args = args
+            .iter()
+            .map(|arg| format!("0x{}", hex::encode(arg)))
+            .collect();
+        Self {
+            base,
+            frames: vec![entry_frame],
+            write_set: vec![],
+        }
+    }
+
+    fn record(&mut self, event: TraceEvent) {
+        self.frames
+            .last_mut()
+            .expect("frame must exist")
+            .events
+            .push(event);
+    }
+
+    fn push_frame(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: Vec<TypeTag>,
+        args: impl Iterator<Item = impl ValueView>,
+    ) {
+        self.frames.push(TracedFrame::new(
+            Some(module_id),
+            func_name.to_string(),
+            ty_args,
+            args.map(ValueRenderer::render).collect(),
+        ));
+    }
+
+    /// Pops the returning frame into the calling one. The entry frame is kept to be picked up by
+    /// finish.
+    fn pop_frame(&mut self) {
+        if self.frames.len() > 1 {
+            let mut frame = self.frames.pop().expect("frame must exist");
+            frame.returned = true;
+            self.record(TraceEvent::Call(frame));
+        } else if let Some(frame) = self.frames.last_mut() {
+            frame.returned = true;
+        }
+    }
+
+    pub fn finish(mut self) -> ExecutionTrace {
+        // Frames that did not return (due to an abort) are nested in their callers.
+        while self.frames.len() > 1 {
+            let frame = self.frames.pop().expect("frame must exist");
+            self.record(TraceEvent::Call(frame));
+        }
+
+        ExecutionTrace {
+            call_graph: self.frames.pop().expect("frame must exist"),
+            write_set: self.write_set,
+        }
+    }
+}
+
+impl<G> GasMeter for ExecutionTracer<G>
+where
+    G: AptosGasMeter,
+{
+    delegate_mut! {
+        fn charge_br_true(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()>;
+
+        fn charge_br_false(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()>;
+
+        fn charge_branch(&mut self, target_offset: CodeOffset) -> PartialVMResult<()>;
+
+        fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()>;
+
+        fn charge_ld_const_after_deserialization(&mut self, val: impl ValueView)
+            -> PartialVMResult<()>;
+
+        fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_pack(
+            &mut self,
+            is_generic: bool,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_unpack(
+            &mut self,
+            is_generic: bool,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_write_ref(
+            &mut self,
+            new_val: impl ValueView,
+            old_val: impl ValueView,
+        ) -> PartialVMResult<()>;
+
+        fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_vec_pack<'a>(
+            &mut self,
+            ty: impl TypeView + 'a,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()>;
+
+        fn charge_vec_borrow(
+            &mut self,
+            is_mut: bool,
+            ty: impl TypeView,
+            is_success: bool,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_push_back(
+            &mut self,
+            ty: impl TypeView,
+            val: impl ValueView,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_pop_back(
+            &mut self,
+            ty: impl TypeView,
+            val: Option<impl ValueView>,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_unpack(
+            &mut self,
+            ty: impl TypeView,
+            expect_num_elements: NumArgs,
+            elems: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()>;
+
+        fn charge_drop_frame(
+            &mut self,
+            locals: impl Iterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+    }
+
+    fn balance_internal(&self) -> InternalGas {
+        self.base.balance_internal()
+    }
+
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        let res = self.base.charge_simple_instr(instr);
+        if matches!(instr, SimpleInstruction::Ret) {
+            self.pop_frame();
+        }
+        res
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.push_frame(module_id, func_name, vec![], args.clone());
+        self.base
+            .charge_call(module_id, func_name, args, num_locals)
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let ty_tags = ty_args.clone().map(|ty| ty.to_type_tag()).collect();
+        self.push_frame(module_id, func_name, ty_tags, args.clone());
+        self.base
+            .charge_call_generic(module_id, func_name, ty_args, args, num_locals)
+    }
+
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.record(TraceEvent::BorrowGlobal {
+            ty: ty.to_type_tag().to_string(),
+            is_mut,
+            success: is_success,
+        });
+        self.base
+            .charge_borrow_global(is_mut, is_generic, ty, is_success)
+    }
+
+    fn charge_exists(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        exists: bool,
+    ) -> PartialVMResult<()> {
+        self.record(TraceEvent::Exists {
+            ty: ty.to_type_tag().to_string(),
+            exists,
+        });
+        self.base.charge_exists(is_generic, ty, exists)
+    }
+
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record(TraceEvent::MoveFrom {
+            ty: ty.to_type_tag().to_string(),
+            value: val.as_ref().map(ValueRenderer::render),
+        });
+        self.base.charge_move_from(is_generic, ty, val)
+    }
+
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.record(TraceEvent::MoveTo {
+            ty: ty.to_type_tag().to_string(),
+            value: ValueRenderer::render(&val),
+            success: is_success,
+        });
+        self.base.charge_move_to(is_generic, ty, val, is_success)
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        addr: AccountAddress,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+        bytes_loaded: NumBytes,
+    ) -> PartialVMResult<()> {
+        self.record(TraceEvent::LoadResource {
+            address: addr,
+            ty: ty.to_type_tag().to_string(),
+            value: val.as_ref().map(ValueRenderer::render),
+        });
+        self.base.charge_load_resource(addr, ty, val, bytes_loaded)
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        // Events are emitted by 0x1::event::write_to_event_store<T>(guid, count, msg: T), which
+        // is recorded in the frame of its caller.
+        let frame = self.frames.last().expect("frame must exist");
+        if frame.module_id.as_deref() == Some("0x1::event")
+            && frame.function == "write_to_event_store"
+        {
+            if let (Some(ty), Some(msg)) = (ty_args.clone().next(), args.clone().last()) {
+                let event = TraceEvent::EmitEvent {
+                    ty: ty.to_type_tag().to_string(),
+                    value: ValueRenderer::render(msg),
+                };
+                let num_frames = self.frames.len();
+                if num_frames > 1 {
+                    self.frames[num_frames - 2].events.push(event);
+                }
+            }
+        }
+        self.base
+            .charge_native_function_before_execution(ty_args, args)
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView> + Clone>,
+    ) -> PartialVMResult<()> {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.return_values = ret_vals
+                .clone()
+                .map(|vals| vals.map(ValueRenderer::render).collect());
+        }
+        // Native functions do not execute a Ret instruction.
+        self.pop_frame();
+        self.base.charge_native_function(amount, ret_vals)
+    }
+}
+
+impl<G> AptosGasMeter for ExecutionTracer<G>
+where
+    G: AptosGasMeter,
+{
+    type Algebra = G::Algebra;
+
+    delegate! {
+        fn algebra(&self) -> &Self::Algebra;
+
+        fn storage_fee_per_write(&self, key: &StateKey, op: &WriteOp) -> Fee;
+
+        fn storage_fee_per_event(&self, event: &ContractEvent) -> Fee;
+
+        fn storage_discount_for_events(&self, total_cost: Fee) -> Fee;
+
+        fn storage_fee_for_transaction_storage(&self, txn_size: NumBytes) -> Fee;
+    }
+
+    delegate_mut! {
+        fn algebra_mut(&mut self) -> &mut Self::Algebra;
+
+        fn charge_storage_fee(
+            &mut self,
+            amount: Fee,
+            gas_unit_price: FeePerGasUnit,
+        ) -> PartialVMResult<()>;
+
+        fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()>;
+    }
+
+    fn charge_storage_fee_for_all<'a>(
+        &mut self,
+        write_ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+        events: impl IntoIterator<Item = &'a ContractEvent>,
+        txn_size: NumBytes,
+        gas_unit_price: FeePerGasUnit,
+    ) -> VMResult<()> {
+        self.base
+            .charge_storage_fee_for_all(write_ops, events, txn_size, gas_unit_price)
+    }
+
+    fn charge_io_gas_for_write(&mut self, key: &StateKey, op: &WriteOp) -> VMResult<()> {
+        let op_name = match op {
+            WriteOp::Creation(..) | WriteOp::CreationWithMetadata { .. } => "creation",
+            WriteOp::Modification(..) | WriteOp::ModificationWithMetadata { .. } => "modification",
+            WriteOp::Deletion | WriteOp::DeletionWithMetadata { .. } => "deletion",
+        };
+        self.write_set.push(TracedWrite {
+            key: format!("{:?}", key),
+            op: op_name.to_string(),
+        });
+        self.base.charge_io_gas_for_write(key, op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueRenderer;
+    use move_core_types::account_address::AccountAddress;
+    use move_vm_types::values::{Struct, Value};
+
+    #[test]
+    fn render_values() {
+        let value = Value::struct_(Struct::pack(vec![
+            Value::address(AccountAddress::ONE),
+            Value::vector_u8(vec![0xAB, 0xCD]),
+            Value::vector_u64(vec![1, 2]),
+            Value::struct_(Struct::pack(vec![])),
+            Value::bool(true),
+        ]));
+        assert_eq!(
+            ValueRenderer::render(&value),
+            "{ 0x1, 0xabcd, [1, 2], {  }, true }"
+        );
+    }
+}
@@ -3,12 +3,14 @@
 
 //! MoveVM and Session wrapped, to make sure Aptos natives and extensions are always installed and
 //! taken care of after session finish.
+mod execution_tracer;
 mod resolver;
 mod respawned_session;
 mod session;
 mod vm;
 
 pub use crate::move_vm_ext::{
+    execution_tracer::{ExecutionTrace, ExecutionTracer, TraceEvent, TracedFrame, TracedWrite},
     resolver::MoveResolverExt,
     respawned_session::RespawnedSession,
     session::{SessionExt, SessionId},
@@ -16,11 +16,16 @@ use std::sync::Arc;
 pub struct GlobalExecutor<S: StateView + Sync + Send + 'static> {
     global_cross_shard_client: Arc<GlobalCrossShardClient>,
     executor_thread_pool: Arc<rayon::ThreadPool>,
+    use_estimated_write_sets: bool,
     phantom: std::marker::PhantomData<S>,
 }
 
 impl<S: StateView + Sync + Send + 'static> GlobalExecutor<S> {
-    pub fn new(cross_shard_client: Arc<GlobalCrossShardClient>, num_threads: usize) -> Self {
+    pub fn new(
+        cross_shard_client: Arc<GlobalCrossShardClient>,
+        num_threads: usize,
+        use_estimated_write_sets: bool,
+    ) -> Self {
         let executor_thread_pool = Arc::new(
             rayon::ThreadPoolBuilder::new()
                 // We need two extra threads for the cross-shard commit receiver and the thread
@@ -32,6 +37,7 @@ impl<S: StateView + Sync + Send + 'static> GlobalExecutor<S> {
         Self {
             global_cross_shard_client: cross_shard_client,
             executor_thread_pool,
+            use_estimated_write_sets,
             phantom: std::marker::PhantomData,
         }
     }
@@ -57,6 +63,7 @@ impl<S: StateView + Sync + Send + 'static> GlobalExecutor<S> {
             state_view,
             concurrency_level,
             maybe_block_gas_limit,
+            self.use_estimated_write_sets,
         )
     }
 }
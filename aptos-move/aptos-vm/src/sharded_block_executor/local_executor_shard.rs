@@ -38,6 +38,7 @@ impl<S: StateView + Sync + Send + 'static> LocalExecutorService<S> {
         command_rx: Receiver<ExecutorShardCommand<S>>,
         result_tx: Sender<Result<Vec<Vec<TransactionOutput>>, VMStatus>>,
         cross_shard_client: LocalCrossShardClient,
+        use_estimated_write_sets: bool,
     ) -> Self {
         let coordinator_client = Arc::new(LocalCoordinatorClient::new(command_rx, result_tx));
         let executor_service = Arc::new(ShardedExecutorService::new(
@@ -46,6 +47,7 @@ impl<S: StateView + Sync + Send + 'static> LocalExecutorService<S> {
             num_threads,
             coordinator_client,
             Arc::new(cross_shard_client),
+            use_estimated_write_sets,
         ));
         let join_handle = thread::Builder::new()
             .name(format!("executor-shard-{}", shard_id))
@@ -57,7 +59,9 @@ impl<S: StateView + Sync + Send + 'static> LocalExecutorService<S> {
         }
     }
 
-    fn setup_global_executor() -> (GlobalExecutor<S>, Sender<CrossShardMsg>) {
+    fn setup_global_executor(
+        use_estimated_write_sets: bool,
+    ) -> (GlobalExecutor<S>, Sender<CrossShardMsg>) {
         let (cross_shard_tx, cross_shard_rx) = unbounded();
         let cross_shard_client = Arc::new(GlobalCrossShardClient::new(
             cross_shard_tx.clone(),
@@ -65,15 +69,21 @@ impl<S: StateView + Sync + Send + 'static> LocalExecutorService<S> {
         ));
         // Limit the number of global executor threads to 32 as parallel execution doesn't scale well beyond that.
         let executor_threads = num_cpus::get().min(32);
-        let global_executor = GlobalExecutor::new(cross_shard_client, executor_threads);
+        let global_executor = GlobalExecutor::new(
+            cross_shard_client,
+            executor_threads,
+            use_estimated_write_sets,
+        );
         (global_executor, cross_shard_tx)
     }
 
     pub fn setup_local_executor_shards(
         num_shards: usize,
         num_threads: Option<usize>,
+        use_estimated_write_sets: bool,
     ) -> LocalExecutorClient<S> {
-        let (global_executor, global_cross_shard_tx) = Self::setup_global_executor();
+        let (global_executor, global_cross_shard_tx) =
+            Self::setup_global_executor(use_estimated_write_sets);
         let num_threads = num_threads
             .unwrap_or_else(|| (num_cpus::get() as f64 / num_shards as f64).ceil() as usize);
         let (command_txs, command_rxs): (
@@ -115,6 +125,7 @@ impl<S: StateView + Sync + Send + 'static> LocalExecutorService<S> {
                     command_rx,
                     result_tx,
                     cross_shard_client,
+                    use_estimated_write_sets,
                 )
             })
             .collect();
@@ -17,7 +17,11 @@ use aptos_types::{
     block_executor::partitioner::{
         ShardId, SubBlock, SubBlocksForShard, TransactionWithDependencies,
     },
-    transaction::{analyzed_transaction::AnalyzedTransaction, TransactionOutput},
+    state_store::state_key::StateKey,
+    transaction::{
+        analyzed_transaction::{AnalyzedTransaction, StorageLocation},
+        TransactionOutput,
+    },
 };
 use futures::{channel::oneshot, executor::block_on};
 use move_core_types::vm_status::VMStatus;
@@ -29,6 +33,7 @@ pub struct ShardedExecutorService<S: StateView + Sync + Send + 'static> {
     executor_thread_pool: Arc<rayon::ThreadPool>,
     coordinator_client: Arc<dyn CoordinatorClient<S>>,
     cross_shard_client: Arc<dyn CrossShardClient>,
+    use_estimated_write_sets: bool,
 }
 
 impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
@@ -38,6 +43,7 @@ impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
         num_threads: usize,
         coordinator_client: Arc<dyn CoordinatorClient<S>>,
         cross_shard_client: Arc<dyn CrossShardClient>,
+        use_estimated_write_sets: bool,
     ) -> Self {
         let executor_thread_pool = Arc::new(
             rayon::ThreadPoolBuilder::new()
@@ -53,6 +59,7 @@ impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
             executor_thread_pool,
             coordinator_client,
             cross_shard_client,
+            use_estimated_write_sets,
         }
     }
 
@@ -81,6 +88,7 @@ impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
             state_view,
             concurrency_level,
             maybe_block_gas_limit,
+            self.use_estimated_write_sets,
         )
     }
 
@@ -94,9 +102,17 @@ impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
         state_view: &S,
         concurrency_level: usize,
         maybe_block_gas_limit: Option<u64>,
+        use_estimated_write_sets: bool,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
         let (callback, callback_receiver) = oneshot::channel();
 
+        let maybe_estimated_write_sets = use_estimated_write_sets.then(|| {
+            transactions
+                .iter()
+                .map(|txn| Self::estimated_write_set(txn.txn()))
+                .collect()
+        });
+
         let cross_shard_state_view = Arc::new(CrossShardStateView::create_cross_shard_state_view(
             state_view,
             &transactions,
@@ -123,6 +139,7 @@ impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
                     concurrency_level,
                     maybe_block_gas_limit,
                     cross_shard_commit_sender,
+                    maybe_estimated_write_sets,
                 );
                 if let Some(shard_id) = shard_id {
                     trace!(
@@ -147,6 +164,17 @@ impl<S: StateView + Sync + Send + 'static> ShardedExecutorService<S> {
         block_on(callback_receiver).unwrap()
     }
 
+    /// The keys the partitioner estimates the transaction to write, wildcards are ignored.
+    fn estimated_write_set(txn: &AnalyzedTransaction) -> Vec<StateKey> {
+        txn.write_hints()
+            .iter()
+            .filter_map(|location| match location {
+                StorageLocation::Specific(state_key) => Some(state_key.clone()),
+                StorageLocation::WildCardStruct(_) | StorageLocation::WildCardTable(_) => None,
+            })
+            .collect()
+    }
+
     fn execute_block(
         &self,
         transactions: SubBlocksForShard<AnalyzedTransaction>,
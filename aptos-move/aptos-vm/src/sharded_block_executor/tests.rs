@@ -15,7 +15,7 @@ fn setup_sharded_block_executor<S: StateView + Sync + Send + 'static>(
     num_threads_per_shard: Option<usize>,
 ) -> ShardedBlockExecutor<S, LocalExecutorClient<S>> {
     let client =
-        LocalExecutorService::setup_local_executor_shards(num_shards, num_threads_per_shard);
+        LocalExecutorService::setup_local_executor_shards(num_shards, num_threads_per_shard, false);
     ShardedBlockExecutor::new(client)
 }
 
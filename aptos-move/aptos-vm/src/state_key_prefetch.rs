@@ -0,0 +1,119 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Derivation of the state keys that the transactions of a block are likely to read, so that the
+//! executor can fetch them from storage in parallel before executing the block, instead of one at
+//! a time during execution.
+
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    state_store::state_key::StateKey,
+    transaction::{analyzed_transaction::AnalyzedTransaction, Transaction, TransactionPayload},
+};
+use std::collections::HashSet;
+
+/// Returns the (deduplicated) state keys to prefetch for the transactions: the account and coin
+/// store of the senders, the modules of the entry functions called and, for the known coin
+/// transfer and account creation entry functions, the account and coin store of the receivers.
+pub fn state_keys_to_prefetch<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+) -> Vec<StateKey> {
+    let mut seen = HashSet::new();
+    let mut keys = vec![];
+    for txn in transactions {
+        for key in state_keys_for_transaction(txn) {
+            if seen.insert(key.clone()) {
+                keys.push(key);
+            }
+        }
+    }
+    keys
+}
+
+fn state_keys_for_transaction(txn: &Transaction) -> Vec<StateKey> {
+    let signed_txn = match txn {
+        Transaction::UserTransaction(signed_txn) => signed_txn,
+        _ => return vec![],
+    };
+
+    let mut keys = account_state_keys(signed_txn.sender());
+    if let TransactionPayload::EntryFunction(func) = signed_txn.payload() {
+        keys.push(StateKey::access_path(AccessPath::code_access_path(
+            func.module().clone(),
+        )));
+
+        let is_known_transfer = matches!(
+            (
+                *func.module().address(),
+                func.module().name().as_str(),
+                func.function().as_str(),
+            ),
+            (AccountAddress::ONE, "coin", "transfer")
+                | (AccountAddress::ONE, "aptos_account", "transfer")
+                | (AccountAddress::ONE, "aptos_account", "transfer_coins")
+                | (AccountAddress::ONE, "aptos_account", "create_account")
+        );
+        if is_known_transfer {
+            // The receiver is the first argument. Malformed arguments fail in execution, and
+            // only lose the prefetch here.
+            if let Some(Ok(receiver)) = func
+                .args()
+                .first()
+                .map(|arg| bcs::from_bytes::<AccountAddress>(arg))
+            {
+                keys.extend(account_state_keys(receiver));
+            }
+        }
+    }
+    keys
+}
+
+fn account_state_keys(address: AccountAddress) -> Vec<StateKey> {
+    vec![
+        AnalyzedTransaction::account_resource_location(address).into_state_key(),
+        AnalyzedTransaction::coin_store_location(address).into_state_key(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{account_state_keys, state_keys_to_prefetch};
+    use aptos_crypto::HashValue;
+    use aptos_language_e2e_tests::{
+        account::Account,
+        common_transactions::{create_account_txn, peer_to_peer_txn},
+    };
+    use aptos_types::{
+        access_path::AccessPath, account_address::AccountAddress, state_store::state_key::StateKey,
+        transaction::Transaction,
+    };
+    use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+
+    fn module_key(name: &str) -> StateKey {
+        StateKey::access_path(AccessPath::code_access_path(ModuleId::new(
+            AccountAddress::ONE,
+            Identifier::new(name).unwrap(),
+        )))
+    }
+
+    #[test]
+    fn test_state_keys_to_prefetch() {
+        let sender = Account::new();
+        let receiver = Account::new();
+        let new_account = Account::new();
+        let transactions = vec![
+            Transaction::UserTransaction(peer_to_peer_txn(&sender, &receiver, 0, 1_000, 100)),
+            Transaction::UserTransaction(create_account_txn(&sender, &new_account, 1)),
+            Transaction::StateCheckpoint(HashValue::zero()),
+        ];
+
+        let mut expected = account_state_keys(*sender.address());
+        expected.push(module_key("coin"));
+        expected.extend(account_state_keys(*receiver.address()));
+        // The keys of the sender are only returned once.
+        expected.push(module_key("aptos_account"));
+        expected.extend(account_state_keys(*new_account.address()));
+        assert_eq!(state_keys_to_prefetch(&transactions), expected);
+    }
+}
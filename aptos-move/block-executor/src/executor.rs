@@ -9,13 +9,18 @@ use crate::{
         TASK_VALIDATE_SECONDS, VM_INIT_SECONDS, WORK_WITH_TASK_SECONDS,
     },
     errors::*,
+    schedule_replay::{
+        Schedule, ScheduleController, ScheduleEvent, ScheduleMode, WorkerSchedule,
+        WorkerScheduleGuard,
+    },
     scheduler::{DependencyStatus, ExecutionTaskType, Scheduler, SchedulerTask, Wave},
     task::{ExecutionStatus, ExecutorTask, Transaction, TransactionOutput},
     txn_commit_hook::TransactionCommitHook,
-    txn_last_input_output::TxnLastInputOutput,
+    txn_last_input_output::{TxnConflictStats, TxnLastInputOutput},
     view::{LatestView, MVHashMapView},
 };
 use aptos_aggregator::delta_change_set::{deserialize, serialize};
+use aptos_infallible::Mutex;
 use aptos_logger::{debug, info};
 use aptos_mvhashmap::{
     types::{MVDataError, MVDataOutput, TxnIndex, Version},
@@ -67,7 +72,7 @@ enum CommitRole {
     Worker(Receiver<TxnIndex>),
 }
 
-pub struct BlockExecutor<T, E, S, L, X> {
+pub struct BlockExecutor<T: Transaction, E, S, L, X> {
     // number of active concurrent tasks, corresponding
     // to the maximum number of rayon
     // threads that may be concurrently participating in parallel execution.
@@ -75,6 +80,10 @@ pub struct BlockExecutor<T, E, S, L, X> {
     executor_thread_pool: Arc<ThreadPool>,
     maybe_block_gas_limit: Option<u64>,
     transaction_commit_hook: Option<L>,
+    maybe_schedule_mode: Option<ScheduleMode>,
+    recorded_schedule: Mutex<Option<Schedule>>,
+    collect_conflict_stats: bool,
+    conflict_stats: Mutex<Option<Vec<TxnConflictStats<T::Key>>>>,
     phantom: PhantomData<(T, E, S, L, X)>,
 }
 
@@ -104,10 +113,38 @@ where
             executor_thread_pool,
             maybe_block_gas_limit,
             transaction_commit_hook,
+            maybe_schedule_mode: None,
+            recorded_schedule: Mutex::new(None),
+            collect_conflict_stats: false,
+            conflict_stats: Mutex::new(None),
             phantom: PhantomData,
         }
     }
 
+    /// Records or replays the schedule of parallel execution, for debugging purposes. This
+    /// serializes the execution, see the schedule_replay module.
+    pub fn with_schedule_mode(mut self, schedule_mode: ScheduleMode) -> Self {
+        self.maybe_schedule_mode = Some(schedule_mode);
+        self
+    }
+
+    /// Takes the schedule recorded by the last parallel execution in ScheduleMode::Record.
+    pub fn take_recorded_schedule(&self) -> Option<Schedule> {
+        self.recorded_schedule.lock().take()
+    }
+
+    /// Collects per-transaction conflict statistics, which are passed to the commit hook and
+    /// available after execution via take_conflict_stats.
+    pub fn with_conflict_stats(mut self) -> Self {
+        self.collect_conflict_stats = true;
+        self
+    }
+
+    /// Takes the conflict statistics of the last execution (one per transaction, like outputs).
+    pub fn take_conflict_stats(&self) -> Option<Vec<TxnConflictStats<T::Key>>> {
+        self.conflict_stats.lock().take()
+    }
+
     fn execute(
         &self,
         version: Version,
@@ -115,6 +152,7 @@ where
         last_input_output: &TxnLastInputOutput<T::Key, E::Output, E::Error>,
         versioned_cache: &MVHashMap<T::Key, T::Value, X>,
         scheduler: &Scheduler,
+        schedule: WorkerSchedule,
         executor: &E,
         base_view: &S,
     ) -> SchedulerTask {
@@ -122,7 +160,7 @@ where
         let (idx_to_execute, incarnation) = version;
         let txn = &signature_verified_block[idx_to_execute as usize];
 
-        let speculative_view = MVHashMapView::new(versioned_cache, scheduler);
+        let speculative_view = MVHashMapView::new(versioned_cache, scheduler, schedule);
 
         // VM execution.
         let execute_result = executor.execute_transaction(
@@ -186,10 +224,17 @@ where
         {
             // When there is module publishing r/w intersection, can early halt BlockSTM to
             // fallback to sequential execution.
-            scheduler.halt();
+            schedule.step(|| scheduler.halt(), |_| ScheduleEvent::Halt);
             return SchedulerTask::NoTask;
         }
-        scheduler.finish_execution(idx_to_execute, incarnation, updates_outside)
+        schedule.step(
+            || scheduler.finish_execution(idx_to_execute, incarnation, updates_outside),
+            |task| ScheduleEvent::FinishExecution {
+                txn_idx: idx_to_execute,
+                incarnation,
+                next_task: task.into(),
+            },
+        )
     }
 
     fn validate(
@@ -199,6 +244,7 @@ where
         last_input_output: &TxnLastInputOutput<T::Key, E::Output, E::Error>,
         versioned_cache: &MVHashMap<T::Key, T::Value, X>,
         scheduler: &Scheduler,
+        schedule: WorkerSchedule,
     ) -> SchedulerTask {
         use MVDataError::*;
         use MVDataOutput::*;
@@ -209,8 +255,8 @@ where
             .read_set(idx_to_validate)
             .expect("[BlockSTM]: Prior read-set must be recorded");
 
-        let valid = read_set.iter().all(|r| {
-            match versioned_cache.fetch_data(r.path(), idx_to_validate) {
+        let maybe_failed_read = read_set.iter().find(|r| {
+            let valid = match versioned_cache.fetch_data(r.path(), idx_to_validate) {
                 Ok(Versioned(version, _)) => r.validate_version(version),
                 Ok(Resolved(value)) => r.validate_resolved(value),
                 // Dependency implies a validation failure, and if the original read were to
@@ -225,13 +271,30 @@ where
                 // materializing deltas as writes in the final output preparation state. Panic
                 // is also preferable as it allows testing for this scenario.
                 Err(DeltaApplicationFailure) => r.validate_delta_application_failure(),
-            }
+            };
+            !valid
         });
-
-        let aborted = !valid && scheduler.try_abort(idx_to_validate, incarnation);
+        let valid = maybe_failed_read.is_none();
+
+        let aborted = !valid
+            && schedule.step(
+                || scheduler.try_abort(idx_to_validate, incarnation),
+                |aborted| ScheduleEvent::TryAbort {
+                    txn_idx: idx_to_validate,
+                    incarnation,
+                    aborted: *aborted,
+                },
+            );
 
         if aborted {
             counters::SPECULATIVE_ABORT_COUNT.inc();
+            last_input_output.record_conflict(
+                idx_to_validate,
+                maybe_failed_read
+                    .expect("Aborted transaction must have failed validation")
+                    .path()
+                    .clone(),
+            );
 
             // Any logs from the aborted execution should be cleared and not reported.
             clear_speculative_txn_logs(idx_to_validate as usize);
@@ -241,9 +304,22 @@ where
                 versioned_cache.mark_estimate(&k, idx_to_validate);
             }
 
-            scheduler.finish_abort(idx_to_validate, incarnation)
+            schedule.step(
+                || scheduler.finish_abort(idx_to_validate, incarnation),
+                |task| ScheduleEvent::FinishAbort {
+                    txn_idx: idx_to_validate,
+                    incarnation,
+                    next_task: task.into(),
+                },
+            )
         } else {
-            scheduler.finish_validation(idx_to_validate, validation_wave);
+            schedule.step(
+                || scheduler.finish_validation(idx_to_validate, validation_wave),
+                |_| ScheduleEvent::FinishValidation {
+                    txn_idx: idx_to_validate,
+                    wave: validation_wave,
+                },
+            );
             SchedulerTask::NoTask
         }
     }
@@ -252,6 +328,7 @@ where
         &self,
         maybe_block_gas_limit: Option<u64>,
         scheduler: &Scheduler,
+        schedule: WorkerSchedule,
         post_commit_txs: &Vec<Sender<u32>>,
         worker_idx: &mut usize,
         scheduler_task: &mut SchedulerTask,
@@ -259,7 +336,10 @@ where
         accumulated_fee_statement: &mut FeeStatement,
         txn_fee_statements: &mut Vec<FeeStatement>,
     ) {
-        while let Some(txn_idx) = scheduler.try_commit() {
+        while let Some(txn_idx) = schedule.step(
+            || scheduler.try_commit(),
+            |maybe_txn_idx| ScheduleEvent::TryCommit(*maybe_txn_idx),
+        ) {
             // Create a CommitGuard to ensure Coordinator sends the committed txn index to Worker.
             let _commit_guard: CommitGuard =
                 CommitGuard::new(post_commit_txs, *worker_idx, txn_idx);
@@ -309,7 +389,7 @@ where
                 }
 
                 // Either all txn committed, or a committed txn caused an early halt.
-                scheduler.halt();
+                schedule.step(|| scheduler.halt(), |_| ScheduleEvent::Halt);
 
                 counters::update_parallel_block_gas_counters(
                     accumulated_fee_statement,
@@ -383,6 +463,9 @@ where
         }
         last_input_output.record_delta_writes(txn_idx, delta_writes);
         if let Some(txn_commit_listener) = &self.transaction_commit_hook {
+            if let Some(stats) = last_input_output.conflict_stats(txn_idx) {
+                txn_commit_listener.on_transaction_conflict_stats(txn_idx, &stats);
+            }
             let txn_output = last_input_output.txn_output(txn_idx).unwrap();
             let execution_status = txn_output.output_status();
 
@@ -404,6 +487,7 @@ where
         last_input_output: &TxnLastInputOutput<T::Key, E::Output, E::Error>,
         versioned_cache: &MVHashMap<T::Key, T::Value, X>,
         scheduler: &Scheduler,
+        schedule: WorkerSchedule,
         base_view: &S,
         role: CommitRole,
     ) {
@@ -413,6 +497,8 @@ where
         drop(init_timer);
 
         let committing = matches!(role, CommitRole::Coordinator(_));
+        // Makes sure the other workers can proceed once this one exits.
+        let _schedule_guard = WorkerScheduleGuard(schedule);
 
         let _timer = WORK_WITH_TASK_SECONDS.start_timer();
         let mut scheduler_task = SchedulerTask::NoTask;
@@ -427,6 +513,7 @@ where
                     self.coordinator_commit_hook(
                         self.maybe_block_gas_limit,
                         scheduler,
+                        schedule,
                         post_commit_txs,
                         &mut worker_idx,
                         &mut scheduler_task,
@@ -454,6 +541,7 @@ where
                     last_input_output,
                     versioned_cache,
                     scheduler,
+                    schedule,
                 ),
                 SchedulerTask::ExecutionTask(version_to_execute, ExecutionTaskType::Execution) => {
                     self.execute(
@@ -462,6 +550,7 @@ where
                         last_input_output,
                         versioned_cache,
                         scheduler,
+                        schedule,
                         &executor,
                         base_view,
                     )
@@ -475,8 +564,12 @@ where
 
                     SchedulerTask::NoTask
                 },
-                SchedulerTask::NoTask => scheduler.next_task(committing),
+                SchedulerTask::NoTask => schedule.step(
+                    || scheduler.next_task(committing),
+                    |task| ScheduleEvent::NextTask(task.into()),
+                ),
                 SchedulerTask::Done => {
+                    schedule.release();
                     // Make sure to drain any remaining commit tasks assigned by the coordinator.
                     if let CommitRole::Worker(rx) = &role {
                         // Until the sender drops the tx, an index for commit_hook might be sent.
@@ -500,6 +593,7 @@ where
         executor_initial_arguments: E::Argument,
         signature_verified_block: &Vec<T>,
         base_view: &S,
+        maybe_estimated_write_sets: Option<&[Vec<T::Key>]>,
     ) -> Result<Vec<E::Output>, E::Error> {
         let _timer = PARALLEL_EXECUTION_SECONDS.start_timer();
         // Using parallel execution with 1 thread currently will not work as it
@@ -511,12 +605,20 @@ where
         let versioned_cache = MVHashMap::new();
 
         if signature_verified_block.is_empty() {
+            if self.collect_conflict_stats {
+                *self.conflict_stats.lock() = Some(vec![]);
+            }
             return Ok(vec![]);
         }
 
         let num_txns = signature_verified_block.len() as u32;
-        let last_input_output = TxnLastInputOutput::new(num_txns);
-        let scheduler = Scheduler::new(num_txns);
+        let last_input_output = TxnLastInputOutput::new(num_txns, self.collect_conflict_stats);
+        let scheduler = match maybe_estimated_write_sets {
+            Some(estimated_write_sets) => {
+                Scheduler::new_with_estimated_write_sets(num_txns, estimated_write_sets)
+            },
+            None => Scheduler::new(num_txns),
+        };
 
         let mut roles: Vec<CommitRole> = vec![];
         let mut senders: Vec<Sender<u32>> = Vec::with_capacity(self.concurrency_level - 1);
@@ -532,17 +634,28 @@ where
         // executors are running concurrently, they will all have active coordinator.
         roles.push(CommitRole::Coordinator(senders));
 
+        let maybe_schedule_controller = self
+            .maybe_schedule_mode
+            .as_ref()
+            .map(|mode| ScheduleController::new(mode, self.concurrency_level, num_txns));
+
         let timer = RAYON_EXECUTION_SECONDS.start_timer();
         self.executor_thread_pool.scope(|s| {
-            for _ in 0..self.concurrency_level {
+            let executor_initial_arguments = &executor_initial_arguments;
+            let last_input_output = &last_input_output;
+            let versioned_cache = &versioned_cache;
+            let scheduler = &scheduler;
+            for worker_idx in 0..self.concurrency_level {
                 let role = roles.pop().expect("Role must be set for all threads");
-                s.spawn(|_| {
+                let schedule = WorkerSchedule::new(maybe_schedule_controller.as_ref(), worker_idx);
+                s.spawn(move |_| {
                     self.work_task_with_scope(
-                        &executor_initial_arguments,
+                        executor_initial_arguments,
                         signature_verified_block,
-                        &last_input_output,
-                        &versioned_cache,
-                        &scheduler,
+                        last_input_output,
+                        versioned_cache,
+                        scheduler,
+                        schedule,
                         base_view,
                         role,
                     );
@@ -551,6 +664,12 @@ where
         });
         drop(timer);
 
+        if let Some(schedule) =
+            maybe_schedule_controller.and_then(ScheduleController::into_recorded_schedule)
+        {
+            *self.recorded_schedule.lock() = Some(schedule);
+        }
+
         let num_txns = num_txns as usize;
         // TODO: for large block sizes and many cores, extract outputs in parallel.
         let mut final_results = Vec::with_capacity(num_txns);
@@ -576,6 +695,18 @@ where
             ret
         };
 
+        if self.collect_conflict_stats {
+            *self.conflict_stats.lock() = Some(
+                (0..num_txns)
+                    .map(|idx| {
+                        last_input_output
+                            .take_conflict_stats(idx as TxnIndex)
+                            .expect("Conflict stats must be collected")
+                    })
+                    .collect(),
+            );
+        }
+
         self.executor_thread_pool.spawn(move || {
             // Explicit async drops.
             drop(last_input_output);
@@ -638,12 +769,24 @@ where
                     output.incorporate_delta_writes(vec![]);
                     //
                     if let Some(commit_hook) = &self.transaction_commit_hook {
+                        if self.collect_conflict_stats {
+                            commit_hook.on_transaction_conflict_stats(
+                                idx as TxnIndex,
+                                &TxnConflictStats::new(vec![]),
+                            );
+                        }
                         commit_hook.on_transaction_committed(idx as TxnIndex, &output);
                     }
                     ret.push(output);
                 },
                 ExecutionStatus::Abort(err) => {
                     if let Some(commit_hook) = &self.transaction_commit_hook {
+                        if self.collect_conflict_stats {
+                            commit_hook.on_transaction_conflict_stats(
+                                idx as TxnIndex,
+                                &TxnConflictStats::new(vec![]),
+                            );
+                        }
                         commit_hook.on_execution_aborted(idx as TxnIndex);
                     }
                     // Record the status indicating abort.
@@ -691,20 +834,32 @@ where
 
         counters::update_sequential_block_gas_counters(&accumulated_fee_statement, ret.len());
         ret.resize_with(num_txns, E::Output::skip_output);
+        if self.collect_conflict_stats {
+            // Sequential execution executes each transaction once.
+            *self.conflict_stats.lock() = Some(
+                (0..num_txns)
+                    .map(|_| TxnConflictStats::new(vec![]))
+                    .collect(),
+            );
+        }
         Ok(ret)
     }
 
+    /// Executes the block. If provided (one per transaction), the estimated write-sets are used
+    /// by parallel execution to delay the transactions likely to conflict with earlier ones.
     pub fn execute_block(
         &self,
         executor_arguments: E::Argument,
         signature_verified_block: Vec<T>,
         base_view: &S,
+        maybe_estimated_write_sets: Option<Vec<Vec<T::Key>>>,
     ) -> Result<Vec<E::Output>, E::Error> {
         let mut ret = if self.concurrency_level > 1 {
             self.execute_transactions_parallel(
                 executor_arguments,
                 &signature_verified_block,
                 base_view,
+                maybe_estimated_write_sets.as_deref(),
             )
         } else {
             self.execute_transactions_sequential(
@@ -141,6 +141,7 @@ pub mod errors;
 pub mod executor;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod proptest_types;
+pub mod schedule_replay;
 mod scheduler;
 pub mod task;
 pub mod txn_commit_hook;
@@ -135,7 +135,7 @@ where
             NoOpTransactionCommitHook<MockOutput<KeyType<K>, ValueType<V>, E>, usize>,
             ExecutableTestType,
         >::new(num_cpus::get(), executor_thread_pool, None, None)
-        .execute_transactions_parallel((), &self.transactions, &data_view);
+        .execute_transactions_parallel((), &self.transactions, &data_view, None);
 
         self.baseline_output.assert_output(&output);
     }
@@ -12,6 +12,7 @@ use crate::{
             MockTransaction, TransactionGen, TransactionGenParams, ValueType, MAX_GAS_PER_TXN,
         },
     },
+    schedule_replay::{Schedule, ScheduleMode},
     txn_commit_hook::NoOpTransactionCommitHook,
 };
 use aptos_types::{contract_event::ReadWriteEvent, executable::ExecutableTestType};
@@ -25,7 +26,48 @@ use proptest::{
     test_runner::TestRunner,
 };
 use rand::Rng;
-use std::{cmp::max, fmt::Debug, hash::Hash, marker::PhantomData, sync::Arc};
+use std::{
+    cmp::max,
+    env,
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    sync::{atomic::Ordering, Arc},
+};
+
+/// Set to the path of a schedule dumped by a failed test, to replay it (with the same seed).
+const REPLAY_SCHEDULE_ENV: &str = "BLOCK_STM_REPLAY_SCHEDULE";
+
+fn schedule_mode() -> ScheduleMode {
+    match env::var_os(REPLAY_SCHEDULE_ENV) {
+        Some(path) => ScheduleMode::Replay(
+            Schedule::load(Path::new(&path)).expect("Schedule to replay must be loaded"),
+        ),
+        None => ScheduleMode::Record,
+    }
+}
+
+/// Runs the check of the execution output, and if it fails, dumps the recorded schedule of
+/// the execution to a temporary file, to be replayed via REPLAY_SCHEDULE_ENV.
+fn check_output(maybe_schedule: Option<Schedule>, check: impl FnOnce()) {
+    if let Err(err) = panic::catch_unwind(AssertUnwindSafe(check)) {
+        if let Some(schedule) = maybe_schedule {
+            let path = env::temp_dir().join(format!(
+                "block_stm_schedule_{}.bcs",
+                rand::thread_rng().gen::<u64>()
+            ));
+            schedule.save(&path).expect("Schedule must be saved");
+            eprintln!(
+                "Schedule of the failed execution saved, replay with {}={}",
+                REPLAY_SCHEDULE_ENV,
+                path.display()
+            );
+        }
+        panic::resume_unwind(err);
+    }
+}
 
 fn run_transactions<K, V, E>(
     key_universe: &[K],
@@ -65,8 +107,8 @@ fn run_transactions<K, V, E>(
             .unwrap(),
     );
 
-    for _ in 0..num_repeat {
-        let output = BlockExecutor::<
+    for idx in 0..num_repeat {
+        let mut block_executor = BlockExecutor::<
             MockTransaction<KeyType<K>, ValueType<V>, E>,
             MockTask<KeyType<K>, ValueType<V>, E>,
             EmptyDataView<KeyType<K>, ValueType<V>>,
@@ -77,15 +119,103 @@ fn run_transactions<K, V, E>(
             executor_thread_pool.clone(),
             maybe_block_gas_limit,
             None,
-        )
-        .execute_transactions_parallel((), &transactions, &data_view);
+        );
+        // Recording serializes the execution, so other repetitions run freely.
+        if idx == 0 {
+            block_executor = block_executor.with_schedule_mode(schedule_mode());
+        }
+        let output =
+            block_executor.execute_transactions_parallel((), &transactions, &data_view, None);
 
         if module_access.0 && module_access.1 {
             assert_eq!(output.unwrap_err(), Error::ModulePathReadWrite);
             continue;
         }
 
-        BaselineOutput::generate(&transactions, maybe_block_gas_limit).assert_output(&output);
+        check_output(block_executor.take_recorded_schedule(), || {
+            BaselineOutput::generate(&transactions, maybe_block_gas_limit).assert_output(&output)
+        });
+    }
+}
+
+/// Runs the transactions with estimated write-sets. Without given estimates, the write-set of
+/// each transaction is estimated accurately from the writes of its first incarnation, otherwise
+/// the given (likely inaccurate) keys of the universe are used.
+fn run_transactions_with_estimated_write_sets<K, V>(
+    key_universe: &[K],
+    transaction_gens: Vec<TransactionGen<V>>,
+    maybe_estimated_key_indices: Option<Vec<Vec<Index>>>,
+    num_repeat: usize,
+) where
+    K: Hash + Clone + Debug + Eq + Send + Sync + PartialOrd + Ord + 'static,
+    V: Clone + Eq + Send + Sync + Arbitrary + 'static,
+    Vec<u8>: From<V>,
+{
+    let transactions: Vec<MockTransaction<KeyType<K>, ValueType<V>, MockEvent>> = transaction_gens
+        .into_iter()
+        .map(|txn_gen| txn_gen.materialize(key_universe, (false, false)))
+        .collect();
+
+    let estimated_write_sets: Vec<Vec<KeyType<K>>> = match maybe_estimated_key_indices {
+        None => transactions
+            .iter()
+            .map(|txn| match txn {
+                MockTransaction::Write {
+                    incarnation_counter: _,
+                    incarnation_behaviors,
+                } => incarnation_behaviors[0]
+                    .writes
+                    .iter()
+                    .map(|(key, _)| key.clone())
+                    .collect(),
+                MockTransaction::SkipRest | MockTransaction::Abort => vec![],
+            })
+            .collect(),
+        Some(estimated_key_indices) => estimated_key_indices
+            .into_iter()
+            .take(transactions.len())
+            .map(|indices| {
+                indices
+                    .into_iter()
+                    .map(|idx| KeyType(key_universe[idx.index(key_universe.len())].clone(), false))
+                    .collect()
+            })
+            .collect(),
+    };
+
+    let data_view = EmptyDataView::<KeyType<K>, ValueType<V>> {
+        phantom: PhantomData,
+    };
+
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    );
+
+    for idx in 0..num_repeat {
+        let mut block_executor =
+            BlockExecutor::<
+                MockTransaction<KeyType<K>, ValueType<V>, MockEvent>,
+                MockTask<KeyType<K>, ValueType<V>, MockEvent>,
+                EmptyDataView<KeyType<K>, ValueType<V>>,
+                NoOpTransactionCommitHook<MockOutput<KeyType<K>, ValueType<V>, MockEvent>, usize>,
+                ExecutableTestType,
+            >::new(num_cpus::get(), executor_thread_pool.clone(), None, None);
+        if idx == 0 {
+            block_executor = block_executor.with_schedule_mode(schedule_mode());
+        }
+        let output = block_executor.execute_transactions_parallel(
+            (),
+            &transactions,
+            &data_view,
+            Some(estimated_write_sets.as_slice()),
+        );
+
+        check_output(block_executor.take_recorded_schedule(), || {
+            BaselineOutput::generate(&transactions, None).assert_output(&output)
+        });
     }
 }
 
@@ -140,6 +270,23 @@ proptest! {
     ) {
         run_transactions::<[u8; 32], [u8; 32], MockEvent>(&universe, transaction_gen, abort_transactions, skip_rest_transactions, 1, (false, false), None);
     }
+
+    #[test]
+    fn accurate_estimated_write_sets(
+        universe in vec(any::<[u8; 32]>(), 10),
+        transaction_gen in vec(any_with::<TransactionGen<[u8;32]>>(TransactionGenParams::new_dynamic()), 3000).no_shrink(),
+    ) {
+        run_transactions_with_estimated_write_sets::<[u8; 32], [u8; 32]>(&universe, transaction_gen, None, 1);
+    }
+
+    #[test]
+    fn inaccurate_estimated_write_sets(
+        universe in vec(any::<[u8; 32]>(), 10),
+        transaction_gen in vec(any_with::<TransactionGen<[u8;32]>>(TransactionGenParams::new_dynamic()), 3000).no_shrink(),
+        estimated_key_indices in vec(vec(any::<Index>(), 0..4), 3000),
+    ) {
+        run_transactions_with_estimated_write_sets::<[u8; 32], [u8; 32]>(&universe, transaction_gen, Some(estimated_key_indices), 1);
+    }
 }
 
 fn dynamic_read_writes_with_block_gas_limit(num_txns: usize, maybe_block_gas_limit: Option<u64>) {
@@ -216,7 +363,7 @@ fn deltas_writes_mixed_with_block_gas_limit(num_txns: usize, maybe_block_gas_lim
             maybe_block_gas_limit,
             None,
         )
-        .execute_transactions_parallel((), &transactions, &data_view);
+        .execute_transactions_parallel((), &transactions, &data_view, None);
 
         BaselineOutput::generate(&transactions, maybe_block_gas_limit).assert_output(&output);
     }
@@ -270,7 +417,7 @@ fn deltas_resolver_with_block_gas_limit(num_txns: usize, maybe_block_gas_limit:
             maybe_block_gas_limit,
             None,
         )
-        .execute_transactions_parallel((), &transactions, &data_view);
+        .execute_transactions_parallel((), &transactions, &data_view, None);
 
         BaselineOutput::generate(&transactions, maybe_block_gas_limit).assert_output(&output);
     }
@@ -429,7 +576,7 @@ fn publishing_fixed_params_with_block_gas_limit(
         maybe_block_gas_limit,
         None,
     )
-    .execute_transactions_parallel((), &transactions, &data_view);
+    .execute_transactions_parallel((), &transactions, &data_view, None);
     assert_ok!(output);
 
     // Adjust the reads of txn indices[2] to contain module read to key 42.
@@ -475,12 +622,155 @@ fn publishing_fixed_params_with_block_gas_limit(
             Some(max(w_index, r_index) as u64 * MAX_GAS_PER_TXN + 1),
             None,
         ) // Ensure enough gas limit to commit the module txns (4 is maximum gas per txn)
-        .execute_transactions_parallel((), &transactions, &data_view);
+        .execute_transactions_parallel((), &transactions, &data_view, None);
 
         assert_eq!(output.unwrap_err(), Error::ModulePathReadWrite);
     }
 }
 
+#[test]
+fn schedule_record_and_replay() {
+    let mut runner = TestRunner::default();
+
+    let universe = vec(any::<[u8; 32]>(), 10)
+        .new_tree(&mut runner)
+        .expect("creating a new value should succeed")
+        .current();
+    let transaction_gen = vec(
+        any_with::<TransactionGen<[u8; 32]>>(TransactionGenParams::new_dynamic()),
+        1000,
+    )
+    .new_tree(&mut runner)
+    .expect("creating a new value should succeed")
+    .current();
+    // Incarnation counters of the mock transactions must start from zero for each execution.
+    let materialize =
+        || -> Vec<MockTransaction<KeyType<[u8; 32]>, ValueType<[u8; 32]>, MockEvent>> {
+            transaction_gen
+                .iter()
+                .cloned()
+                .map(|txn_gen| txn_gen.materialize(&universe, (false, false)))
+                .collect()
+        };
+
+    let data_view = EmptyDataView::<KeyType<[u8; 32]>, ValueType<[u8; 32]>> {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    );
+    let block_executor = |schedule_mode| {
+        BlockExecutor::<
+            MockTransaction<KeyType<[u8; 32]>, ValueType<[u8; 32]>, MockEvent>,
+            MockTask<KeyType<[u8; 32]>, ValueType<[u8; 32]>, MockEvent>,
+            EmptyDataView<KeyType<[u8; 32]>, ValueType<[u8; 32]>>,
+            NoOpTransactionCommitHook<
+                MockOutput<KeyType<[u8; 32]>, ValueType<[u8; 32]>, MockEvent>,
+                usize,
+            >,
+            ExecutableTestType,
+        >::new(num_cpus::get(), executor_thread_pool.clone(), None, None)
+        .with_schedule_mode(schedule_mode)
+    };
+
+    let transactions = materialize();
+    let recording_executor = block_executor(ScheduleMode::Record);
+    let output =
+        recording_executor.execute_transactions_parallel((), &transactions, &data_view, None);
+    BaselineOutput::generate(&transactions, None).assert_output(&output);
+    let schedule = recording_executor
+        .take_recorded_schedule()
+        .expect("Schedule must be recorded");
+    assert_eq!(schedule.num_txns(), transactions.len() as u32);
+
+    let path = env::temp_dir().join(format!(
+        "block_stm_schedule_{}.bcs",
+        rand::thread_rng().gen::<u64>()
+    ));
+    schedule.save(&path).unwrap();
+    let loaded_schedule = Schedule::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded_schedule, schedule);
+
+    // Replay panics if any step diverges from the recorded schedule.
+    let transactions = materialize();
+    let replaying_executor = block_executor(ScheduleMode::Replay(loaded_schedule));
+    let output =
+        replaying_executor.execute_transactions_parallel((), &transactions, &data_view, None);
+    BaselineOutput::generate(&transactions, None).assert_output(&output);
+    assert!(replaying_executor.take_recorded_schedule().is_none());
+}
+
+#[test]
+fn conflict_stats() {
+    let mut runner = TestRunner::default();
+
+    let universe = vec(any::<[u8; 32]>(), 10)
+        .new_tree(&mut runner)
+        .expect("creating a new value should succeed")
+        .current();
+    let transactions: Vec<MockTransaction<KeyType<[u8; 32]>, ValueType<[u8; 32]>, MockEvent>> =
+        vec(
+            any_with::<TransactionGen<[u8; 32]>>(TransactionGenParams::new_dynamic()),
+            1000,
+        )
+        .new_tree(&mut runner)
+        .expect("creating a new value should succeed")
+        .current()
+        .into_iter()
+        .map(|txn_gen| txn_gen.materialize(&universe, (false, false)))
+        .collect();
+
+    let data_view = EmptyDataView::<KeyType<[u8; 32]>, ValueType<[u8; 32]>> {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    );
+    let block_executor = BlockExecutor::<
+        MockTransaction<KeyType<[u8; 32]>, ValueType<[u8; 32]>, MockEvent>,
+        MockTask<KeyType<[u8; 32]>, ValueType<[u8; 32]>, MockEvent>,
+        EmptyDataView<KeyType<[u8; 32]>, ValueType<[u8; 32]>>,
+        NoOpTransactionCommitHook<
+            MockOutput<KeyType<[u8; 32]>, ValueType<[u8; 32]>, MockEvent>,
+            usize,
+        >,
+        ExecutableTestType,
+    >::new(num_cpus::get(), executor_thread_pool, None, None)
+    .with_conflict_stats();
+
+    let output = block_executor.execute_transactions_parallel((), &transactions, &data_view, None);
+    BaselineOutput::generate(&transactions, None).assert_output(&output);
+
+    let stats = block_executor
+        .take_conflict_stats()
+        .expect("Conflict stats must be collected");
+    assert_eq!(stats.len(), transactions.len());
+    for (txn, txn_stats) in transactions.iter().zip(stats.iter()) {
+        if let MockTransaction::Write {
+            incarnation_counter,
+            incarnation_behaviors: _,
+        } = txn
+        {
+            // Each incarnation executes the mock transaction once.
+            assert_eq!(
+                txn_stats.num_incarnations() as usize,
+                incarnation_counter.load(Ordering::SeqCst)
+            );
+        }
+        assert!(txn_stats
+            .conflicting_keys()
+            .iter()
+            .all(|KeyType(key, _)| universe.contains(key)));
+    }
+}
+
 #[test]
 fn dynamic_read_writes() {
     dynamic_read_writes_with_block_gas_limit(3000, None);
@@ -0,0 +1,353 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording and replaying of Block-STM schedules for debugging.
+//!
+//! When enabled, the worker threads of parallel execution take turns: a worker holds the turn
+//! from one scheduler operation (a step) until it reaches its next step, waits on a dependency
+//! or is done. Execution is thus serialized, and the order of the steps fully determines the
+//! interleaving. Recording logs the steps in the order the workers happened to take turns,
+//! while replaying forces the workers to take turns in the logged order, and panics as soon
+//! as the outcome of a step diverges from the log.
+//!
+//! Note: replay requires the same block, concurrency level and a thread pool with at least as
+//! many threads as the concurrency level (so all workers can run at the same time).
+
+use crate::scheduler::{ExecutionTaskType, SchedulerTask, Wave};
+use anyhow::Result;
+use aptos_mvhashmap::types::{Incarnation, TxnIndex};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fs,
+    path::Path,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+/// A task returned by the scheduler, without the condition variable of wakeup tasks.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ScheduledTask {
+    Execution {
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+    },
+    Wakeup {
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+    },
+    Validation {
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        wave: Wave,
+    },
+    NoTask,
+    Done,
+}
+
+impl From<&SchedulerTask> for ScheduledTask {
+    fn from(task: &SchedulerTask) -> Self {
+        match task {
+            SchedulerTask::ExecutionTask((txn_idx, incarnation), ExecutionTaskType::Execution) => {
+                ScheduledTask::Execution {
+                    txn_idx: *txn_idx,
+                    incarnation: *incarnation,
+                }
+            },
+            SchedulerTask::ExecutionTask((txn_idx, incarnation), ExecutionTaskType::Wakeup(_)) => {
+                ScheduledTask::Wakeup {
+                    txn_idx: *txn_idx,
+                    incarnation: *incarnation,
+                }
+            },
+            SchedulerTask::ValidationTask((txn_idx, incarnation), wave) => {
+                ScheduledTask::Validation {
+                    txn_idx: *txn_idx,
+                    incarnation: *incarnation,
+                    wave: *wave,
+                }
+            },
+            SchedulerTask::NoTask => ScheduledTask::NoTask,
+            SchedulerTask::Done => ScheduledTask::Done,
+        }
+    }
+}
+
+/// A scheduler operation performed by a worker, together with its outcome.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ScheduleEvent {
+    NextTask(ScheduledTask),
+    FinishExecution {
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        next_task: ScheduledTask,
+    },
+    TryAbort {
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        aborted: bool,
+    },
+    FinishAbort {
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        next_task: ScheduledTask,
+    },
+    FinishValidation {
+        txn_idx: TxnIndex,
+        wave: Wave,
+    },
+    TryCommit(Option<TxnIndex>),
+    Halt,
+    /// The worker executing txn_idx read an estimate of dep_txn_idx, and was suspended if the
+    /// dependency was not resolved.
+    WaitForDependency {
+        txn_idx: TxnIndex,
+        dep_txn_idx: TxnIndex,
+        suspended: bool,
+    },
+    /// The worker executing txn_idx resumed after being suspended on a dependency.
+    Resume {
+        txn_idx: TxnIndex,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ScheduleStep {
+    pub worker_idx: usize,
+    pub event: ScheduleEvent,
+}
+
+/// The steps of a parallel execution, in the order the workers performed them.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Schedule {
+    concurrency_level: usize,
+    num_txns: TxnIndex,
+    steps: Vec<ScheduleStep>,
+}
+
+impl Schedule {
+    pub fn concurrency_level(&self) -> usize {
+        self.concurrency_level
+    }
+
+    pub fn num_txns(&self) -> TxnIndex {
+        self.num_txns
+    }
+
+    pub fn steps(&self) -> &[ScheduleStep] {
+        &self.steps
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, bcs::to_bytes(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(bcs::from_bytes(&fs::read(path)?)?)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ScheduleMode {
+    /// Record the schedule, which is available after execution via
+    /// BlockExecutor::take_recorded_schedule.
+    Record,
+    /// Force the interleaving of the given (previously recorded) schedule.
+    Replay(Schedule),
+}
+
+struct ControllerState {
+    // The worker whose turn it is, if any.
+    holder: Option<usize>,
+    // When recording, the workers waiting for their turn, in order of arrival.
+    waiting: VecDeque<usize>,
+    // The recorded steps, or the steps to replay.
+    steps: Vec<ScheduleStep>,
+    // When replaying, the index of the next step.
+    cursor: usize,
+    // Set if a worker panicked (e.g. due to a diverging replay), to fail the other workers
+    // instead of letting them wait for their turn forever.
+    aborted: bool,
+}
+
+/// Coordinates the turns of the workers in a single parallel execution.
+pub(crate) struct ScheduleController {
+    replay: bool,
+    concurrency_level: usize,
+    num_txns: TxnIndex,
+    state: Mutex<ControllerState>,
+    cvar: Condvar,
+}
+
+impl ScheduleController {
+    pub(crate) fn new(mode: &ScheduleMode, concurrency_level: usize, num_txns: TxnIndex) -> Self {
+        let (replay, steps) = match mode {
+            ScheduleMode::Record => (false, Vec::new()),
+            ScheduleMode::Replay(schedule) => {
+                assert_eq!(
+                    (schedule.concurrency_level, schedule.num_txns),
+                    (concurrency_level, num_txns),
+                    "[BlockSTM]: Schedule recorded with a different concurrency level or block"
+                );
+                (true, schedule.steps.clone())
+            },
+        };
+
+        Self {
+            replay,
+            concurrency_level,
+            num_txns,
+            state: Mutex::new(ControllerState {
+                holder: None,
+                waiting: VecDeque::new(),
+                steps,
+                cursor: 0,
+                aborted: false,
+            }),
+            cvar: Condvar::new(),
+        }
+    }
+
+    /// Returns the recorded schedule, or None when replaying.
+    pub(crate) fn into_recorded_schedule(self) -> Option<Schedule> {
+        (!self.replay).then(|| Schedule {
+            concurrency_level: self.concurrency_level,
+            num_txns: self.num_txns,
+            steps: self
+                .state
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+                .steps,
+        })
+    }
+
+    // Poisoning is ignored, so that a panicking worker fails the others via the aborted flag.
+    fn lock(&self) -> MutexGuard<'_, ControllerState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_turn(&self, state: &ControllerState, worker_idx: usize) -> bool {
+        state.holder.is_none()
+            && if self.replay {
+                // Once the steps are exhausted, let the worker proceed to detect the divergence.
+                state
+                    .steps
+                    .get(state.cursor)
+                    .map_or(true, |step| step.worker_idx == worker_idx)
+            } else {
+                state.waiting.front() == Some(&worker_idx)
+            }
+    }
+
+    fn step<R>(
+        &self,
+        worker_idx: usize,
+        op: impl FnOnce() -> R,
+        event: impl FnOnce(&R) -> ScheduleEvent,
+    ) -> R {
+        let mut state = self.lock();
+        if state.holder == Some(worker_idx) {
+            state.holder = None;
+            self.cvar.notify_all();
+        }
+        if !self.replay {
+            state.waiting.push_back(worker_idx);
+        }
+        loop {
+            assert!(
+                !state.aborted,
+                "[BlockSTM]: Schedule aborted by another worker"
+            );
+            if self.is_turn(&state, worker_idx) {
+                break;
+            }
+            state = self
+                .cvar
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        if !self.replay {
+            state.waiting.pop_front();
+        }
+
+        let ret = op();
+        let step = ScheduleStep {
+            worker_idx,
+            event: event(&ret),
+        };
+        if self.replay {
+            if state.steps.get(state.cursor) != Some(&step) {
+                state.aborted = true;
+                self.cvar.notify_all();
+                panic!(
+                    "[BlockSTM]: Schedule replay diverged at step {}: expected {:?}, got {:?}",
+                    state.cursor,
+                    state.steps.get(state.cursor),
+                    step
+                );
+            }
+            state.cursor += 1;
+        } else {
+            state.steps.push(step);
+        }
+        state.holder = Some(worker_idx);
+        ret
+    }
+
+    fn release(&self, worker_idx: usize) {
+        let mut state = self.lock();
+        if state.holder == Some(worker_idx) {
+            state.holder = None;
+        }
+        if thread::panicking() {
+            state.aborted = true;
+        }
+        self.cvar.notify_all();
+    }
+}
+
+/// The handle of a worker to the schedule controller, a no-op unless recording or replaying.
+#[derive(Clone, Copy)]
+pub(crate) struct WorkerSchedule<'a> {
+    maybe_controller: Option<&'a ScheduleController>,
+    worker_idx: usize,
+}
+
+impl<'a> WorkerSchedule<'a> {
+    pub(crate) fn new(maybe_controller: Option<&'a ScheduleController>, worker_idx: usize) -> Self {
+        Self {
+            maybe_controller,
+            worker_idx,
+        }
+    }
+
+    /// Performs the scheduler operation in the worker's turn, and ends the turn of the worker
+    /// at its next step (or release).
+    pub(crate) fn step<R>(
+        &self,
+        op: impl FnOnce() -> R,
+        event: impl FnOnce(&R) -> ScheduleEvent,
+    ) -> R {
+        match self.maybe_controller {
+            Some(controller) => controller.step(self.worker_idx, op, event),
+            None => op(),
+        }
+    }
+
+    /// Ends the turn of the worker, which must be called before blocking outside of a step.
+    pub(crate) fn release(&self) {
+        if let Some(controller) = self.maybe_controller {
+            controller.release(self.worker_idx);
+        }
+    }
+}
+
+/// Releases the turn when the worker exits, including when it panics.
+pub(crate) struct WorkerScheduleGuard<'a>(pub(crate) WorkerSchedule<'a>);
+
+impl<'a> Drop for WorkerScheduleGuard<'a> {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
@@ -9,6 +9,8 @@ use crossbeam::utils::CachePadded;
 use parking_lot::{RwLock, RwLockUpgradableReadGuard};
 use std::{
     cmp::{max, min},
+    collections::HashMap,
+    hash::Hash,
     hint,
     ops::DerefMut,
     sync::{
@@ -238,6 +240,15 @@ pub struct Scheduler {
 
     /// Shared marker that is set when a thread detects that all txns can be committed.
     done_marker: CachePadded<AtomicBool>,
+
+    /// An index i maps to the estimated dependency of transaction i, i.e. the last transaction
+    /// before i that is estimated to write a key that i is estimated to access. Empty if no
+    /// estimates were provided, see new_with_estimated_write_sets.
+    estimated_dependency: Vec<Option<TxnIndex>>,
+    /// An index i maps to indices of transactions whose first incarnation was delayed until
+    /// transaction i is executed, i.e. they should be executed once transaction i finishes its
+    /// first execution. Empty if no estimates were provided.
+    delayed_txns: Vec<CachePadded<Mutex<Vec<TxnIndex>>>>,
 }
 
 /// Public Interfaces for the Scheduler
@@ -263,6 +274,50 @@ impl Scheduler {
             execution_idx: AtomicU32::new(0),
             validation_idx: AtomicU64::new(0),
             done_marker: CachePadded::new(AtomicBool::new(false)),
+            estimated_dependency: Vec::new(),
+            delayed_txns: Vec::new(),
+        }
+    }
+
+    /// Creates a scheduler that, given the estimated write-set of each transaction (e.g. from
+    /// the block partitioner or a prior execution), delays the first incarnation of a transaction
+    /// until the last earlier transaction estimated to write an overlapping key is executed.
+    /// Estimated write-sets are also treated as read-sets (most writes are read-modify-writes),
+    /// so the delay avoids executing a transaction that would likely read a value about to be
+    /// overwritten, and hence be aborted. Estimates only affect scheduling: a transaction
+    /// accessing keys outside of its estimate is still handled by validation and re-execution.
+    pub fn new_with_estimated_write_sets<K: Hash + Eq>(
+        num_txns: TxnIndex,
+        estimated_write_sets: &[Vec<K>],
+    ) -> Self {
+        assert_eq!(
+            estimated_write_sets.len(),
+            num_txns as usize,
+            "An estimated write-set is required for every transaction"
+        );
+
+        let mut last_writer: HashMap<&K, TxnIndex> = HashMap::new();
+        let estimated_dependency = estimated_write_sets
+            .iter()
+            .enumerate()
+            .map(|(txn_idx, keys)| {
+                let dep_txn_idx = keys
+                    .iter()
+                    .filter_map(|key| last_writer.get(key).copied())
+                    .max();
+                for key in keys {
+                    last_writer.insert(key, txn_idx as TxnIndex);
+                }
+                dep_txn_idx
+            })
+            .collect();
+
+        Self {
+            estimated_dependency,
+            delayed_txns: (0..num_txns)
+                .map(|_| CachePadded::new(Mutex::new(Vec::new())))
+                .collect(),
+            ..Self::new(num_txns)
         }
     }
 
@@ -408,8 +463,9 @@ impl Scheduler {
         let mut stored_deps = self.txn_dependency[dep_txn_idx as usize].lock();
 
         // Note: is_executed & suspend calls acquire (a different, status) mutex, while holding
-        // (dependency) mutex. This is the only place in scheduler where a thread may hold > 1
-        // mutexes. Thus, acquisitions always happen in the same order (here), may not deadlock.
+        // (dependency) mutex. Other than delay_execution, this is the only place in scheduler
+        // where a thread may hold > 1 mutexes. Thus, acquisitions always happen in the same
+        // order (status mutex last), may not deadlock.
 
         if self.is_executed(dep_txn_idx, true).is_some() {
             // Current status of dep_txn_idx is 'executed', so the dependency got resolved.
@@ -475,8 +531,17 @@ impl Scheduler {
             // Holding the lock, take dependency vector.
             std::mem::take(&mut stored_deps)
         };
-
-        // Mark dependencies as resolved and find the minimum index among them.
+        // Transactions delayed until txn_idx is executed. Taken after setting the executed
+        // status, so a transaction can not be delayed (see delay_execution) after this point.
+        let delayed_txns: Vec<TxnIndex> = self
+            .delayed_txns
+            .get(txn_idx as usize)
+            .map_or_else(Vec::new, |delayed_txns| {
+                std::mem::take(&mut *delayed_txns.lock())
+            });
+
+        // Mark dependencies as resolved and find the minimum index among them and the
+        // delayed transactions (still in Ready status).
         let min_dep = txn_deps
             .into_iter()
             .map(|dep| {
@@ -486,6 +551,7 @@ impl Scheduler {
 
                 dep
             })
+            .chain(delayed_txns)
             .min();
         if let Some(execution_target_idx) = min_dep {
             // Decrease the execution index as necessary to ensure resolved dependencies
@@ -770,6 +836,11 @@ impl Scheduler {
             return None;
         }
 
+        if self.delay_execution(idx_to_execute) {
+            // Will be re-scheduled once the estimated dependency is executed.
+            return None;
+        }
+
         // If successfully incarnated (changed status from ready to executing),
         // return version for execution task, otherwise None.
         self.try_incarnate(idx_to_execute)
@@ -778,6 +849,35 @@ impl Scheduler {
             })
     }
 
+    /// Returns true if the first incarnation of the transaction must wait for its estimated
+    /// dependency to be executed. In this case the transaction remains Ready, and is recorded to
+    /// be re-scheduled for execution when the dependency finishes its execution.
+    fn delay_execution(&self, txn_idx: TxnIndex) -> bool {
+        let dep_txn_idx = match self.estimated_dependency.get(txn_idx as usize) {
+            Some(Some(dep_txn_idx)) => *dep_txn_idx,
+            _ => return false,
+        };
+        // Only delay transactions that have not started executing. In particular, a Ready
+        // status with a Wakeup task has a suspended execution waiting on it.
+        if !matches!(
+            *self.txn_status[txn_idx as usize].0.read(),
+            ExecutionStatus::Ready(0, ExecutionTaskType::Execution)
+        ) {
+            return false;
+        }
+
+        let mut delayed_txns = self.delayed_txns[dep_txn_idx as usize].lock();
+        // Note: never_executed acquires a status lock, while holding the delayed txns mutex.
+        // The lock is never acquired while holding a status lock, so this may not deadlock.
+        // finish_execution of dep_txn_idx sets the executed status before taking the delayed
+        // txns, so if the dependency is not executed here, it will re-schedule txn_idx.
+        if !self.never_executed(dep_txn_idx) {
+            return false;
+        }
+        delayed_txns.push(txn_idx);
+        true
+    }
+
     /// Put a transaction in a suspended state, with a condition variable that can be
     /// used to wake it up after the dependency is resolved.
     /// Return true when the txn is successfully suspended.
@@ -1,17 +1,29 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::task::TransactionOutput;
+use crate::{
+    task::{Transaction, TransactionOutput},
+    txn_last_input_output::TxnConflictStats,
+};
 use aptos_mvhashmap::types::TxnIndex;
 
 /// An interface for listening to transaction commit events. The listener is called only once
 /// for each transaction commit.
 pub trait TransactionCommitHook: Send + Sync {
-    type Output;
+    type Output: TransactionOutput;
 
     fn on_transaction_committed(&self, txn_idx: TxnIndex, output: &Self::Output);
 
     fn on_execution_aborted(&self, txn_idx: TxnIndex);
+
+    /// Called before the commit (or abort) events of the transaction, if the block executor
+    /// collects conflict statistics.
+    fn on_transaction_conflict_stats(
+        &self,
+        _txn_idx: TxnIndex,
+        _stats: &TxnConflictStats<<<Self::Output as TransactionOutput>::Txn as Transaction>::Key>,
+    ) {
+    }
 }
 
 pub struct NoOpTransactionCommitHook<T, E> {
@@ -6,6 +6,7 @@ use crate::{
     task::{ExecutionStatus, Transaction, TransactionOutput},
 };
 use anyhow::anyhow;
+use aptos_infallible::Mutex;
 use aptos_mvhashmap::types::{Incarnation, TxnIndex, Version};
 use aptos_types::{
     access_path::AccessPath, executable::ModulePath, fee_statement::FeeStatement,
@@ -124,6 +125,29 @@ impl<K: ModulePath> ReadDescriptor<K> {
     }
 }
 
+/// Conflict statistics of a transaction in parallel execution: the keys whose reads failed
+/// validation and caused the aborts (and hence, re-executions) of the transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxnConflictStats<K> {
+    // One key per abort, in the order of aborts (a key may repeat).
+    conflicting_keys: Vec<K>,
+}
+
+impl<K> TxnConflictStats<K> {
+    pub fn new(conflicting_keys: Vec<K>) -> Self {
+        Self { conflicting_keys }
+    }
+
+    /// Number of incarnations, i.e. executions of the transaction.
+    pub fn num_incarnations(&self) -> Incarnation {
+        self.conflicting_keys.len() as Incarnation + 1
+    }
+
+    pub fn conflicting_keys(&self) -> &[K] {
+        &self.conflicting_keys
+    }
+}
+
 pub struct TxnLastInputOutput<K, T: TransactionOutput, E: Debug> {
     inputs: Vec<CachePadded<ArcSwapOption<TxnInput<K>>>>, // txn_idx -> input.
 
@@ -136,10 +160,13 @@ pub struct TxnLastInputOutput<K, T: TransactionOutput, E: Debug> {
     module_reads: DashSet<AccessPath>,
 
     module_read_write_intersection: AtomicBool,
+
+    // Keys of the reads that caused aborts, only if conflict statistics are collected.
+    conflicting_keys: Option<Vec<CachePadded<Mutex<Vec<K>>>>>, // txn_idx -> keys.
 }
 
 impl<K: ModulePath, T: TransactionOutput, E: Debug + Send + Clone> TxnLastInputOutput<K, T, E> {
-    pub fn new(num_txns: TxnIndex) -> Self {
+    pub fn new(num_txns: TxnIndex, collect_conflict_stats: bool) -> Self {
         Self {
             inputs: (0..num_txns)
                 .map(|_| CachePadded::new(ArcSwapOption::empty()))
@@ -150,6 +177,11 @@ impl<K: ModulePath, T: TransactionOutput, E: Debug + Send + Clone> TxnLastInputO
             module_writes: DashSet::new(),
             module_reads: DashSet::new(),
             module_read_write_intersection: AtomicBool::new(false),
+            conflicting_keys: collect_conflict_stats.then(|| {
+                (0..num_txns)
+                    .map(|_| CachePadded::new(Mutex::new(Vec::new())))
+                    .collect()
+            }),
         }
     }
 
@@ -217,6 +249,34 @@ impl<K: ModulePath, T: TransactionOutput, E: Debug + Send + Clone> TxnLastInputO
         Ok(())
     }
 
+    /// Records the key of the read that failed validation and caused the abort of the
+    /// transaction, if conflict statistics are collected.
+    pub(crate) fn record_conflict(&self, txn_idx: TxnIndex, key: K) {
+        if let Some(conflicting_keys) = &self.conflicting_keys {
+            conflicting_keys[txn_idx as usize].lock().push(key);
+        }
+    }
+
+    /// Returns the conflict statistics of the transaction, if collected. The statistics are
+    /// final once the transaction is committed.
+    pub(crate) fn conflict_stats(&self, txn_idx: TxnIndex) -> Option<TxnConflictStats<K>>
+    where
+        K: Clone,
+    {
+        self.conflicting_keys.as_ref().map(|conflicting_keys| {
+            TxnConflictStats::new(conflicting_keys[txn_idx as usize].lock().clone())
+        })
+    }
+
+    // Must be executed after parallel execution is done, grabs conflict statistics.
+    pub(crate) fn take_conflict_stats(&self, txn_idx: TxnIndex) -> Option<TxnConflictStats<K>> {
+        self.conflicting_keys.as_ref().map(|conflicting_keys| {
+            TxnConflictStats::new(std::mem::take(
+                &mut *conflicting_keys[txn_idx as usize].lock(),
+            ))
+        })
+    }
+
     pub(crate) fn module_publishing_may_race(&self) -> bool {
         self.module_read_write_intersection.load(Ordering::Acquire)
     }
@@ -52,7 +52,7 @@ where
         NoOpTransactionCommitHook<MockOutput<K, V, E>, usize>,
         ExecutableTestType,
     >::new(num_cpus::get(), executor_thread_pool, None, None)
-    .execute_transactions_parallel((), &transactions, &data_view);
+    .execute_transactions_parallel((), &transactions, &data_view, None);
 
     let baseline = BaselineOutput::generate(&transactions, None);
     baseline.assert_output(&output);
@@ -528,6 +528,45 @@ fn scheduler_dependency() {
     ));
 }
 
+#[test]
+fn scheduler_estimated_dependency() {
+    // Estimated dependencies: 2 on 0 (key 1) and 3 on 2 (key 3).
+    let s = Scheduler::new_with_estimated_write_sets(4, &[vec![1], vec![2], vec![1, 3], vec![3]]);
+
+    for i in 0..2 {
+        assert!(matches!(
+            s.next_task(false),
+            SchedulerTask::ExecutionTask((j, 0), ExecutionTaskType::Execution) if j == i
+        ));
+    }
+    // Transactions 2 and 3 are delayed until their estimated dependencies are executed.
+    assert!(matches!(s.next_task(false), SchedulerTask::NoTask));
+
+    assert!(matches!(
+        s.finish_execution(0, 0, false),
+        SchedulerTask::NoTask
+    ));
+    assert!(matches!(
+        s.next_task(false),
+        SchedulerTask::ValidationTask((0, 0), 0)
+    ));
+    assert!(matches!(
+        s.next_task(false),
+        SchedulerTask::ExecutionTask((2, 0), ExecutionTaskType::Execution)
+    ));
+    // Transaction 3 is still delayed, transaction 1 is still executing.
+    assert!(matches!(s.next_task(false), SchedulerTask::NoTask));
+
+    assert!(matches!(
+        s.finish_execution(2, 0, false),
+        SchedulerTask::NoTask
+    ));
+    assert!(matches!(
+        s.next_task(false),
+        SchedulerTask::ExecutionTask((3, 0), ExecutionTaskType::Execution)
+    ));
+}
+
 // Will return a scheduler in a state where all transactions are scheduled for
 // for execution, validation index = num_txns, and wave = 0.
 fn incarnation_one_scheduler(num_txns: TxnIndex) -> Scheduler {
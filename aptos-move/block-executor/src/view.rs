@@ -3,6 +3,7 @@
 
 use crate::{
     counters,
+    schedule_replay::{ScheduleEvent, WorkerSchedule},
     scheduler::{DependencyResult, DependencyStatus, Scheduler},
     task::Transaction,
     txn_last_input_output::ReadDescriptor,
@@ -35,6 +36,7 @@ use std::{cell::RefCell, fmt::Debug, hash::Hash, sync::Arc};
 pub(crate) struct MVHashMapView<'a, K, V: TransactionWrite, X: Executable> {
     versioned_map: &'a MVHashMap<K, V, X>,
     scheduler: &'a Scheduler,
+    schedule: WorkerSchedule<'a>,
     captured_reads: RefCell<Vec<ReadDescriptor<K>>>,
 }
 
@@ -61,10 +63,15 @@ impl<
         X: Executable,
     > MVHashMapView<'a, K, V, X>
 {
-    pub(crate) fn new(versioned_map: &'a MVHashMap<K, V, X>, scheduler: &'a Scheduler) -> Self {
+    pub(crate) fn new(
+        versioned_map: &'a MVHashMap<K, V, X>,
+        scheduler: &'a Scheduler,
+        schedule: WorkerSchedule<'a>,
+    ) -> Self {
         Self {
             versioned_map,
             scheduler,
+            schedule,
             captured_reads: RefCell::new(Vec::new()),
         }
     }
@@ -123,7 +130,14 @@ impl<
                 Err(Unresolved(_)) => return ReadResult::Unresolved,
                 Err(Dependency(dep_idx)) => {
                     // `self.txn_idx` estimated to depend on a write from `dep_idx`.
-                    match self.scheduler.wait_for_dependency(txn_idx, dep_idx) {
+                    match self.schedule.step(
+                        || self.scheduler.wait_for_dependency(txn_idx, dep_idx),
+                        |result| ScheduleEvent::WaitForDependency {
+                            txn_idx,
+                            dep_txn_idx: dep_idx,
+                            suspended: matches!(result, DependencyResult::Dependency(_)),
+                        },
+                    ) {
                         DependencyResult::Dependency(dep_condition) => {
                             let _timer = counters::DEPENDENCY_WAIT_SECONDS.start_timer();
                             // Wait on a condition variable corresponding to the encountered
@@ -140,12 +154,18 @@ impl<
                             // thread that aborted dep_idx was alive, and again, since lower txns
                             // than txn_idx are not blocked, so the execution of dep_idx will
                             // eventually finish and lead to unblocking txn_idx, contradiction.
+                            // Other workers must be able to proceed (and resolve the dependency).
+                            self.schedule.release();
                             let (lock, cvar) = &*dep_condition;
                             let mut dep_resolved = lock.lock();
                             while let DependencyStatus::Unresolved = *dep_resolved {
                                 dep_resolved = cvar.wait(dep_resolved).unwrap();
                             }
-                            if let DependencyStatus::ExecutionHalted = *dep_resolved {
+                            let halted = matches!(*dep_resolved, DependencyStatus::ExecutionHalted);
+                            drop(dep_resolved);
+                            self.schedule
+                                .step(|| (), |_| ScheduleEvent::Resume { txn_idx });
+                            if halted {
                                 return ReadResult::ExecutionHalted;
                             }
                         },
@@ -13,14 +13,14 @@ use aptos_gas_schedule::{
 };
 use aptos_language_e2e_tests::{
     account::{Account, AccountData},
-    executor::FakeExecutor,
+    executor::{FakeExecutor, FakeExecutorSnapshot},
 };
 use aptos_types::{
     access_path::AccessPath,
     account_address::AccountAddress,
-    account_config::{AccountResource, CORE_CODE_ADDRESS},
+    account_config::{AccountResource, BlockResource, CORE_CODE_ADDRESS},
     contract_event::ContractEvent,
-    on_chain_config::{FeatureFlag, GasScheduleV2, OnChainConfig},
+    on_chain_config::{ConfigurationResource, FeatureFlag, GasScheduleV2, OnChainConfig},
     state_store::{
         state_key::StateKey,
         state_value::{StateValue, StateValueMetadata},
@@ -70,6 +70,62 @@ pub struct MoveHarness {
     default_gas_unit_price: u64,
 }
 
+/// A snapshot of the state of a [`MoveHarness`], taken by [`MoveHarness::snapshot`].
+#[derive(Clone)]
+pub struct HarnessSnapshot {
+    executor: FakeExecutorSnapshot,
+    txn_seq_no: BTreeMap<AccountAddress, u64>,
+}
+
+/// A block of a multi-block scenario, run by [`MoveHarness::run_scenario_block`].
+///
+/// The block consists of the block metadata followed by the given transactions. By default, the
+/// block is proposed by the first validator one second after the previous block, and executed
+/// both sequentially and in parallel, checking that both executions agree.
+pub struct ScenarioBlock {
+    txns: Vec<SignedTransaction>,
+    seconds_since_last_block: u64,
+    proposer: Option<AccountAddress>,
+    failed_proposer_indices: Vec<u32>,
+    parallel_only: bool,
+}
+
+impl ScenarioBlock {
+    pub fn new(txns: Vec<SignedTransaction>) -> Self {
+        Self {
+            txns,
+            seconds_since_last_block: 1,
+            proposer: None,
+            failed_proposer_indices: vec![],
+            parallel_only: false,
+        }
+    }
+
+    /// Advances the block time by the given number of seconds before the block. Block times must
+    /// strictly increase, so this must not be zero. If the epoch interval elapses, the block
+    /// metadata triggers reconfiguration, and the transactions run in the new epoch.
+    pub fn after_seconds(mut self, seconds: u64) -> Self {
+        self.seconds_since_last_block = seconds;
+        self
+    }
+
+    pub fn proposer(mut self, proposer: AccountAddress) -> Self {
+        self.proposer = Some(proposer);
+        self
+    }
+
+    pub fn failed_proposer_indices(mut self, failed_proposer_indices: Vec<u32>) -> Self {
+        self.failed_proposer_indices = failed_proposer_indices;
+        self
+    }
+
+    /// Executes the block only with Block-STM in parallel mode.
+    pub fn in_parallel(mut self) -> Self {
+        self.parallel_only = true;
+        self
+    }
+}
+
 impl MoveHarness {
     /// Creates a new harness.
     pub fn new() -> Self {
@@ -431,6 +487,81 @@ impl MoveHarness {
             .run_block_with_metadata(proposer, failed_proposer_indices, txns)
     }
 
+    /// Runs a block of a multi-block scenario and applies its outputs. Returns the statuses of the
+    /// transactions of the block, without the block metadata.
+    pub fn run_scenario_block(&mut self, block: ScenarioBlock) -> Vec<TransactionStatus> {
+        let ScenarioBlock {
+            txns,
+            seconds_since_last_block,
+            proposer,
+            failed_proposer_indices,
+            parallel_only,
+        } = block;
+        self.fast_forward(seconds_since_last_block);
+        let proposer = proposer.unwrap_or_else(|| self.executor.default_proposer());
+        let results = if parallel_only {
+            self.executor.run_block_with_metadata_in_parallel(
+                proposer,
+                failed_proposer_indices,
+                txns,
+            )
+        } else {
+            self.executor
+                .run_block_with_metadata(proposer, failed_proposer_indices, txns)
+        };
+        results
+            .into_iter()
+            .skip(1)
+            .map(|(status, _)| status)
+            .collect()
+    }
+
+    /// Advances the time to the end of the current epoch for the given number of epochs, running
+    /// a block at the end of each epoch to trigger reconfiguration.
+    pub fn advance_epochs(&mut self, count: u64) {
+        for _ in 0..count {
+            let epoch = self.current_epoch();
+            let configuration = self
+                .executor
+                .read_resource::<ConfigurationResource>(&CORE_CODE_ADDRESS)
+                .unwrap();
+            let epoch_end =
+                configuration.last_reconfiguration_time() + self.epoch_interval_micros();
+            let block_time = std::cmp::max(epoch_end, self.executor.get_block_time() + 1);
+            self.executor.new_block_with_timestamp(block_time);
+            assert_eq!(self.current_epoch(), epoch + 1, "reconfiguration expected");
+        }
+    }
+
+    pub fn current_epoch(&self) -> u64 {
+        self.executor
+            .read_resource::<ConfigurationResource>(&CORE_CODE_ADDRESS)
+            .unwrap()
+            .epoch()
+    }
+
+    pub fn epoch_interval_micros(&self) -> u64 {
+        self.executor
+            .read_resource::<BlockResource>(&CORE_CODE_ADDRESS)
+            .unwrap()
+            .epoch_interval()
+    }
+
+    /// Takes a snapshot of the state of the harness, which can later be restored with
+    /// [`Self::restore`] to run alternative scenarios from the same state.
+    pub fn snapshot(&self) -> HarnessSnapshot {
+        HarnessSnapshot {
+            executor: self.executor.snapshot(),
+            txn_seq_no: self.txn_seq_no.clone(),
+        }
+    }
+
+    /// Restores the state of the harness from a snapshot, discarding all changes since.
+    pub fn restore(&mut self, snapshot: HarnessSnapshot) {
+        self.executor.restore(snapshot.executor);
+        self.txn_seq_no = snapshot.txn_seq_no;
+    }
+
     pub fn read_state_value(&self, state_key: &StateKey) -> Option<StateValue> {
         self.executor.read_state_value(state_key)
     }
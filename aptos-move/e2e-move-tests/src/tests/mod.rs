@@ -29,6 +29,7 @@ mod offer_signer_capability;
 mod per_category_gas_limits;
 mod resource_groups;
 mod rotate_auth_key;
+mod scenario;
 mod scripts;
 mod simple_defi;
 mod smart_data_structures;
@@ -0,0 +1,82 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, get_stake_pool, setup_staking, MoveHarness, ScenarioBlock};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::account_address::AccountAddress;
+
+#[test]
+fn test_scenario_rewards_and_lockup_expiry() {
+    let mut harness = MoveHarness::new();
+    let validator = harness.new_account_at(AccountAddress::from_hex_literal("0x123").unwrap());
+    let user = harness.new_account_at(AccountAddress::from_hex_literal("0x234").unwrap());
+    let validator_address = *validator.address();
+
+    let stake_amount = 25_000_000;
+    assert_success!(setup_staking(&mut harness, &validator, stake_amount));
+    harness.advance_epochs(1);
+
+    // Unlock half of the stake in a block with other transactions, executed in parallel.
+    let txns = vec![
+        harness
+            .create_transaction_payload(&validator, aptos_stdlib::stake_unlock(stake_amount / 2)),
+        harness.create_transaction_payload(
+            &user,
+            aptos_stdlib::aptos_account_transfer(validator_address, 1_000),
+        ),
+        harness.create_transaction_payload(
+            &user,
+            aptos_stdlib::aptos_account_transfer(validator_address, 1_000),
+        ),
+    ];
+    let block = ScenarioBlock::new(txns)
+        .proposer(validator_address)
+        .in_parallel();
+    for status in harness.run_scenario_block(block) {
+        assert_success!(status);
+    }
+    let stake_pool = get_stake_pool(&harness, &validator_address);
+    assert_eq!(stake_pool.pending_inactive, stake_amount / 2);
+
+    let epoch = harness.current_epoch();
+    let block_time = harness.executor.get_block_time();
+    let snapshot = harness.snapshot();
+
+    // The validator proposed a block in the epoch, so both its active and pending inactive stake
+    // earn rewards. Once the lockup expires, the pending inactive stake becomes withdrawable.
+    harness.advance_epochs(1);
+    let stake_pool = get_stake_pool(&harness, &validator_address);
+    assert!(stake_pool.active > stake_amount / 2);
+    while harness.executor.get_block_time_seconds() < stake_pool.locked_until_secs {
+        harness.advance_epochs(1);
+    }
+    let stake_pool = get_stake_pool(&harness, &validator_address);
+    assert_eq!(stake_pool.pending_inactive, 0);
+    assert!(stake_pool.inactive > stake_amount / 2);
+    assert_success!(harness.run_transaction_payload(
+        &validator,
+        aptos_stdlib::stake_withdraw(stake_pool.inactive)
+    ));
+
+    // Going back to before the lockup expired, the validator can reactivate the stake instead.
+    harness.restore(snapshot);
+    assert_eq!(harness.current_epoch(), epoch);
+    assert_eq!(harness.executor.get_block_time(), block_time);
+    let stake_pool = get_stake_pool(&harness, &validator_address);
+    assert_eq!(stake_pool.pending_inactive, stake_amount / 2);
+    assert_eq!(stake_pool.inactive, 0);
+
+    let txn = harness.create_transaction_payload(
+        &validator,
+        aptos_stdlib::stake_reactivate_stake(stake_amount / 2),
+    );
+    let block = ScenarioBlock::new(vec![txn])
+        .after_seconds(60)
+        .proposer(validator_address);
+    assert_success!(harness.run_scenario_block(block).pop().unwrap());
+    harness.advance_epochs(2);
+    let stake_pool = get_stake_pool(&harness, &validator_address);
+    assert_eq!(stake_pool.pending_inactive, 0);
+    assert_eq!(stake_pool.inactive, 0);
+    assert!(stake_pool.active > stake_amount);
+}
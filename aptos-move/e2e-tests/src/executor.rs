@@ -104,6 +104,13 @@ pub struct FakeExecutor {
     chain_id: u8,
 }
 
+/// A snapshot of the state of a [`FakeExecutor`], taken by [`FakeExecutor::snapshot`].
+#[derive(Clone)]
+pub struct FakeExecutorSnapshot {
+    data_store: FakeDataStore,
+    block_time: u64,
+}
+
 pub enum GasMeterType {
     RegularMeter(Vec<u128>),
     AbstractMeter(Vec<DynamicExpression>),
@@ -434,6 +441,7 @@ impl FakeExecutor {
             usize::min(4, num_cpus::get()),
             None,
             None,
+            None,
         )
     }
 
@@ -590,18 +598,56 @@ impl FakeExecutor {
     pub fn new_block_with_timestamp(&mut self, time_microseconds: u64) {
         self.block_time = time_microseconds;
 
-        let validator_set = ValidatorSet::fetch_config(&self.data_store.as_move_resolver())
-            .expect("Unable to retrieve the validator set from storage");
-        let proposer = *validator_set.payload().next().unwrap().account_address();
         // when updating time, proposer cannot be ZERO.
+        let proposer = self.default_proposer();
         self.new_block_with_metadata(proposer, vec![])
     }
 
+    /// Returns the first validator of the current validator set, which is used as the proposer of
+    /// blocks created without an explicit one.
+    pub fn default_proposer(&self) -> AccountAddress {
+        let validator_set = ValidatorSet::fetch_config(&self.data_store.as_move_resolver())
+            .expect("Unable to retrieve the validator set from storage");
+        *validator_set.payload().next().unwrap().account_address()
+    }
+
     pub fn run_block_with_metadata(
         &mut self,
         proposer: AccountAddress,
         failed_proposer_indices: Vec<u32>,
         txns: Vec<SignedTransaction>,
+    ) -> Vec<(TransactionStatus, u64)> {
+        self.run_block_with_metadata_impl(
+            proposer,
+            failed_proposer_indices,
+            txns,
+            Self::execute_transaction_block,
+        )
+    }
+
+    /// Like [`Self::run_block_with_metadata`], but only executes the block with Block-STM in
+    /// parallel mode, instead of executing it sequentially and comparing with the parallel
+    /// execution.
+    pub fn run_block_with_metadata_in_parallel(
+        &mut self,
+        proposer: AccountAddress,
+        failed_proposer_indices: Vec<u32>,
+        txns: Vec<SignedTransaction>,
+    ) -> Vec<(TransactionStatus, u64)> {
+        self.run_block_with_metadata_impl(
+            proposer,
+            failed_proposer_indices,
+            txns,
+            Self::execute_transaction_block_parallel,
+        )
+    }
+
+    fn run_block_with_metadata_impl(
+        &mut self,
+        proposer: AccountAddress,
+        failed_proposer_indices: Vec<u32>,
+        txns: Vec<SignedTransaction>,
+        execute: impl FnOnce(&Self, Vec<Transaction>) -> Result<Vec<TransactionOutput>, VMStatus>,
     ) -> Vec<(TransactionStatus, u64)> {
         let mut txn_block: Vec<Transaction> =
             txns.into_iter().map(Transaction::UserTransaction).collect();
@@ -618,9 +664,7 @@ impl FakeExecutor {
         );
         txn_block.insert(0, Transaction::BlockMetadata(new_block_metadata));
 
-        let outputs = self
-            .execute_transaction_block(txn_block)
-            .expect("Must execute transactions");
+        let outputs = execute(self, txn_block).expect("Must execute transactions");
 
         // Check if we emit the expected event for block metadata, there might be more events for transaction fees.
         let event = outputs[0].events()[0].clone();
@@ -665,6 +709,21 @@ impl FakeExecutor {
         self.block_time / 1_000_000
     }
 
+    /// Takes a snapshot of the data store and block time, which can later be restored with
+    /// [`Self::restore`], e.g. to run alternative scenarios from the same state.
+    pub fn snapshot(&self) -> FakeExecutorSnapshot {
+        FakeExecutorSnapshot {
+            data_store: self.data_store.clone(),
+            block_time: self.block_time,
+        }
+    }
+
+    /// Restores the data store and block time from a snapshot, discarding all changes since.
+    pub fn restore(&mut self, snapshot: FakeExecutorSnapshot) {
+        self.data_store = snapshot.data_store;
+        self.block_time = snapshot.block_time;
+    }
+
     /// exec_func_record_running_time is like exec(), however, we can run a Module published under
     /// the creator address instead of 0x1, as what is currently done in exec.
     pub fn exec_func_record_running_time(
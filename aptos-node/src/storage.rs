@@ -96,16 +96,17 @@ pub fn initialize_database_and_checkpoints(
 
     // Open the database
     let instant = Instant::now();
-    let aptos_db = AptosDB::open(
+    let mut aptos_db = AptosDB::open(
         &node_config.storage.dir(),
         false, /* readonly */
-        node_config.storage.storage_pruner_config,
+        node_config.storage.storage_pruner_config.clone(),
         node_config.storage.rocksdb_configs,
         node_config.storage.enable_indexer,
         node_config.storage.buffered_state_target_items,
         node_config.storage.max_num_nodes_per_lru_cache_shard,
     )
     .map_err(|err| anyhow!("DB failed to open {}", err))?;
+    aptos_db.start_consistency_checker(node_config.storage.consistency_checker_config);
     let (aptos_db, db_rw, backup_service) =
         bootstrap_db(aptos_db, node_config.storage.backup_service_address);
 
@@ -53,6 +53,7 @@ pub fn set_aptos_vm_configurations(node_config: &NodeConfig) {
     AptosVM::set_num_proof_reading_threads_once(
         node_config.execution.num_proof_reading_threads as usize,
     );
+    AptosVM::set_prefetch_state_keys_once(node_config.execution.prefetch_state_keys);
 
     if node_config
         .execution
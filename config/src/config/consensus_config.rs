@@ -23,6 +23,8 @@ pub struct ConsensusConfig {
     pub max_sending_block_txns_quorum_store_override: u64,
     pub max_sending_block_bytes: u64,
     pub max_sending_block_bytes_quorum_store_override: u64,
+    // Estimated gas budget of a proposed block, packing payload by fee priority. None if unlimited.
+    pub max_sending_block_gas: Option<u64>,
     pub max_receiving_block_txns: u64,
     pub max_receiving_block_txns_quorum_store_override: u64,
     pub max_receiving_block_bytes: u64,
@@ -65,6 +67,51 @@ pub struct ConsensusConfig {
     // must match one of the CHAIN_HEALTH_WINDOW_SIZES values.
     pub window_for_chain_health: usize,
     pub chain_health_backoff: Vec<ChainHealthBackoffValues>,
+    // Compact round-by-round trace of consensus events, for offline analysis of liveness incidents
+    pub trace: ConsensusTraceConfig,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsensusTraceConfig {
+    pub enabled: bool,
+    // Directory of the trace files, relative to the data dir unless absolute
+    pub path: PathBuf,
+    // The current trace file is rotated once it exceeds this size
+    pub max_file_size_bytes: u64,
+    // Number of rotated trace files to keep, besides the current one
+    pub max_rotated_files: usize,
+    // Events are dropped if the writer falls behind by more than this many events
+    pub channel_size: usize,
+    #[serde(skip)]
+    data_dir: PathBuf,
+}
+
+impl Default for ConsensusTraceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: PathBuf::from("consensus_trace"),
+            max_file_size_bytes: 64 * 1024 * 1024, // 64MB
+            max_rotated_files: 4,
+            channel_size: 10_000,
+            data_dir: PathBuf::from("/opt/aptos/data"),
+        }
+    }
+}
+
+impl ConsensusTraceConfig {
+    pub fn path(&self) -> PathBuf {
+        if self.path.is_relative() {
+            self.data_dir.join(&self.path)
+        } else {
+            self.path.clone()
+        }
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -101,6 +148,7 @@ impl Default for ConsensusConfig {
             // over 1gbps link
             max_sending_block_bytes: 600 * 1024, // 600 KB
             max_sending_block_bytes_quorum_store_override: 5 * 1024 * 1024, // 5MB
+            max_sending_block_gas: None,
             max_receiving_block_txns: 10000,
             max_receiving_block_txns_quorum_store_override: 10000
                 .max(2 * MAX_SENDING_BLOCK_TXNS_QUORUM_STORE_OVERRIDE),
@@ -205,12 +253,14 @@ impl Default for ConsensusConfig {
                     backoff_proposal_delay_ms: 300,
                 },
             ],
+            trace: ConsensusTraceConfig::default(),
         }
     }
 }
 
 impl ConsensusConfig {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.trace.set_data_dir(data_dir.clone());
         self.safety_rules.set_data_dir(data_dir);
     }
 
@@ -34,6 +34,9 @@ pub struct ExecutionConfig {
     pub paranoid_hot_potato_verification: bool,
     /// Enables enhanced metrics around processed transactions
     pub processed_transactions_detailed_counters: bool,
+    /// Enables prefetching (in parallel) the state that blocks are likely to read, before
+    /// executing them
+    pub prefetch_state_keys: bool,
 }
 
 impl std::fmt::Debug for ExecutionConfig {
@@ -63,6 +66,7 @@ impl Default for ExecutionConfig {
             paranoid_type_verification: true,
             paranoid_hot_potato_verification: true,
             processed_transactions_detailed_counters: false,
+            prefetch_state_keys: false,
         }
     }
 }
@@ -11,7 +11,7 @@ use crate::{
     utils,
 };
 use aptos_crypto::{x25519, Uniform};
-use aptos_secure_storage::{CryptoStorage, KVStorage, Storage};
+use aptos_secure_storage::{KVStorage, Storage};
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_types::{
     account_address::from_identity_public_key, network_address::NetworkAddress,
@@ -183,24 +183,9 @@ impl NetworkConfig {
     }
 
     pub fn identity_key(&self) -> x25519::PrivateKey {
-        let key = match &self.identity {
-            Identity::FromConfig(config) => Some(config.key.private_key()),
-            Identity::FromStorage(config) => {
-                let storage: Storage = (&config.backend).into();
-                let key = storage
-                    .export_private_key(&config.key_name)
-                    .expect("Unable to read key");
-                let key = x25519::PrivateKey::from_ed25519_private_bytes(&key.to_bytes())
-                    .expect("Unable to convert key");
-                Some(key)
-            },
-            Identity::FromFile(config) => {
-                let identity_blob: IdentityBlob = IdentityBlob::from_file(&config.path).unwrap();
-                Some(identity_blob.network_private_key)
-            },
-            Identity::None => None,
-        };
-        key.expect("identity key should be present")
+        self.identity
+            .private_key()
+            .expect("identity key should be present")
     }
 
     pub fn identity_from_storage(&self) -> IdentityFromStorage {
@@ -6,16 +6,17 @@
 use crate::config::persistable_config::PersistableConfig;
 use crate::{
     config::{
-        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, IdentityBlob,
-        LoggerConfig, NodeConfig, SecureBackend, WaypointConfig,
+        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, Identity,
+        IdentityBlob, LoggerConfig, NodeConfig, SecureBackend, WaypointConfig,
     },
     keys::ConfigKey,
 };
-use aptos_crypto::{bls12381, Uniform};
+use aptos_crypto::{bls12381, x25519, Uniform};
 use aptos_types::{chain_id::ChainId, network_address::NetworkAddress, waypoint::Waypoint, PeerId};
 use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashSet,
     net::{SocketAddr, ToSocketAddrs},
     path::PathBuf,
 };
@@ -120,6 +121,15 @@ impl ConfigSanitizer for SafetyRulesConfig {
             }
         }
 
+        // Verify that the noise config of a remote service is complete
+        if let SafetyRulesService::Process(RemoteService {
+            noise: Some(noise_config),
+            ..
+        }) = &safety_rules_config.service
+        {
+            noise_config.sanitize(sanitizer_name)?;
+        }
+
         Ok(())
     }
 }
@@ -161,7 +171,7 @@ impl InitialSafetyRulesConfig {
 }
 
 /// Defines how safety rules should be executed
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum SafetyRulesService {
     /// This runs safety rules in the same thread as event processor
@@ -182,10 +192,13 @@ impl SafetyRulesService {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RemoteService {
     pub server_address: NetworkAddress,
+    /// Authenticates and encrypts the connection with Noise if set, it is plain TCP otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noise: Option<RemoteServiceNoiseConfig>,
 }
 
 impl RemoteService {
@@ -198,6 +211,66 @@ impl RemoteService {
     }
 }
 
+/// Noise IK configuration of a remote safety rules service. The same configuration is read by
+/// the consensus side (the client) and the safety rules side (the server) of the connection.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteServiceNoiseConfig {
+    /// Static x25519 key of this side of the connection.
+    pub identity: Identity,
+    /// Public key the safety rules service must authenticate with. Required by clients.
+    #[serde(default)]
+    pub server_public_key: Option<x25519::PublicKey>,
+    /// Public keys of the validators allowed to connect. Required by the service, which refuses
+    /// every client if empty.
+    #[serde(default)]
+    pub allowed_client_keys: HashSet<x25519::PublicKey>,
+    /// How long in milliseconds a client keeps reconnecting and retrying a request before
+    /// failing it.
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+}
+
+fn default_request_timeout_ms() -> u64 {
+    10_000
+}
+
+impl RemoteServiceNoiseConfig {
+    /// Verifies that both the client and the service can be started with this config
+    fn sanitize(&self, sanitizer_name: String) -> Result<(), Error> {
+        if let Identity::None = self.identity {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The noise identity of the safety rules service must be set!".to_string(),
+            ));
+        }
+        if self.server_public_key.is_none() {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The noise server public key of the safety rules service must be set!".to_string(),
+            ));
+        }
+        if self.allowed_client_keys.is_empty() {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The safety rules service must allow at least one noise client key!".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn private_key(&self) -> x25519::PrivateKey {
+        self.identity
+            .private_key()
+            .expect("Noise identity key should be present")
+    }
+
+    pub fn server_public_key(&self) -> x25519::PublicKey {
+        self.server_public_key
+            .expect("Noise server public key should be present")
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SafetyRulesTestConfig {
     pub author: PeerId,
@@ -228,6 +301,7 @@ impl SafetyRulesTestConfig {
 mod tests {
     use super::*;
     use crate::config::ConsensusConfig;
+    use rand::SeedableRng;
 
     #[test]
     fn test_sanitize_invalid_backend_for_mainnet() {
@@ -315,6 +389,70 @@ mod tests {
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
 
+    fn node_config_with_noise(noise_config: RemoteServiceNoiseConfig) -> NodeConfig {
+        NodeConfig {
+            consensus: ConsensusConfig {
+                safety_rules: SafetyRulesConfig {
+                    service: SafetyRulesService::Process(RemoteService {
+                        server_address: "/ip4/127.0.0.1/tcp/6185".parse().unwrap(),
+                        noise: Some(noise_config),
+                    }),
+                    initial_safety_rules_config: InitialSafetyRulesConfig::from_file(
+                        PathBuf::from("identity.yaml"),
+                        WaypointConfig::None,
+                    ),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sanitize_noise_config() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let client_key = x25519::PrivateKey::generate(&mut rng);
+        let client_public_key = client_key.public_key();
+        let server_key = x25519::PrivateKey::generate(&mut rng);
+        let noise_config = RemoteServiceNoiseConfig {
+            identity: Identity::from_config(client_key, PeerId::random()),
+            server_public_key: Some(server_key.public_key()),
+            allowed_client_keys: HashSet::from([client_public_key]),
+            request_timeout_ms: default_request_timeout_ms(),
+        };
+
+        // Verify that the config sanitizer passes for a complete noise config
+        let mut node_config = node_config_with_noise(noise_config.clone());
+        SafetyRulesConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+            .unwrap();
+
+        // Verify that the config sanitizer fails if any part of the noise config is missing
+        for noise_config in [
+            RemoteServiceNoiseConfig {
+                identity: Identity::None,
+                ..noise_config.clone()
+            },
+            RemoteServiceNoiseConfig {
+                server_public_key: None,
+                ..noise_config.clone()
+            },
+            RemoteServiceNoiseConfig {
+                allowed_client_keys: HashSet::new(),
+                ..noise_config
+            },
+        ] {
+            let mut node_config = node_config_with_noise(noise_config);
+            let error = SafetyRulesConfig::sanitize(
+                &mut node_config,
+                NodeType::Validator,
+                ChainId::testnet(),
+            )
+            .unwrap_err();
+            assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        }
+    }
+
     #[test]
     fn test_sanitize_missing_initial_safety_rules() {
         // Create a node config with a test config
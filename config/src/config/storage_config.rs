@@ -7,11 +7,13 @@ use crate::{
     utils,
 };
 use aptos_logger::warn;
-use aptos_types::chain_id::ChainId;
+use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
+use move_core_types::language_storage::TypeTag;
 use serde::{Deserialize, Serialize};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::PathBuf,
+    str::FromStr,
 };
 
 // Lru cache will consume about 2G RAM based on this default value.
@@ -119,6 +121,8 @@ pub struct StorageConfig {
     /// since genesis. To recover operation after data loss, or to bootstrap a node in fast sync
     /// mode, the indexer db needs to be copied in from another node.
     pub enable_indexer: bool,
+    /// Background verification of the data in the DB
+    pub consistency_checker_config: ConsistencyCheckerConfig,
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -127,11 +131,17 @@ pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
         prune_window: 0,
         batch_size: 0,
         user_pruning_window_offset: 0,
+        prune_window_secs: None,
+        target_size_bytes: None,
+        archived_addresses: Vec::new(),
+        archived_event_types: Vec::new(),
     },
     state_merkle_pruner_config: StateMerklePrunerConfig {
         enable: false,
         prune_window: 0,
         batch_size: 0,
+        prune_window_secs: None,
+        target_size_bytes: None,
     },
     epoch_snapshot_pruner_config: EpochSnapshotPrunerConfig {
         enable: false,
@@ -140,7 +150,7 @@ pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
     },
 };
 
-#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct LedgerPrunerConfig {
     /// Boolean to enable/disable the ledger pruner. The ledger pruner is responsible for pruning
@@ -156,6 +166,22 @@ pub struct LedgerPrunerConfig {
     pub batch_size: usize,
     /// The offset for user pruning window to adjust
     pub user_pruning_window_offset: u64,
+    /// If set, keeps the versions committed in this many seconds before the latest block,
+    /// according to block timestamps, instead of `prune_window` versions. `prune_window` is only
+    /// used until the window by time is first calculated.
+    pub prune_window_secs: Option<u64>,
+    /// If set, sizes the window so that the ledger data is estimated to fit in this many bytes on
+    /// disk, instead of keeping `prune_window` versions. If both this and `prune_window_secs` are
+    /// set, the smaller window wins.
+    pub target_size_bytes: Option<u64>,
+    /// Transactions sent by these accounts, or emitting events or writing resources under them,
+    /// are never pruned, together with their events, write sets and indices. Note that the
+    /// transaction accumulator is not pruned at all once any archival filter is set, in order to
+    /// keep serving proofs for the archived transactions.
+    pub archived_addresses: Vec<AccountAddress>,
+    /// Like `archived_addresses`, but matching transactions emitting events of these types, e.g.
+    /// "0x1::coin::DepositEvent".
+    pub archived_event_types: Vec<String>,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -168,6 +194,14 @@ pub struct StateMerklePrunerConfig {
     pub prune_window: u64,
     /// Number of stale nodes to prune a time.
     pub batch_size: usize,
+    /// If set, keeps the tree nodes of versions committed in this many seconds before the latest
+    /// block, according to block timestamps, instead of `prune_window` versions. `prune_window`
+    /// is only used until the window by time is first calculated.
+    pub prune_window_secs: Option<u64>,
+    /// If set, sizes the window so that the state merkle data is estimated to fit in this many
+    /// bytes on disk, instead of keeping `prune_window` versions. If both this and
+    /// `prune_window_secs` are set, the smaller window wins.
+    pub target_size_bytes: Option<u64>,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -190,11 +224,13 @@ impl From<EpochSnapshotPrunerConfig> for StateMerklePrunerConfig {
             enable: config.enable,
             prune_window: config.prune_window,
             batch_size: config.batch_size,
+            prune_window_secs: None,
+            target_size_bytes: None,
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, Default)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Default)]
 #[serde(default, deny_unknown_fields)]
 pub struct PrunerConfig {
     pub ledger_pruner_config: LedgerPrunerConfig,
@@ -211,6 +247,10 @@ impl Default for LedgerPrunerConfig {
             prune_window: 150_000_000,
             batch_size: 500,
             user_pruning_window_offset: 200_000,
+            prune_window_secs: None,
+            target_size_bytes: None,
+            archived_addresses: vec![],
+            archived_event_types: vec![],
         }
     }
 }
@@ -228,6 +268,8 @@ impl Default for StateMerklePrunerConfig {
             // A 10k transaction block (touching 60k state values, in the case of the account
             // creation benchmark) on a 4B items DB (or 1.33B accounts) yields 300k JMT nodes
             batch_size: 1_000,
+            prune_window_secs: None,
+            target_size_bytes: None,
         }
     }
 }
@@ -265,12 +307,36 @@ impl Default for StorageConfig {
             data_dir: PathBuf::from("/opt/aptos/data"),
             rocksdb_configs: RocksdbConfigs::default(),
             enable_indexer: false,
+            consistency_checker_config: ConsistencyCheckerConfig::default(),
             buffered_state_target_items: BUFFERED_STATE_TARGET_ITEMS,
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         }
     }
 }
 
+/// Configuration of the background checker that continuously samples versions in the DB and
+/// verifies the data of them against the accumulators and the state tree.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsistencyCheckerConfig {
+    /// Boolean to enable/disable the checker.
+    pub enable: bool,
+    /// Number of versions to sample and check in each round.
+    pub versions_per_round: usize,
+    /// Interval between two rounds, in milliseconds.
+    pub round_interval_ms: u64,
+}
+
+impl Default for ConsistencyCheckerConfig {
+    fn default() -> Self {
+        ConsistencyCheckerConfig {
+            enable: false,
+            versions_per_round: 10,
+            round_interval_ms: 10_000,
+        }
+    }
+}
+
 impl StorageConfig {
     pub fn dir(&self) -> PathBuf {
         if self.dir.is_relative() {
@@ -337,6 +403,37 @@ impl ConfigSanitizer for StorageConfig {
                 "user_pruning_window_offset is larger than the ledger prune window, the API will refuse to return any data.".to_string(),
             ));
         }
+        let ledger_pruner_config = &config.storage_pruner_config.ledger_pruner_config;
+        let state_merkle_pruner_config = &config.storage_pruner_config.state_merkle_pruner_config;
+        if [
+            ledger_pruner_config.prune_window_secs,
+            ledger_pruner_config.target_size_bytes,
+            state_merkle_pruner_config.prune_window_secs,
+            state_merkle_pruner_config.target_size_bytes,
+        ]
+        .contains(&Some(0))
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "prune_window_secs and target_size_bytes must be positive if set.".to_string(),
+            ));
+        }
+        for event_type in &ledger_pruner_config.archived_event_types {
+            if let Err(error) = TypeTag::from_str(event_type) {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!("Invalid archived event type {}: {}", event_type, error),
+                ));
+            }
+        }
+        if config.consistency_checker_config.enable
+            && config.consistency_checker_config.versions_per_round == 0
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "versions_per_round of the consistency checker must be positive.".to_string(),
+            ));
+        }
 
         Ok(())
     }
@@ -344,7 +441,7 @@ impl ConfigSanitizer for StorageConfig {
 
 #[cfg(test)]
 mod test {
-    use crate::config::PrunerConfig;
+    use super::*;
 
     #[test]
     pub fn test_default_prune_window() {
@@ -355,4 +452,58 @@ mod test {
         assert!(config.state_merkle_pruner_config.prune_window >= 100_000);
         assert!(config.epoch_snapshot_pruner_config.prune_window > 50_000_000);
     }
+
+    #[test]
+    fn test_sanitize_zero_dynamic_prune_window() {
+        // Create a node config with a zero retention time for the ledger pruner
+        let mut node_config = NodeConfig {
+            storage: StorageConfig {
+                storage_pruner_config: PrunerConfig {
+                    ledger_pruner_config: LedgerPrunerConfig {
+                        prune_window_secs: Some(0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config sanitizer fails
+        let error =
+            StorageConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::mainnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_archived_event_types() {
+        let mut node_config = NodeConfig {
+            storage: StorageConfig {
+                storage_pruner_config: PrunerConfig {
+                    ledger_pruner_config: LedgerPrunerConfig {
+                        archived_event_types: vec!["0x1::coin::DepositEvent".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        StorageConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::mainnet()).unwrap();
+
+        // Verify that the config sanitizer fails on a malformed event type
+        node_config
+            .storage
+            .storage_pruner_config
+            .ledger_pruner_config
+            .archived_event_types
+            .push("0x1::coin".to_string());
+        let error =
+            StorageConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::mainnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 }
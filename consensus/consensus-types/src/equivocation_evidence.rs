@@ -0,0 +1,253 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::Block,
+    common::{Author, Round},
+    vote::Vote,
+};
+use anyhow::{ensure, format_err, Context};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_infallible::RwLock;
+use aptos_types::{epoch_state::EpochState, validator_verifier::ValidatorVerifier};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+#[cfg(test)]
+#[path = "equivocation_evidence_test.rs"]
+mod equivocation_evidence_test;
+
+/// Equivocation evidence collected by consensus on this node, for the node services to expose.
+pub static EQUIVOCATION_EVIDENCE: Lazy<EquivocationEvidenceRegistry> =
+    Lazy::new(EquivocationEvidenceRegistry::default);
+
+/// Number of epochs, the current one included, equivocation evidence is kept for.
+pub const NUM_EVIDENCE_EPOCHS: u64 = 2;
+
+/// Maximum number of evidence kept in the registry, the oldest is dropped first.
+pub const MAX_EVIDENCE: usize = 1_000;
+
+/// Maximum number of evidence the node services return, the most recent first.
+pub const MAX_EVIDENCE_PER_RESPONSE: usize = 100;
+
+/// Evidence is kept once per validator and round.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EquivocationEvidenceKey {
+    pub epoch: u64,
+    pub round: Round,
+    pub author: Author,
+}
+
+/// Two conflicting messages signed by the same validator for the same round, which an honest
+/// validator never sends. Anyone holding the validator set of the epoch can verify it.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum EquivocationEvidence {
+    /// Two different blocks proposed by the same validator in the same round.
+    Proposal(Box<Block>, Box<Block>),
+    /// Two votes of the same validator in the same round, for different ledger infos.
+    Vote(Box<Vote>, Box<Vote>),
+}
+
+impl Display for EquivocationEvidence {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            EquivocationEvidence::Proposal(first, second) => write!(
+                f,
+                "EquivocatingProposals: [{}, {}]",
+                first.id(),
+                second.id()
+            ),
+            EquivocationEvidence::Vote(first, second) => write!(
+                f,
+                "EquivocatingVotes: [{}, {}]",
+                first.ledger_info().hash(),
+                second.ledger_info().hash()
+            ),
+        }
+    }
+}
+
+impl EquivocationEvidence {
+    pub fn new_proposal(first: Block, second: Block) -> Self {
+        Self::Proposal(Box::new(first), Box::new(second))
+    }
+
+    pub fn new_vote(first: Vote, second: Vote) -> Self {
+        Self::Vote(Box::new(first), Box::new(second))
+    }
+
+    /// The equivocating validator.
+    pub fn author(&self) -> Author {
+        match self {
+            EquivocationEvidence::Proposal(first, _) => first.author().unwrap_or(Author::ZERO),
+            EquivocationEvidence::Vote(first, _) => first.author(),
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        match self {
+            EquivocationEvidence::Proposal(first, _) => first.epoch(),
+            EquivocationEvidence::Vote(first, _) => first.epoch(),
+        }
+    }
+
+    pub fn round(&self) -> Round {
+        match self {
+            EquivocationEvidence::Proposal(first, _) => first.round(),
+            EquivocationEvidence::Vote(first, _) => first.vote_data().proposed().round(),
+        }
+    }
+
+    pub fn key(&self) -> EquivocationEvidenceKey {
+        EquivocationEvidenceKey {
+            epoch: self.epoch(),
+            round: self.round(),
+            author: self.author(),
+        }
+    }
+
+    /// Identifies the evidence by the conflicting messages, regardless of their order.
+    pub fn id(&self) -> HashValue {
+        let (kind, first, second) = match self {
+            EquivocationEvidence::Proposal(first, second) => ("proposal", first.id(), second.id()),
+            EquivocationEvidence::Vote(first, second) => (
+                "vote",
+                first.ledger_info().hash(),
+                second.ledger_info().hash(),
+            ),
+        };
+        let digests = if first <= second {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        HashValue::sha3_256_of(
+            &bcs::to_bytes(&(kind, self.author(), digests))
+                .expect("Unable to serialize equivocation evidence id"),
+        )
+    }
+
+    /// Verifies that both messages are validly signed by the same validator of `validator`, for
+    /// the same epoch and round, and that they conflict.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        match self {
+            EquivocationEvidence::Proposal(first, second) => {
+                let author = first
+                    .author()
+                    .ok_or_else(|| format_err!("Proposal {} has no author", first.id()))?;
+                ensure!(
+                    second.author() == Some(author),
+                    "Proposals {} and {} have different authors",
+                    first.id(),
+                    second.id()
+                );
+                ensure!(
+                    (first.epoch(), first.round()) == (second.epoch(), second.round()),
+                    "Proposals {} and {} are for different rounds",
+                    first.id(),
+                    second.id()
+                );
+                ensure!(first.id() != second.id(), "Proposals are identical");
+                first
+                    .validate_signature(validator)
+                    .context("Failed to verify first proposal")?;
+                second
+                    .validate_signature(validator)
+                    .context("Failed to verify second proposal")?;
+            },
+            EquivocationEvidence::Vote(first, second) => {
+                ensure!(
+                    first.author() == second.author(),
+                    "Votes {} and {} have different authors",
+                    first,
+                    second
+                );
+                ensure!(
+                    (first.epoch(), first.vote_data().proposed().round())
+                        == (second.epoch(), second.vote_data().proposed().round()),
+                    "Votes {} and {} are for different rounds",
+                    first,
+                    second
+                );
+                ensure!(
+                    first.ledger_info().hash() != second.ledger_info().hash(),
+                    "Votes are for the same ledger info"
+                );
+                first
+                    .verify(validator)
+                    .context("Failed to verify first vote")?;
+                second
+                    .verify(validator)
+                    .context("Failed to verify second vote")?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Verifies the evidence against the validator set of the epoch it is for.
+    pub fn verify_for_epoch(&self, epoch_state: &EpochState) -> anyhow::Result<()> {
+        ensure!(
+            self.epoch() == epoch_state.epoch,
+            "Evidence is for epoch {}, validators are for epoch {}",
+            self.epoch(),
+            epoch_state.epoch
+        );
+        self.verify(&epoch_state.verifier)
+    }
+}
+
+/// Collected equivocation evidence, ordered by epoch and round. At most `MAX_EVIDENCE` are kept.
+#[derive(Default)]
+pub struct EquivocationEvidenceRegistry {
+    evidence: RwLock<BTreeMap<EquivocationEvidenceKey, EquivocationEvidence>>,
+}
+
+impl EquivocationEvidenceRegistry {
+    /// Returns whether there wasn't already evidence for the validator and round, and the
+    /// evidence is recent enough to be kept.
+    pub fn insert(&self, evidence: EquivocationEvidence) -> bool {
+        let mut evidence_by_key = self.evidence.write();
+        let key = evidence.key();
+        if evidence_by_key.contains_key(&key) {
+            return false;
+        }
+        evidence_by_key.insert(key, evidence);
+        if evidence_by_key.len() > MAX_EVIDENCE {
+            evidence_by_key.pop_first();
+        }
+        evidence_by_key.contains_key(&key)
+    }
+
+    /// Drops the evidence of epochs before `epoch`.
+    pub fn prune_before_epoch(&self, epoch: u64) {
+        let mut evidence_by_key = self.evidence.write();
+        *evidence_by_key = evidence_by_key.split_off(&EquivocationEvidenceKey {
+            epoch,
+            round: 0,
+            author: Author::ZERO,
+        });
+    }
+
+    /// All collected evidence, ordered by epoch and round.
+    pub fn get_all(&self) -> Vec<EquivocationEvidence> {
+        self.evidence.read().values().cloned().collect()
+    }
+
+    /// The `limit` most recent evidence, ordered by epoch and round.
+    pub fn get_latest(&self, limit: usize) -> Vec<EquivocationEvidence> {
+        let mut evidence: Vec<_> = self
+            .evidence
+            .read()
+            .values()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect();
+        evidence.reverse();
+        evidence
+    }
+}
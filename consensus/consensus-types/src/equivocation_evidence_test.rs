@@ -0,0 +1,195 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::Payload,
+    equivocation_evidence::{EquivocationEvidence, EquivocationEvidenceRegistry, MAX_EVIDENCE},
+    vote::Vote,
+    vote_data::VoteData,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    block_info::{BlockInfo, Round},
+    epoch_state::EpochState,
+    ledger_info::LedgerInfo,
+    validator_signer::ValidatorSigner,
+    validator_verifier::random_validator_verifier,
+};
+
+fn proposal(round: Round, timestamp_usecs: u64, signer: &ValidatorSigner) -> Block {
+    Block::new_proposal(
+        Payload::empty(false),
+        round,
+        timestamp_usecs,
+        certificate_for_genesis(),
+        signer,
+        vec![],
+    )
+    .unwrap()
+}
+
+fn vote(round: Round, id: HashValue, signer: &ValidatorSigner) -> Vote {
+    vote_in_epoch(1, round, id, signer)
+}
+
+fn vote_in_epoch(epoch: u64, round: Round, id: HashValue, signer: &ValidatorSigner) -> Vote {
+    let vote_data = VoteData::new(
+        BlockInfo::new(epoch, round, id, HashValue::zero(), 0, 1, None),
+        BlockInfo::new(
+            epoch,
+            round - 1,
+            HashValue::zero(),
+            HashValue::zero(),
+            0,
+            0,
+            None,
+        ),
+    );
+    let ledger_info = LedgerInfo::new(BlockInfo::empty(), vote_data.hash());
+    Vote::new(vote_data, signer.author(), ledger_info, signer).unwrap()
+}
+
+#[test]
+fn test_proposal_evidence() {
+    let (signers, verifier) = random_validator_verifier(2, None, false);
+    let first = proposal(1, 1, &signers[0]);
+    let second = proposal(1, 2, &signers[0]);
+
+    let evidence = EquivocationEvidence::new_proposal(first.clone(), second.clone());
+    evidence.verify(&verifier).unwrap();
+    assert_eq!(evidence.author(), signers[0].author());
+    assert_eq!((evidence.epoch(), evidence.round()), (1, 1));
+    assert_eq!(
+        evidence.id(),
+        EquivocationEvidence::new_proposal(second.clone(), first.clone()).id()
+    );
+    evidence
+        .verify_for_epoch(&EpochState {
+            epoch: 1,
+            verifier: verifier.clone(),
+        })
+        .unwrap();
+    assert!(evidence
+        .verify_for_epoch(&EpochState {
+            epoch: 2,
+            verifier: verifier.clone(),
+        })
+        .is_err());
+
+    // Not conflicting
+    assert!(
+        EquivocationEvidence::new_proposal(first.clone(), first.clone())
+            .verify(&verifier)
+            .is_err()
+    );
+    assert!(
+        EquivocationEvidence::new_proposal(first.clone(), proposal(2, 2, &signers[0]))
+            .verify(&verifier)
+            .is_err()
+    );
+    assert!(
+        EquivocationEvidence::new_proposal(first, proposal(1, 2, &signers[1]))
+            .verify(&verifier)
+            .is_err()
+    );
+
+    // Not signed by a validator
+    let other_signer = ValidatorSigner::random(None);
+    let evidence = EquivocationEvidence::new_proposal(
+        proposal(1, 1, &other_signer),
+        proposal(1, 2, &other_signer),
+    );
+    assert!(evidence.verify(&verifier).is_err());
+}
+
+#[test]
+fn test_vote_evidence() {
+    let (signers, verifier) = random_validator_verifier(2, None, false);
+    let first = vote(2, HashValue::random(), &signers[0]);
+    let second = vote(2, HashValue::random(), &signers[0]);
+
+    let evidence = EquivocationEvidence::new_vote(first.clone(), second.clone());
+    evidence.verify(&verifier).unwrap();
+    assert_eq!(evidence.author(), signers[0].author());
+    assert_eq!((evidence.epoch(), evidence.round()), (1, 2));
+    assert_eq!(
+        evidence.id(),
+        EquivocationEvidence::new_vote(second, first.clone()).id()
+    );
+
+    // Not conflicting
+    assert!(EquivocationEvidence::new_vote(first.clone(), first.clone())
+        .verify(&verifier)
+        .is_err());
+    assert!(EquivocationEvidence::new_vote(
+        first.clone(),
+        vote(3, HashValue::random(), &signers[0])
+    )
+    .verify(&verifier)
+    .is_err());
+    assert!(
+        EquivocationEvidence::new_vote(first, vote(2, HashValue::random(), &signers[1]))
+            .verify(&verifier)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_registry() {
+    let (signers, _) = random_validator_verifier(1, None, false);
+    let registry = EquivocationEvidenceRegistry::default();
+    let later = EquivocationEvidence::new_proposal(
+        proposal(2, 1, &signers[0]),
+        proposal(2, 2, &signers[0]),
+    );
+    let earlier = EquivocationEvidence::new_vote(
+        vote(1, HashValue::random(), &signers[0]),
+        vote(1, HashValue::random(), &signers[0]),
+    );
+
+    assert!(registry.insert(later.clone()));
+    assert!(registry.insert(earlier.clone()));
+    assert!(!registry.insert(later.clone()));
+    assert_eq!(registry.get_all(), vec![earlier.clone(), later.clone()]);
+    assert_eq!(registry.get_latest(1), vec![later.clone()]);
+
+    // Only one evidence is kept per validator and round
+    assert!(!registry.insert(EquivocationEvidence::new_vote(
+        vote(2, HashValue::random(), &signers[0]),
+        vote(2, HashValue::random(), &signers[0]),
+    )));
+    assert_eq!(registry.get_all(), vec![earlier, later.clone()]);
+
+    // Old epochs are pruned
+    let next_epoch = EquivocationEvidence::new_vote(
+        vote_in_epoch(2, 1, HashValue::random(), &signers[0]),
+        vote_in_epoch(2, 1, HashValue::random(), &signers[0]),
+    );
+    assert!(registry.insert(next_epoch.clone()));
+    assert_eq!(registry.get_latest(2), vec![later, next_epoch.clone()]);
+    registry.prune_before_epoch(2);
+    assert_eq!(registry.get_all(), vec![next_epoch]);
+}
+
+#[test]
+fn test_registry_is_bounded() {
+    let (signers, _) = random_validator_verifier(1, None, false);
+    let registry = EquivocationEvidenceRegistry::default();
+    let evidence = |round| {
+        EquivocationEvidence::new_vote(
+            vote(round, HashValue::random(), &signers[0]),
+            vote(round, HashValue::random(), &signers[0]),
+        )
+    };
+    for round in 2..MAX_EVIDENCE as Round + 2 {
+        assert!(registry.insert(evidence(round)));
+    }
+
+    // The oldest evidence is dropped for newer evidence, older evidence isn't added
+    assert!(registry.insert(evidence(MAX_EVIDENCE as Round + 2)));
+    assert!(!registry.insert(evidence(1)));
+    let all = registry.get_all();
+    assert_eq!(all.len(), MAX_EVIDENCE);
+    assert_eq!(all[0].round(), 3);
+}
@@ -3,8 +3,9 @@
 
 use crate::common::{Payload, PayloadFilter};
 use anyhow::Result;
+use aptos_types::transaction::SignedTransaction;
 use futures::channel::oneshot;
-use std::{fmt, fmt::Formatter};
+use std::{fmt, fmt::Formatter, iter::Sum, ops::Add};
 
 pub enum GetPayloadCommand {
     /// Request to pull block to submit to consensus.
@@ -13,6 +14,8 @@ pub enum GetPayloadCommand {
         u64,
         // max byte size
         u64,
+        // max estimated gas
+        u64,
         // return non full
         bool,
         // block payloads to exclude from the requested block
@@ -28,14 +31,15 @@ impl fmt::Display for GetPayloadCommand {
             GetPayloadCommand::GetPayloadRequest(
                 max_txns,
                 max_bytes,
+                max_gas,
                 return_non_full,
                 excluded,
                 _,
             ) => {
                 write!(
                     f,
-                    "GetPayloadRequest [max_txns: {}, max_bytes: {}, return_non_full: {},  excluded: {}]",
-                    max_txns, max_bytes, return_non_full, excluded
+                    "GetPayloadRequest [max_txns: {}, max_bytes: {}, max_gas: {}, return_non_full: {},  excluded: {}]",
+                    max_txns, max_bytes, max_gas, return_non_full, excluded
                 )
             },
         }
@@ -44,5 +48,67 @@ impl fmt::Display for GetPayloadCommand {
 
 #[derive(Debug)]
 pub enum GetPayloadResponse {
-    GetPayloadResponse(Payload),
+    GetPayloadResponse(Payload, PayloadGasEstimate),
+}
+
+/// Estimated gas of a set of transactions, and the fees paid for it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GasEstimate {
+    pub gas: u64,
+    pub fees: u64,
+}
+
+impl GasEstimate {
+    pub fn new(gas: u64, fees: u64) -> Self {
+        Self { gas, fees }
+    }
+
+    /// Estimates the gas of transactions by the max gas amount they are willing to spend, at
+    /// their gas unit price.
+    pub fn from_txns(txns: &[SignedTransaction]) -> Self {
+        txns.iter()
+            .map(|txn| {
+                Self::new(
+                    txn.max_gas_amount(),
+                    txn.max_gas_amount().saturating_mul(txn.gas_unit_price()),
+                )
+            })
+            .sum()
+    }
+}
+
+impl Add for GasEstimate {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.gas.saturating_add(other.gas),
+            self.fees.saturating_add(other.fees),
+        )
+    }
+}
+
+impl Sum for GasEstimate {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// Estimated gas of a pulled payload, for each of its batches, and of all the payload that was
+/// available to pull from.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PayloadGasEstimate {
+    pub batches: Vec<GasEstimate>,
+    pub available: GasEstimate,
+}
+
+impl PayloadGasEstimate {
+    pub fn new(batches: Vec<GasEstimate>, available: GasEstimate) -> Self {
+        Self { batches, available }
+    }
+
+    /// Estimated gas of the pulled payload.
+    pub fn packed(&self) -> GasEstimate {
+        self.batches.iter().copied().sum()
+    }
 }
@@ -7,18 +7,37 @@ use crate::{
     serializer::{SafetyRulesInput, SerializerClient, SerializerService, TSerializerClient},
     Error, SafetyRules, TSafetyRules,
 };
+use aptos_config::config::RemoteServiceNoiseConfig;
 use aptos_logger::warn;
-use aptos_secure_net::{NetworkClient, NetworkServer};
+use aptos_secure_net::{
+    noise::{NoiseNetworkClient, NoiseNetworkServer},
+    NetworkClient, NetworkServer,
+};
 use std::net::SocketAddr;
 
 pub trait RemoteService {
     fn client(&self) -> SerializerClient {
-        let network_client = NetworkClient::new(
-            "safety-rules".to_string(),
-            self.server_address(),
-            self.network_timeout_ms(),
-        );
-        let service = Box::new(RemoteClient::new(network_client));
+        let service: Box<dyn TSerializerClient> = match self.noise_config() {
+            Some(noise_config) => {
+                let network_client = NoiseNetworkClient::new(
+                    "safety-rules".to_string(),
+                    self.server_address(),
+                    noise_config.private_key(),
+                    noise_config.server_public_key(),
+                    self.network_timeout_ms(),
+                    noise_config.request_timeout_ms,
+                );
+                Box::new(NoiseRemoteClient::new(network_client))
+            },
+            None => {
+                let network_client = NetworkClient::new(
+                    "safety-rules".to_string(),
+                    self.server_address(),
+                    self.network_timeout_ms(),
+                );
+                Box::new(RemoteClient::new(network_client))
+            },
+        };
         SerializerClient::new_client(service)
     }
 
@@ -26,27 +45,76 @@ pub trait RemoteService {
 
     /// Network Timeout in milliseconds.
     fn network_timeout_ms(&self) -> u64;
+
+    /// Noise authentication of the connection, which is plain TCP if None.
+    fn noise_config(&self) -> Option<&RemoteServiceNoiseConfig> {
+        None
+    }
 }
 
-pub fn execute(storage: PersistentSafetyStorage, listen_addr: SocketAddr, network_timeout_ms: u64) {
+pub fn execute(
+    storage: PersistentSafetyStorage,
+    listen_addr: SocketAddr,
+    network_timeout_ms: u64,
+    noise_config: Option<RemoteServiceNoiseConfig>,
+) {
     let mut safety_rules = SafetyRules::new(storage);
     if let Err(e) = safety_rules.consensus_state() {
         warn!("Unable to print consensus state: {}", e);
     }
 
     let mut serializer_service = SerializerService::new(safety_rules);
-    let mut network_server =
-        NetworkServer::new("safety-rules".to_string(), listen_addr, network_timeout_ms);
+    let mut network_server: Box<dyn RemoteServer> = match noise_config {
+        Some(noise_config) => Box::new(NoiseNetworkServer::new(
+            "safety-rules".to_string(),
+            listen_addr,
+            noise_config.private_key(),
+            noise_config.allowed_client_keys,
+            network_timeout_ms,
+        )),
+        None => Box::new(NetworkServer::new(
+            "safety-rules".to_string(),
+            listen_addr,
+            network_timeout_ms,
+        )),
+    };
 
     loop {
-        if let Err(e) = process_one_message(&mut network_server, &mut serializer_service) {
+        if let Err(e) = process_one_message(network_server.as_mut(), &mut serializer_service) {
             warn!("Failed to process message: {}", e);
         }
     }
 }
 
+/// The server side of the connection to consensus, either plain or Noise authenticated.
+trait RemoteServer {
+    fn read(&mut self) -> Result<Vec<u8>, aptos_secure_net::Error>;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), aptos_secure_net::Error>;
+}
+
+impl RemoteServer for NetworkServer {
+    fn read(&mut self) -> Result<Vec<u8>, aptos_secure_net::Error> {
+        NetworkServer::read(self)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), aptos_secure_net::Error> {
+        NetworkServer::write(self, data)
+    }
+}
+
+impl RemoteServer for NoiseNetworkServer {
+    fn read(&mut self) -> Result<Vec<u8>, aptos_secure_net::Error> {
+        NoiseNetworkServer::read(self)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), aptos_secure_net::Error> {
+        NoiseNetworkServer::write(self, data)
+    }
+}
+
 fn process_one_message(
-    network_server: &mut NetworkServer,
+    network_server: &mut dyn RemoteServer,
     serializer_service: &mut SerializerService,
 ) -> Result<(), Error> {
     let request = network_server.read()?;
@@ -81,3 +149,24 @@ impl TSerializerClient for RemoteClient {
         }
     }
 }
+
+struct NoiseRemoteClient {
+    network_client: NoiseNetworkClient,
+}
+
+impl NoiseRemoteClient {
+    pub fn new(network_client: NoiseNetworkClient) -> Self {
+        Self { network_client }
+    }
+}
+
+impl TSerializerClient for NoiseRemoteClient {
+    /// Unlike `RemoteClient`, gives up once the request timeout expires, so that a signing host
+    /// that is gone doesn't block consensus forever.
+    fn request(&mut self, input: SafetyRulesInput) -> Result<Vec<u8>, Error> {
+        let input_message = serde_json::to_vec(&input)?;
+        self.network_client
+            .request(&input_message)
+            .map_err(|e| e.into())
+    }
+}
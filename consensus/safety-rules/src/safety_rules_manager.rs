@@ -11,7 +11,9 @@ use crate::{
     thread::ThreadService,
     SafetyRules, TSafetyRules,
 };
-use aptos_config::config::{InitialSafetyRulesConfig, SafetyRulesConfig, SafetyRulesService};
+use aptos_config::config::{
+    InitialSafetyRulesConfig, RemoteServiceNoiseConfig, SafetyRulesConfig, SafetyRulesService,
+};
 use aptos_infallible::RwLock;
 use aptos_secure_storage::{KVStorage, Storage};
 use std::{convert::TryInto, net::SocketAddr, sync::Arc};
@@ -88,7 +90,11 @@ pub struct SafetyRulesManager {
 impl SafetyRulesManager {
     pub fn new(config: &SafetyRulesConfig) -> Self {
         if let SafetyRulesService::Process(conf) = &config.service {
-            return Self::new_process(conf.server_address(), config.network_timeout_ms);
+            return Self::new_process(
+                conf.server_address(),
+                config.network_timeout_ms,
+                conf.noise.clone(),
+            );
         }
 
         let storage = storage(config);
@@ -107,8 +113,12 @@ impl SafetyRulesManager {
         }
     }
 
-    pub fn new_process(server_addr: SocketAddr, timeout_ms: u64) -> Self {
-        let process_service = ProcessService::new(server_addr, timeout_ms);
+    pub fn new_process(
+        server_addr: SocketAddr,
+        timeout_ms: u64,
+        noise_config: Option<RemoteServiceNoiseConfig>,
+    ) -> Self {
+        let process_service = ProcessService::new(server_addr, timeout_ms, noise_config);
         Self {
             internal_safety_rules: SafetyRulesWrapper::Process(process_service),
         }
@@ -2,8 +2,18 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{test_utils, SafetyRulesManager};
+use crate::{remote_service, test_utils, SafetyRulesManager};
+use aptos_config::{
+    config::{Identity, RemoteServiceNoiseConfig},
+    utils,
+};
+use aptos_crypto::{x25519, Uniform};
 use aptos_types::validator_signer::ValidatorSigner;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    thread,
+};
 
 #[test]
 fn test_reconnect() {
@@ -18,3 +28,52 @@ fn test_reconnect() {
     let state1 = safety_rules_manager.client().consensus_state().unwrap();
     assert_eq!(state0, state1);
 }
+
+fn noise_key(seed: u8) -> x25519::PrivateKey {
+    x25519::PrivateKey::generate(&mut StdRng::from_seed([seed; 32]))
+}
+
+#[test]
+fn test_noise_authentication() {
+    let signer = ValidatorSigner::from_int(0);
+    let storage = test_utils::test_storage(&signer);
+    // test value for network timeout, in milliseconds.
+    let network_timeout = 5_000;
+    let noise_config = |key: x25519::PrivateKey| RemoteServiceNoiseConfig {
+        identity: Identity::from_config(key, signer.author()),
+        server_public_key: Some(noise_key(0).public_key()),
+        allowed_client_keys: vec![noise_key(1).public_key()].into_iter().collect(),
+        request_timeout_ms: 1_000,
+    };
+    let server_config = noise_config(noise_key(0));
+    let server_port = utils::get_available_port();
+    let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+    thread::spawn(move || {
+        remote_service::execute(storage, server_addr, network_timeout, Some(server_config))
+    });
+
+    // Allowlisted clients can connect one after the other
+    let safety_rules_manager = SafetyRulesManager::new_process(
+        server_addr,
+        network_timeout,
+        Some(noise_config(noise_key(1))),
+    );
+    let state0 = safety_rules_manager.client().consensus_state().unwrap();
+    let state1 = safety_rules_manager.client().consensus_state().unwrap();
+    assert_eq!(state0, state1);
+
+    // Other clients time out
+    let safety_rules_manager = SafetyRulesManager::new_process(
+        server_addr,
+        network_timeout,
+        Some(noise_config(noise_key(2))),
+    );
+    safety_rules_manager.client().consensus_state().unwrap_err();
+
+    // And so do allowlisted clients expecting another server
+    let mut client_config = noise_config(noise_key(1));
+    client_config.server_public_key = Some(noise_key(3).public_key());
+    let safety_rules_manager =
+        SafetyRulesManager::new_process(server_addr, network_timeout, Some(client_config));
+    safety_rules_manager.client().consensus_state().unwrap_err();
+}
@@ -0,0 +1,104 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::consensus_trace::{LogEvent, Timeline, TraceEvent, TraceWriter, TRACE_FILE_NAME};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_temppath::TempPath;
+use std::{fs, io::Write};
+
+fn create_event(author: Author, timestamp_usecs: u64, round: Round, event: LogEvent) -> TraceEvent {
+    TraceEvent {
+        timestamp_usecs,
+        author,
+        event,
+        epoch: 1,
+        round,
+        block_id: Some(HashValue::random()),
+        remote_peer: None,
+    }
+}
+
+#[test]
+fn test_writer_rotation() {
+    let dir = TempPath::new();
+    let author = Author::random();
+    let line_size = serde_json::to_vec(&create_event(author, 0, 0, LogEvent::Vote))
+        .unwrap()
+        .len() as u64
+        + 1;
+    // Two events per file, two rotated files kept
+    let mut writer = TraceWriter::new(dir.path().to_path_buf(), 2 * line_size, 2).unwrap();
+    for round in 0..7 {
+        writer
+            .write(&create_event(author, round, round, LogEvent::Vote))
+            .unwrap();
+    }
+    writer.flush().unwrap();
+
+    assert!(dir.path().join(TRACE_FILE_NAME).exists());
+    assert!(dir.path().join(format!("{}.1", TRACE_FILE_NAME)).exists());
+    assert!(dir.path().join(format!("{}.2", TRACE_FILE_NAME)).exists());
+    assert!(!dir.path().join(format!("{}.3", TRACE_FILE_NAME)).exists());
+
+    // The oldest events were dropped with the oldest file
+    let timeline = Timeline::from_paths(&[dir.path().to_path_buf()]).unwrap();
+    let rounds: Vec<_> = timeline.rounds().map(|((_, round), _)| *round).collect();
+    assert_eq!(rounds, vec![2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn test_timeline_merge() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let author_0 = Author::random();
+    let author_1 = Author::random();
+
+    let path_0 = dir.path().join("validator_0.jsonl");
+    let mut file = fs::File::create(&path_0).unwrap();
+    for event in [
+        create_event(author_0, 30, 2, LogEvent::ReceiveProposal),
+        create_event(author_0, 10, 1, LogEvent::Propose),
+        create_event(author_0, 25, 1, LogEvent::AggregateQC),
+    ] {
+        writeln!(file, "{}", serde_json::to_string(&event).unwrap()).unwrap();
+    }
+    // Truncated by a crash
+    write!(file, "{{\"timestamp_usecs\":").unwrap();
+
+    let path_1 = dir.path().join("validator_1.jsonl");
+    let mut file = fs::File::create(&path_1).unwrap();
+    for event in [
+        create_event(author_1, 15, 1, LogEvent::ReceiveProposal),
+        create_event(author_1, 20, 1, LogEvent::Vote),
+    ] {
+        writeln!(file, "{}", serde_json::to_string(&event).unwrap()).unwrap();
+    }
+
+    let timeline = Timeline::from_paths(&[path_0, path_1]).unwrap();
+    assert_eq!(timeline.num_malformed_lines(), 1);
+    let rounds: Vec<_> = timeline
+        .rounds()
+        .map(|(key, events)| {
+            (
+                *key,
+                events
+                    .iter()
+                    .map(|event| (event.author, event.event))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    assert_eq!(rounds, vec![
+        ((1, 1), vec![
+            (author_0, LogEvent::Propose),
+            (author_1, LogEvent::ReceiveProposal),
+            (author_1, LogEvent::Vote),
+            (author_0, LogEvent::AggregateQC),
+        ]),
+        ((1, 2), vec![(author_0, LogEvent::ReceiveProposal)]),
+    ]);
+
+    let filtered = timeline.filter(Some(1), 2, Round::MAX);
+    assert_eq!(filtered.rounds().count(), 1);
+}
@@ -0,0 +1,40 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_consensus::consensus_trace::Timeline;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Merge the consensus traces of several validators into a per-round timeline.
+#[derive(Parser)]
+#[clap(name = "consensus-trace")]
+struct Args {
+    /// Trace files, or trace directories including their rotated files.
+    #[clap(required = true, value_parser)]
+    paths: Vec<PathBuf>,
+
+    /// Only print the rounds of this epoch.
+    #[clap(long)]
+    epoch: Option<u64>,
+
+    #[clap(long, default_value_t = 0)]
+    from_round: u64,
+
+    #[clap(long, default_value_t = u64::MAX)]
+    to_round: u64,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let timeline =
+        Timeline::from_paths(&args.paths)?.filter(args.epoch, args.from_round, args.to_round);
+    print!("{}", timeline);
+    Ok(())
+}
+
+#[test]
+fn verify_tool() {
+    use clap::CommandFactory;
+    Args::command().debug_assert()
+}
@@ -0,0 +1,213 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compact round-by-round trace of consensus events, for debugging liveness incidents.
+//!
+//! When enabled, `RoundManager`, `RoundState` and `BufferManager` record an event for every
+//! proposal, vote, certificate, timeout and execution pipeline phase. Events are written as JSON
+//! lines to a rotating local file by a dedicated thread, so that recording never blocks
+//! consensus. Traces of several validators are merged into a per-round [`Timeline`] offline.
+
+#[cfg(test)]
+mod consensus_trace_test;
+mod timeline;
+
+use crate::counters;
+pub use crate::logging::LogEvent;
+use aptos_config::config::ConsensusTraceConfig;
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_logger::prelude::*;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread,
+    time::Duration,
+};
+pub use timeline::Timeline;
+
+/// The name of the current trace file. Rotated files get a numeric suffix, `.1` being the most
+/// recent one.
+pub const TRACE_FILE_NAME: &str = "consensus_trace.jsonl";
+
+static TRACER: OnceCell<Tracer> = OnceCell::new();
+
+/// A consensus event, as recorded by the validator that observed it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TraceEvent {
+    /// Local time of the event, in microseconds since the unix epoch.
+    pub timestamp_usecs: u64,
+    /// The validator that recorded the event.
+    pub author: Author,
+    /// What happened.
+    pub event: LogEvent,
+    /// Epoch of the event.
+    pub epoch: u64,
+    /// Round of the event.
+    pub round: Round,
+    /// The block the event is about, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<HashValue>,
+    /// The peer the event was received from or sent to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_peer: Option<Author>,
+}
+
+struct Tracer {
+    author: Author,
+    sender: SyncSender<TraceEvent>,
+}
+
+/// Starts the trace writer if tracing is enabled. Events recorded before, or while disabled,
+/// are ignored.
+pub(crate) fn init(author: Author, config: &ConsensusTraceConfig) {
+    if !config.enabled {
+        return;
+    }
+    let writer = match TraceWriter::new(
+        config.path(),
+        config.max_file_size_bytes,
+        config.max_rotated_files,
+    ) {
+        Ok(writer) => writer,
+        Err(e) => {
+            error!(error = ?e, "Failed to open the consensus trace, tracing is disabled");
+            return;
+        },
+    };
+    let (sender, receiver) = mpsc::sync_channel(config.channel_size);
+    if TRACER.set(Tracer { author, sender }).is_err() {
+        warn!("Consensus trace is already initialized");
+        return;
+    }
+    thread::Builder::new()
+        .name("consensus-trace".into())
+        .spawn(move || writer.run(receiver))
+        .expect("Failed to spawn the consensus trace writer");
+    info!("Writing the consensus trace to {:?}", config.path());
+}
+
+/// Records a consensus event in the trace, if enabled. Events are dropped if the writer falls
+/// behind.
+pub(crate) fn record(
+    event: LogEvent,
+    epoch: u64,
+    round: Round,
+    block_id: Option<HashValue>,
+    remote_peer: Option<Author>,
+) {
+    if let Some(tracer) = TRACER.get() {
+        let event = TraceEvent {
+            timestamp_usecs: aptos_infallible::duration_since_epoch().as_micros() as u64,
+            author: tracer.author,
+            event,
+            epoch,
+            round,
+            block_id,
+            remote_peer,
+        };
+        if tracer.sender.try_send(event).is_err() {
+            counters::CONSENSUS_TRACE_DROPPED_EVENTS.inc();
+        }
+    }
+}
+
+/// Appends trace events to `TRACE_FILE_NAME` in the trace directory, rotating it once it
+/// exceeds the max size.
+pub(crate) struct TraceWriter {
+    dir: PathBuf,
+    max_file_size_bytes: u64,
+    max_rotated_files: usize,
+    file: BufWriter<File>,
+    file_size: u64,
+}
+
+impl TraceWriter {
+    pub(crate) fn new(
+        dir: PathBuf,
+        max_file_size_bytes: u64,
+        max_rotated_files: usize,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let file = Self::open(&dir)?;
+        let file_size = file.metadata()?.len();
+        Ok(Self {
+            dir,
+            max_file_size_bytes,
+            max_rotated_files,
+            file: BufWriter::new(file),
+            file_size,
+        })
+    }
+
+    fn open(dir: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(TRACE_FILE_NAME))
+    }
+
+    fn path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(TRACE_FILE_NAME)
+        } else {
+            self.dir.join(format!("{}.{}", TRACE_FILE_NAME, index))
+        }
+    }
+
+    pub(crate) fn write(&mut self, event: &TraceEvent) -> io::Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        if self.file_size > 0 && self.file_size + line.len() as u64 > self.max_file_size_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(&line)?;
+        self.file_size += line.len() as u64;
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.flush()?;
+        if self.max_rotated_files == 0 {
+            fs::remove_file(self.path(0))?;
+        } else {
+            // The oldest rotated file is overwritten.
+            for index in (1..self.max_rotated_files).rev() {
+                if self.path(index).exists() {
+                    fs::rename(self.path(index), self.path(index + 1))?;
+                }
+            }
+            fs::rename(self.path(0), self.path(1))?;
+        }
+        self.file = BufWriter::new(Self::open(&self.dir)?);
+        self.file_size = 0;
+        Ok(())
+    }
+
+    /// Writes events until all senders are dropped, flushing whenever there is no pending event.
+    fn run(mut self, receiver: Receiver<TraceEvent>) {
+        while let Ok(event) = receiver.recv() {
+            let mut result = self.write(&event);
+            while result.is_ok() {
+                match receiver.try_recv() {
+                    Ok(event) => result = self.write(&event),
+                    Err(_) => break,
+                }
+            }
+            if let Err(e) = result.and_then(|_| self.flush()) {
+                sample!(
+                    SampleRate::Duration(Duration::from_secs(60)),
+                    error!(error = ?e, "Failed to write the consensus trace")
+                );
+            }
+        }
+    }
+}
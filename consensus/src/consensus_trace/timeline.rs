@@ -0,0 +1,126 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::consensus_trace::{TraceEvent, TRACE_FILE_NAME};
+use anyhow::{Context, Result};
+use aptos_consensus_types::common::Round;
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+/// Trace events of several validators, grouped by round and ordered by time.
+///
+/// Events are ordered by the local clock of the validator that recorded them, so events of
+/// different validators are only as comparable as their clocks are synchronized.
+#[derive(Debug, Default)]
+pub struct Timeline {
+    rounds: BTreeMap<(u64, Round), Vec<TraceEvent>>,
+    num_malformed_lines: usize,
+}
+
+impl Timeline {
+    /// Groups the events by epoch and round.
+    pub fn new(events: impl IntoIterator<Item = TraceEvent>) -> Self {
+        let mut timeline = Self::default();
+        timeline.extend(events);
+        timeline
+    }
+
+    fn extend(&mut self, events: impl IntoIterator<Item = TraceEvent>) {
+        for event in events {
+            self.rounds
+                .entry((event.epoch, event.round))
+                .or_default()
+                .push(event);
+        }
+        for events in self.rounds.values_mut() {
+            events.sort_by_key(|event| event.timestamp_usecs);
+        }
+    }
+
+    /// Reads the given trace files, and the current and rotated trace files in the given
+    /// directories. Malformed lines, e.g. truncated by a crash, are skipped and counted.
+    pub fn from_paths(paths: &[PathBuf]) -> Result<Self> {
+        let mut timeline = Self::default();
+        for path in paths {
+            if path.is_dir() {
+                for entry in fs::read_dir(path)? {
+                    let file = entry?.path();
+                    if file
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .map_or(false, |name| name.starts_with(TRACE_FILE_NAME))
+                    {
+                        timeline.read_file(&file)?;
+                    }
+                }
+            } else {
+                timeline.read_file(path)?;
+            }
+        }
+        Ok(timeline)
+    }
+
+    fn read_file(&mut self, path: &Path) -> Result<()> {
+        let file = fs::File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        let mut events = vec![];
+        for line in BufReader::new(file).lines() {
+            match serde_json::from_str::<TraceEvent>(&line?) {
+                Ok(event) => events.push(event),
+                Err(_) => self.num_malformed_lines += 1,
+            }
+        }
+        self.extend(events);
+        Ok(())
+    }
+
+    /// Keeps the rounds of the given epoch, if any, in the given range of rounds.
+    pub fn filter(mut self, epoch: Option<u64>, from_round: Round, to_round: Round) -> Self {
+        self.rounds.retain(|(e, round), _| {
+            epoch.map_or(true, |epoch| epoch == *e) && (from_round..=to_round).contains(round)
+        });
+        self
+    }
+
+    /// Iterates over the events of each (epoch, round), in order.
+    pub fn rounds(&self) -> impl Iterator<Item = (&(u64, Round), &Vec<TraceEvent>)> {
+        self.rounds.iter()
+    }
+
+    /// Number of lines that could not be parsed as trace events.
+    pub fn num_malformed_lines(&self) -> usize {
+        self.num_malformed_lines
+    }
+}
+
+impl fmt::Display for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for ((epoch, round), events) in &self.rounds {
+            writeln!(f, "epoch {} round {}", epoch, round)?;
+            let start = events.first().map_or(0, |event| event.timestamp_usecs);
+            for event in events {
+                write!(
+                    f,
+                    "  +{:>10.3}ms  {}  {:?}",
+                    (event.timestamp_usecs - start) as f64 / 1000.0,
+                    event.author.short_str_lossless(),
+                    event.event,
+                )?;
+                if let Some(block_id) = event.block_id {
+                    write!(f, "  block {}", &block_id.to_hex()[..8])?;
+                }
+                if let Some(peer) = event.remote_peer {
+                    write!(f, "  peer {}", peer.short_str_lossless())?;
+                }
+                writeln!(f)?;
+            }
+        }
+        if self.num_malformed_lines > 0 {
+            writeln!(f, "skipped {} malformed lines", self.num_malformed_lines)?;
+        }
+        Ok(())
+    }
+}
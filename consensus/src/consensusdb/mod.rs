@@ -17,12 +17,13 @@ use aptos_schemadb::{
 pub use schema::{
     block::BlockSchema,
     dag::{CertifiedNodeSchema, DagVoteSchema, NodeSchema, OrderedAnchorIdSchema},
+    equivocation_evidence::EquivocationEvidenceSchema,
     quorum_certificate::QCSchema,
 };
 use schema::{
     single_entry::{SingleEntryKey, SingleEntrySchema},
-    BLOCK_CF_NAME, CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, NODE_CF_NAME,
-    ORDERED_ANCHOR_ID_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME,
+    BLOCK_CF_NAME, CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, EQUIVOCATION_EVIDENCE_CF_NAME,
+    NODE_CF_NAME, ORDERED_ANCHOR_ID_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME,
 };
 use std::{iter::Iterator, path::Path, time::Instant};
 
@@ -60,6 +61,7 @@ impl ConsensusDB {
             CERTIFIED_NODE_CF_NAME,
             DAG_VOTE_CF_NAME,
             ORDERED_ANCHOR_ID_CF_NAME,
+            EQUIVOCATION_EVIDENCE_CF_NAME,
         ];
 
         let path = db_root_path.as_ref().join(CONSENSUS_DB_NAME);
@@ -79,6 +81,18 @@ impl ConsensusDB {
         Self { db }
     }
 
+    /// Opens an existing DB without write access, for offline inspection. Only the column
+    /// families of blocks and quorum certificates are opened.
+    pub fn new_readonly<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let path = db_root_path.as_ref().join(CONSENSUS_DB_NAME);
+        let db = DB::open_cf_readonly(&Options::default(), path, "consensus", vec![
+            DEFAULT_COLUMN_FAMILY_NAME,
+            BLOCK_CF_NAME,
+            QC_CF_NAME,
+        ])?;
+        Ok(Self { db })
+    }
+
     pub fn get_data(
         &self,
     ) -> Result<(
@@ -0,0 +1,50 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for evidence of equivocating validators.
+//!
+//! Serialized evidence identified by the epoch, round and author it is for.
+//! ```text
+//! |<---------key--------->|<---value--->|
+//! | epoch | round | author |  evidence   |
+//! ```
+
+use crate::define_schema;
+use anyhow::Result;
+use aptos_consensus_types::equivocation_evidence::{EquivocationEvidence, EquivocationEvidenceKey};
+use aptos_schemadb::{
+    schema::{KeyCodec, ValueCodec},
+    ColumnFamilyName,
+};
+
+pub const EQUIVOCATION_EVIDENCE_CF_NAME: ColumnFamilyName = "equivocation_evidence";
+
+define_schema!(
+    EquivocationEvidenceSchema,
+    EquivocationEvidenceKey,
+    EquivocationEvidence,
+    EQUIVOCATION_EVIDENCE_CF_NAME
+);
+
+impl KeyCodec<EquivocationEvidenceSchema> for EquivocationEvidenceKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<EquivocationEvidenceSchema> for EquivocationEvidence {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,30 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::Payload,
+};
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::validator_signer::ValidatorSigner;
+
+#[test]
+fn test_encode_decode() {
+    let signer = ValidatorSigner::random(None);
+    let proposal = |timestamp_usecs| {
+        Block::new_proposal(
+            Payload::empty(false),
+            1,
+            timestamp_usecs,
+            certificate_for_genesis(),
+            &signer,
+            vec![],
+        )
+        .unwrap()
+    };
+    let evidence = EquivocationEvidence::new_proposal(proposal(1), proposal(2));
+    assert_encode_decode::<EquivocationEvidenceSchema>(&evidence.key(), &evidence);
+}
+
+test_no_panic_decoding!(EquivocationEvidenceSchema);
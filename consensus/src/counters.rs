@@ -102,6 +102,15 @@ pub static VOTE_NIL_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of the distinct equivocations observed by this node since last restart.
+pub static EQUIVOCATION_EVIDENCE_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_equivocation_evidence_count",
+        "Count of the distinct equivocations observed by this node since last restart."
+    )
+    .unwrap()
+});
+
 /// Total voting power of validators in validator set
 pub static TOTAL_VOTING_POWER: Lazy<Gauge> = Lazy::new(|| {
     register_gauge!(
@@ -291,6 +300,54 @@ pub static PROPOSER_PENDING_BLOCKS_FILL_FRACTION: Lazy<Gauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of consensus trace events dropped because the trace writer fell behind
+pub static CONSENSUS_TRACE_DROPPED_EVENTS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_trace_dropped_events",
+        "Count of consensus trace events dropped because the trace writer fell behind"
+    )
+    .unwrap()
+});
+
+/// Estimated gas of the payload packed into a proposal
+pub static PROPOSER_PACKED_BLOCK_GAS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "aptos_proposer_packed_block_gas",
+        "Estimated gas of the payload packed into a proposal",
+        exponential_buckets(/*start=*/ 1.0, /*factor=*/ 4.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
+
+/// Estimated fees of the payload packed into a proposal
+pub static PROPOSER_PACKED_BLOCK_FEES: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "aptos_proposer_packed_block_fees",
+        "Estimated fees of the payload packed into a proposal",
+        exponential_buckets(/*start=*/ 1.0, /*factor=*/ 4.0, /*count=*/ 25).unwrap(),
+    )
+    .unwrap()
+});
+
+/// Estimated fees of all the payload available to pack, when we make a proposal
+pub static PROPOSER_AVAILABLE_BLOCK_FEES: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "aptos_proposer_available_block_fees",
+        "Estimated fees of all the payload available to pack, when we make a proposal",
+        exponential_buckets(/*start=*/ 1.0, /*factor=*/ 4.0, /*count=*/ 25).unwrap(),
+    )
+    .unwrap()
+});
+
+/// Fraction of the available fees that were packed into a proposal (between 0 and 1)
+pub static PROPOSER_PACKED_FEES_FRACTION: Lazy<Gauge> = Lazy::new(|| {
+    register_gauge!(
+        "aptos_proposer_packed_fees_fraction",
+        "Fraction of the available fees that were packed into a proposal (between 0 and 1)",
+    )
+    .unwrap()
+});
+
 /// Next set of counters are computed at leader election time, with some delay.
 
 /// Current voting power fraction that participated in consensus
@@ -1,12 +1,54 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::{
+    dag::types::NodeMetadata,
+    liveness::{leader_reputation::ReputationHeuristic, proposer_election::choose_index},
+};
+use aptos_bitvec::BitVec;
 use aptos_consensus_types::common::{Author, Round};
+use aptos_types::{account_address::AccountAddress, account_config::NewBlockEvent};
+use std::collections::{HashMap, VecDeque};
 
-pub trait AnchorElection {
+/// What the anchor election learns from an anchor being ordered.
+pub struct CommitEvent {
+    anchor: NodeMetadata,
+    /// Authors of the strong links of the anchor, indexed by validator index.
+    parents: BitVec,
+    /// Authors of the anchors in the skipped rounds since the previous ordered anchor.
+    failed_authors: Vec<Author>,
+}
+
+impl CommitEvent {
+    pub fn new(anchor: NodeMetadata, parents: BitVec, failed_authors: Vec<Author>) -> Self {
+        Self {
+            anchor,
+            parents,
+            failed_authors,
+        }
+    }
+}
+
+pub trait AnchorElection: Send + Sync {
     fn get_anchor(&self, round: Round) -> Author;
 
-    fn commit(&mut self, round: Round);
+    fn commit(&mut self, commit_event: CommitEvent);
+
+    /// Forgets all the committed anchors, e.g. after state syncing past them.
+    fn reset(&mut self) {}
+
+    /// Number of rounds from the lowest unordered anchor round for which `get_anchor` only
+    /// depends on anchors that are already ordered, so is the same on all validators. Anchors
+    /// past that are not looked at until more anchors are ordered.
+    fn lookahead(&self) -> Round {
+        Round::MAX
+    }
+
+    /// Number of the latest ordered anchors `get_anchor` depends on, which need to be committed
+    /// again after a restart.
+    fn history_len(&self) -> usize {
+        0
+    }
 }
 
 pub struct RoundRobinAnchorElection {
@@ -24,5 +66,129 @@ impl AnchorElection for RoundRobinAnchorElection {
         self.validators[(round / 2) as usize % self.validators.len()]
     }
 
-    fn commit(&mut self, _round: Round) {}
+    fn commit(&mut self, _commit_event: CommitEvent) {}
+}
+
+/// Anchor election biased towards validators that recently got their anchors ordered and were
+/// linked to by them, so that a crashed validator stops being elected after a few failures.
+///
+/// The ordered anchors are fed back in as `NewBlockEvent`s, so the weights are computed by the
+/// same `ReputationHeuristic` as the leader reputation of the Jolteon proposer election. The
+/// anchor of a round only depends on the anchors ordered up to `round - exclude_round`, which is
+/// why the order rule never looks `exclude_round` or more rounds past its lowest unordered anchor
+/// round: up to there, all anchors have been ordered the same way on all honest validators.
+pub struct LeaderReputationAnchorElection {
+    epoch: u64,
+    epoch_to_validators: HashMap<u64, Vec<Author>>,
+    validator_indices: HashMap<Author, usize>,
+    voting_powers: Vec<u64>,
+    heuristic: Box<dyn ReputationHeuristic>,
+    window_size: usize,
+    exclude_round: u64,
+    /// Ordered anchors, newest first.
+    history: VecDeque<NewBlockEvent>,
+}
+
+impl LeaderReputationAnchorElection {
+    pub fn new(
+        epoch: u64,
+        validators: Vec<Author>,
+        voting_powers: Vec<u64>,
+        heuristic: Box<dyn ReputationHeuristic>,
+        window_size: usize,
+        exclude_round: u64,
+    ) -> Self {
+        assert_eq!(validators.len(), voting_powers.len());
+        assert!(
+            exclude_round > 0,
+            "exclude_round must be positive for the anchors to only depend on ordered anchors"
+        );
+        let validator_indices = validators
+            .iter()
+            .enumerate()
+            .map(|(idx, author)| (*author, idx))
+            .collect();
+
+        Self {
+            epoch,
+            epoch_to_validators: HashMap::from([(epoch, validators)]),
+            validator_indices,
+            voting_powers,
+            heuristic,
+            window_size,
+            exclude_round,
+            history: VecDeque::new(),
+        }
+    }
+
+    fn history_before(&self, target_round: Round) -> Vec<NewBlockEvent> {
+        self.history
+            .iter()
+            .filter(|event| event.round() <= target_round)
+            .take(self.window_size)
+            .cloned()
+            .collect()
+    }
+}
+
+impl AnchorElection for LeaderReputationAnchorElection {
+    fn get_anchor(&self, round: Round) -> Author {
+        let history = self.history_before(round.saturating_sub(self.exclude_round));
+        let weights = self
+            .heuristic
+            .get_weights(self.epoch, &self.epoch_to_validators, &history);
+        let validators = &self.epoch_to_validators[&self.epoch];
+        assert_eq!(weights.len(), validators.len());
+
+        let stake_weights: Vec<u128> = weights
+            .iter()
+            .zip(self.voting_powers.iter())
+            .map(|(w, vp)| *w as u128 * *vp as u128)
+            .collect();
+        let state = [
+            self.epoch.to_le_bytes().to_vec(),
+            round.to_le_bytes().to_vec(),
+        ]
+        .concat();
+        validators[choose_index(stake_weights, state)]
+    }
+
+    fn commit(&mut self, commit_event: CommitEvent) {
+        let CommitEvent {
+            anchor,
+            parents,
+            failed_authors,
+        } = commit_event;
+        let failed_proposer_indices = failed_authors
+            .iter()
+            .filter_map(|author| self.validator_indices.get(author))
+            .map(|idx| *idx as u64)
+            .collect();
+
+        self.history.push_front(NewBlockEvent::new(
+            AccountAddress::ZERO, // the block hash is not used by the heuristics
+            anchor.epoch(),
+            anchor.round(),
+            0, // height is not used by the heuristics
+            parents.into(),
+            *anchor.author(),
+            failed_proposer_indices,
+            anchor.timestamp(),
+        ));
+        self.history.truncate(self.history_len());
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    fn lookahead(&self) -> Round {
+        self.exclude_round
+    }
+
+    fn history_len(&self) -> usize {
+        // Anchors newer than `round - exclude_round` don't count, keep enough to fill the window
+        // after skipping them.
+        self.window_size + self.exclude_round as usize
+    }
 }
@@ -5,14 +5,19 @@ use super::{storage::DAGStorage, types::DAGMessage};
 use crate::{
     dag::{
         dag_store::Dag,
-        types::{CertificateAckState, CertifiedNode, Node, NodeCertificate, SignatureBuilder},
+        types::{
+            CertificateAckState, CertifiedNode, CertifiedNodeMessage, Node, NodeCertificate,
+            SignatureBuilder,
+        },
     },
     state_replication::PayloadClient,
     util::time_service::TimeService,
 };
 use aptos_consensus_types::common::{Author, Payload};
 use aptos_infallible::RwLock;
+use aptos_logger::error;
 use aptos_reliable_broadcast::ReliableBroadcast;
+use aptos_storage_interface::DbReader;
 use aptos_types::{block_info::Round, epoch_state::EpochState};
 use futures::{
     future::{AbortHandle, Abortable},
@@ -31,6 +36,7 @@ pub(crate) struct DagDriver {
     time_service: Arc<dyn TimeService>,
     rb_abort_handle: Option<AbortHandle>,
     storage: Arc<dyn DAGStorage>,
+    aptos_db: Arc<dyn DbReader>,
 }
 
 impl DagDriver {
@@ -43,9 +49,9 @@ impl DagDriver {
         current_round: Round,
         time_service: Arc<dyn TimeService>,
         storage: Arc<dyn DAGStorage>,
+        aptos_db: Arc<dyn DbReader>,
     ) -> Self {
-        // TODO: rebroadcast nodes after recovery
-        Self {
+        let mut driver = Self {
             author,
             epoch_state,
             dag,
@@ -55,7 +61,49 @@ impl DagDriver {
             time_service,
             rb_abort_handle: None,
             storage,
+            aptos_db,
+        };
+        match driver.recover_own_node() {
+            Ok(Some(node)) => {
+                driver.current_round = node.metadata().round();
+                if let Err(e) = driver.broadcast_node(node) {
+                    error!("Error broadcasting recovered node: {:?}", e);
+                }
+            },
+            Ok(None) => {},
+            Err(e) => error!("Error recovering own node: {:?}", e),
+        }
+        driver
+    }
+
+    /// Returns the node this validator was broadcasting before a restart if it didn't get
+    /// certified yet, so that it's broadcast again instead of proposing a conflicting node for
+    /// the same round. The other saved nodes are deleted.
+    fn recover_own_node(&self) -> anyhow::Result<Option<Node>> {
+        let (mut own_nodes, mut to_delete): (Vec<_>, Vec<_>) = self
+            .storage
+            .get_nodes()?
+            .into_iter()
+            .partition(|(_, node)| {
+                node.metadata().epoch() == self.epoch_state.epoch
+                    && *node.metadata().author() == self.author
+            });
+        own_nodes.sort_by_key(|(_, node)| node.metadata().round());
+        let latest = own_nodes.pop().and_then(|(digest, node)| {
+            if node.metadata().round() >= self.current_round
+                && !self.dag.read().exists(node.metadata())
+            {
+                Some(node)
+            } else {
+                to_delete.push((digest, node));
+                None
+            }
+        });
+        to_delete.extend(own_nodes);
+        for (digest, _) in to_delete {
+            self.storage.delete_node(digest)?;
         }
+        Ok(latest)
     }
 
     pub fn add_node(&mut self, node: CertifiedNode) -> anyhow::Result<()> {
@@ -68,7 +116,7 @@ impl DagDriver {
                     .get_strong_links_for_round(self.current_round, &self.epoch_state.verifier);
                 drop(dag_writer);
                 if let Some(strong_links) = maybe_strong_links {
-                    self.enter_new_round(strong_links);
+                    self.enter_new_round(strong_links)?;
                 }
             }
         }
@@ -76,7 +124,7 @@ impl DagDriver {
         Ok(())
     }
 
-    pub fn enter_new_round(&mut self, strong_links: Vec<NodeCertificate>) {
+    pub fn enter_new_round(&mut self, strong_links: Vec<NodeCertificate>) -> anyhow::Result<()> {
         // TODO: support pulling payload
         let payload = Payload::empty(false);
         // TODO: need to wait to pass median of parents timestamp
@@ -93,25 +141,40 @@ impl DagDriver {
         self.storage
             .save_node(&new_node)
             .expect("node must be saved");
-        self.broadcast_node(new_node);
+        self.broadcast_node(new_node)
     }
 
-    pub fn broadcast_node(&mut self, node: Node) {
+    pub fn broadcast_node(&mut self, node: Node) -> anyhow::Result<()> {
         let rb = self.reliable_broadcast.clone();
         let (abort_handle, abort_registration) = AbortHandle::new_pair();
         let signature_builder =
             SignatureBuilder::new(node.metadata().clone(), self.epoch_state.clone());
         let cert_ack_set = CertificateAckState::new(self.epoch_state.verifier.len());
+        // Lets peers that fell far behind know what to state sync to.
+        let ledger_info = self.aptos_db.get_latest_ledger_info()?;
+        let commit_info = ledger_info.commit_info();
+        let committed_anchor_id = self
+            .storage
+            .get_ordered_anchor_ids()?
+            .into_iter()
+            .map(|(node_id, _)| node_id)
+            .find(|node_id| {
+                node_id.epoch() == commit_info.epoch() && node_id.round() == commit_info.round()
+            });
         let task = self
             .reliable_broadcast
             .broadcast(node.clone(), signature_builder)
             .then(move |certificate| {
                 let certified_node = CertifiedNode::new(node, certificate.signatures().to_owned());
-                rb.broadcast(certified_node, cert_ack_set)
+                rb.broadcast(
+                    CertifiedNodeMessage::new(certified_node, ledger_info, committed_anchor_id),
+                    cert_ack_set,
+                )
             });
         tokio::spawn(Abortable::new(task, abort_registration));
         if let Some(prev_handle) = self.rb_abort_handle.replace(abort_handle) {
             prev_handle.abort();
         }
+        Ok(())
     }
 }
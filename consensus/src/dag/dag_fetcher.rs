@@ -7,7 +7,7 @@ use crate::dag::{
     dag_store::Dag,
     types::{CertifiedNode, FetchResponse, Node, RemoteFetchRequest},
 };
-use anyhow::ensure;
+use anyhow::{bail, ensure};
 use aptos_consensus_types::common::Author;
 use aptos_infallible::RwLock;
 use aptos_logger::error;
@@ -55,15 +55,14 @@ impl LocalFetchRequest {
     }
 }
 
-struct DagFetcher {
+pub struct DagFetcherService {
     epoch_state: Arc<EpochState>,
-    network: Arc<dyn DAGNetworkSender>,
     dag: Arc<RwLock<Dag>>,
     request_rx: Receiver<LocalFetchRequest>,
-    time_service: TimeService,
+    fetcher: DagFetcher,
 }
 
-impl DagFetcher {
+impl DagFetcherService {
     pub fn new(
         epoch_state: Arc<EpochState>,
         network: Arc<dyn DAGNetworkSender>,
@@ -73,11 +72,10 @@ impl DagFetcher {
         let (request_tx, request_rx) = tokio::sync::mpsc::channel(16);
         (
             Self {
-                epoch_state,
-                network,
+                epoch_state: epoch_state.clone(),
                 dag,
                 request_rx,
-                time_service,
+                fetcher: DagFetcher::new(epoch_state, network, time_service),
             },
             request_tx,
         )
@@ -108,48 +106,91 @@ impl DagFetcher {
                 )
             };
 
-            let mut rpc = RpcWithFallback::new(
-                responders,
-                remote_request.clone().into(),
-                Duration::from_millis(500),
-                Duration::from_secs(1),
-                self.network.clone(),
-                self.time_service.clone(),
-            );
-            while let Some(response) = rpc.next().await {
-                if let Ok(response) =
-                    response
-                        .and_then(FetchResponse::try_from)
-                        .and_then(|response| {
-                            response.verify(&remote_request, &self.epoch_state.verifier)
-                        })
+            match self
+                .fetcher
+                .fetch(remote_request, responders, self.dag.clone())
+                .await
+            {
+                Ok(()) => local_request.notify(),
+                // TODO retry
+                Err(e) => error!("Failed to fetch parents: {:?}", e),
+            }
+        }
+    }
+}
+
+/// Fetches the causal history of nodes from peers into a DAG.
+pub struct DagFetcher {
+    epoch_state: Arc<EpochState>,
+    network: Arc<dyn DAGNetworkSender>,
+    time_service: TimeService,
+}
+
+impl DagFetcher {
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        network: Arc<dyn DAGNetworkSender>,
+        time_service: TimeService,
+    ) -> Self {
+        Self {
+            epoch_state,
+            network,
+            time_service,
+        }
+    }
+
+    /// Requests the targets of `remote_request` and the nodes they reach which are missing from
+    /// `dag` from `responders` until all the targets are in `dag`.
+    pub async fn fetch(
+        &self,
+        remote_request: RemoteFetchRequest,
+        responders: Vec<Author>,
+        dag: Arc<RwLock<Dag>>,
+    ) -> anyhow::Result<()> {
+        let mut rpc = RpcWithFallback::new(
+            responders,
+            remote_request.clone().into(),
+            Duration::from_millis(500),
+            Duration::from_secs(1),
+            self.network.clone(),
+            self.time_service.clone(),
+        );
+        while let Some(response) = rpc.next().await {
+            if let Ok(response) = response
+                .and_then(FetchResponse::try_from)
+                .and_then(|response| response.verify(&remote_request, &self.epoch_state.verifier))
+            {
+                let mut certified_nodes = response.certified_nodes();
+                // Parents need to be added before their children.
+                certified_nodes.sort_by_key(|node| node.round());
+                // TODO: support chunk response
                 {
-                    let certified_nodes = response.certified_nodes();
-                    // TODO: support chunk response or fallback to state sync
-                    {
-                        let mut dag_writer = self.dag.write();
-                        for node in certified_nodes {
-                            if let Err(e) = dag_writer.add_node(node) {
-                                error!("Failed to add node {}", e);
-                            }
+                    let mut dag_writer = dag.write();
+                    for node in certified_nodes {
+                        if dag_writer.exists(node.metadata()) {
+                            continue;
+                        }
+                        if let Err(e) = dag_writer.add_node(node) {
+                            error!("Failed to add node {}", e);
                         }
                     }
+                }
 
-                    if self
-                        .dag
-                        .read()
-                        .all_exists(local_request.node().parents_metadata())
-                    {
-                        local_request.notify();
-                        break;
-                    }
+                if dag.read().all_exists(remote_request.targets().iter()) {
+                    return Ok(());
                 }
             }
-            // TODO retry
         }
+        bail!(DagFetchError::Exhausted)
     }
 }
 
+#[derive(Debug, ThisError)]
+pub enum DagFetchError {
+    #[error("no responder could provide the requested nodes")]
+    Exhausted,
+}
+
 #[derive(Debug, ThisError)]
 pub enum FetchRequestHandleError {
     #[error("parents are missing")]
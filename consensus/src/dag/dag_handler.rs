@@ -1,8 +1,11 @@
 // Copyright © Aptos Foundation
 
 use super::{
-    dag_fetcher::FetchRequestHandler, reliable_broadcast::CertifiedNodeHandler,
-    storage::DAGStorage, types::TDAGMessage,
+    dag_fetcher::FetchRequestHandler,
+    dag_state_sync::StateSyncManager,
+    reliable_broadcast::CertifiedNodeHandler,
+    storage::DAGStorage,
+    types::{CertifiedAck, CertifiedNodeMessage, TDAGMessage},
 };
 use crate::{
     dag::{
@@ -20,13 +23,19 @@ use aptos_network::protocols::network::RpcError;
 use aptos_types::{epoch_state::EpochState, validator_signer::ValidatorSigner};
 use bytes::Bytes;
 use futures::StreamExt;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 struct NetworkHandler {
     dag_rpc_rx: aptos_channel::Receiver<Author, IncomingDAGRequest>,
     node_receiver: NodeBroadcastHandler,
     certified_node_receiver: CertifiedNodeHandler,
     fetch_receiver: FetchRequestHandler,
+    state_sync: Arc<StateSyncManager>,
+    /// Set while a state sync is running in the background.
+    syncing: Arc<AtomicBool>,
     epoch_state: Arc<EpochState>,
 }
 
@@ -37,6 +46,7 @@ impl NetworkHandler {
         signer: ValidatorSigner,
         epoch_state: Arc<EpochState>,
         storage: Arc<dyn DAGStorage>,
+        state_sync: StateSyncManager,
     ) -> Self {
         Self {
             dag_rpc_rx,
@@ -49,6 +59,8 @@ impl NetworkHandler {
             certified_node_receiver: CertifiedNodeHandler::new(dag.clone()),
             epoch_state: epoch_state.clone(),
             fetch_receiver: FetchRequestHandler::new(dag, epoch_state),
+            state_sync: Arc::new(state_sync),
+            syncing: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -76,10 +88,9 @@ impl NetworkHandler {
                 .verify(&self.epoch_state.verifier)
                 .and_then(|_| self.node_receiver.process(node))
                 .map(|r| r.into()),
-            DAGMessage::CertifiedNodeMsg(node) => node
-                .verify(&self.epoch_state.verifier)
-                .and_then(|_| self.certified_node_receiver.process(node))
-                .map(|r| r.into()),
+            DAGMessage::CertifiedNodeMsg(node) => {
+                self.process_certified_node(node).await.map(|r| r.into())
+            },
             DAGMessage::FetchRequest(request) => request
                 .verify(&self.epoch_state.verifier)
                 .and_then(|_| self.fetch_receiver.process(request))
@@ -104,4 +115,29 @@ impl NetworkHandler {
             .send(response)
             .map_err(|_| anyhow::anyhow!("unable to respond to rpc"))
     }
+
+    async fn process_certified_node(
+        &mut self,
+        node: CertifiedNodeMessage,
+    ) -> anyhow::Result<CertifiedAck> {
+        node.verify(&self.epoch_state.verifier)?;
+        if self.state_sync.need_sync(node.ledger_info()) {
+            // Syncing takes a while, so it's done in the background for the other messages to be
+            // processed meanwhile. The node isn't acked until it's in the DAG, which it is once
+            // synced to, so its sender retries until then.
+            let round = node.round();
+            if !self.syncing.swap(true, Ordering::SeqCst) {
+                let state_sync = self.state_sync.clone();
+                let syncing = self.syncing.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = state_sync.sync_to(&node).await {
+                        warn!(error = ?e, "error state syncing");
+                    }
+                    syncing.store(false, Ordering::SeqCst);
+                });
+            }
+            bail!("state syncing, node of round {} not added yet", round);
+        }
+        self.certified_node_receiver.process(node.certified_node())
+    }
 }
@@ -0,0 +1,173 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::{
+        dag_fetcher::DagFetcher,
+        dag_store::Dag,
+        order_rule::OrderRule,
+        storage::DAGStorage,
+        types::{CertifiedNode, CertifiedNodeMessage, NodeId, RemoteFetchRequest},
+    },
+    state_replication::StateComputer,
+};
+use anyhow::{anyhow, ensure};
+use aptos_consensus_types::common::Round;
+use aptos_infallible::{Mutex, RwLock};
+use aptos_logger::info;
+use aptos_types::{epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures};
+use std::{mem, sync::Arc};
+
+/// Number of rounds below the committed anchor fetched into the DAG after a state sync.
+pub const DAG_WINDOW: Round = 10;
+/// A node that is this many rounds behind the ledger committed by its peers can't catch up by
+/// fetching missing parents anymore and needs to state sync.
+pub const STATE_SYNC_THRESHOLD: Round = 3 * DAG_WINDOW;
+
+/// Catches up a node that fell far behind its peers, by state syncing the committed ledger and
+/// fetching only the recent window of the DAG instead of its whole history.
+pub struct StateSyncManager {
+    epoch_state: Arc<EpochState>,
+    dag: Arc<RwLock<Dag>>,
+    order_rule: Arc<Mutex<OrderRule>>,
+    dag_fetcher: DagFetcher,
+    state_computer: Arc<dyn StateComputer>,
+    storage: Arc<dyn DAGStorage>,
+}
+
+impl StateSyncManager {
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        dag: Arc<RwLock<Dag>>,
+        order_rule: Arc<Mutex<OrderRule>>,
+        dag_fetcher: DagFetcher,
+        state_computer: Arc<dyn StateComputer>,
+        storage: Arc<dyn DAGStorage>,
+    ) -> Self {
+        Self {
+            epoch_state,
+            dag,
+            order_rule,
+            dag_fetcher,
+            state_computer,
+            storage,
+        }
+    }
+
+    /// Whether the local DAG is too far behind the given commit of a peer to catch up by fetching.
+    pub fn need_sync(&self, ledger_info: &LedgerInfoWithSignatures) -> bool {
+        let commit_info = ledger_info.commit_info();
+        commit_info.epoch() == self.epoch_state.epoch
+            && self.dag.read().highest_round() + STATE_SYNC_THRESHOLD < commit_info.round()
+    }
+
+    /// Syncs the ledger to the commit carried by `node` and replaces the DAG with the window of
+    /// `DAG_WINDOW` rounds below the committed anchor up to `node`. The committed anchor, as
+    /// identified by the sender of `node` from the anchors it ordered, and its causal history are
+    /// marked as committed, and the order rule resumes ordering from the next anchor. The current
+    /// DAG is kept if fetching or syncing fails.
+    pub async fn sync_to(&self, node: &CertifiedNodeMessage) -> anyhow::Result<()> {
+        let commit_li = node.ledger_info();
+        let commit_info = commit_li.commit_info();
+        ensure!(
+            commit_info.epoch() == self.epoch_state.epoch,
+            "can't sync to a commit of epoch {} in epoch {}",
+            commit_info.epoch(),
+            self.epoch_state.epoch,
+        );
+        ensure!(
+            commit_info.round() <= node.round(),
+            "node of round {} can't reach the anchor committed at round {}",
+            node.round(),
+            commit_info.round(),
+        );
+        let anchor_id = node.committed_anchor_id().ok_or_else(|| {
+            anyhow!(
+                "no anchor known for the commit at round {}",
+                commit_info.round()
+            )
+        })?;
+        ensure!(
+            anchor_id.epoch() == commit_info.epoch() && anchor_id.round() == commit_info.round(),
+            "anchor {:?} isn't the one committed at round {}",
+            anchor_id,
+            commit_info.round(),
+        );
+        info!(
+            "Syncing DAG from round {} to the anchor committed at round {}",
+            self.dag.read().highest_round(),
+            commit_info.round(),
+        );
+
+        // The window is fetched into a DAG of its own, the current one is kept until it's
+        // complete. Being far behind, none of the fetched nodes are in the current DAG.
+        let start_round = commit_info.round().saturating_sub(DAG_WINDOW);
+        let new_dag = Arc::new(RwLock::new(Dag::new_empty(
+            self.epoch_state.clone(),
+            self.storage.clone(),
+            start_round,
+        )));
+        let result = self.fetch_and_sync(node, anchor_id, new_dag.clone()).await;
+        let mut new_dag = Arc::try_unwrap(new_dag)
+            .map_err(|_| anyhow!("fetched DAG still in use"))?
+            .into_inner();
+        let anchor = match result {
+            Ok(anchor) => anchor,
+            Err(e) => {
+                // Forget whatever got fetched.
+                new_dag.gc_before_round(Round::MAX)?;
+                return Err(e);
+            },
+        };
+        new_dag.mark_as_committed(&anchor);
+
+        // Nothing in the old DAG nor the anchors ordered from it are of use anymore.
+        let mut old_dag = mem::replace(&mut *self.dag.write(), new_dag);
+        old_dag.gc_before_round(Round::MAX)?;
+        let ordered_anchor_ids = self
+            .storage
+            .get_ordered_anchor_ids()?
+            .into_iter()
+            .map(|(node_id, _)| node_id)
+            .collect();
+        self.storage.delete_ordered_anchor_ids(ordered_anchor_ids)?;
+        self.storage.save_ordered_anchor_id(&anchor.id())?;
+
+        self.order_rule
+            .lock()
+            .reset(commit_li.ledger_info().clone());
+        Ok(())
+    }
+
+    /// Fetches the DAG up to `node` into `dag` and syncs the ledger to the commit carried by
+    /// `node`, returning the committed anchor.
+    async fn fetch_and_sync(
+        &self,
+        node: &CertifiedNodeMessage,
+        anchor_id: &NodeId,
+        dag: Arc<RwLock<Dag>>,
+    ) -> anyhow::Result<Arc<CertifiedNode>> {
+        let request = RemoteFetchRequest::new(
+            self.epoch_state.epoch,
+            vec![node.metadata().clone()],
+            dag.read().bitmask(node.round()),
+        );
+        let responders = node
+            .certificate()
+            .signers(&self.epoch_state.verifier.get_ordered_account_addresses());
+        self.dag_fetcher
+            .fetch(request, responders, dag.clone())
+            .await?;
+
+        let anchor = dag
+            .read()
+            .get_node_by_round_author(anchor_id.round(), &anchor_id.author())
+            .cloned()
+            .ok_or_else(|| anyhow!("committed anchor {:?} not fetched", anchor_id))?;
+
+        self.state_computer
+            .sync_to(node.ledger_info().clone())
+            .await?;
+        Ok(anchor)
+    }
+}
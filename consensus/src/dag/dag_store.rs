@@ -13,6 +13,7 @@ use aptos_logger::error;
 use aptos_types::{epoch_state::EpochState, validator_verifier::ValidatorVerifier};
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
+    mem,
     sync::Arc,
 };
 
@@ -36,6 +37,11 @@ impl NodeStatus {
         assert!(matches!(self, NodeStatus::Unordered(_)));
         *self = NodeStatus::Ordered(self.as_node().clone());
     }
+
+    pub fn mark_as_committed(&mut self) {
+        assert!(!matches!(self, NodeStatus::Committed(_)));
+        *self = NodeStatus::Committed(self.as_node().clone());
+    }
 }
 
 /// Data structure that stores the DAG representation, it maintains round based index.
@@ -44,11 +50,19 @@ pub struct Dag {
     nodes_by_round: BTreeMap<Round, Vec<Option<NodeStatus>>>,
     /// Map between peer id to vector index
     author_to_index: HashMap<Author, usize>,
+    /// The lowest round the DAG is allowed to hold, parents below it are not required.
+    start_round: Round,
     storage: Arc<dyn DAGStorage>,
 }
 
 impl Dag {
-    pub fn new(epoch_state: Arc<EpochState>, storage: Arc<dyn DAGStorage>) -> Self {
+    /// Restores the DAG from the storage. `committed_round` is the round of the latest anchor
+    /// committed to the ledger, everything below it is garbage collected.
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        storage: Arc<dyn DAGStorage>,
+        committed_round: Round,
+    ) -> Self {
         let epoch = epoch_state.epoch;
         let author_to_index = epoch_state.verifier.address_to_validator_index().clone();
         let num_validators = author_to_index.len();
@@ -73,11 +87,83 @@ impl Dag {
         if let Err(e) = storage.delete_certified_nodes(expired) {
             error!("Error deleting expired nodes: {:?}", e);
         }
-        Self {
+        let start_round = nodes_by_round
+            .first_key_value()
+            .map_or(0, |(round, _)| *round);
+        let mut dag = Self {
             nodes_by_round,
             author_to_index,
+            start_round,
             storage,
+        };
+        if let Err(e) = dag.recover_ordered_anchors(epoch, committed_round) {
+            error!("Error recovering ordered anchors: {:?}", e);
         }
+        dag
+    }
+
+    /// Creates an empty DAG that starts at `start_round`, e.g. to fetch a recent window of the DAG
+    /// into after falling behind.
+    pub fn new_empty(
+        epoch_state: Arc<EpochState>,
+        storage: Arc<dyn DAGStorage>,
+        start_round: Round,
+    ) -> Self {
+        Self {
+            nodes_by_round: BTreeMap::new(),
+            author_to_index: epoch_state.verifier.address_to_validator_index().clone(),
+            start_round,
+            storage,
+        }
+    }
+
+    /// Marks the nodes ordered by the anchors up to `committed_round` as committed. The anchors
+    /// ordered after `committed_round` are forgotten, since the order rule orders them again after
+    /// a restart. The committed ones are left for the order rule to restore the anchor election
+    /// from, which also garbage collects the rounds it doesn't need anymore.
+    fn recover_ordered_anchors(
+        &mut self,
+        epoch: u64,
+        committed_round: Round,
+    ) -> anyhow::Result<()> {
+        let (committed_anchor_ids, to_delete): (Vec<_>, Vec<_>) = self
+            .storage
+            .get_ordered_anchor_ids()?
+            .into_iter()
+            .map(|(node_id, _)| node_id)
+            .partition(|node_id| node_id.epoch() == epoch && node_id.round() <= committed_round);
+
+        for anchor_id in &committed_anchor_ids {
+            if let Some(anchor) = self
+                .get_node_by_round_author(anchor_id.round(), &anchor_id.author())
+                .cloned()
+            {
+                self.mark_as_committed(&anchor);
+            }
+        }
+        self.storage.delete_ordered_anchor_ids(to_delete)
+    }
+
+    /// Marks the anchor and the nodes it orders as committed.
+    pub fn mark_as_committed(&mut self, anchor: &Arc<CertifiedNode>) {
+        for node_status in self.reachable_mut(anchor, None) {
+            node_status.mark_as_committed();
+        }
+    }
+
+    /// Removes the rounds below `min_round` from memory and the storage.
+    pub fn gc_before_round(&mut self, min_round: Round) -> anyhow::Result<()> {
+        let to_retain = self.nodes_by_round.split_off(&min_round);
+        let to_delete = mem::replace(&mut self.nodes_by_round, to_retain);
+        self.start_round = self.start_round.max(min_round);
+
+        let digests = to_delete
+            .values()
+            .flatten()
+            .flatten()
+            .map(|node_status| node_status.as_node().digest())
+            .collect();
+        self.storage.delete_certified_nodes(digests)
     }
 
     pub(crate) fn lowest_round(&self) -> Round {
@@ -85,15 +171,13 @@ impl Dag {
             .nodes_by_round
             .first_key_value()
             .map(|(round, _)| round)
-            .unwrap_or(&0)
+            .unwrap_or(&self.start_round)
     }
 
     pub fn highest_round(&self) -> Round {
-        *self
-            .nodes_by_round
+        self.nodes_by_round
             .last_key_value()
-            .map(|(round, _)| round)
-            .unwrap_or(&0)
+            .map_or(self.start_round.saturating_sub(1), |(round, _)| *round)
     }
 
     pub fn add_node(&mut self, node: CertifiedNode) -> anyhow::Result<()> {
@@ -107,7 +191,10 @@ impl Dag {
         ensure!(round >= self.lowest_round(), "round too low");
         ensure!(round <= self.highest_round() + 1, "round too high");
         for parent in node.parents() {
-            ensure!(self.exists(parent.metadata()), "parent not exist");
+            ensure!(
+                parent.metadata().round() < self.lowest_round() || self.exists(parent.metadata()),
+                "parent not exist"
+            );
         }
         let round_ref = self
             .nodes_by_round
@@ -3,8 +3,13 @@
 
 use super::dag_store::NodeStatus;
 use crate::dag::{
-    anchor_election::AnchorElection, dag_store::Dag, types::NodeMetadata, CertifiedNode,
+    anchor_election::{AnchorElection, CommitEvent},
+    dag_store::Dag,
+    storage::DAGStorage,
+    types::NodeMetadata,
+    CertifiedNode,
 };
+use aptos_bitvec::BitVec;
 use aptos_consensus_types::common::Round;
 use aptos_crypto::HashValue;
 use aptos_infallible::RwLock;
@@ -20,6 +25,7 @@ pub struct OrderRule {
     dag: Arc<RwLock<Dag>>,
     anchor_election: Box<dyn AnchorElection>,
     ordered_nodes_sender: UnboundedSender<Vec<Arc<CertifiedNode>>>,
+    storage: Arc<dyn DAGStorage>,
 }
 
 impl OrderRule {
@@ -29,16 +35,72 @@ impl OrderRule {
         dag: Arc<RwLock<Dag>>,
         anchor_election: Box<dyn AnchorElection>,
         ordered_nodes_sender: UnboundedSender<Vec<Arc<CertifiedNode>>>,
+        storage: Arc<dyn DAGStorage>,
     ) -> Self {
-        // TODO: we need to initialize the anchor election based on the dag
-        Self {
+        let mut order_rule = Self {
             epoch_state,
             ordered_block_id: latest_ledger_info.commit_info().id(),
             lowest_unordered_anchor_round: latest_ledger_info.commit_info().round() + 1,
             dag,
             anchor_election,
             ordered_nodes_sender,
+            storage,
+        };
+        if let Err(e) = order_rule.recover_anchor_election() {
+            error!("Error recovering anchor election: {:?}", e);
+        }
+        order_rule
+    }
+
+    /// Resumes ordering after the commit of `ledger_info`, e.g. after state syncing to it. The
+    /// anchor election is restored from the anchors committed up to there.
+    pub fn reset(&mut self, ledger_info: LedgerInfo) {
+        self.ordered_block_id = ledger_info.commit_info().id();
+        self.lowest_unordered_anchor_round = ledger_info.commit_info().round() + 1;
+        self.anchor_election.reset();
+        if let Err(e) = self.recover_anchor_election() {
+            error!("Error recovering anchor election: {:?}", e);
+        }
+    }
+
+    /// Commits the anchors committed before a restart to the anchor election again, and forgets
+    /// the ones, and the rounds of the DAG, older than what the anchor election needs.
+    fn recover_anchor_election(&mut self) -> anyhow::Result<()> {
+        let mut anchor_ids: Vec<_> = self
+            .storage
+            .get_ordered_anchor_ids()?
+            .into_iter()
+            .map(|(node_id, _)| node_id)
+            .collect();
+        anchor_ids.sort_by_key(|node_id| node_id.round());
+        // One more than the anchor election needs is kept, since the anchors that failed before
+        // the oldest one aren't known anymore once the rounds below it are garbage collected.
+        let num_to_keep = self.anchor_election.history_len() + 1;
+        if anchor_ids.len() > num_to_keep {
+            let to_delete = anchor_ids.drain(..anchor_ids.len() - num_to_keep).collect();
+            self.storage.delete_ordered_anchor_ids(to_delete)?;
+            self.dag.write().gc_before_round(anchor_ids[0].round())?;
+        }
+
+        let anchors: Vec<_> = {
+            let dag_reader = self.dag.read();
+            anchor_ids
+                .iter()
+                .filter_map(|node_id| {
+                    dag_reader
+                        .get_node_by_round_author(node_id.round(), &node_id.author())
+                        .cloned()
+                })
+                .collect()
+        };
+        // Unless the DAG was garbage collected, in which case the oldest anchor falls out of the
+        // history of the anchor election anyways, the DAG starts with the first round to order.
+        let mut lowest_unordered_anchor_round = self.dag.read().lowest_round();
+        for anchor in anchors {
+            self.commit_to_anchor_election(&anchor, lowest_unordered_anchor_round);
+            lowest_unordered_anchor_round = anchor.round() + 1;
         }
+        Ok(())
     }
 
     /// Check if two rounds have the same parity
@@ -77,6 +139,11 @@ impl OrderRule {
         target_round: Round,
     ) -> Option<Arc<CertifiedNode>> {
         let dag_reader = self.dag.read();
+        // Anchors further out may still change with the anchors ordered before them.
+        let target_round = target_round.min(
+            self.lowest_unordered_anchor_round
+                .saturating_add(self.anchor_election.lookahead()),
+        );
         while start_round < target_round {
             let anchor_author = self.anchor_election.get_anchor(start_round);
             // I "think" it's impossible to get ordered/committed node here but to double check
@@ -122,17 +189,43 @@ impl OrderRule {
         current_anchor
     }
 
-    /// Finalize the ordering with the given anchor node, update anchor election and construct blocks for execution.
-    pub fn finalize_order(&mut self, anchor: Arc<CertifiedNode>) {
-        let _failed_anchors: Vec<_> = (self.lowest_unordered_anchor_round..anchor.round())
+    /// Lets the anchor election know about an ordered anchor, the anchors of the rounds since
+    /// `lowest_unordered_anchor_round` having failed.
+    fn commit_to_anchor_election(
+        &mut self,
+        anchor: &CertifiedNode,
+        lowest_unordered_anchor_round: Round,
+    ) {
+        let failed_anchors: Vec<_> = (lowest_unordered_anchor_round..anchor.round())
             .step_by(2)
             .map(|failed_round| self.anchor_election.get_anchor(failed_round))
             .collect();
+
+        let validator_indices = self.epoch_state.verifier.address_to_validator_index();
+        let mut parents = BitVec::with_num_bits(self.epoch_state.verifier.len() as u16);
+        for parent in anchor.parents_metadata() {
+            if let Some(idx) = validator_indices.get(parent.author()) {
+                parents.set(*idx as u16);
+            }
+        }
+        self.anchor_election.commit(CommitEvent::new(
+            anchor.metadata().clone(),
+            parents,
+            failed_anchors,
+        ));
+    }
+
+    /// Finalize the ordering with the given anchor node, update anchor election and construct blocks for execution.
+    pub fn finalize_order(&mut self, anchor: Arc<CertifiedNode>) {
         assert!(Self::check_parity(
             self.lowest_unordered_anchor_round,
             anchor.round(),
         ));
+        self.commit_to_anchor_election(&anchor, self.lowest_unordered_anchor_round);
         self.lowest_unordered_anchor_round = anchor.round() + 1;
+        if let Err(e) = self.storage.save_ordered_anchor_id(&anchor.id()) {
+            error!("Failed to save ordered anchor id {:?}", e);
+        }
 
         let mut dag_writer = self.dag.write();
         let mut ordered_nodes: Vec<_> = dag_writer
@@ -13,6 +13,8 @@ use aptos_crypto::HashValue;
 pub trait DAGStorage: Send + Sync {
     fn save_node(&self, node: &Node) -> anyhow::Result<()>;
 
+    fn get_nodes(&self) -> anyhow::Result<Vec<(HashValue, Node)>>;
+
     fn delete_node(&self, digest: HashValue) -> anyhow::Result<()>;
 
     fn save_vote(&self, node_id: &NodeId, vote: &Vote) -> anyhow::Result<()>;
@@ -39,6 +41,10 @@ impl DAGStorage for ConsensusDB {
         Ok(self.save_data::<NodeSchema>(&node.digest(), node)?)
     }
 
+    fn get_nodes(&self) -> anyhow::Result<Vec<(HashValue, Node)>> {
+        Ok(self.get_all_data::<NodeSchema>()?)
+    }
+
     fn delete_node(&self, digest: HashValue) -> anyhow::Result<()> {
         Ok(self.delete_data::<NodeSchema>(vec![digest])?)
     }
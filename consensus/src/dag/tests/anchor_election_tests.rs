@@ -0,0 +1,288 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::{
+        anchor_election::{
+            AnchorElection, CommitEvent, LeaderReputationAnchorElection, RoundRobinAnchorElection,
+        },
+        dag_store::Dag,
+        order_rule::OrderRule,
+        tests::{dag_test::MockStorage, helpers::new_certified_node},
+        types::{NodeCertificate, NodeMetadata},
+        CertifiedNode,
+    },
+    liveness::leader_reputation::ProposerAndVoterHeuristic,
+    test_utils::placeholder_ledger_info,
+};
+use aptos_bitvec::BitVec;
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_infallible::RwLock;
+use aptos_types::{
+    aggregate_signature::AggregateSignature, block_info::BlockInfo, epoch_state::EpochState,
+    ledger_info::LedgerInfo, validator_verifier::random_validator_verifier,
+};
+use futures_channel::mpsc::{unbounded, UnboundedReceiver};
+use std::sync::Arc;
+
+const NUM_VALIDATORS: usize = 4;
+const CRASHED: usize = NUM_VALIDATORS - 1;
+
+pub(super) fn new_reputation_election(validators: &[Author]) -> LeaderReputationAnchorElection {
+    LeaderReputationAnchorElection::new(
+        1,
+        validators.to_vec(),
+        vec![1; validators.len()],
+        Box::new(ProposerAndVoterHeuristic::new(
+            validators[0],
+            1000,
+            10,
+            1,
+            10,
+            10,
+            100,
+            false,
+        )),
+        100,
+        10,
+    )
+}
+
+/// Bitvec with all but the crashed validator set.
+fn live_validators_bitvec() -> BitVec {
+    let mut bitvec = BitVec::with_num_bits(NUM_VALIDATORS as u16);
+    for idx in 0..CRASHED {
+        bitvec.set(idx as u16);
+    }
+    bitvec
+}
+
+#[test]
+#[should_panic]
+fn test_reputation_rejects_zero_exclude_round() {
+    let (_, validator_verifier) = random_validator_verifier(NUM_VALIDATORS, None, false);
+    let validators = validator_verifier.get_ordered_account_addresses();
+    LeaderReputationAnchorElection::new(
+        1,
+        validators.clone(),
+        vec![1; validators.len()],
+        Box::new(ProposerAndVoterHeuristic::new(
+            validators[0],
+            1000,
+            10,
+            1,
+            10,
+            10,
+            100,
+            false,
+        )),
+        100,
+        0,
+    );
+}
+
+#[test]
+fn test_reputation_excludes_crashed_validator() {
+    let (_, validator_verifier) = random_validator_verifier(NUM_VALIDATORS, None, false);
+    let validators = validator_verifier.get_ordered_account_addresses();
+    let mut anchor_election = new_reputation_election(&validators);
+
+    // The crashed validator's anchors keep failing, everyone else's get ordered.
+    let mut failed_authors = vec![];
+    for round in (1..40).step_by(2) {
+        let anchor = anchor_election.get_anchor(round);
+        if anchor == validators[CRASHED] {
+            failed_authors.push(anchor);
+            continue;
+        }
+        anchor_election.commit(CommitEvent::new(
+            NodeMetadata::new_for_test(1, round, anchor, 0, HashValue::zero()),
+            live_validators_bitvec(),
+            std::mem::take(&mut failed_authors),
+        ));
+    }
+
+    let num_crashed_anchors = (41..241)
+        .step_by(2)
+        .filter(|round| anchor_election.get_anchor(*round) == validators[CRASHED])
+        .count();
+    assert!(num_crashed_anchors <= 1);
+}
+
+/// Builds a DAG of `num_rounds` rounds, in which every node links to all nodes of the previous
+/// round, with the crashed validator never producing a node.
+fn dag_with_crashed_validator(validators: &[Author], num_rounds: Round) -> Vec<CertifiedNode> {
+    let mut nodes = vec![];
+    let mut parents: Vec<NodeCertificate> = vec![];
+    for round in 1..=num_rounds {
+        let round_nodes: Vec<_> = validators[..CRASHED]
+            .iter()
+            .map(|author| new_certified_node(round, *author, parents.clone()))
+            .collect();
+        parents = round_nodes
+            .iter()
+            .map(|node| NodeCertificate::new(node.metadata().clone(), AggregateSignature::empty()))
+            .collect();
+        nodes.extend(round_nodes);
+    }
+    nodes
+}
+
+/// Returns the rounds of the ordered anchors.
+fn order_anchors(
+    epoch_state: Arc<EpochState>,
+    nodes: &[CertifiedNode],
+    anchor_election: Box<dyn AnchorElection>,
+) -> Vec<Round> {
+    let mut dag = Dag::new(epoch_state.clone(), Arc::new(MockStorage::new()), 0);
+    for node in nodes {
+        dag.add_node(node.clone()).unwrap();
+    }
+    let (tx, mut rx) = unbounded();
+    let mut order_rule = OrderRule::new(
+        epoch_state,
+        placeholder_ledger_info(),
+        Arc::new(RwLock::new(dag)),
+        anchor_election,
+        tx,
+        Arc::new(MockStorage::new()),
+    );
+    for node in nodes {
+        order_rule.process_new_node(node);
+    }
+
+    let mut anchor_rounds = vec![];
+    while let Ok(Some(ordered_nodes)) = rx.try_next() {
+        anchor_rounds.push(ordered_nodes.last().unwrap().round());
+    }
+    anchor_rounds
+}
+
+/// Feeds the nodes to the order rule and returns the anchors it ordered.
+fn process_nodes(
+    order_rule: &mut OrderRule,
+    rx: &mut UnboundedReceiver<Vec<Arc<CertifiedNode>>>,
+    nodes: &[CertifiedNode],
+) -> Vec<Arc<CertifiedNode>> {
+    for node in nodes {
+        order_rule.process_new_node(node);
+    }
+    let mut anchors = vec![];
+    while let Ok(Some(ordered_nodes)) = rx.try_next() {
+        anchors.push(ordered_nodes.last().unwrap().clone());
+    }
+    anchors
+}
+
+#[test]
+fn test_order_rule_recovers_anchor_election() {
+    let (_, validator_verifier) = random_validator_verifier(NUM_VALIDATORS, None, false);
+    let validators = validator_verifier.get_ordered_account_addresses();
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+    let nodes = dag_with_crashed_validator(&validators, 100);
+    let (nodes_before, nodes_after) = nodes.split_at(60 * CRASHED);
+
+    let storage = Arc::new(MockStorage::new());
+    let mut dag = Dag::new(epoch_state.clone(), storage.clone(), 0);
+    for node in nodes_before {
+        dag.add_node(node.clone()).unwrap();
+    }
+    let dag = Arc::new(RwLock::new(dag));
+    let (tx, mut rx) = unbounded();
+    let mut order_rule = OrderRule::new(
+        epoch_state.clone(),
+        placeholder_ledger_info(),
+        dag.clone(),
+        Box::new(new_reputation_election(&validators)),
+        tx,
+        storage.clone(),
+    );
+    let committed_anchor = process_nodes(&mut order_rule, &mut rx, nodes_before)
+        .pop()
+        .unwrap();
+    let committed_round = committed_anchor.round();
+
+    // Restart from what's in the storage, with everything ordered so far committed.
+    let mut recovered_dag = Dag::new(epoch_state.clone(), storage.clone(), committed_round);
+    let (recovered_tx, mut recovered_rx) = unbounded();
+    for node in nodes_after {
+        dag.write().add_node(node.clone()).unwrap();
+        recovered_dag.add_node(node.clone()).unwrap();
+    }
+    let mut recovered_order_rule = OrderRule::new(
+        epoch_state,
+        LedgerInfo::new(
+            BlockInfo::new(
+                1,
+                committed_round,
+                committed_anchor.digest(),
+                HashValue::zero(),
+                0,
+                0,
+                None,
+            ),
+            HashValue::zero(),
+        ),
+        Arc::new(RwLock::new(recovered_dag)),
+        Box::new(new_reputation_election(&validators)),
+        recovered_tx,
+        storage,
+    );
+
+    // The recovered anchor election elects the same anchors as the one that kept running.
+    let anchor_ids = |anchors: Vec<Arc<CertifiedNode>>| {
+        anchors.iter().map(|anchor| anchor.id()).collect::<Vec<_>>()
+    };
+    let expected = anchor_ids(process_nodes(&mut order_rule, &mut rx, nodes_after));
+    assert!(!expected.is_empty());
+    assert_eq!(
+        anchor_ids(process_nodes(
+            &mut recovered_order_rule,
+            &mut recovered_rx,
+            nodes_after
+        )),
+        expected
+    );
+}
+
+/// Number of anchor rounds after `after_round` that got skipped, because the anchor wasn't
+/// there to be ordered.
+fn num_skipped_anchors(anchor_rounds: &[Round], after_round: Round) -> usize {
+    anchor_rounds
+        .windows(2)
+        .filter(|w| w[0] >= after_round)
+        .map(|w| ((w[1] - w[0]) / 2 - 1) as usize)
+        .sum()
+}
+
+#[test]
+fn test_order_rule_with_crashed_validator() {
+    let (_, validator_verifier) = random_validator_verifier(NUM_VALIDATORS, None, false);
+    let validators = validator_verifier.get_ordered_account_addresses();
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+    let nodes = dag_with_crashed_validator(&validators, 100);
+
+    let round_robin_anchors = order_anchors(
+        epoch_state.clone(),
+        &nodes,
+        Box::new(RoundRobinAnchorElection::new(validators.clone())),
+    );
+    // Every 4th anchor round belongs to the crashed validator.
+    assert!(num_skipped_anchors(&round_robin_anchors, 20) >= 8);
+
+    let reputation_anchors = order_anchors(
+        epoch_state,
+        &nodes,
+        Box::new(new_reputation_election(&validators)),
+    );
+    // Once the others are seen active, the crashed validator is hardly ever elected.
+    assert!(num_skipped_anchors(&reputation_anchors, 20) <= 1);
+    assert!(*reputation_anchors.last().unwrap() >= 95);
+}
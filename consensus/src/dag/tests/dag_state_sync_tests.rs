@@ -0,0 +1,346 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::dag_test::MockStorage;
+use crate::{
+    dag::{
+        anchor_election::{AnchorElection, RoundRobinAnchorElection},
+        dag_fetcher::{DagFetcher, FetchRequestHandler},
+        dag_network::{DAGNetworkSender, RpcWithFallback},
+        dag_state_sync::{StateSyncManager, DAG_WINDOW},
+        dag_store::{Dag, NodeStatus},
+        order_rule::OrderRule,
+        storage::DAGStorage,
+        types::{CertifiedNodeMessage, DAGMessage, NodeCertificate, RemoteFetchRequest},
+        CertifiedNode, Node, NodeId, RpcHandler,
+    },
+    test_utils::{placeholder_ledger_info, EmptyStateComputer},
+};
+use aptos_consensus_types::common::{Author, Payload, Round};
+use aptos_crypto::HashValue;
+use aptos_infallible::{Mutex, RwLock};
+use aptos_time_service::TimeService;
+use aptos_types::{
+    aggregate_signature::{AggregateSignature, PartialSignatures},
+    block_info::BlockInfo,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+    validator_verifier::{random_validator_verifier, ValidatorVerifier},
+};
+use async_trait::async_trait;
+use futures_channel::mpsc::{unbounded, UnboundedReceiver};
+use std::{sync::Arc, time::Duration};
+
+const NUM_ROUNDS: Round = 40;
+const COMMITTED_ROUND: Round = 33;
+
+#[derive(Clone)]
+struct MockDAGNetworkSender {
+    fetch_handler: Arc<Mutex<FetchRequestHandler>>,
+    time_service: TimeService,
+}
+
+#[async_trait]
+impl DAGNetworkSender for MockDAGNetworkSender {
+    async fn send_rpc(
+        &self,
+        _receiver: Author,
+        message: DAGMessage,
+        _timeout: Duration,
+    ) -> anyhow::Result<DAGMessage> {
+        let request: RemoteFetchRequest = message.try_into()?;
+        Ok(self.fetch_handler.lock().process(request)?.into())
+    }
+
+    async fn send_rpc_with_fallbacks(
+        &self,
+        responders: Vec<Author>,
+        message: DAGMessage,
+        retry_interval: Duration,
+        rpc_timeout: Duration,
+    ) -> RpcWithFallback {
+        RpcWithFallback::new(
+            responders,
+            message,
+            retry_interval,
+            rpc_timeout,
+            Arc::new(self.clone()),
+            self.time_service.clone(),
+        )
+    }
+}
+
+fn new_signed_certified_node(
+    round: Round,
+    author: Author,
+    parents: Vec<NodeCertificate>,
+    signers: &[ValidatorSigner],
+    verifier: &ValidatorVerifier,
+) -> CertifiedNode {
+    let node = Node::new(1, round, author, 0, Payload::empty(false), parents);
+    let signatures = PartialSignatures::new(
+        signers
+            .iter()
+            .map(|signer| (signer.author(), node.sign_vote(signer).unwrap()))
+            .collect(),
+    );
+    CertifiedNode::new(node, verifier.aggregate_signatures(&signatures).unwrap())
+}
+
+/// Rounds 1 to `NUM_ROUNDS`, in which every validator's node links to all nodes of the previous
+/// round.
+fn generate_nodes(
+    signers: &[ValidatorSigner],
+    verifier: &ValidatorVerifier,
+) -> Vec<Vec<CertifiedNode>> {
+    let mut nodes_by_round = vec![vec![]];
+    for round in 1..=NUM_ROUNDS {
+        let parents: Vec<_> = nodes_by_round
+            .last()
+            .unwrap()
+            .iter()
+            .map(|node: &CertifiedNode| node.certificate())
+            .collect();
+        nodes_by_round.push(
+            signers
+                .iter()
+                .map(|signer| {
+                    new_signed_certified_node(
+                        round,
+                        signer.author(),
+                        parents.clone(),
+                        signers,
+                        verifier,
+                    )
+                })
+                .collect(),
+        );
+    }
+    nodes_by_round
+}
+
+/// The id of the committed block is unrelated to the digest of its anchor.
+fn new_ledger_info(round: Round) -> LedgerInfoWithSignatures {
+    LedgerInfoWithSignatures::new(
+        LedgerInfo::new(
+            BlockInfo::new(1, round, HashValue::random(), HashValue::zero(), 0, 0, None),
+            HashValue::zero(),
+        ),
+        AggregateSignature::empty(),
+    )
+}
+
+fn new_dag(epoch_state: &Arc<EpochState>, nodes: &[CertifiedNode]) -> Arc<RwLock<Dag>> {
+    let mut dag = Dag::new(epoch_state.clone(), Arc::new(MockStorage::new()), 0);
+    for node in nodes {
+        dag.add_node(node.clone()).unwrap();
+    }
+    Arc::new(RwLock::new(dag))
+}
+
+/// A lagging node that only has the first two rounds of the DAG, and can fetch the rest from a
+/// peer that has it all.
+struct LaggingNode {
+    dag: Arc<RwLock<Dag>>,
+    storage: Arc<MockStorage>,
+    order_rule: Arc<Mutex<OrderRule>>,
+    ordered_nodes_rx: UnboundedReceiver<Vec<Arc<CertifiedNode>>>,
+    state_sync: StateSyncManager,
+}
+
+fn new_lagging_node(
+    epoch_state: &Arc<EpochState>,
+    nodes_by_round: &[Vec<CertifiedNode>],
+) -> LaggingNode {
+    let validators = epoch_state.verifier.get_ordered_account_addresses();
+    let peer_dag = new_dag(
+        epoch_state,
+        &nodes_by_round.iter().flatten().cloned().collect::<Vec<_>>(),
+    );
+    let storage = Arc::new(MockStorage::new());
+    let dag = Arc::new(RwLock::new(Dag::new(
+        epoch_state.clone(),
+        storage.clone(),
+        0,
+    )));
+    for node in nodes_by_round[1..=2].iter().flatten() {
+        dag.write().add_node(node.clone()).unwrap();
+    }
+
+    let (tx, ordered_nodes_rx) = unbounded();
+    let order_rule = Arc::new(Mutex::new(OrderRule::new(
+        epoch_state.clone(),
+        placeholder_ledger_info(),
+        dag.clone(),
+        Box::new(RoundRobinAnchorElection::new(validators)),
+        tx,
+        storage.clone(),
+    )));
+
+    let time_service = TimeService::real();
+    let network = Arc::new(MockDAGNetworkSender {
+        fetch_handler: Arc::new(Mutex::new(FetchRequestHandler::new(
+            peer_dag,
+            epoch_state.clone(),
+        ))),
+        time_service: time_service.clone(),
+    });
+    let state_sync = StateSyncManager::new(
+        epoch_state.clone(),
+        dag.clone(),
+        order_rule.clone(),
+        DagFetcher::new(epoch_state.clone(), network, time_service),
+        Arc::new(EmptyStateComputer),
+        storage.clone(),
+    );
+
+    LaggingNode {
+        dag,
+        storage,
+        order_rule,
+        ordered_nodes_rx,
+        state_sync,
+    }
+}
+
+#[tokio::test]
+async fn test_dag_state_sync() {
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    let validators = verifier.get_ordered_account_addresses();
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: verifier.clone(),
+    });
+    let nodes_by_round = generate_nodes(&signers, &verifier);
+    let anchor_election = RoundRobinAnchorElection::new(validators);
+    let anchor = nodes_by_round[COMMITTED_ROUND as usize]
+        .iter()
+        .find(|node| *node.author() == anchor_election.get_anchor(COMMITTED_ROUND))
+        .unwrap()
+        .clone();
+    let LaggingNode {
+        dag,
+        storage,
+        order_rule,
+        mut ordered_nodes_rx,
+        state_sync,
+    } = new_lagging_node(&epoch_state, &nodes_by_round);
+
+    // Peers committing a few rounds ahead can be caught up with by fetching.
+    assert!(!state_sync.need_sync(&new_ledger_info(30)));
+    let ledger_info = new_ledger_info(COMMITTED_ROUND);
+    assert!(state_sync.need_sync(&ledger_info));
+
+    let target = nodes_by_round[NUM_ROUNDS as usize][0].clone();
+    state_sync
+        .sync_to(&CertifiedNodeMessage::new(
+            target,
+            ledger_info,
+            Some(anchor.id()),
+        ))
+        .await
+        .unwrap();
+
+    // Only the window below the committed anchor got fetched, the old rounds are gone.
+    let start_round = COMMITTED_ROUND - DAG_WINDOW;
+    assert_eq!(dag.read().lowest_round(), start_round);
+    assert_eq!(dag.read().highest_round(), NUM_ROUNDS);
+    assert!(storage
+        .get_certified_nodes()
+        .unwrap()
+        .iter()
+        .all(|(_, node)| node.round() >= start_round));
+    assert_eq!(
+        storage
+            .get_ordered_anchor_ids()
+            .unwrap()
+            .into_iter()
+            .map(|(node_id, _)| node_id)
+            .collect::<Vec<_>>(),
+        vec![anchor.id()]
+    );
+    let num_committed = dag
+        .read()
+        .reachable(&[anchor.metadata().clone()], None, |node_status| {
+            matches!(node_status, NodeStatus::Committed(_))
+        })
+        .count();
+    assert_eq!(
+        num_committed,
+        1 + 4 * (COMMITTED_ROUND - start_round) as usize
+    );
+
+    // The order rule resumes ordering from the anchor after the committed one.
+    for node in nodes_by_round[COMMITTED_ROUND as usize + 1..]
+        .iter()
+        .flatten()
+    {
+        order_rule.lock().process_new_node(node);
+    }
+    let ordered_nodes = ordered_nodes_rx.try_next().unwrap().unwrap();
+    assert_eq!(ordered_nodes.last().unwrap().round(), COMMITTED_ROUND + 1);
+    // The anchor round's other nodes are all that's left to order below it.
+    assert_eq!(ordered_nodes.len(), 4);
+    assert!(ordered_nodes
+        .iter()
+        .all(|node| node.round() >= COMMITTED_ROUND && node.digest() != anchor.digest()));
+}
+
+#[tokio::test]
+async fn test_dag_state_sync_failure_keeps_dag() {
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: verifier.clone(),
+    });
+    let nodes_by_round = generate_nodes(&signers, &verifier);
+    let LaggingNode {
+        dag,
+        storage,
+        state_sync,
+        ..
+    } = new_lagging_node(&epoch_state, &nodes_by_round);
+    let num_nodes = storage.get_certified_nodes().unwrap().len();
+
+    let target = nodes_by_round[NUM_ROUNDS as usize][0].clone();
+    let ledger_info = new_ledger_info(COMMITTED_ROUND);
+    // The sender doesn't know the committed anchor.
+    assert!(state_sync
+        .sync_to(&CertifiedNodeMessage::new(
+            target.clone(),
+            ledger_info.clone(),
+            None
+        ))
+        .await
+        .is_err());
+    // The anchor isn't of the committed round.
+    let anchor = &nodes_by_round[COMMITTED_ROUND as usize - 2][0];
+    assert!(state_sync
+        .sync_to(&CertifiedNodeMessage::new(
+            target.clone(),
+            ledger_info.clone(),
+            Some(anchor.id())
+        ))
+        .await
+        .is_err());
+    // The committed anchor isn't in the DAG of the peers.
+    assert!(state_sync
+        .sync_to(&CertifiedNodeMessage::new(
+            target,
+            ledger_info,
+            Some(NodeId::new(1, COMMITTED_ROUND, Author::random()))
+        ))
+        .await
+        .is_err());
+
+    // Neither the DAG nor its storage have changed.
+    assert_eq!(dag.read().lowest_round(), 1);
+    assert_eq!(dag.read().highest_round(), 2);
+    assert_eq!(storage.get_certified_nodes().unwrap().len(), num_nodes);
+    assert!(storage
+        .get_certified_nodes()
+        .unwrap()
+        .iter()
+        .all(|(_, node)| node.round() <= 2));
+}
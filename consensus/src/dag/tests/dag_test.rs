@@ -1,25 +1,33 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::dag::{
-    dag_store::Dag,
-    storage::DAGStorage,
-    tests::helpers::new_certified_node,
-    types::{CertifiedNode, DagSnapshotBitmask, Node},
-    NodeId, Vote,
+use crate::{
+    consensusdb::ConsensusDB,
+    dag::{
+        anchor_election::RoundRobinAnchorElection,
+        dag_store::{Dag, NodeStatus},
+        order_rule::OrderRule,
+        storage::DAGStorage,
+        tests::helpers::new_certified_node,
+        types::{CertifiedNode, DagSnapshotBitmask, Node},
+        NodeId, Vote,
+    },
 };
 use aptos_crypto::HashValue;
-use aptos_infallible::Mutex;
+use aptos_infallible::{Mutex, RwLock};
+use aptos_temppath::TempPath;
 use aptos_types::{
-    epoch_state::EpochState, validator_signer::ValidatorSigner,
-    validator_verifier::random_validator_verifier,
+    block_info::BlockInfo, epoch_state::EpochState, ledger_info::LedgerInfo,
+    validator_signer::ValidatorSigner, validator_verifier::random_validator_verifier,
 };
+use futures_channel::mpsc::unbounded;
 use std::{collections::HashMap, sync::Arc};
 
 pub struct MockStorage {
     node_data: Mutex<HashMap<HashValue, Node>>,
     vote_data: Mutex<HashMap<NodeId, Vote>>,
     certified_node_data: Mutex<HashMap<HashValue, CertifiedNode>>,
+    ordered_anchor_ids: Mutex<HashMap<NodeId, ()>>,
 }
 
 impl MockStorage {
@@ -28,6 +36,7 @@ impl MockStorage {
             node_data: Mutex::new(HashMap::new()),
             vote_data: Mutex::new(HashMap::new()),
             certified_node_data: Mutex::new(HashMap::new()),
+            ordered_anchor_ids: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -38,6 +47,10 @@ impl DAGStorage for MockStorage {
         Ok(())
     }
 
+    fn get_nodes(&self) -> anyhow::Result<Vec<(HashValue, Node)>> {
+        Ok(self.node_data.lock().clone().into_iter().collect())
+    }
+
     fn delete_node(&self, digest: HashValue) -> anyhow::Result<()> {
         self.node_data.lock().remove(&digest);
         Ok(())
@@ -82,16 +95,20 @@ impl DAGStorage for MockStorage {
         Ok(())
     }
 
-    fn save_ordered_anchor_id(&self, _node_id: &NodeId) -> anyhow::Result<()> {
-        todo!()
+    fn save_ordered_anchor_id(&self, node_id: &NodeId) -> anyhow::Result<()> {
+        self.ordered_anchor_ids.lock().insert(node_id.clone(), ());
+        Ok(())
     }
 
     fn get_ordered_anchor_ids(&self) -> anyhow::Result<Vec<(NodeId, ())>> {
-        todo!()
+        Ok(self.ordered_anchor_ids.lock().clone().into_iter().collect())
     }
 
-    fn delete_ordered_anchor_ids(&self, _node_ids: Vec<NodeId>) -> anyhow::Result<()> {
-        todo!()
+    fn delete_ordered_anchor_ids(&self, node_ids: Vec<NodeId>) -> anyhow::Result<()> {
+        for node_id in node_ids {
+            self.ordered_anchor_ids.lock().remove(&node_id);
+        }
+        Ok(())
     }
 }
 
@@ -102,7 +119,7 @@ fn setup() -> (Vec<ValidatorSigner>, Arc<EpochState>, Dag, Arc<MockStorage>) {
         verifier: validator_verifier,
     });
     let storage = Arc::new(MockStorage::new());
-    let dag = Dag::new(epoch_state.clone(), storage.clone());
+    let dag = Dag::new(epoch_state.clone(), storage.clone(), 0);
     (signers, epoch_state, dag, storage)
 }
 
@@ -190,7 +207,7 @@ fn test_dag_recover_from_storage() {
             assert!(dag.add_node(node).is_ok());
         }
     }
-    let new_dag = Dag::new(epoch_state.clone(), storage.clone());
+    let new_dag = Dag::new(epoch_state.clone(), storage.clone(), 0);
 
     for metadata in &metadatas {
         assert!(new_dag.exists(metadata));
@@ -201,10 +218,128 @@ fn test_dag_recover_from_storage() {
         verifier: epoch_state.verifier.clone(),
     });
 
-    let _new_epoch_dag = Dag::new(new_epoch_state, storage.clone());
+    let _new_epoch_dag = Dag::new(new_epoch_state, storage.clone(), 0);
     assert!(storage.certified_node_data.lock().is_empty());
 }
 
+#[test]
+fn test_dag_recover_ordered_anchors() {
+    let (signers, epoch_state, mut dag, storage) = setup();
+
+    let mut nodes_by_round = vec![vec![]];
+    for round in 1..10 {
+        let parents = dag
+            .get_strong_links_for_round(round, &epoch_state.verifier)
+            .unwrap_or_default();
+        let mut nodes = vec![];
+        for signer in &signers[0..3] {
+            let node = new_certified_node(round, signer.author(), parents.clone());
+            assert!(dag.add_node(node.clone()).is_ok());
+            nodes.push(node);
+        }
+        nodes_by_round.push(nodes);
+    }
+    // Anchors of rounds 1, 3, 5 and 7 got ordered, those up to round 5 got committed.
+    for round in [1, 3, 5, 7] {
+        storage
+            .save_ordered_anchor_id(&nodes_by_round[round][0].id())
+            .unwrap();
+    }
+
+    let ordered_anchor_ids = || {
+        let mut node_ids: Vec<_> = storage
+            .get_ordered_anchor_ids()
+            .unwrap()
+            .into_iter()
+            .map(|(node_id, _)| node_id)
+            .collect();
+        node_ids.sort_by_key(|node_id| node_id.round());
+        node_ids
+    };
+
+    // The committed anchors are kept for the order rule to restore the anchor election from.
+    let new_dag = Dag::new(epoch_state.clone(), storage.clone(), 5);
+    assert_eq!(new_dag.lowest_round(), 1);
+    assert_eq!(
+        ordered_anchor_ids(),
+        [1, 3, 5]
+            .iter()
+            .map(|round| nodes_by_round[*round][0].id())
+            .collect::<Vec<_>>()
+    );
+
+    // The round robin anchor election needs none of them, only the latest one is kept.
+    let (tx, _rx) = unbounded();
+    let _order_rule = OrderRule::new(
+        epoch_state.clone(),
+        LedgerInfo::new(
+            BlockInfo::new(
+                1,
+                5,
+                nodes_by_round[5][0].digest(),
+                HashValue::zero(),
+                0,
+                0,
+                None,
+            ),
+            HashValue::zero(),
+        ),
+        Arc::new(RwLock::new(new_dag)),
+        Box::new(RoundRobinAnchorElection::new(
+            epoch_state.verifier.get_ordered_account_addresses(),
+        )),
+        tx,
+        storage.clone(),
+    );
+    assert_eq!(ordered_anchor_ids(), vec![nodes_by_round[5][0].id()]);
+
+    let new_dag = Dag::new(epoch_state.clone(), storage.clone(), 5);
+    assert_eq!(new_dag.lowest_round(), 5);
+    assert!(storage
+        .certified_node_data
+        .lock()
+        .values()
+        .all(|node| node.round() >= 5));
+
+    let unordered = |node: &CertifiedNode| {
+        new_dag
+            .reachable(&[node.metadata().clone()], None, |node_status| {
+                matches!(node_status, NodeStatus::Unordered(_))
+            })
+            .count()
+    };
+    // The committed anchor isn't ordered again, the rest of its round will be.
+    assert_eq!(unordered(&nodes_by_round[5][0]), 0);
+    assert_eq!(unordered(&nodes_by_round[6][0]), 3);
+}
+
+#[test]
+fn test_dag_recover_from_consensus_db() {
+    let (signers, epoch_state, _, _) = setup();
+    let tmp_dir = TempPath::new();
+    let mut metadatas = vec![];
+    {
+        let storage = Arc::new(ConsensusDB::new(tmp_dir.path()));
+        let mut dag = Dag::new(epoch_state.clone(), storage, 0);
+        for round in 1..5 {
+            let parents = dag
+                .get_strong_links_for_round(round, &epoch_state.verifier)
+                .unwrap_or_default();
+            for signer in &signers[0..3] {
+                let node = new_certified_node(round, signer.author(), parents.clone());
+                metadatas.push(node.metadata().clone());
+                assert!(dag.add_node(node).is_ok());
+            }
+        }
+    }
+
+    let storage = Arc::new(ConsensusDB::new(tmp_dir.path()));
+    let new_dag = Dag::new(epoch_state, storage, 0);
+    for metadata in &metadatas {
+        assert!(new_dag.exists(metadata));
+    }
+}
+
 #[test]
 fn test_dag_bitmask() {
     let (signers, epoch_state, mut dag, _) = setup();
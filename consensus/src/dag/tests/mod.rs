@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod anchor_election_tests;
 mod dag_network_test;
+mod dag_state_sync_tests;
 mod dag_test;
 mod fetcher_test;
 mod helpers;
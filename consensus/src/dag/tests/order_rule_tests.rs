@@ -3,10 +3,13 @@
 
 use crate::{
     dag::{
-        anchor_election::RoundRobinAnchorElection,
+        anchor_election::{AnchorElection, RoundRobinAnchorElection},
         dag_store::Dag,
         order_rule::OrderRule,
-        tests::{dag_test::MockStorage, helpers::new_certified_node},
+        tests::{
+            anchor_election_tests::new_reputation_election, dag_test::MockStorage,
+            helpers::new_certified_node,
+        },
         types::{NodeCertificate, NodeMetadata},
         CertifiedNode,
     },
@@ -117,17 +120,32 @@ fn generate_dag_nodes(
     nodes
 }
 
+fn round_robin_election(validators: &[Author]) -> Box<dyn AnchorElection> {
+    Box::new(RoundRobinAnchorElection::new(validators.to_vec()))
+}
+
+fn reputation_election(validators: &[Author]) -> Box<dyn AnchorElection> {
+    Box::new(new_reputation_election(validators))
+}
+
 fn create_order_rule(
     epoch_state: Arc<EpochState>,
     dag: Arc<RwLock<Dag>>,
+    new_anchor_election: fn(&[Author]) -> Box<dyn AnchorElection>,
 ) -> (OrderRule, UnboundedReceiver<Vec<Arc<CertifiedNode>>>) {
     let ledger_info = placeholder_ledger_info();
-    let anchor_election = Box::new(RoundRobinAnchorElection::new(
-        epoch_state.verifier.get_ordered_account_addresses(),
-    ));
+    let anchor_election =
+        new_anchor_election(&epoch_state.verifier.get_ordered_account_addresses());
     let (tx, rx) = unbounded();
     (
-        OrderRule::new(epoch_state, ledger_info, dag, anchor_election, tx),
+        OrderRule::new(
+            epoch_state,
+            ledger_info,
+            dag,
+            anchor_election,
+            tx,
+            Arc::new(MockStorage::new()),
+        ),
         rx,
     )
 }
@@ -140,51 +158,84 @@ const NUM_PERMUTATION: usize = 100;
 proptest! {
     #[test]
     fn test_order_rule_safety(
-        mut dag_with_holes in generate_virtual_dag(NUM_VALIDATORS, NUM_HOLES, NUM_ROUNDS),
-        mut dag in generate_virtual_dag(NUM_VALIDATORS, 0, NUM_ROUNDS),
+        dag_with_holes in generate_virtual_dag(NUM_VALIDATORS, NUM_HOLES, NUM_ROUNDS),
+        dag in generate_virtual_dag(NUM_VALIDATORS, 0, NUM_ROUNDS),
         sequences in generate_permutations(NUM_PERMUTATION, (NUM_VALIDATORS - NUM_HOLES) * NUM_ROUNDS as usize)
     ) {
-        let (_, validator_verifier) = random_validator_verifier(NUM_VALIDATORS, None, false);
-        let validators = validator_verifier.get_ordered_account_addresses();
-        let author_indexes = validator_verifier.address_to_validator_index().clone();
-        dag.append(&mut dag_with_holes);
-        let nodes = generate_dag_nodes(&dag, &validators);
-        let epoch_state = Arc::new(EpochState {
-            epoch: 1,
-            verifier: validator_verifier,
-        });
-        let mut dag = Dag::new(epoch_state.clone(), Arc::new(MockStorage::new()));
-        for round_nodes in &nodes {
-            for node in round_nodes.iter().flatten() {
-                dag.add_node(node.clone()).unwrap();
-            }
+        verify_order_rule_safety(dag, dag_with_holes, sequences, round_robin_election);
+    }
+
+    /// The anchors elected by reputation depend on the anchors ordered before, which must not
+    /// depend on the order the nodes arrive in.
+    #[test]
+    fn test_order_rule_safety_with_reputation(
+        dag_with_holes in generate_virtual_dag(NUM_VALIDATORS, NUM_HOLES, NUM_ROUNDS),
+        dag in generate_virtual_dag(NUM_VALIDATORS, 0, NUM_ROUNDS),
+        sequences in generate_permutations(NUM_PERMUTATION, (NUM_VALIDATORS - NUM_HOLES) * NUM_ROUNDS as usize)
+    ) {
+        verify_order_rule_safety(dag, dag_with_holes, sequences, reputation_election);
+    }
+}
+
+/// Orders the same dag with the nodes arriving in each of the `sequences`, and checks that all
+/// of them order the nodes the same way.
+fn verify_order_rule_safety(
+    mut dag: Vec<Vec<Option<Vec<bool>>>>,
+    mut dag_with_holes: Vec<Vec<Option<Vec<bool>>>>,
+    sequences: Vec<Vec<usize>>,
+    new_anchor_election: fn(&[Author]) -> Box<dyn AnchorElection>,
+) {
+    let (_, validator_verifier) = random_validator_verifier(NUM_VALIDATORS, None, false);
+    let validators = validator_verifier.get_ordered_account_addresses();
+    let author_indexes = validator_verifier.address_to_validator_index().clone();
+    dag.append(&mut dag_with_holes);
+    let nodes = generate_dag_nodes(&dag, &validators);
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+    let mut dag = Dag::new(epoch_state.clone(), Arc::new(MockStorage::new()), 0);
+    for round_nodes in &nodes {
+        for node in round_nodes.iter().flatten() {
+            dag.add_node(node.clone()).unwrap();
         }
-        let flatten_nodes: Vec<_> = nodes.into_iter().flatten().flatten().collect();
-        let all_ordered = Arc::new(Mutex::new(vec![]));
-        rayon::scope(|s| {
-            for seq in sequences {
-                s.spawn(|_| {
-                    let dag = Arc::new(RwLock::new(dag.clone()));
-                    let (mut order_rule, mut receiver) = create_order_rule(epoch_state.clone(), dag);
-                    for idx in seq {
-                        order_rule.process_new_node(&flatten_nodes[idx]);
-                    }
-                    let mut ordered = vec![];
-                    while let Ok(Some(mut ordered_nodes)) = receiver.try_next() {
-                        ordered.append(&mut ordered_nodes);
-                    }
-                    all_ordered.lock().push(ordered);
-                });
-            }
-        });
-        let display = |node: &Arc<CertifiedNode>| {
-            (node.metadata().round(), *author_indexes.get(node.metadata().author()).unwrap())
-        };
-        let longest: Vec<_> = all_ordered.lock().iter().max_by(|v1, v2| v1.len().cmp(&v2.len())).unwrap().iter().map(display).collect();
-        for ordered in all_ordered.lock().iter() {
-            let a: Vec<_> = ordered.iter().map(display).collect();
-            assert_eq!(a, longest[..a.len()]);
+    }
+    let flatten_nodes: Vec<_> = nodes.into_iter().flatten().flatten().collect();
+    let all_ordered = Arc::new(Mutex::new(vec![]));
+    rayon::scope(|s| {
+        for seq in sequences {
+            s.spawn(|_| {
+                let dag = Arc::new(RwLock::new(dag.clone()));
+                let (mut order_rule, mut receiver) =
+                    create_order_rule(epoch_state.clone(), dag, new_anchor_election);
+                for idx in seq {
+                    order_rule.process_new_node(&flatten_nodes[idx]);
+                }
+                let mut ordered = vec![];
+                while let Ok(Some(mut ordered_nodes)) = receiver.try_next() {
+                    ordered.append(&mut ordered_nodes);
+                }
+                all_ordered.lock().push(ordered);
+            });
         }
+    });
+    let display = |node: &Arc<CertifiedNode>| {
+        (
+            node.metadata().round(),
+            *author_indexes.get(node.metadata().author()).unwrap(),
+        )
+    };
+    let longest: Vec<_> = all_ordered
+        .lock()
+        .iter()
+        .max_by(|v1, v2| v1.len().cmp(&v2.len()))
+        .unwrap()
+        .iter()
+        .map(display)
+        .collect();
+    for ordered in all_ordered.lock().iter() {
+        let a: Vec<_> = ordered.iter().map(display).collect();
+        assert_eq!(a, longest[..a.len()]);
     }
 }
 
@@ -231,7 +282,7 @@ fn test_order_rule_basic() {
         epoch: 1,
         verifier: validator_verifier,
     });
-    let mut dag = Dag::new(epoch_state.clone(), Arc::new(MockStorage::new()));
+    let mut dag = Dag::new(epoch_state.clone(), Arc::new(MockStorage::new()), 0);
     for round_nodes in &nodes {
         for node in round_nodes.iter().flatten() {
             dag.add_node(node.clone()).unwrap();
@@ -239,7 +290,7 @@ fn test_order_rule_basic() {
     }
     let display = |node: &NodeMetadata| (node.round(), *author_indexes.get(node.author()).unwrap());
     let dag = Arc::new(RwLock::new(dag.clone()));
-    let (mut order_rule, mut receiver) = create_order_rule(epoch_state, dag);
+    let (mut order_rule, mut receiver) = create_order_rule(epoch_state, dag, round_robin_election);
     for node in nodes.iter().flatten().flatten() {
         order_rule.process_new_node(node);
     }
@@ -31,7 +31,11 @@ async fn test_node_broadcast_receiver_succeed() {
         verifier: validator_verifier,
     });
     let storage = Arc::new(MockStorage::new());
-    let dag = Arc::new(RwLock::new(Dag::new(epoch_state.clone(), storage.clone())));
+    let dag = Arc::new(RwLock::new(Dag::new(
+        epoch_state.clone(),
+        storage.clone(),
+        0,
+    )));
 
     let wellformed_node = new_node(0, 10, signers[0].author(), vec![]);
     let equivocating_node = new_node(0, 20, signers[0].author(), vec![]);
@@ -62,7 +66,11 @@ async fn test_node_broadcast_receiver_failure() {
         .iter()
         .map(|signer| {
             let storage = Arc::new(MockStorage::new());
-            let dag = Arc::new(RwLock::new(Dag::new(epoch_state.clone(), storage.clone())));
+            let dag = Arc::new(RwLock::new(Dag::new(
+                epoch_state.clone(),
+                storage.clone(),
+                0,
+            )));
 
             NodeBroadcastHandler::new(dag, signer.clone(), epoch_state.clone(), storage)
         })
@@ -127,7 +135,11 @@ fn test_node_broadcast_receiver_storage() {
         verifier: validator_verifier,
     });
     let storage = Arc::new(MockStorage::new());
-    let dag = Arc::new(RwLock::new(Dag::new(epoch_state.clone(), storage.clone())));
+    let dag = Arc::new(RwLock::new(Dag::new(
+        epoch_state.clone(),
+        storage.clone(),
+        0,
+    )));
 
     let node = new_node(1, 10, signers[0].author(), vec![]);
 
@@ -158,7 +170,7 @@ fn test_certified_node_receiver() {
         verifier: validator_verifier,
     });
     let storage = Arc::new(MockStorage::new());
-    let dag = Arc::new(RwLock::new(Dag::new(epoch_state, storage)));
+    let dag = Arc::new(RwLock::new(Dag::new(epoch_state, storage, 0)));
 
     let zeroth_round_node = new_certified_node(0, signers[0].author(), vec![]);
 
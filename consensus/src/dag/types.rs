@@ -16,6 +16,7 @@ use aptos_reliable_broadcast::{BroadcastStatus, RBMessage};
 use aptos_types::{
     aggregate_signature::{AggregateSignature, PartialSignatures},
     epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures,
     validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
 };
@@ -115,6 +116,10 @@ impl NodeMetadata {
     pub fn epoch(&self) -> u64 {
         self.epoch
     }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
 }
 
 impl Deref for NodeMetadata {
@@ -359,6 +364,14 @@ impl CertifiedNode {
     pub fn certificate(&self) -> NodeCertificate {
         NodeCertificate::new(self.node.metadata.clone(), self.signatures.clone())
     }
+
+    pub fn verify(&self, verifier: &ValidatorVerifier) -> anyhow::Result<()> {
+        ensure!(self.digest() == self.calculate_digest(), "invalid digest");
+
+        verifier
+            .verify_multi_signatures(self.metadata(), self.certificate().signatures())
+            .map_err(|e| anyhow::anyhow!("unable to verify: {}", e))
+    }
 }
 
 impl Deref for CertifiedNode {
@@ -369,13 +382,63 @@ impl Deref for CertifiedNode {
     }
 }
 
-impl TDAGMessage for CertifiedNode {
-    fn verify(&self, verifier: &ValidatorVerifier) -> anyhow::Result<()> {
-        ensure!(self.digest() == self.calculate_digest(), "invalid digest");
+/// A certified node broadcast together with the latest ledger info committed by its sender, so
+/// that a receiver that fell too far behind can tell and state sync to it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CertifiedNodeMessage {
+    inner: CertifiedNode,
+    ledger_info: LedgerInfoWithSignatures,
+    /// The anchor whose commit `ledger_info` certifies, if the sender still has it among its
+    /// ordered anchors. The ledger info alone doesn't tell which node the anchor is.
+    committed_anchor_id: Option<NodeId>,
+}
 
-        verifier
-            .verify_multi_signatures(self.metadata(), self.certificate().signatures())
-            .map_err(|e| anyhow::anyhow!("unable to verify: {}", e))
+impl CertifiedNodeMessage {
+    pub fn new(
+        certified_node: CertifiedNode,
+        ledger_info: LedgerInfoWithSignatures,
+        committed_anchor_id: Option<NodeId>,
+    ) -> Self {
+        Self {
+            inner: certified_node,
+            ledger_info,
+            committed_anchor_id,
+        }
+    }
+
+    pub fn ledger_info(&self) -> &LedgerInfoWithSignatures {
+        &self.ledger_info
+    }
+
+    pub fn committed_anchor_id(&self) -> Option<&NodeId> {
+        self.committed_anchor_id.as_ref()
+    }
+
+    pub fn certified_node(self) -> CertifiedNode {
+        self.inner
+    }
+}
+
+impl Deref for CertifiedNodeMessage {
+    type Target = CertifiedNode;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl TDAGMessage for CertifiedNodeMessage {
+    fn verify(&self, verifier: &ValidatorVerifier) -> anyhow::Result<()> {
+        self.inner.verify(verifier)?;
+
+        // A ledger info of an earlier epoch can't be verified with the current validators, but it
+        // is never used for state sync either.
+        if self.ledger_info.commit_info().epoch() == self.inner.epoch() {
+            self.ledger_info
+                .verify_signatures(verifier)
+                .map_err(|e| anyhow::anyhow!("unable to verify ledger info: {}", e))?;
+        }
+        Ok(())
     }
 }
 
@@ -472,11 +535,11 @@ impl<M> BroadcastStatus<M> for CertificateAckState
 where
     M: RBMessage,
     CertifiedAck: TryFrom<M> + Into<M>,
-    CertifiedNode: TryFrom<M> + Into<M>,
+    CertifiedNodeMessage: TryFrom<M> + Into<M>,
 {
     type Ack = CertifiedAck;
     type Aggregated = ();
-    type Message = CertifiedNode;
+    type Message = CertifiedNodeMessage;
 
     fn add(&mut self, peer: Author, _ack: Self::Ack) -> anyhow::Result<Option<Self::Aggregated>> {
         self.received.insert(peer);
@@ -581,7 +644,7 @@ pub struct DAGNetworkMessage {
 pub enum DAGMessage {
     NodeMsg(Node),
     VoteMsg(Vote),
-    CertifiedNodeMsg(CertifiedNode),
+    CertifiedNodeMsg(CertifiedNodeMessage),
     CertifiedAckMsg(CertifiedAck),
     FetchRequest(RemoteFetchRequest),
     FetchResponse(FetchResponse),
@@ -0,0 +1,480 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters::TXN_SHUFFLE_SECONDS, transaction_shuffler::TransactionShuffler};
+use aptos_types::transaction::{SignedTransaction, TransactionPayload};
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+/// An implementation of transaction shuffler, which generalizes the `SenderAwareShuffler` from
+/// senders to the storage a transaction is expected to touch. Each transaction is given a set of
+/// conflict keys derived from its payload (its sender, the accounts passed as arguments to an entry
+/// function and the module targeted by it when it is not a framework module), and the shuffler
+/// tries to avoid having two transactions sharing a key within `conflict_window_size` consecutive
+/// transactions of the block, so that Block-STM can execute them in parallel.
+///
+/// Conflict keys are hints that can be inaccurate: they only affect the ordering, never the outcome
+/// of execution. The shuffler maintains the following invariants
+/// 1. Relative ordering of all transactions from the same sender is the same before and after
+/// shuffling.
+/// 2. If no transactions of the input block share a conflict key, the ordering is unchanged.
+///
+/// On a high level, it works as follows
+/// loop:
+///   if keys fell out of the sliding window in previous iteration,
+///      then: we add the first pending transaction that touches them and no longer conflicts
+///   else while we have transactions to process in the original transaction order
+///         take a new one,
+///         if it conflicts, or its sender has pending transactions, add to the pending set
+///         else we add it to the block
+///   else
+///       take the first transaction from the pending transactions and add it to the block
+pub struct DependencyAwareShuffler {
+    conflict_window_size: usize,
+}
+
+impl TransactionShuffler for DependencyAwareShuffler {
+    fn shuffle(&self, txns: Vec<SignedTransaction>) -> Vec<SignedTransaction> {
+        let _timer = TXN_SHUFFLE_SECONDS.start_timer();
+
+        // Early return for performance reason if there are no transactions to shuffle
+        if txns.is_empty() {
+            return txns;
+        }
+
+        // handle the corner case of conflict window being 0, in which case we don't do any shuffling
+        if self.conflict_window_size == 0 {
+            return txns;
+        }
+
+        let num_transactions = txns.len();
+        let mut sliding_window =
+            SlidingWindowState::new(self.conflict_window_size, num_transactions);
+        let mut pending_txns = PendingTransactions::new();
+        let mut orig_txns = txns.into_iter().enumerate().map(|(index, txn)| {
+            let keys = conflict_keys(&txn);
+            (index, txn, keys)
+        });
+        while sliding_window.num_txns() < num_transactions {
+            // First check if keys dropped off of conflict window in previous step, if so, we try to
+            // find the first pending transaction they were blocking.
+            if let Some(index) = pending_txns.first_unblocked(&sliding_window) {
+                let (txn, keys) = pending_txns.remove(index);
+                sliding_window.add_transaction(txn, keys);
+                continue;
+            }
+            // Otherwise iterate through the original transactions and try to find the next
+            // candidate. Transactions of senders with pending transactions are held back, to
+            // preserve the ordering by sender.
+            let mut candidate = None;
+            for (index, txn, keys) in orig_txns.by_ref() {
+                if !sliding_window.has_conflict(&keys) && !pending_txns.has_sender(&txn.sender()) {
+                    candidate = Some((txn, keys));
+                    break;
+                }
+                pending_txns.add_transaction(index, txn, keys);
+            }
+            // If we can't find any candidate in above steps, then lastly add pending transactions
+            // in the order
+            let (txn, keys) = candidate.unwrap_or_else(|| pending_txns.remove_first().unwrap());
+            sliding_window.add_transaction(txn, keys);
+        }
+        sliding_window.finalize()
+    }
+}
+
+impl DependencyAwareShuffler {
+    pub fn new(conflict_window_size: usize) -> Self {
+        Self {
+            conflict_window_size,
+        }
+    }
+}
+
+/// Storage a transaction is expected to touch, as far as the shuffler is concerned.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum ConflictKey {
+    /// Resources stored under an account.
+    Account(AccountAddress),
+    /// Resources published by a module, e.g. a global counter or order book.
+    Module(ModuleId),
+}
+
+/// Derives the conflict keys of a transaction from its payload. Arguments of entry functions that
+/// can be decoded as an address are assumed to be accounts the transaction touches. Framework
+/// modules keep their state under the accounts they operate on, so they don't conflict by
+/// themselves.
+fn conflict_keys(txn: &SignedTransaction) -> Vec<ConflictKey> {
+    let mut keys = vec![ConflictKey::Account(txn.sender())];
+    if let TransactionPayload::EntryFunction(entry_function) = txn.payload() {
+        if !entry_function.module().address().is_special() {
+            keys.push(ConflictKey::Module(entry_function.module().clone()));
+        }
+        keys.extend(
+            entry_function
+                .args()
+                .iter()
+                .filter(|arg| arg.len() == AccountAddress::LENGTH)
+                .filter_map(|arg| bcs::from_bytes::<AccountAddress>(arg).ok())
+                .map(ConflictKey::Account),
+        );
+    }
+    let mut seen = HashSet::new();
+    keys.retain(|key| seen.insert(key.clone()));
+    keys
+}
+
+/// Transactions that are pending to be added to the block, indexed by their position in the
+/// original block, by sender and by conflict key.
+struct PendingTransactions {
+    txns: BTreeMap<usize, (SignedTransaction, Vec<ConflictKey>)>,
+    txns_by_senders: HashMap<AccountAddress, VecDeque<usize>>,
+    txns_by_keys: HashMap<ConflictKey, BTreeSet<usize>>,
+}
+
+impl PendingTransactions {
+    pub fn new() -> Self {
+        Self {
+            txns: BTreeMap::new(),
+            txns_by_senders: HashMap::new(),
+            txns_by_keys: HashMap::new(),
+        }
+    }
+
+    pub fn add_transaction(
+        &mut self,
+        index: usize,
+        txn: SignedTransaction,
+        keys: Vec<ConflictKey>,
+    ) {
+        self.txns_by_senders
+            .entry(txn.sender())
+            .or_insert_with(VecDeque::new)
+            .push_back(index);
+        for key in &keys {
+            self.txns_by_keys
+                .entry(key.clone())
+                .or_insert_with(BTreeSet::new)
+                .insert(index);
+        }
+        self.txns.insert(index, (txn, keys));
+    }
+
+    pub fn has_sender(&self, sender: &AccountAddress) -> bool {
+        self.txns_by_senders
+            .get(sender)
+            .map_or(false, |txns| !txns.is_empty())
+    }
+
+    /// Returns the first pending transaction touching the keys that dropped off of the window in
+    /// previous iteration, which doesn't conflict with the window anymore and is the first pending
+    /// transaction of its sender.
+    pub fn first_unblocked(&self, sliding_window: &SlidingWindowState) -> Option<usize> {
+        sliding_window
+            .last_dropped_keys()
+            .iter()
+            .filter_map(|key| {
+                self.txns_by_keys.get(key)?.iter().copied().find(|index| {
+                    let (txn, keys) = &self.txns[index];
+                    !sliding_window.has_conflict(keys)
+                        && self.txns_by_senders[&txn.sender()].front() == Some(index)
+                })
+            })
+            .min()
+    }
+
+    pub fn remove(&mut self, index: usize) -> (SignedTransaction, Vec<ConflictKey>) {
+        let (txn, keys) = self
+            .txns
+            .remove(&index)
+            .expect("Pending transaction expected");
+        for key in &keys {
+            if let Some(txns) = self.txns_by_keys.get_mut(key) {
+                txns.remove(&index);
+                if txns.is_empty() {
+                    self.txns_by_keys.remove(key);
+                }
+            }
+        }
+        // Transactions of a sender are always removed in order.
+        self.txns_by_senders
+            .get_mut(&txn.sender())
+            .and_then(|txns| txns.pop_front());
+        (txn, keys)
+    }
+
+    /// Removes the pending transaction that came first in the original block, which is
+    /// necessarily the first pending transaction of its sender.
+    pub fn remove_first(&mut self) -> Option<(SignedTransaction, Vec<ConflictKey>)> {
+        let index = *self.txns.keys().next()?;
+        Some(self.remove(index))
+    }
+}
+
+/// A stateful data structure maintained by the transaction shuffler during shuffling, which keeps
+/// track of the conflict keys of the last `window_size` transactions added to the block.
+struct SlidingWindowState {
+    window_size: usize,
+    // Conflict keys of the transactions in the window, in the order they were added.
+    keys_in_window: VecDeque<Vec<ConflictKey>>,
+    // Number of transactions in the window for each conflict key.
+    key_counts: HashMap<ConflictKey, usize>,
+    // Keys which are no longer in the window since the last transaction was added.
+    last_dropped_keys: Vec<ConflictKey>,
+    // Partially ordered transactions, needs to be updated every time add_transactions is called.
+    txns: Vec<SignedTransaction>,
+}
+
+impl SlidingWindowState {
+    pub fn new(window_size: usize, num_txns: usize) -> Self {
+        Self {
+            window_size,
+            keys_in_window: VecDeque::with_capacity(window_size),
+            key_counts: HashMap::new(),
+            last_dropped_keys: vec![],
+            txns: Vec::with_capacity(num_txns),
+        }
+    }
+
+    /// Slides the current window, dropping the keys of the oldest transaction once the window is
+    /// full.
+    pub fn add_transaction(&mut self, txn: SignedTransaction, keys: Vec<ConflictKey>) {
+        self.last_dropped_keys.clear();
+        if self.keys_in_window.len() == self.window_size {
+            for key in self.keys_in_window.pop_front().expect("Keys expected") {
+                let count = self
+                    .key_counts
+                    .get_mut(&key)
+                    .expect("Key expected in window");
+                *count -= 1;
+                if *count == 0 {
+                    self.key_counts.remove(&key);
+                    self.last_dropped_keys.push(key);
+                }
+            }
+        }
+        for key in &keys {
+            *self.key_counts.entry(key.clone()).or_insert(0) += 1;
+        }
+        self.keys_in_window.push_back(keys);
+        self.txns.push(txn);
+    }
+
+    pub fn has_conflict(&self, keys: &[ConflictKey]) -> bool {
+        keys.iter().any(|key| self.key_counts.contains_key(key))
+    }
+
+    pub fn last_dropped_keys(&self) -> &[ConflictKey] {
+        &self.last_dropped_keys
+    }
+
+    pub fn num_txns(&self) -> usize {
+        self.txns.len()
+    }
+
+    pub fn finalize(self) -> Vec<SignedTransaction> {
+        self.txns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dependency_aware_shuffler::{conflict_keys, ConflictKey, DependencyAwareShuffler},
+        transaction_shuffler::TransactionShuffler,
+    };
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, SigningKey, Uniform};
+    use aptos_types::{
+        chain_id::ChainId,
+        transaction::{
+            EntryFunction, RawTransaction, Script, SignedTransaction, TransactionPayload,
+        },
+    };
+    use move_core_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    };
+    use rand::{rngs::OsRng, Rng};
+    use std::collections::HashMap;
+
+    fn create_signed_transaction(
+        sender: AccountAddress,
+        sequence_number: u64,
+        payload: TransactionPayload,
+    ) -> SignedTransaction {
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        let raw_transaction =
+            RawTransaction::new(sender, sequence_number, payload, 0, 0, 0, ChainId::new(10));
+        SignedTransaction::new(
+            raw_transaction.clone(),
+            private_key.public_key(),
+            private_key.sign(&raw_transaction).unwrap(),
+        )
+    }
+
+    fn script() -> TransactionPayload {
+        TransactionPayload::Script(Script::new(vec![], vec![], vec![]))
+    }
+
+    fn entry_function(
+        address: AccountAddress,
+        module: &str,
+        args: Vec<AccountAddress>,
+    ) -> TransactionPayload {
+        TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(address, Identifier::new(module).unwrap()),
+            Identifier::new("run").unwrap(),
+            vec![],
+            args.iter().map(|arg| bcs::to_bytes(arg).unwrap()).collect(),
+        ))
+    }
+
+    fn transfer(sender: AccountAddress, receiver: AccountAddress) -> SignedTransaction {
+        create_signed_transaction(
+            sender,
+            0,
+            entry_function(AccountAddress::ONE, "aptos_account", vec![receiver]),
+        )
+    }
+
+    #[test]
+    fn test_conflict_keys() {
+        let sender = AccountAddress::random();
+        let receiver = AccountAddress::random();
+        assert_eq!(conflict_keys(&transfer(sender, receiver)), vec![
+            ConflictKey::Account(sender),
+            ConflictKey::Account(receiver)
+        ]);
+        assert_eq!(conflict_keys(&transfer(sender, sender)), vec![
+            ConflictKey::Account(sender)
+        ]);
+        assert_eq!(
+            conflict_keys(&create_signed_transaction(sender, 0, script())),
+            vec![ConflictKey::Account(sender)]
+        );
+
+        let publisher = AccountAddress::random();
+        let txn =
+            create_signed_transaction(sender, 0, entry_function(publisher, "counter", vec![]));
+        assert_eq!(conflict_keys(&txn), vec![
+            ConflictKey::Account(sender),
+            ConflictKey::Module(ModuleId::new(
+                publisher,
+                Identifier::new("counter").unwrap()
+            ))
+        ]);
+    }
+
+    #[test]
+    fn test_single_user_txns() {
+        let sender = AccountAddress::random();
+        let txns: Vec<_> = (0..50)
+            .map(|i| create_signed_transaction(sender, i, script()))
+            .collect();
+        let optimized_txns = DependencyAwareShuffler::new(10).shuffle(txns.clone());
+        // Assert that ordering is unchanged in case of single sender block
+        assert_eq!(txns, optimized_txns);
+    }
+
+    #[test]
+    fn test_independent_txns() {
+        let txns: Vec<_> = (0..50)
+            .map(|_| transfer(AccountAddress::random(), AccountAddress::random()))
+            .collect();
+        let optimized_txns = DependencyAwareShuffler::new(10).shuffle(txns.clone());
+        // Assert that the ordering is unchanged if no transactions conflict
+        assert_eq!(txns, optimized_txns);
+    }
+
+    #[test]
+    // A->R, B->R, C->X, D->Y
+    // with conflict_window_size=2, should return (transfers to the same receiver are separated):
+    // A->R, C->X, D->Y, B->R
+    fn test_same_receiver_shuffling() {
+        let receiver = AccountAddress::random();
+        let txns = vec![
+            transfer(AccountAddress::random(), receiver),
+            transfer(AccountAddress::random(), receiver),
+            transfer(AccountAddress::random(), AccountAddress::random()),
+            transfer(AccountAddress::random(), AccountAddress::random()),
+        ];
+        let optimized_txns = DependencyAwareShuffler::new(2).shuffle(txns.clone());
+        assert_eq!(optimized_txns, vec![
+            txns[0].clone(),
+            txns[2].clone(),
+            txns[3].clone(),
+            txns[1].clone()
+        ]);
+    }
+
+    #[test]
+    // P1, P2, S1, S2 where P are calls to the same non-framework module
+    // with conflict_window_size=1, should return: P1, S1, P2, S2
+    fn test_same_module_shuffling() {
+        let publisher = AccountAddress::random();
+        let call = || {
+            create_signed_transaction(
+                AccountAddress::random(),
+                0,
+                entry_function(publisher, "order_book", vec![]),
+            )
+        };
+        let txns = vec![
+            call(),
+            call(),
+            create_signed_transaction(AccountAddress::random(), 0, script()),
+            create_signed_transaction(AccountAddress::random(), 0, script()),
+        ];
+        let optimized_txns = DependencyAwareShuffler::new(1).shuffle(txns.clone());
+        assert_eq!(optimized_txns, vec![
+            txns[0].clone(),
+            txns[2].clone(),
+            txns[1].clone(),
+            txns[3].clone()
+        ]);
+    }
+
+    #[test]
+    fn test_same_sender_relative_order() {
+        let mut rng = OsRng;
+        let senders: Vec<_> = (0..20).map(|_| AccountAddress::random()).collect();
+        let receivers: Vec<_> = (0..5).map(|_| AccountAddress::random()).collect();
+        let mut sequence_numbers = HashMap::new();
+        let orig_txns: Vec<_> = (0..500)
+            .map(|_| {
+                let sender = senders[rng.gen_range(0, senders.len())];
+                let receiver = receivers[rng.gen_range(0, receivers.len())];
+                let sequence_number = sequence_numbers.entry(sender).or_insert(0);
+                *sequence_number += 1;
+                create_signed_transaction(
+                    sender,
+                    *sequence_number,
+                    entry_function(AccountAddress::ONE, "aptos_account", vec![receiver]),
+                )
+            })
+            .collect();
+        let optimized_txns = DependencyAwareShuffler::new(8).shuffle(orig_txns.clone());
+        assert_eq!(orig_txns.len(), optimized_txns.len());
+
+        let by_sender = |txns: &[SignedTransaction]| {
+            let mut txns_by_sender = HashMap::new();
+            for txn in txns {
+                txns_by_sender
+                    .entry(txn.sender())
+                    .or_insert_with(Vec::new)
+                    .push(txn.clone());
+            }
+            txns_by_sender
+        };
+        assert_eq!(by_sender(&orig_txns), by_sender(&optimized_txns));
+    }
+
+    #[test]
+    fn test_shuffling_zero_conflict_window() {
+        let receiver = AccountAddress::random();
+        let txns: Vec<_> = (0..50)
+            .map(|_| transfer(AccountAddress::random(), receiver))
+            .collect();
+        let optimized_txns = DependencyAwareShuffler::new(0).shuffle(txns.clone());
+        assert_eq!(txns, optimized_txns);
+    }
+}
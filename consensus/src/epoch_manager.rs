@@ -739,6 +739,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 .max_sending_block_txns(self.quorum_store_enabled),
             self.config
                 .max_sending_block_bytes(self.quorum_store_enabled),
+            self.config.max_sending_block_gas.unwrap_or(u64::MAX),
             onchain_consensus_config.max_failed_authors_to_store(),
             pipeline_backpressure_config,
             chain_health_backoff_config,
@@ -0,0 +1,55 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters, persistent_liveness_storage::PersistentLivenessStorage};
+use aptos_consensus_types::equivocation_evidence::{
+    EquivocationEvidence, EQUIVOCATION_EVIDENCE, NUM_EVIDENCE_EPOCHS,
+};
+use aptos_logger::prelude::*;
+use std::sync::Arc;
+
+/// Persists the evidence of equivocating validators observed by consensus and publishes it to
+/// `EQUIVOCATION_EVIDENCE`, where the inspection service and the node API read it from.
+pub struct EquivocationEvidenceCollector {
+    storage: Arc<dyn PersistentLivenessStorage>,
+}
+
+impl EquivocationEvidenceCollector {
+    /// Publishes the evidence persisted before a restart, after dropping the evidence of the
+    /// epochs before the last `NUM_EVIDENCE_EPOCHS`.
+    pub fn new(storage: Arc<dyn PersistentLivenessStorage>, epoch: u64) -> Self {
+        let min_epoch = epoch.saturating_sub(NUM_EVIDENCE_EPOCHS - 1);
+        EQUIVOCATION_EVIDENCE.prune_before_epoch(min_epoch);
+        if let Err(e) = storage.prune_equivocation_evidence(min_epoch) {
+            error!(error = ?e, "Failed to prune equivocation evidence");
+        }
+        match storage.retrieve_equivocation_evidence() {
+            Ok(evidence) => {
+                for evidence in evidence {
+                    EQUIVOCATION_EVIDENCE.insert(evidence);
+                }
+            },
+            Err(e) => error!(error = ?e, "Failed to retrieve equivocation evidence"),
+        }
+        Self { storage }
+    }
+
+    /// The messages of the evidence must already have been verified.
+    pub fn collect(&self, evidence: EquivocationEvidence) {
+        if !EQUIVOCATION_EVIDENCE.insert(evidence.clone()) {
+            return;
+        }
+        error!(
+            SecurityEvent::ConsensusEquivocationEvidence,
+            remote_peer = evidence.author(),
+            epoch = evidence.epoch(),
+            round = evidence.round(),
+            "Collected equivocation evidence {}",
+            evidence
+        );
+        counters::EQUIVOCATION_EVIDENCE_COUNT.inc();
+        if let Err(e) = self.storage.save_equivocation_evidence(&evidence) {
+            error!(error = ?e, "Failed to persist equivocation evidence {}", evidence);
+        }
+    }
+}
@@ -4,7 +4,7 @@
 
 use crate::{
     block_storage::tracing::{observe_block, BlockStage},
-    counters,
+    consensus_trace, counters,
     experimental::{
         buffer::{Buffer, Cursor},
         buffer_item::BufferItem,
@@ -13,6 +13,7 @@ use crate::{
         pipeline_phase::CountedRequest,
         signing_phase::{SigningRequest, SigningResponse},
     },
+    logging::LogEvent,
     monitor,
     network::NetworkSender,
     round_manager::VerifiedEvent,
@@ -22,7 +23,7 @@ use aptos_consensus_types::{common::Author, executed_block::ExecutedBlock};
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_types::{
-    account_address::AccountAddress, epoch_change::EpochChangeProof,
+    account_address::AccountAddress, block_info::BlockInfo, epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures, validator_verifier::ValidatorVerifier,
 };
 use futures::{
@@ -185,6 +186,11 @@ impl BufferManager {
             ordered_proof.commit_info(),
             self.buffer.len() + 1,
         );
+        trace(
+            LogEvent::ReceiveOrderedBlocks,
+            ordered_proof.commit_info(),
+            None,
+        );
         let item = BufferItem::new_ordered(ordered_blocks, ordered_proof, callback);
         self.buffer.push_back(item);
     }
@@ -270,6 +276,11 @@ impl BufferManager {
             if item.block_id() == target_block_id {
                 let aggregated_item = item.unwrap_aggregated();
                 let block = aggregated_item.executed_blocks.last().unwrap().block();
+                trace(
+                    LogEvent::PersistBlocks,
+                    aggregated_item.commit_proof.ledger_info().commit_info(),
+                    None,
+                );
                 observe_block(block.timestamp_usecs(), BlockStage::COMMIT_CERTIFIED);
                 // if we're the proposer for the block, we're responsible to broadcast the commit decision.
                 if block.author() == Some(self.author) {
@@ -357,6 +368,11 @@ impl BufferManager {
             "Receive executed response {}",
             executed_blocks.last().unwrap().block_info()
         );
+        trace(
+            LogEvent::ExecuteBlocks,
+            &executed_blocks.last().unwrap().block_info(),
+            None,
+        );
 
         // Handle reconfiguration timestamp reconciliation.
         // end epoch timestamp is set to the first block that causes the reconfiguration.
@@ -404,6 +420,11 @@ impl BufferManager {
             "Receive signing response {}",
             commit_ledger_info.commit_info()
         );
+        trace(
+            LogEvent::SignCommitVote,
+            commit_ledger_info.commit_info(),
+            None,
+        );
         // find the corresponding item, may not exist if a reset or aggregated happened
         let current_cursor = self
             .buffer
@@ -447,6 +468,7 @@ impl BufferManager {
                 let author = vote.author();
                 let commit_info = vote.commit_info().clone();
                 info!("Receive commit vote {} from {}", commit_info, author);
+                trace(LogEvent::ReceiveCommitVote, &commit_info, Some(author));
                 let target_block_id = vote.commit_info().id();
                 let current_cursor = self
                     .buffer
@@ -477,6 +499,11 @@ impl BufferManager {
                     "Receive commit decision {}",
                     commit_proof.ledger_info().commit_info()
                 );
+                trace(
+                    LogEvent::ReceiveCommitDecision,
+                    commit_proof.ledger_info().commit_info(),
+                    None,
+                );
                 let cursor = self
                     .buffer
                     .find_elem_by_key(*self.buffer.head_cursor(), target_block_id);
@@ -622,3 +649,13 @@ impl BufferManager {
         info!("Buffer manager stops.");
     }
 }
+
+fn trace(event: LogEvent, commit_info: &BlockInfo, remote_peer: Option<Author>) {
+    consensus_trace::record(
+        event,
+        commit_info.epoch(),
+        commit_info.round(),
+        Some(commit_info.id()),
+        remote_peer,
+    );
+}
@@ -16,9 +16,13 @@
 extern crate core;
 
 mod block_storage;
+/// Round-by-round trace of consensus events for offline analysis
+pub mod consensus_trace;
 mod consensusdb;
 mod dag;
+mod dependency_aware_shuffler;
 mod epoch_manager;
+mod equivocation_evidence_collector;
 mod error;
 mod experimental;
 mod liveness;
@@ -51,13 +55,17 @@ pub mod network_interface;
 mod payload_manager;
 mod sender_aware_shuffler;
 mod transaction_deduper;
-mod transaction_shuffler;
+/// Transaction shufflers, also used by the executor benchmark
+pub mod transaction_shuffler;
 mod txn_hash_and_authenticator_deduper;
 
 use aptos_metrics_core::IntGauge;
 pub use consensusdb::create_checkpoint;
 /// Required by the smoke tests
 pub use consensusdb::CONSENSUS_DB_NAME;
+/// Offline inspection and maintenance of the quorum store DB
+#[cfg(feature = "db-debugger")]
+pub use quorum_store::db_debugger as quorum_store_db_debugger;
 pub use quorum_store::quorum_store_db::QUORUM_STORE_DB_NAME;
 #[cfg(feature = "fuzzing")]
 pub use round_manager::round_manager_fuzzing;
@@ -9,7 +9,8 @@ use crate::{
     block_storage::BlockReader,
     counters::{
         CHAIN_HEALTH_BACKOFF_TRIGGERED, PIPELINE_BACKPRESSURE_ON_PROPOSAL_TRIGGERED,
-        PROPOSER_DELAY_PROPOSAL, PROPOSER_PENDING_BLOCKS_COUNT,
+        PROPOSER_AVAILABLE_BLOCK_FEES, PROPOSER_DELAY_PROPOSAL, PROPOSER_PACKED_BLOCK_FEES,
+        PROPOSER_PACKED_BLOCK_GAS, PROPOSER_PACKED_FEES_FRACTION, PROPOSER_PENDING_BLOCKS_COUNT,
         PROPOSER_PENDING_BLOCKS_FILL_FRACTION,
     },
     state_replication::PayloadClient,
@@ -159,6 +160,8 @@ pub struct ProposalGenerator {
     max_block_txns: u64,
     // Max number of bytes to be added to a proposed block.
     max_block_bytes: u64,
+    // Max estimated gas to be added to a proposed block, u64::MAX if unlimited.
+    max_block_gas: u64,
     // Max number of failed authors to be added to a proposed block.
     max_failed_authors_to_store: usize,
 
@@ -179,6 +182,7 @@ impl ProposalGenerator {
         quorum_store_poll_time: Duration,
         max_block_txns: u64,
         max_block_bytes: u64,
+        max_block_gas: u64,
         max_failed_authors_to_store: usize,
         pipeline_backpressure_config: PipelineBackpressureConfig,
         chain_health_backoff_config: ChainHealthBackoffConfig,
@@ -192,6 +196,7 @@ impl ProposalGenerator {
             quorum_store_poll_time,
             max_block_txns,
             max_block_bytes,
+            max_block_gas,
             max_failed_authors_to_store,
             pipeline_backpressure_config,
             chain_health_backoff_config,
@@ -309,12 +314,13 @@ impl ProposalGenerator {
                 .max(max_pending_block_bytes as f32 / self.max_block_bytes as f32);
             PROPOSER_PENDING_BLOCKS_COUNT.set(pending_blocks.len() as i64);
             PROPOSER_PENDING_BLOCKS_FILL_FRACTION.set(max_fill_fraction as f64);
-            let payload = self
+            let (payload, gas_estimate) = self
                 .payload_client
                 .pull_payload(
                     self.quorum_store_poll_time.saturating_sub(proposal_delay),
                     max_block_txns,
                     max_block_bytes,
+                    self.max_block_gas,
                     payload_filter,
                     wait_callback,
                     pending_ordering,
@@ -323,6 +329,14 @@ impl ProposalGenerator {
                 )
                 .await
                 .context("Fail to retrieve payload")?;
+            let packed = gas_estimate.packed();
+            PROPOSER_PACKED_BLOCK_GAS.observe(packed.gas as f64);
+            PROPOSER_PACKED_BLOCK_FEES.observe(packed.fees as f64);
+            PROPOSER_AVAILABLE_BLOCK_FEES.observe(gas_estimate.available.fees as f64);
+            if gas_estimate.available.fees > 0 {
+                PROPOSER_PACKED_FEES_FRACTION
+                    .set(packed.fees as f64 / gas_estimate.available.fees as f64);
+            }
 
             (payload, timestamp.as_micros() as u64)
         };
@@ -38,6 +38,7 @@ async fn test_proposal_generation_empty_tree() {
         Duration::ZERO,
         1,
         10,
+        u64::MAX,
         10,
         PipelineBackpressureConfig::new_no_backoff(),
         ChainHealthBackoffConfig::new_no_backoff(),
@@ -78,6 +79,7 @@ async fn test_proposal_generation_parent() {
         Duration::ZERO,
         1,
         1000,
+        u64::MAX,
         10,
         PipelineBackpressureConfig::new_no_backoff(),
         ChainHealthBackoffConfig::new_no_backoff(),
@@ -150,6 +152,7 @@ async fn test_old_proposal_generation() {
         Duration::ZERO,
         1,
         1000,
+        u64::MAX,
         10,
         PipelineBackpressureConfig::new_no_backoff(),
         ChainHealthBackoffConfig::new_no_backoff(),
@@ -187,6 +190,7 @@ async fn test_correct_failed_authors() {
         Duration::ZERO,
         1,
         1000,
+        u64::MAX,
         10,
         PipelineBackpressureConfig::new_no_backoff(),
         ChainHealthBackoffConfig::new_no_backoff(),
@@ -3,7 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    counters,
+    consensus_trace, counters,
+    logging::LogEvent,
     pending_votes::{PendingVotes, VoteReceptionResult},
     util::time_service::{SendTask, TimeService},
 };
@@ -266,6 +267,13 @@ impl RoundState {
                 prev_round_timeout_votes,
             };
             info!(round = new_round, "Starting new round: {}", new_round_event);
+            consensus_trace::record(
+                LogEvent::NewRound,
+                sync_info.highest_quorum_cert().certified_block().epoch(),
+                new_round,
+                None,
+                None,
+            );
             return Some(new_round_event);
         }
         None
@@ -2,14 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::proposer_election::ProposerElection;
+use crate::equivocation_evidence_collector::EquivocationEvidenceCollector;
 use aptos_consensus_types::{
     block::Block,
     common::{Author, Round},
+    equivocation_evidence::EquivocationEvidence,
 };
-use aptos_crypto::HashValue;
 use aptos_infallible::Mutex;
 use aptos_logger::{error, SecurityEvent};
-use std::cmp::Ordering;
+use std::{cmp::Ordering, sync::Arc};
 
 // Wrapper around ProposerElection.
 //
@@ -17,7 +18,8 @@ use std::cmp::Ordering;
 // the same leader proposes multiple blocks.
 pub struct UnequivocalProposerElection {
     proposer_election: Box<dyn ProposerElection + Send + Sync>,
-    already_proposed: Mutex<(Round, HashValue)>,
+    already_proposed: Mutex<(Round, Option<Block>)>,
+    evidence_collector: Option<Arc<EquivocationEvidenceCollector>>,
 }
 
 impl ProposerElection for UnequivocalProposerElection {
@@ -35,7 +37,18 @@ impl UnequivocalProposerElection {
     pub fn new(proposer_election: Box<dyn ProposerElection + Send + Sync>) -> Self {
         Self {
             proposer_election,
-            already_proposed: Mutex::new((0, HashValue::zero())),
+            already_proposed: Mutex::new((0, None)),
+            evidence_collector: None,
+        }
+    }
+
+    pub fn new_with_evidence_collector(
+        proposer_election: Box<dyn ProposerElection + Send + Sync>,
+        evidence_collector: Arc<EquivocationEvidenceCollector>,
+    ) -> Self {
+        Self {
+            evidence_collector: Some(evidence_collector),
+            ..Self::new(proposer_election)
         }
     }
 
@@ -64,23 +77,28 @@ impl UnequivocalProposerElection {
             match block.round().cmp(&already_proposed.0) {
                 Ordering::Greater => {
                     already_proposed.0 = block.round();
-                    already_proposed.1 = block.id();
+                    already_proposed.1 = Some(block.clone());
                     true
                 },
-                Ordering::Equal => {
-                    if already_proposed.1 != block.id() {
+                Ordering::Equal => match &already_proposed.1 {
+                    Some(first) if first.id() != block.id() => {
                         error!(
                             SecurityEvent::InvalidConsensusProposal,
                             "Multiple proposals from {} for round {}: {} and {}",
                             author,
                             block.round(),
-                            already_proposed.1,
+                            first.id(),
                             block.id()
                         );
+                        if let Some(evidence_collector) = &self.evidence_collector {
+                            evidence_collector.collect(EquivocationEvidence::new_proposal(
+                                first.clone(),
+                                block.clone(),
+                            ));
+                        }
                         false
-                    } else {
-                        true
-                    }
+                    },
+                    _ => true,
                 },
                 Ordering::Less => {
                     println!("Older Block");
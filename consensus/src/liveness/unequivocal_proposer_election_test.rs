@@ -2,13 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::proposer_election::ProposerElection;
-use crate::liveness::unequivocal_proposer_election::UnequivocalProposerElection;
+use crate::{
+    equivocation_evidence_collector::EquivocationEvidenceCollector,
+    liveness::unequivocal_proposer_election::UnequivocalProposerElection,
+    persistent_liveness_storage::PersistentLivenessStorage, test_utils::MockStorage,
+};
 use aptos_consensus_types::{
     block::{block_test_utils::certificate_for_genesis, Block},
     common::{Author, Payload, Round},
+    equivocation_evidence::{EquivocationEvidence, EQUIVOCATION_EVIDENCE},
 };
-use aptos_types::validator_signer::ValidatorSigner;
-use std::collections::HashMap;
+use aptos_types::{on_chain_config::ValidatorSet, validator_signer::ValidatorSigner};
+use std::{collections::HashMap, sync::Arc};
 
 struct MockProposerElection {
     proposers: HashMap<Round, Author>,
@@ -104,3 +109,46 @@ fn test_is_valid_proposal() {
     // Proposal from previous round is not valid any more:
     assert!(!pe.is_valid_proposal(&good_proposal));
 }
+
+#[test]
+fn test_equivocation_evidence() {
+    let signer = ValidatorSigner::random([2u8; 32]);
+    let first = Block::new_proposal(
+        Payload::empty(false),
+        1,
+        1,
+        certificate_for_genesis(),
+        &signer,
+        Vec::new(),
+    )
+    .unwrap();
+    let second = Block::new_proposal(
+        Payload::empty(false),
+        1,
+        2,
+        certificate_for_genesis(),
+        &signer,
+        Vec::new(),
+    )
+    .unwrap();
+
+    let (_, storage) = MockStorage::start_for_testing(ValidatorSet::empty());
+    let pe = UnequivocalProposerElection::new_with_evidence_collector(
+        Box::new(MockProposerElection::new(HashMap::from([(
+            1,
+            signer.author(),
+        )]))),
+        Arc::new(EquivocationEvidenceCollector::new(storage.clone(), 1)),
+    );
+
+    assert!(pe.is_valid_proposal(&first));
+    assert!(pe.is_valid_proposal(&first));
+    assert!(storage.retrieve_equivocation_evidence().unwrap().is_empty());
+
+    assert!(!pe.is_valid_proposal(&second));
+    let evidence = EquivocationEvidence::new_proposal(first, second);
+    assert_eq!(storage.retrieve_equivocation_evidence().unwrap(), vec![
+        evidence.clone()
+    ]);
+    assert!(EQUIVOCATION_EVIDENCE.get_all().contains(&evidence));
+}
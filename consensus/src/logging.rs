@@ -5,7 +5,7 @@
 use aptos_consensus_types::common::Author;
 use aptos_logger::Schema;
 use aptos_types::block_info::Round;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Schema)]
 pub struct LogSchema {
@@ -15,26 +15,62 @@ pub struct LogSchema {
     round: Option<Round>,
 }
 
-#[derive(Serialize)]
+/// Consensus events, logged and recorded in the consensus trace.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum LogEvent {
+    /// A quorum certificate was aggregated from the received votes.
+    AggregateQC,
+    /// A timeout certificate was aggregated from the received timeout votes.
+    AggregateTC,
+    /// Blocks were committed by the block tree.
     CommitViaBlock,
+    /// Blocks were committed by syncing to a newer ledger info.
     CommitViaSync,
+    /// Ordered blocks finished execution.
+    ExecuteBlocks,
+    /// A new epoch started.
     NewEpoch,
+    /// A new round started.
     NewRound,
+    /// Ordered blocks with an aggregated commit proof were sent to be persisted.
+    PersistBlocks,
+    /// A proposal was generated and signed.
     Propose,
+    /// A batch retrieval request was received.
     ReceiveBatchRetrieval,
+    /// A block retrieval request was received.
     ReceiveBlockRetrieval,
+    /// A commit decision was received.
+    ReceiveCommitDecision,
+    /// A commit vote was received.
+    ReceiveCommitVote,
+    /// An epoch change proof was received.
     ReceiveEpochChangeProof,
+    /// An epoch retrieval request was received.
     ReceiveEpochRetrieval,
+    /// A message from a different epoch was received.
     ReceiveMessageFromDifferentEpoch,
+    /// A new certificate was received.
     ReceiveNewCertificate,
+    /// Ordered blocks were received by the execution pipeline.
+    ReceiveOrderedBlocks,
+    /// A proposal was received.
     ReceiveProposal,
+    /// A sync info was received.
     ReceiveSyncInfo,
+    /// A vote was received.
     ReceiveVote,
+    /// Blocks were retrieved from a peer.
     RetrieveBlock,
+    /// A commit vote was signed for executed blocks.
+    SignCommitVote,
+    /// The node started syncing to a peer.
     StateSync,
+    /// The round timed out.
     Timeout,
+    /// A vote was sent.
     Vote,
+    /// A vote for a NIL block was generated.
     VoteNIL,
 }
 
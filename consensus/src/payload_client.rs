@@ -8,7 +8,7 @@ use crate::{
 use anyhow::Result;
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter},
-    request_response::{GetPayloadCommand, GetPayloadResponse},
+    request_response::{GetPayloadCommand, GetPayloadResponse, PayloadGasEstimate},
 };
 use aptos_logger::prelude::*;
 use fail::fail_point;
@@ -50,13 +50,15 @@ impl QuorumStoreClient {
         &self,
         max_items: u64,
         max_bytes: u64,
+        max_gas: u64,
         return_non_full: bool,
         exclude_payloads: PayloadFilter,
-    ) -> Result<Payload, QuorumStoreError> {
+    ) -> Result<(Payload, PayloadGasEstimate), QuorumStoreError> {
         let (callback, callback_rcv) = oneshot::channel();
         let req = GetPayloadCommand::GetPayloadRequest(
             max_items,
             max_bytes,
+            max_gas,
             return_non_full,
             exclude_payloads.clone(),
             callback,
@@ -75,7 +77,9 @@ impl QuorumStoreClient {
                 Err(anyhow::anyhow!("[consensus] did not receive GetBlockResponse on time").into())
             },
             Ok(resp) => match resp.map_err(anyhow::Error::from)?? {
-                GetPayloadResponse::GetPayloadResponse(payload) => Ok(payload),
+                GetPayloadResponse::GetPayloadResponse(payload, gas_estimate) => {
+                    Ok((payload, gas_estimate))
+                },
             },
         }
     }
@@ -88,12 +92,13 @@ impl PayloadClient for QuorumStoreClient {
         max_poll_time: Duration,
         max_items: u64,
         max_bytes: u64,
+        max_gas: u64,
         exclude_payloads: PayloadFilter,
         wait_callback: BoxFuture<'static, ()>,
         pending_ordering: bool,
         pending_uncommitted_blocks: usize,
         recent_max_fill_fraction: f32,
-    ) -> Result<Payload, QuorumStoreError> {
+    ) -> Result<(Payload, PayloadGasEstimate), QuorumStoreError> {
         let return_non_full = recent_max_fill_fraction
             < self.wait_for_full_blocks_above_recent_fill_threshold
             && pending_uncommitted_blocks < self.wait_for_full_blocks_above_pending_blocks;
@@ -108,13 +113,14 @@ impl PayloadClient for QuorumStoreClient {
         // keep polling QuorumStore until there's payloads available or there's still pending payloads
         let start_time = Instant::now();
 
-        let payload = loop {
+        let (payload, gas_estimate) = loop {
             // Make sure we don't wait more than expected, due to thread scheduling delays/processing time consumed
             let done = start_time.elapsed() >= max_poll_time;
-            let payload = self
+            let (payload, gas_estimate) = self
                 .pull_internal(
                     max_items,
                     max_bytes,
+                    max_gas,
                     return_non_full || return_empty || done,
                     exclude_payloads.clone(),
                 )
@@ -126,7 +132,7 @@ impl PayloadClient for QuorumStoreClient {
                 sleep(Duration::from_millis(NO_TXN_DELAY)).await;
                 continue;
             }
-            break payload;
+            break (payload, gas_estimate);
         };
         info!(
             elapsed_time = start_time.elapsed(),
@@ -134,12 +140,13 @@ impl PayloadClient for QuorumStoreClient {
             payload_len = payload.len(),
             max_items = max_items,
             max_bytes = max_bytes,
+            max_gas = max_gas,
             pending_ordering = pending_ordering,
             return_empty = return_empty,
             return_non_full = return_non_full,
             duration = start_time.elapsed().as_secs_f32(),
             "Pull payloads from QuorumStore: proposal"
         );
-        Ok(payload)
+        Ok((payload, gas_estimate))
     }
 }
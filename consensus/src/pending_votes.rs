@@ -11,6 +11,7 @@
 use crate::counters;
 use aptos_consensus_types::{
     common::Author,
+    equivocation_evidence::EquivocationEvidence,
     quorum_cert::QuorumCert,
     timeout_2chain::{TwoChainTimeoutCertificate, TwoChainTimeoutWithPartialSignatures},
     vote::Vote,
@@ -38,7 +39,7 @@ pub enum VoteReceptionResult {
     /// The very same vote message has been processed in past.
     DuplicateVote,
     /// The very same author has already voted for another proposal in this round (equivocation).
-    EquivocateVote,
+    EquivocateVote(Box<EquivocationEvidence>),
     /// This block has just been certified after adding the vote.
     NewQuorumCertificate(Arc<QuorumCert>),
     /// The vote completes a new TwoChainTimeoutCertificate
@@ -115,7 +116,9 @@ impl PendingVotes {
                     previous_vote = previously_seen_vote
                 );
 
-                return VoteReceptionResult::EquivocateVote;
+                return VoteReceptionResult::EquivocateVote(Box::new(
+                    EquivocationEvidence::new_vote(previously_seen_vote.clone(), vote.clone()),
+                ));
             }
         }
 
@@ -339,7 +342,8 @@ impl fmt::Display for PendingVotes {
 mod tests {
     use super::{PendingVotes, VoteReceptionResult};
     use aptos_consensus_types::{
-        block::block_test_utils::certificate_for_genesis, vote::Vote, vote_data::VoteData,
+        block::block_test_utils::certificate_for_genesis,
+        equivocation_evidence::EquivocationEvidence, vote::Vote, vote_data::VoteData,
     };
     use aptos_crypto::HashValue;
     use aptos_types::{
@@ -401,7 +405,10 @@ mod tests {
         .unwrap();
         assert_eq!(
             pending_votes.insert_vote(&vote_data_2_author_0, &validator),
-            VoteReceptionResult::EquivocateVote
+            VoteReceptionResult::EquivocateVote(Box::new(EquivocationEvidence::new_vote(
+                vote_data_1_author_0.clone(),
+                vote_data_2_author_0.clone()
+            )))
         );
 
         // a different author voting for a different result -> VoteAdded
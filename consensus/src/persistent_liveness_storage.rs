@@ -2,11 +2,16 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{consensusdb::ConsensusDB, epoch_manager::LivenessStorageData, error::DbError};
+use crate::{
+    consensusdb::{ConsensusDB, EquivocationEvidenceSchema},
+    epoch_manager::LivenessStorageData,
+    error::DbError,
+};
 use anyhow::{format_err, Context, Result};
 use aptos_config::config::NodeConfig;
 use aptos_consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
+    block::Block, equivocation_evidence::EquivocationEvidence, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
 };
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
@@ -49,6 +54,15 @@ pub trait PersistentLivenessStorage: Send + Sync {
     /// ValidatorVerifier.
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof>;
 
+    /// Persist evidence of an equivocating validator.
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()>;
+
+    /// Retrieve all the persisted equivocation evidence.
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>>;
+
+    /// Delete the persisted equivocation evidence of epochs before `epoch`.
+    fn prune_equivocation_evidence(&self, epoch: u64) -> Result<()>;
+
     /// Returns a handle of the aptosdb.
     fn aptos_db(&self) -> Arc<dyn DbReader>;
 }
@@ -441,6 +455,32 @@ impl PersistentLivenessStorage for StorageWriteProxy {
         Ok(proofs)
     }
 
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()> {
+        Ok(self
+            .db
+            .save_data::<EquivocationEvidenceSchema>(&evidence.key(), evidence)?)
+    }
+
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        Ok(self
+            .db
+            .get_all_data::<EquivocationEvidenceSchema>()?
+            .into_iter()
+            .map(|(_, evidence)| evidence)
+            .collect())
+    }
+
+    fn prune_equivocation_evidence(&self, epoch: u64) -> Result<()> {
+        let keys = self
+            .db
+            .get_all_data::<EquivocationEvidenceSchema>()?
+            .into_iter()
+            .map(|(key, _)| key)
+            .filter(|key| key.epoch < epoch)
+            .collect();
+        Ok(self.db.delete_data::<EquivocationEvidenceSchema>(keys)?)
+    }
+
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         self.aptos_db.clone()
     }
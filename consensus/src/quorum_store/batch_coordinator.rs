@@ -6,14 +6,19 @@ use crate::{
     quorum_store::{
         batch_store::BatchStore,
         counters,
+        proof_manager::ProofManagerCommand,
         types::{Batch, PersistedValue},
     },
 };
 use anyhow::ensure;
+use aptos_consensus_types::request_response::GasEstimate;
 use aptos_logger::prelude::*;
 use aptos_types::PeerId;
-use std::sync::Arc;
-use tokio::sync::{mpsc::Receiver, oneshot};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::{
+    mpsc::{Receiver, Sender},
+    oneshot,
+};
 
 #[derive(Debug)]
 pub enum BatchCoordinatorCommand {
@@ -25,6 +30,7 @@ pub struct BatchCoordinator {
     my_peer_id: PeerId,
     network_sender: NetworkSender,
     batch_store: Arc<BatchStore<NetworkSender>>,
+    proof_manager_cmd_tx: Sender<ProofManagerCommand>,
     max_batch_txns: u64,
     max_batch_bytes: u64,
     max_total_txns: u64,
@@ -36,6 +42,7 @@ impl BatchCoordinator {
         my_peer_id: PeerId,
         network_sender: NetworkSender,
         batch_store: Arc<BatchStore<NetworkSender>>,
+        proof_manager_cmd_tx: Sender<ProofManagerCommand>,
         max_batch_txns: u64,
         max_batch_bytes: u64,
         max_total_txns: u64,
@@ -45,6 +52,7 @@ impl BatchCoordinator {
             my_peer_id,
             network_sender,
             batch_store,
+            proof_manager_cmd_tx,
             max_batch_txns,
             max_batch_bytes,
             max_total_txns,
@@ -89,14 +97,38 @@ impl BatchCoordinator {
 
         let batch_store = self.batch_store.clone();
         let network_sender = self.network_sender.clone();
+        let proof_manager_cmd_tx = self.proof_manager_cmd_tx.clone();
         let my_peer_id = self.my_peer_id;
         tokio::spawn(async move {
             let peer_id = persist_requests[0].author();
+            let mut gas_estimates: HashMap<_, _> = persist_requests
+                .iter()
+                .filter_map(|request| {
+                    request
+                        .payload()
+                        .map(|txns| (*request.digest(), GasEstimate::from_txns(txns)))
+                })
+                .collect();
             let signed_batch_infos = batch_store.persist(persist_requests);
             if !signed_batch_infos.is_empty() {
                 if my_peer_id != peer_id {
                     counters::RECEIVED_REMOTE_BATCHES_COUNT.inc_by(signed_batch_infos.len() as u64);
                 }
+                let gas_estimates = signed_batch_infos
+                    .iter()
+                    .filter_map(|info| {
+                        gas_estimates
+                            .remove(info.digest())
+                            .map(|estimate| (info.batch_info().clone(), estimate))
+                    })
+                    .collect();
+                if proof_manager_cmd_tx
+                    .send(ProofManagerCommand::ReceiveGasEstimates(gas_estimates))
+                    .await
+                    .is_err()
+                {
+                    debug!("Failed to send gas estimates to proof manager");
+                }
                 network_sender
                     .send_signed_batch_info_msg(signed_batch_infos, vec![peer_id])
                     .await;
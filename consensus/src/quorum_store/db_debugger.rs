@@ -0,0 +1,347 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline inspection and maintenance of the quorum store DB, for validators whose consensus DB
+//! grew unexpectedly. The node must be stopped while running these commands.
+
+use crate::{
+    consensusdb::{BlockSchema, ConsensusDB, QCSchema},
+    quorum_store::{
+        quorum_store_db::{QuorumStoreDB, QuorumStoreStorage, QUORUM_STORE_DB_NAME},
+        types::{Batch, PersistedValue},
+    },
+};
+use anyhow::{ensure, Result};
+use aptos_consensus_types::{block::Block, common::Payload, proof_of_store::BatchInfo};
+use aptos_crypto::HashValue;
+use aptos_types::PeerId;
+use clap::Parser;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Inspect, clean and audit the quorum store DB.
+#[derive(Parser)]
+pub enum Cmd {
+    /// Print the stored batches by author, with their expiration and size.
+    Summary(SummaryCmd),
+    /// Delete the batches that expired at or before the cutoff.
+    Clean(CleanCmd),
+    /// Report inconsistencies between the stored batches and the proofs in blocks.
+    Audit(AuditCmd),
+}
+
+impl Cmd {
+    /// Runs the command.
+    pub fn run(self) -> Result<()> {
+        match self {
+            Cmd::Summary(cmd) => cmd.run(),
+            Cmd::Clean(cmd) => cmd.run(),
+            Cmd::Audit(cmd) => cmd.run(),
+        }
+    }
+}
+
+/// Print the stored batches by author, with their expiration and size.
+#[derive(Parser)]
+pub struct SummaryCmd {
+    /// Directory containing the quorum store DB and the consensus DB.
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+
+    /// Batches expiring at or before this timestamp are reported as expired. Defaults to the
+    /// latest committed timestamp in the consensus DB.
+    #[clap(long)]
+    expiration_cutoff_usecs: Option<u64>,
+}
+
+impl SummaryCmd {
+    /// Runs the command.
+    pub fn run(self) -> Result<()> {
+        let cutoff = expiration_cutoff(&self.db_dir, self.expiration_cutoff_usecs)?;
+        let batches = QuorumStoreDB::new_readonly(&self.db_dir)?.get_all_batches()?;
+
+        println!("Expiration cutoff: {} usecs", cutoff);
+        let mut total = AuthorSummary::default();
+        for (author, summary) in summarize(&batches, cutoff) {
+            println!("{}: {}", author, summary);
+            total.merge(&summary);
+        }
+        println!("Total: {}", total);
+        Ok(())
+    }
+}
+
+/// Delete the batches that expired at or before the cutoff.
+#[derive(Parser)]
+pub struct CleanCmd {
+    /// Directory containing the quorum store DB and the consensus DB.
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+
+    /// Defaults to the latest committed timestamp in the consensus DB, which is what the node
+    /// uses to clean batches on startup.
+    #[clap(long)]
+    expiration_cutoff_usecs: Option<u64>,
+
+    /// Only print what would be deleted.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+impl CleanCmd {
+    /// Runs the command.
+    pub fn run(self) -> Result<()> {
+        let cutoff = expiration_cutoff(&self.db_dir, self.expiration_cutoff_usecs)?;
+        ensure!(
+            self.db_dir.join(QUORUM_STORE_DB_NAME).exists(),
+            "No quorum store DB in {:?}",
+            self.db_dir
+        );
+        let db = QuorumStoreDB::new(&self.db_dir);
+
+        let expired = expired_batches(&db.get_all_batches()?, cutoff);
+        let num_bytes: u64 = expired.values().map(|batch| batch.num_bytes()).sum();
+        println!(
+            "{} batches ({} bytes) expired at or before {} usecs.",
+            expired.len(),
+            num_bytes,
+            cutoff
+        );
+        if self.dry_run {
+            println!("Dry run, nothing deleted.");
+        } else {
+            db.delete_batches(expired.into_keys().collect())?;
+            println!("Deleted.");
+        }
+        Ok(())
+    }
+}
+
+/// Report inconsistencies between the stored batches and the proofs in blocks.
+#[derive(Parser)]
+pub struct AuditCmd {
+    /// Directory containing the quorum store DB and the consensus DB.
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+
+    /// Batches expiring at or before this timestamp are not required to be stored. Defaults to
+    /// the latest committed timestamp in the consensus DB.
+    #[clap(long)]
+    expiration_cutoff_usecs: Option<u64>,
+}
+
+impl AuditCmd {
+    /// Runs the command.
+    pub fn run(self) -> Result<()> {
+        let cutoff = expiration_cutoff(&self.db_dir, self.expiration_cutoff_usecs)?;
+        let batches = QuorumStoreDB::new_readonly(&self.db_dir)?.get_all_batches()?;
+        let blocks: Vec<Block> = ConsensusDB::new_readonly(&self.db_dir)?
+            .get_all_data::<BlockSchema>()?
+            .into_iter()
+            .map(|(_, block)| block)
+            .collect();
+
+        let inconsistencies = audit(&batches, &blocks, cutoff);
+        for inconsistency in &inconsistencies {
+            println!("{}", inconsistency);
+        }
+        println!(
+            "Audited {} batches and {} blocks: {} inconsistencies.",
+            batches.len(),
+            blocks.len(),
+            inconsistencies.len()
+        );
+        Ok(())
+    }
+}
+
+/// The latest committed timestamp known to the consensus DB, unless overridden.
+fn expiration_cutoff(db_dir: &Path, cutoff: Option<u64>) -> Result<u64> {
+    if let Some(cutoff) = cutoff {
+        return Ok(cutoff);
+    }
+    let qcs = ConsensusDB::new_readonly(db_dir)?.get_all_data::<QCSchema>()?;
+    Ok(qcs
+        .iter()
+        .map(|(_, qc)| qc.commit_info().timestamp_usecs())
+        .max()
+        .unwrap_or(0))
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct AuthorSummary {
+    pub num_batches: u64,
+    pub num_txns: u64,
+    pub num_bytes: u64,
+    pub num_expired: u64,
+    pub min_expiration: Option<u64>,
+    pub max_expiration: Option<u64>,
+}
+
+impl AuthorSummary {
+    fn merge(&mut self, other: &Self) {
+        self.num_batches += other.num_batches;
+        self.num_txns += other.num_txns;
+        self.num_bytes += other.num_bytes;
+        self.num_expired += other.num_expired;
+        self.min_expiration = match (self.min_expiration, other.min_expiration) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_expiration = self.max_expiration.max(other.max_expiration);
+    }
+}
+
+impl fmt::Display for AuthorSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "batches: {}, txns: {}, bytes: {}, expired: {}, expiration: [{:?}, {:?}]",
+            self.num_batches,
+            self.num_txns,
+            self.num_bytes,
+            self.num_expired,
+            self.min_expiration,
+            self.max_expiration
+        )
+    }
+}
+
+pub(crate) fn summarize(
+    batches: &HashMap<HashValue, PersistedValue>,
+    cutoff: u64,
+) -> BTreeMap<PeerId, AuthorSummary> {
+    let mut summaries: BTreeMap<PeerId, AuthorSummary> = BTreeMap::new();
+    for batch in batches.values() {
+        let expiration = batch.expiration();
+        summaries
+            .entry(batch.author())
+            .or_default()
+            .merge(&AuthorSummary {
+                num_batches: 1,
+                num_txns: batch.num_txns(),
+                num_bytes: batch.num_bytes(),
+                num_expired: (expiration <= cutoff) as u64,
+                min_expiration: Some(expiration),
+                max_expiration: Some(expiration),
+            });
+    }
+    summaries
+}
+
+pub(crate) fn expired_batches(
+    batches: &HashMap<HashValue, PersistedValue>,
+    cutoff: u64,
+) -> HashMap<HashValue, PersistedValue> {
+    batches
+        .iter()
+        .filter(|(_, batch)| batch.expiration() <= cutoff)
+        .map(|(digest, batch)| (*digest, batch.clone()))
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Inconsistency {
+    /// The batch is stored under a key that is not its digest.
+    KeyMismatch { key: HashValue, digest: HashValue },
+    /// The batch is stored without its transactions.
+    MissingPayload { digest: HashValue },
+    /// The stored transactions do not match the batch info.
+    InvalidPayload { digest: HashValue, error: String },
+    /// A proof references the batch with a different batch info than the stored one.
+    InfoMismatch {
+        block_id: HashValue,
+        stored: BatchInfo,
+        proof: BatchInfo,
+    },
+    /// A proof in a block references an unexpired batch that is not stored.
+    MissingBatch {
+        block_id: HashValue,
+        info: BatchInfo,
+    },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Inconsistency::KeyMismatch { key, digest } => {
+                write!(f, "Batch {} stored under key {}", digest, key)
+            },
+            Inconsistency::MissingPayload { digest } => {
+                write!(f, "Batch {} stored without payload", digest)
+            },
+            Inconsistency::InvalidPayload { digest, error } => {
+                write!(f, "Batch {} has an invalid payload: {}", digest, error)
+            },
+            Inconsistency::InfoMismatch {
+                block_id,
+                stored,
+                proof,
+            } => write!(
+                f,
+                "Block {} has a proof of {:?} but {:?} is stored",
+                block_id, proof, stored
+            ),
+            Inconsistency::MissingBatch { block_id, info } => write!(
+                f,
+                "Block {} has a proof of {:?} which is not stored",
+                block_id, info
+            ),
+        }
+    }
+}
+
+pub(crate) fn audit(
+    batches: &HashMap<HashValue, PersistedValue>,
+    blocks: &[Block],
+    cutoff: u64,
+) -> Vec<Inconsistency> {
+    let mut inconsistencies = vec![];
+    for (key, batch) in batches {
+        if key != batch.digest() {
+            inconsistencies.push(Inconsistency::KeyMismatch {
+                key: *key,
+                digest: *batch.digest(),
+            });
+        }
+        if batch.payload().is_none() {
+            inconsistencies.push(Inconsistency::MissingPayload {
+                digest: *batch.digest(),
+            });
+        } else if let Err(e) = Batch::try_from(batch.clone()).and_then(|batch| batch.verify()) {
+            inconsistencies.push(Inconsistency::InvalidPayload {
+                digest: *batch.digest(),
+                error: e.to_string(),
+            });
+        }
+    }
+
+    for block in blocks {
+        let proofs = match block.payload() {
+            Some(Payload::InQuorumStore(proof_with_data)) => &proof_with_data.proofs,
+            _ => continue,
+        };
+        for proof in proofs {
+            match batches.get(proof.digest()) {
+                Some(stored) if stored.batch_info() != proof.info() => {
+                    inconsistencies.push(Inconsistency::InfoMismatch {
+                        block_id: block.id(),
+                        stored: stored.batch_info().clone(),
+                        proof: proof.info().clone(),
+                    });
+                },
+                None if proof.expiration() > cutoff => {
+                    inconsistencies.push(Inconsistency::MissingBatch {
+                        block_id: block.id(),
+                        info: proof.info().clone(),
+                    });
+                },
+                _ => {},
+            }
+        }
+    }
+    inconsistencies
+}
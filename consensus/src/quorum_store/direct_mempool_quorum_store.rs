@@ -5,7 +5,7 @@ use crate::{monitor, quorum_store::counters};
 use anyhow::Result;
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter, TransactionInProgress, TransactionSummary},
-    request_response::{GetPayloadCommand, GetPayloadResponse},
+    request_response::{GasEstimate, GetPayloadCommand, GetPayloadResponse, PayloadGasEstimate},
 };
 use aptos_logger::prelude::*;
 use aptos_mempool::{QuorumStoreRequest, QuorumStoreResponse};
@@ -91,6 +91,7 @@ impl DirectMempoolQuorumStore {
         &self,
         max_txns: u64,
         max_bytes: u64,
+        max_gas: u64,
         return_non_full: bool,
         payload_filter: PayloadFilter,
         callback: oneshot::Sender<Result<GetPayloadResponse>>,
@@ -104,7 +105,7 @@ impl DirectMempoolQuorumStore {
             PayloadFilter::Empty => Vec::new(),
         };
 
-        let (txns, result) = match self
+        let (mut txns, result) = match self
             .pull_internal(max_txns, max_bytes, return_non_full, exclude_txns)
             .await
         {
@@ -120,9 +121,30 @@ impl DirectMempoolQuorumStore {
             get_batch_start_time.elapsed(),
         );
 
+        // Mempool returns txns by decreasing gas price, so the prefix that fits the gas budget
+        // is the one with the highest fees.
+        let available = GasEstimate::from_txns(&txns);
+        let mut packed = GasEstimate::default();
+        let num_packed = txns
+            .iter()
+            .take_while(|txn| {
+                let estimate = packed + GasEstimate::from_txns(std::slice::from_ref(*txn));
+                if estimate.gas > max_gas {
+                    return false;
+                }
+                packed = estimate;
+                true
+            })
+            .count();
+        txns.truncate(num_packed);
+
         let get_block_response_start_time = Instant::now();
         let payload = Payload::DirectMempool(txns);
-        let result = match callback.send(Ok(GetPayloadResponse::GetPayloadResponse(payload))) {
+        let gas_estimate = PayloadGasEstimate::new(vec![packed], available);
+        let result = match callback.send(Ok(GetPayloadResponse::GetPayloadResponse(
+            payload,
+            gas_estimate,
+        ))) {
             Err(_) => {
                 error!("Callback failed");
                 counters::CALLBACK_FAIL_LABEL
@@ -141,6 +163,7 @@ impl DirectMempoolQuorumStore {
             GetPayloadCommand::GetPayloadRequest(
                 max_txns,
                 max_bytes,
+                max_gas,
                 return_non_full,
                 payload_filter,
                 callback,
@@ -148,6 +171,7 @@ impl DirectMempoolQuorumStore {
                 self.handle_block_request(
                     max_txns,
                     max_bytes,
+                    max_gas,
                     return_non_full,
                     payload_filter,
                     callback,
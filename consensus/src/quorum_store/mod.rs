@@ -5,6 +5,9 @@ pub mod counters;
 /// Equivalent to directly fetching blocks from mempool without a quorum store.
 pub mod direct_mempool_quorum_store;
 
+#[cfg(feature = "db-debugger")]
+pub mod db_debugger;
+
 pub(crate) mod batch_coordinator;
 pub(crate) mod batch_generator;
 pub(crate) mod batch_requester;
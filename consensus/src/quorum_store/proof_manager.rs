@@ -8,7 +8,7 @@ use crate::{
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter, ProofWithData},
     proof_of_store::{BatchInfo, ProofOfStore, ProofOfStoreMsg},
-    request_response::{GetPayloadCommand, GetPayloadResponse},
+    request_response::{GasEstimate, GetPayloadCommand, GetPayloadResponse},
 };
 use aptos_logger::prelude::*;
 use aptos_types::PeerId;
@@ -19,6 +19,7 @@ use std::collections::HashSet;
 #[derive(Debug)]
 pub enum ProofManagerCommand {
     ReceiveProofs(ProofOfStoreMsg),
+    ReceiveGasEstimates(Vec<(BatchInfo, GasEstimate)>),
     CommitNotification(u64, Vec<BatchInfo>),
     Shutdown(tokio::sync::oneshot::Sender<()>),
 }
@@ -54,6 +55,10 @@ impl ProofManager {
             self.proofs_for_consensus.remaining_txns_and_proofs();
     }
 
+    pub(crate) fn receive_gas_estimates(&mut self, estimates: Vec<(BatchInfo, GasEstimate)>) {
+        self.proofs_for_consensus.add_gas_estimates(estimates);
+    }
+
     pub(crate) fn handle_commit_notification(
         &mut self,
         block_timestamp: u64,
@@ -76,6 +81,7 @@ impl ProofManager {
             GetPayloadCommand::GetPayloadRequest(
                 max_txns,
                 max_bytes,
+                max_gas,
                 return_non_full,
                 filter,
                 callback,
@@ -88,10 +94,11 @@ impl ProofManager {
                     PayloadFilter::InQuorumStore(proofs) => proofs,
                 };
 
-                let proof_block = self.proofs_for_consensus.pull_proofs(
+                let (proof_block, gas_estimate) = self.proofs_for_consensus.pull_proofs(
                     &excluded_batches,
                     max_txns,
                     max_bytes,
+                    max_gas,
                     return_non_full,
                 );
 
@@ -106,6 +113,7 @@ impl ProofManager {
                         );
                         Payload::InQuorumStore(ProofWithData::new(proof_block))
                     },
+                    gas_estimate,
                 );
                 match callback.send(Ok(res)) {
                     Ok(_) => (),
@@ -161,6 +169,9 @@ impl ProofManager {
                             ProofManagerCommand::ReceiveProofs(proofs) => {
                                 self.receive_proofs(proofs.take());
                             },
+                            ProofManagerCommand::ReceiveGasEstimates(estimates) => {
+                                self.receive_gas_estimates(estimates);
+                            },
                             ProofManagerCommand::CommitNotification(block_timestamp, batches) => {
                                 self.handle_commit_notification(
                                     block_timestamp,
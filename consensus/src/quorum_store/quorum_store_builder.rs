@@ -304,6 +304,7 @@ impl InnerBuilder {
                 self.author,
                 self.network_sender.clone(),
                 self.batch_store.clone().unwrap(),
+                self.proof_manager_cmd_tx.clone(),
                 self.config.receiver_max_batch_txns as u64,
                 self.config.receiver_max_batch_bytes as u64,
                 self.config.receiver_max_total_txns as u64,
@@ -0,0 +1,122 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::quorum_store::{
+    db_debugger::{audit, expired_batches, summarize, AuthorSummary, Inconsistency},
+    tests::utils::create_vec_signed_transactions,
+    types::{Batch, PersistedValue},
+};
+use aptos_consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::{Payload, ProofWithData},
+    proof_of_store::{BatchId, ProofOfStore},
+};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    aggregate_signature::AggregateSignature, validator_signer::ValidatorSigner, PeerId,
+};
+use std::collections::HashMap;
+
+fn create_batch(author: PeerId, batch_id: u64, expiration: u64) -> PersistedValue {
+    Batch::new(
+        BatchId::new_for_test(batch_id),
+        create_vec_signed_transactions(10),
+        1,
+        expiration,
+        author,
+        0,
+    )
+    .into()
+}
+
+fn create_block(proofs: Vec<ProofOfStore>) -> Block {
+    Block::new_proposal(
+        Payload::InQuorumStore(ProofWithData::new(proofs)),
+        1,
+        1,
+        certificate_for_genesis(),
+        &ValidatorSigner::random(None),
+        vec![],
+    )
+    .unwrap()
+}
+
+fn to_map(batches: &[PersistedValue]) -> HashMap<HashValue, PersistedValue> {
+    batches
+        .iter()
+        .map(|batch| (*batch.digest(), batch.clone()))
+        .collect()
+}
+
+#[test]
+fn test_summarize_and_expire() {
+    let author_0 = PeerId::random();
+    let author_1 = PeerId::random();
+    let batches = to_map(&[
+        create_batch(author_0, 1, 10),
+        create_batch(author_0, 2, 30),
+        create_batch(author_1, 1, 20),
+    ]);
+
+    let summaries = summarize(&batches, 20);
+    let summary_0 = &summaries[&author_0];
+    assert_eq!(summary_0.num_batches, 2);
+    assert_eq!(summary_0.num_txns, 20);
+    assert_eq!(summary_0.num_expired, 1);
+    assert_eq!(summary_0.min_expiration, Some(10));
+    assert_eq!(summary_0.max_expiration, Some(30));
+    assert_eq!(summaries[&author_1], AuthorSummary {
+        num_batches: 1,
+        num_txns: 10,
+        num_bytes: batches
+            .values()
+            .find(|b| b.author() == author_1)
+            .unwrap()
+            .num_bytes(),
+        num_expired: 1,
+        min_expiration: Some(20),
+        max_expiration: Some(20),
+    });
+
+    let expired = expired_batches(&batches, 20);
+    assert_eq!(expired.len(), 2);
+    assert!(expired.values().all(|batch| batch.expiration() <= 20));
+}
+
+#[test]
+fn test_audit() {
+    let author = PeerId::random();
+    let valid = create_batch(author, 1, 30);
+    let mut without_payload = create_batch(author, 2, 30);
+    without_payload.remove_payload();
+    let misplaced = create_batch(author, 3, 30);
+    let not_stored = create_batch(author, 4, 30);
+    let expired = create_batch(author, 5, 10);
+
+    let mut batches = to_map(&[valid.clone(), without_payload.clone()]);
+    let key = HashValue::random();
+    batches.insert(key, misplaced.clone());
+
+    let proof = |batch: &PersistedValue| {
+        ProofOfStore::new(batch.batch_info().clone(), AggregateSignature::empty())
+    };
+    let block = create_block(vec![proof(&valid), proof(&not_stored), proof(&expired)]);
+
+    let mut inconsistencies = audit(&batches, &[block.clone()], 20);
+    inconsistencies.sort_by_key(|inconsistency| format!("{:?}", inconsistency));
+    let mut expected = vec![
+        Inconsistency::KeyMismatch {
+            key,
+            digest: *misplaced.digest(),
+        },
+        Inconsistency::MissingPayload {
+            digest: *without_payload.digest(),
+        },
+        Inconsistency::MissingBatch {
+            block_id: block.id(),
+            info: not_stored.batch_info().clone(),
+        },
+    ];
+    expected.sort_by_key(|inconsistency| format!("{:?}", inconsistency));
+    assert_eq!(inconsistencies, expected);
+}
@@ -32,6 +32,7 @@ async fn test_block_request_no_txns() {
         .try_send(GetPayloadCommand::GetPayloadRequest(
             100,
             1000,
+            u64::MAX,
             true,
             PayloadFilter::DirectMempool(vec![]),
             consensus_callback,
@@ -65,7 +66,7 @@ async fn test_block_request_no_txns() {
         .unwrap()
         .unwrap()
     {
-        GetPayloadResponse::GetPayloadResponse(payload) => {
+        GetPayloadResponse::GetPayloadResponse(payload, _) => {
             assert!(payload.is_empty());
         },
     }
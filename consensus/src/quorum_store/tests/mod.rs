@@ -3,6 +3,8 @@
 
 mod batch_generator_test;
 mod batch_store_test;
+#[cfg(feature = "db-debugger")]
+mod db_debugger_test;
 mod direct_mempool_quorum_store_test;
 mod proof_coordinator_test;
 mod proof_manager_test;
@@ -5,7 +5,7 @@ use crate::quorum_store::proof_manager::ProofManager;
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter},
     proof_of_store::{BatchId, BatchInfo, ProofOfStore},
-    request_response::{GetPayloadCommand, GetPayloadResponse},
+    request_response::{GasEstimate, GetPayloadCommand, GetPayloadResponse, PayloadGasEstimate},
 };
 use aptos_crypto::HashValue;
 use aptos_types::{aggregate_signature::AggregateSignature, PeerId};
@@ -49,17 +49,29 @@ async fn get_proposal_and_assert(
     filter: &[BatchInfo],
     expected: &[ProofOfStore],
 ) {
+    get_gas_limited_proposal_and_assert(proof_manager, max_txns, u64::MAX, filter, expected).await;
+}
+
+async fn get_gas_limited_proposal_and_assert(
+    proof_manager: &mut ProofManager,
+    max_txns: u64,
+    max_gas: u64,
+    filter: &[BatchInfo],
+    expected: &[ProofOfStore],
+) -> PayloadGasEstimate {
     let (callback_tx, callback_rx) = oneshot::channel();
     let filter_set = HashSet::from_iter(filter.iter().cloned());
     let req = GetPayloadCommand::GetPayloadRequest(
         max_txns,
         1000000,
+        max_gas,
         true,
         PayloadFilter::InQuorumStore(filter_set),
         callback_tx,
     );
     proof_manager.handle_proposal_request(req);
-    let GetPayloadResponse::GetPayloadResponse(payload) = callback_rx.await.unwrap().unwrap();
+    let GetPayloadResponse::GetPayloadResponse(payload, gas_estimate) =
+        callback_rx.await.unwrap().unwrap();
     if let Payload::InQuorumStore(proofs) = payload {
         assert_eq!(proofs.proofs.len(), expected.len());
         for proof in proofs.proofs {
@@ -68,6 +80,7 @@ async fn get_proposal_and_assert(
     } else {
         panic!("Unexpected variant")
     }
+    gas_estimate
 }
 
 #[tokio::test]
@@ -233,3 +246,42 @@ async fn test_duplicate_batches_on_expiration() {
     proof_manager.handle_commit_notification(12, vec![]);
     get_proposal_and_assert(&mut proof_manager, 10, &[], &[]).await;
 }
+
+#[tokio::test]
+async fn test_gas_limited_proposal() {
+    let mut proof_manager = create_proof_manager();
+
+    let high_fee_proof = create_proof(PeerId::random(), 10, 1);
+    let low_fee_proof = create_proof(PeerId::random(), 10, 2);
+    let mid_fee_proof = create_proof(PeerId::random(), 10, 3);
+    proof_manager.receive_proofs(vec![
+        high_fee_proof.clone(),
+        low_fee_proof.clone(),
+        mid_fee_proof.clone(),
+    ]);
+    proof_manager.receive_gas_estimates(vec![
+        (high_fee_proof.info().clone(), GasEstimate::new(100, 10000)),
+        (low_fee_proof.info().clone(), GasEstimate::new(100, 5000)),
+        (mid_fee_proof.info().clone(), GasEstimate::new(50, 4000)),
+    ]);
+
+    // Packed by fee per gas, skipping what does not fit the gas budget
+    let gas_estimate = get_gas_limited_proposal_and_assert(&mut proof_manager, 100, 150, &[], &[
+        high_fee_proof.clone(),
+        mid_fee_proof.clone(),
+    ])
+    .await;
+    assert_eq!(gas_estimate.packed(), GasEstimate::new(150, 14000));
+    assert_eq!(gas_estimate.available, GasEstimate::new(250, 19000));
+
+    let gas_estimate = get_gas_limited_proposal_and_assert(
+        &mut proof_manager,
+        100,
+        120,
+        &[high_fee_proof.info().clone()],
+        &[mid_fee_proof.clone()],
+    )
+    .await;
+    assert_eq!(gas_estimate.packed(), GasEstimate::new(50, 4000));
+    assert_eq!(gas_estimate.available, GasEstimate::new(150, 9000));
+}
@@ -40,6 +40,10 @@ impl PersistedValue {
         }
     }
 
+    pub(crate) fn payload(&self) -> Option<&Vec<SignedTransaction>> {
+        self.maybe_payload.as_ref()
+    }
+
     pub(crate) fn take_payload(&mut self) -> Option<Vec<SignedTransaction>> {
         self.maybe_payload.take()
     }
@@ -5,6 +5,7 @@ use crate::{monitor, quorum_store::counters};
 use aptos_consensus_types::{
     common::TransactionInProgress,
     proof_of_store::{BatchId, BatchInfo, ProofOfStore},
+    request_response::{GasEstimate, PayloadGasEstimate},
 };
 use aptos_logger::prelude::*;
 use aptos_mempool::{QuorumStoreRequest, QuorumStoreResponse};
@@ -201,6 +202,9 @@ pub struct ProofQueue {
     batch_to_proof: HashMap<BatchKey, Option<(ProofOfStore, Instant)>>,
     // Expiration index
     expirations: TimeExpirations<BatchSortKey>,
+    // Estimated gas of batches, reported by the batch coordinator once persisted
+    gas_estimates: HashMap<BatchKey, GasEstimate>,
+    gas_estimate_expirations: TimeExpirations<BatchSortKey>,
     latest_block_timestamp: u64,
     remaining_txns: u64,
     remaining_proofs: u64,
@@ -215,6 +219,8 @@ impl ProofQueue {
             author_to_batches: HashMap::new(),
             batch_to_proof: HashMap::new(),
             expirations: TimeExpirations::new(),
+            gas_estimates: HashMap::new(),
+            gas_estimate_expirations: TimeExpirations::new(),
             latest_block_timestamp: 0,
             remaining_txns: 0,
             remaining_proofs: 0,
@@ -275,19 +281,134 @@ impl ProofQueue {
         self.inc_remaining(&author, num_txns);
     }
 
+    pub(crate) fn add_gas_estimates(&mut self, estimates: Vec<(BatchInfo, GasEstimate)>) {
+        for (batch, estimate) in estimates {
+            if batch.expiration() < self.latest_block_timestamp {
+                continue;
+            }
+            if self
+                .gas_estimates
+                .insert(BatchKey::from_info(&batch), estimate)
+                .is_none()
+            {
+                self.gas_estimate_expirations
+                    .add_item(BatchSortKey::from_info(&batch), batch.expiration());
+            }
+        }
+    }
+
     // gets excluded and iterates over the vector returning non excluded or expired entries.
-    // return the vector of pulled PoS, and the size of the remaining PoS
+    // return the vector of pulled PoS, and the gas estimate of the pulled and available PoS.
+    // If max_gas is u64::MAX, gas is not limited and PoS are pulled round robin between authors,
+    // otherwise PoS are packed by decreasing fee per gas until the gas budget is reached.
     pub(crate) fn pull_proofs(
         &mut self,
         excluded_batches: &HashSet<BatchInfo>,
         max_txns: u64,
         max_bytes: u64,
+        max_gas: u64,
         return_non_full: bool,
-    ) -> Vec<ProofOfStore> {
+    ) -> (Vec<ProofOfStore>, PayloadGasEstimate) {
+        let mut excluded_txns = 0;
+        let mut known_gas = GasEstimate::default();
+        let mut known_txns = 0;
+        let mut unknown = vec![];
+        for batch in self
+            .author_to_batches
+            .values()
+            .flat_map(|batches| batches.values())
+        {
+            if excluded_batches.contains(batch) {
+                excluded_txns += batch.num_txns();
+            } else if let Some(Some(_)) = self.batch_to_proof.get(&BatchKey::from_info(batch)) {
+                match self.gas_estimates.get(&BatchKey::from_info(batch)) {
+                    Some(estimate) => {
+                        known_gas = known_gas + *estimate;
+                        known_txns += batch.num_txns();
+                    },
+                    None => unknown.push(batch),
+                }
+            }
+        }
+        // Batches without a reported estimate are assumed to have the average gas per txn of
+        // the known ones.
+        let default_gas_per_txn = known_gas.gas.checked_div(known_txns).unwrap_or(0);
+        let available = known_gas
+            + unknown
+                .into_iter()
+                .map(|batch| Self::default_gas_estimate(batch, default_gas_per_txn))
+                .sum::<GasEstimate>();
+
+        let (ret, full) = if max_gas == u64::MAX {
+            self.pull_round_robin(excluded_batches, max_txns, max_bytes)
+        } else {
+            self.pull_by_fee(
+                excluded_batches,
+                max_txns,
+                max_bytes,
+                max_gas,
+                default_gas_per_txn,
+            )
+        };
+        let cur_txns: u64 = ret.iter().map(|proof| proof.num_txns()).sum();
+        let cur_bytes: u64 = ret.iter().map(|proof| proof.num_bytes()).sum();
+        info!(
+            // before non full check
+            byte_size = cur_bytes,
+            block_size = cur_txns,
+            batch_count = ret.len(),
+            full = full,
+            return_non_full = return_non_full,
+            "Pull payloads from QuorumStore: internal"
+        );
+
+        if full || return_non_full {
+            counters::BLOCK_SIZE_WHEN_PULL.observe(cur_txns as f64);
+            counters::BLOCK_BYTES_WHEN_PULL.observe(cur_bytes as f64);
+            counters::PROOF_SIZE_WHEN_PULL.observe(ret.len() as f64);
+            counters::EXCLUDED_TXNS_WHEN_PULL.observe(excluded_txns as f64);
+            for proof in &ret {
+                if let Some(Some((_, insertion_time))) =
+                    self.batch_to_proof.get(&BatchKey::from_info(proof.info()))
+                {
+                    counters::pos_to_pull(
+                        proof.gas_bucket_start(),
+                        insertion_time.elapsed().as_secs_f64(),
+                    );
+                }
+            }
+            let batches = ret
+                .iter()
+                .map(|proof| self.gas_estimate(proof.info(), default_gas_per_txn))
+                .collect();
+            (ret, PayloadGasEstimate::new(batches, available))
+        } else {
+            (Vec::new(), PayloadGasEstimate::new(vec![], available))
+        }
+    }
+
+    fn default_gas_estimate(batch: &BatchInfo, gas_per_txn: u64) -> GasEstimate {
+        let gas = gas_per_txn.saturating_mul(batch.num_txns());
+        GasEstimate::new(gas, gas.saturating_mul(batch.gas_bucket_start()))
+    }
+
+    fn gas_estimate(&self, batch: &BatchInfo, default_gas_per_txn: u64) -> GasEstimate {
+        self.gas_estimates
+            .get(&BatchKey::from_info(batch))
+            .copied()
+            .unwrap_or_else(|| Self::default_gas_estimate(batch, default_gas_per_txn))
+    }
+
+    // Round robin between authors, taking the highest gas bucket first within each author.
+    fn pull_round_robin(
+        &self,
+        excluded_batches: &HashSet<BatchInfo>,
+        max_txns: u64,
+        max_bytes: u64,
+    ) -> (Vec<ProofOfStore>, bool) {
         let mut ret = vec![];
         let mut cur_bytes = 0;
         let mut cur_txns = 0;
-        let mut excluded_txns = 0;
         let mut full = false;
 
         let mut iters = vec![];
@@ -303,10 +424,9 @@ impl ProofQueue {
                 }
                 if let Some((sort_key, batch)) = iter.next() {
                     if excluded_batches.contains(batch) {
-                        excluded_txns += batch.num_txns();
-                    } else if let Some(Some((proof, insertion_time))) =
-                        self.batch_to_proof.get(&sort_key.batch_key)
-                    {
+                        return true;
+                    }
+                    if let Some(Some((proof, _))) = self.batch_to_proof.get(&sort_key.batch_key) {
                         cur_bytes += batch.num_bytes();
                         cur_txns += batch.num_txns();
                         if cur_bytes > max_bytes || cur_txns > max_txns {
@@ -314,9 +434,7 @@ impl ProofQueue {
                             full = true;
                             return false;
                         }
-                        let bucket = proof.gas_bucket_start();
                         ret.push(proof.clone());
-                        counters::pos_to_pull(bucket, insertion_time.elapsed().as_secs_f64());
                         if cur_bytes == max_bytes || cur_txns == max_txns {
                             // Exactly the limit for requested bytes or number of transactions.
                             full = true;
@@ -329,25 +447,62 @@ impl ProofQueue {
                 }
             })
         }
-        info!(
-            // before non full check
-            byte_size = cur_bytes,
-            block_size = cur_txns,
-            batch_count = ret.len(),
-            full = full,
-            return_non_full = return_non_full,
-            "Pull payloads from QuorumStore: internal"
-        );
+        (ret, full)
+    }
 
-        if full || return_non_full {
-            counters::BLOCK_SIZE_WHEN_PULL.observe(cur_txns as f64);
-            counters::BLOCK_BYTES_WHEN_PULL.observe(cur_bytes as f64);
-            counters::PROOF_SIZE_WHEN_PULL.observe(ret.len() as f64);
-            counters::EXCLUDED_TXNS_WHEN_PULL.observe(excluded_txns as f64);
-            ret
-        } else {
-            Vec::new()
+    // Greedily packs the PoS with the highest fee per gas first, skipping the ones that do not
+    // fit in the remaining budget.
+    fn pull_by_fee(
+        &self,
+        excluded_batches: &HashSet<BatchInfo>,
+        max_txns: u64,
+        max_bytes: u64,
+        max_gas: u64,
+        default_gas_per_txn: u64,
+    ) -> (Vec<ProofOfStore>, bool) {
+        let mut candidates: Vec<_> = self
+            .author_to_batches
+            .values()
+            .flat_map(|batches| batches.iter())
+            .filter(|(_, batch)| !excluded_batches.contains(batch))
+            .filter_map(
+                |(sort_key, batch)| match self.batch_to_proof.get(&sort_key.batch_key) {
+                    Some(Some((proof, _))) => {
+                        Some((proof, self.gas_estimate(batch, default_gas_per_txn)))
+                    },
+                    _ => None,
+                },
+            )
+            .collect();
+        // Descending fee per gas, compared by cross multiplication to avoid division.
+        candidates.sort_by(|(_, a), (_, b)| {
+            (b.fees as u128 * a.gas as u128).cmp(&(a.fees as u128 * b.gas as u128))
+        });
+
+        let mut ret = vec![];
+        let mut cur_bytes = 0;
+        let mut cur_txns = 0;
+        let mut cur_gas = 0u64;
+        let mut full = false;
+        for (proof, estimate) in candidates {
+            if cur_bytes + proof.num_bytes() > max_bytes
+                || cur_txns + proof.num_txns() > max_txns
+                || cur_gas.saturating_add(estimate.gas) > max_gas
+            {
+                // Does not fit, but a smaller PoS might.
+                full = true;
+                continue;
+            }
+            cur_bytes += proof.num_bytes();
+            cur_txns += proof.num_txns();
+            cur_gas += estimate.gas;
+            ret.push(proof.clone());
+            if cur_bytes == max_bytes || cur_txns == max_txns || cur_gas == max_gas {
+                full = true;
+                break;
+            }
         }
+        (ret, full)
     }
 
     pub(crate) fn handle_updated_block_timestamp(&mut self, block_timestamp: u64) {
@@ -382,6 +537,10 @@ impl ProofQueue {
             }
         }
         counters::NUM_PROOFS_EXPIRED_WHEN_COMMIT.inc_by(num_expired_but_not_committed);
+
+        for key in self.gas_estimate_expirations.expire(block_timestamp) {
+            self.gas_estimates.remove(&key.batch_key);
+        }
     }
 
     pub(crate) fn remaining_txns_and_proofs(&self) -> (u64, u64) {
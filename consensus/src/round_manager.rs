@@ -7,7 +7,8 @@ use crate::{
         tracing::{observe_block, BlockStage},
         BlockReader, BlockRetriever, BlockStore,
     },
-    counters,
+    consensus_trace, counters,
+    equivocation_evidence_collector::EquivocationEvidenceCollector,
     error::{error_kind, VerifyError},
     liveness::{
         proposal_generator::ProposalGenerator,
@@ -199,6 +200,7 @@ pub struct RoundManager {
     block_store: Arc<BlockStore>,
     round_state: RoundState,
     proposer_election: UnequivocalProposerElection,
+    evidence_collector: Arc<EquivocationEvidenceCollector>,
     proposal_generator: ProposalGenerator,
     safety_rules: Arc<Mutex<MetricsSafetyRules>>,
     network: NetworkSender,
@@ -234,11 +236,19 @@ impl RoundManager {
         counters::OP_COUNTERS
             .gauge("decoupled_execution")
             .set(onchain_config.decoupled_execution() as i64);
+        let evidence_collector = Arc::new(EquivocationEvidenceCollector::new(
+            storage.clone(),
+            epoch_state.epoch,
+        ));
         Self {
             epoch_state,
             block_store,
             round_state,
-            proposer_election: UnequivocalProposerElection::new(proposer_election),
+            proposer_election: UnequivocalProposerElection::new_with_evidence_collector(
+                proposer_election,
+                evidence_collector.clone(),
+            ),
+            evidence_collector,
             proposal_generator,
             safety_rules,
             network,
@@ -407,6 +417,13 @@ impl RoundManager {
             Block::new_proposal_from_block_data_and_signature(proposal, signature);
         observe_block(signed_proposal.timestamp_usecs(), BlockStage::SIGNED);
         info!(self.new_log(LogEvent::Propose), "{}", signed_proposal);
+        consensus_trace::record(
+            LogEvent::Propose,
+            self.epoch_state.epoch,
+            signed_proposal.round(),
+            Some(signed_proposal.id()),
+            None,
+        );
         Ok(ProposalMsg::new(
             signed_proposal,
             self.block_store.sync_info(),
@@ -431,6 +448,13 @@ impl RoundManager {
             block_hash = proposal_msg.proposal().id(),
             block_parent_hash = proposal_msg.proposal().quorum_cert().certified_block().id(),
         );
+        consensus_trace::record(
+            LogEvent::ReceiveProposal,
+            self.epoch_state.epoch,
+            proposal_msg.proposal().round(),
+            Some(proposal_msg.proposal().id()),
+            Some(proposal_msg.proposer()),
+        );
 
         if self
             .ensure_round_and_sync_up(
@@ -616,6 +640,13 @@ impl RoundManager {
             voted_nil = is_nil_vote,
             event = LogEvent::Timeout,
         );
+        consensus_trace::record(
+            LogEvent::Timeout,
+            self.epoch_state.epoch,
+            round,
+            None,
+            Some(self.proposer_election.get_valid_proposer(round)),
+        );
         bail!("Round {} timeout, broadcast to all peers", round);
     }
 
@@ -763,6 +794,13 @@ impl RoundManager {
             self.new_log(LogEvent::Vote).remote_peer(recipient),
             "{}", vote
         );
+        consensus_trace::record(
+            LogEvent::Vote,
+            self.epoch_state.epoch,
+            proposal_round,
+            Some(vote.vote_data().proposed().id()),
+            Some(recipient),
+        );
 
         self.round_state.record_vote(vote.clone());
         let vote_msg = VoteMsg::new(vote, self.block_store.sync_info());
@@ -858,6 +896,13 @@ impl RoundManager {
             vote_state = vote.vote_data().proposed().executed_state_id(),
             is_timeout = vote.is_timeout(),
         );
+        consensus_trace::record(
+            LogEvent::ReceiveVote,
+            self.epoch_state.epoch,
+            round,
+            Some(vote.vote_data().proposed().id()),
+            Some(vote.author()),
+        );
 
         if !vote.is_timeout() {
             // Unlike timeout votes regular votes are sent to the leaders of the next round only.
@@ -885,6 +930,13 @@ impl RoundManager {
             .insert_vote(vote, &self.epoch_state.verifier)
         {
             VoteReceptionResult::NewQuorumCertificate(qc) => {
+                consensus_trace::record(
+                    LogEvent::AggregateQC,
+                    self.epoch_state.epoch,
+                    qc.certified_block().round(),
+                    Some(qc.certified_block().id()),
+                    None,
+                );
                 if !vote.is_timeout() {
                     observe_block(
                         qc.certified_block().timestamp_usecs(),
@@ -894,6 +946,13 @@ impl RoundManager {
                 self.new_qc_aggregated(qc, vote.author()).await
             },
             VoteReceptionResult::New2ChainTimeoutCertificate(tc) => {
+                consensus_trace::record(
+                    LogEvent::AggregateTC,
+                    self.epoch_state.epoch,
+                    tc.round(),
+                    None,
+                    None,
+                );
                 self.new_2chain_tc_aggregated(tc).await
             },
             VoteReceptionResult::EchoTimeout(_) if !self.round_state.is_vote_timeout() => {
@@ -902,6 +961,10 @@ impl RoundManager {
             VoteReceptionResult::VoteAdded(_)
             | VoteReceptionResult::EchoTimeout(_)
             | VoteReceptionResult::DuplicateVote => Ok(()),
+            VoteReceptionResult::EquivocateVote(evidence) => {
+                self.evidence_collector.collect(*evidence.clone());
+                bail!("[RoundManager] Received equivocating vote: {}", evidence)
+            },
             e => Err(anyhow::anyhow!("{:?}", e)),
         }
     }
@@ -166,6 +166,7 @@ fn create_node_for_fuzzing() -> RoundManager {
         Duration::ZERO,
         1,
         1024,
+        u64::MAX,
         10,
         PipelineBackpressureConfig::new_no_backoff(),
         ChainHealthBackoffConfig::new_no_backoff(),
@@ -249,6 +249,7 @@ impl NodeSetup {
             Duration::ZERO,
             10,
             1000,
+            u64::MAX,
             10,
             PipelineBackpressureConfig::new_no_backoff(),
             ChainHealthBackoffConfig::new_no_backoff(),
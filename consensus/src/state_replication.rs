@@ -13,6 +13,7 @@ use aptos_consensus_types::{
     block::Block,
     common::{Payload, PayloadFilter},
     executed_block::ExecutedBlock,
+    request_response::PayloadGasEstimate,
 };
 use aptos_crypto::HashValue;
 use aptos_executor_types::{Error as ExecutionError, StateComputeResult};
@@ -32,12 +33,13 @@ pub trait PayloadClient: Send + Sync {
         max_poll_time: Duration,
         max_items: u64,
         max_bytes: u64,
+        max_gas: u64,
         exclude: PayloadFilter,
         wait_callback: BoxFuture<'static, ()>,
         pending_ordering: bool,
         pending_uncommitted_blocks: usize,
         recent_max_fill_fraction: f32,
-    ) -> Result<Payload, QuorumStoreError>;
+    ) -> Result<(Payload, PayloadGasEstimate), QuorumStoreError>;
 
     fn trace_payloads(&self) {}
 }
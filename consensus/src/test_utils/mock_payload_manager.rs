@@ -8,7 +8,7 @@ use anyhow::Result;
 use aptos_consensus_types::{
     block::block_test_utils::random_payload,
     common::{Payload, PayloadFilter},
-    request_response::GetPayloadCommand,
+    request_response::{GetPayloadCommand, PayloadGasEstimate},
 };
 use aptos_types::{
     transaction::{ExecutionStatus, TransactionStatus},
@@ -56,13 +56,14 @@ impl PayloadClient for MockPayloadManager {
         _max_poll_time: Duration,
         _max_size: u64,
         _max_bytes: u64,
+        _max_gas: u64,
         _exclude: PayloadFilter,
         _wait_callback: BoxFuture<'static, ()>,
         _pending_ordering: bool,
         _pending_uncommitted_blocks: usize,
         _recent_fill_fraction: f32,
-    ) -> Result<Payload, QuorumStoreError> {
+    ) -> Result<(Payload, PayloadGasEstimate), QuorumStoreError> {
         // generate 1k txn is too slow with coverage instrumentation
-        Ok(random_payload(10))
+        Ok((random_payload(10), PayloadGasEstimate::default()))
     }
 }
@@ -10,7 +10,11 @@ use crate::{
 };
 use anyhow::Result;
 use aptos_consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
+    block::Block,
+    equivocation_evidence::{EquivocationEvidence, EquivocationEvidenceKey},
+    quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate,
+    vote::Vote,
 };
 use aptos_crypto::HashValue;
 use aptos_infallible::Mutex;
@@ -21,7 +25,10 @@ use aptos_types::{
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     on_chain_config::ValidatorSet,
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 pub struct MockSharedStorage {
     // Safety state
@@ -29,6 +36,7 @@ pub struct MockSharedStorage {
     pub qc: Mutex<HashMap<HashValue, QuorumCert>>,
     pub lis: Mutex<HashMap<u64, LedgerInfoWithSignatures>>,
     pub last_vote: Mutex<Option<Vote>>,
+    pub equivocation_evidence: Mutex<BTreeMap<EquivocationEvidenceKey, EquivocationEvidence>>,
 
     // Liveness state
     pub highest_2chain_timeout_certificate: Mutex<Option<TwoChainTimeoutCertificate>>,
@@ -42,6 +50,7 @@ impl MockSharedStorage {
             qc: Mutex::new(HashMap::new()),
             lis: Mutex::new(HashMap::new()),
             last_vote: Mutex::new(None),
+            equivocation_evidence: Mutex::new(BTreeMap::new()),
             highest_2chain_timeout_certificate: Mutex::new(None),
             validator_set,
         }
@@ -230,6 +239,32 @@ impl PersistentLivenessStorage for MockStorage {
         Ok(EpochChangeProof::new(vec![lis], false))
     }
 
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()> {
+        self.shared_storage
+            .equivocation_evidence
+            .lock()
+            .insert(evidence.key(), evidence.clone());
+        Ok(())
+    }
+
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        Ok(self
+            .shared_storage
+            .equivocation_evidence
+            .lock()
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    fn prune_equivocation_evidence(&self, epoch: u64) -> Result<()> {
+        self.shared_storage
+            .equivocation_evidence
+            .lock()
+            .retain(|key, _| key.epoch >= epoch);
+        Ok(())
+    }
+
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
@@ -298,6 +333,18 @@ impl PersistentLivenessStorage for EmptyStorage {
         Ok(EpochChangeProof::new(vec![], false))
     }
 
+    fn save_equivocation_evidence(&self, _: &EquivocationEvidence) -> Result<()> {
+        Ok(())
+    }
+
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        Ok(vec![])
+    }
+
+    fn prune_equivocation_evidence(&self, _: u64) -> Result<()> {
+        Ok(())
+    }
+
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
@@ -1,12 +1,16 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::sender_aware_shuffler::SenderAwareShuffler;
+use crate::{
+    dependency_aware_shuffler::DependencyAwareShuffler, sender_aware_shuffler::SenderAwareShuffler,
+};
 use aptos_logger::info;
 use aptos_types::{
     on_chain_config::{
         TransactionShufflerType,
-        TransactionShufflerType::{DeprecatedSenderAwareV1, NoShuffling, SenderAwareV2},
+        TransactionShufflerType::{
+            DependencyAwareV1, DeprecatedSenderAwareV1, NoShuffling, SenderAwareV2,
+        },
     },
     transaction::SignedTransaction,
 };
@@ -14,6 +18,7 @@ use std::sync::Arc;
 
 /// Interface to shuffle transactions
 pub trait TransactionShuffler: Send + Sync {
+    /// Reorders the transactions of a block
     fn shuffle(&self, txns: Vec<SignedTransaction>) -> Vec<SignedTransaction>;
 }
 
@@ -26,6 +31,7 @@ impl TransactionShuffler for NoOpShuffler {
     }
 }
 
+/// Creates the shuffler for the given on-chain shuffler type
 pub fn create_transaction_shuffler(
     shuffler_type: TransactionShufflerType,
 ) -> Arc<dyn TransactionShuffler> {
@@ -45,5 +51,12 @@ pub fn create_transaction_shuffler(
             );
             Arc::new(SenderAwareShuffler::new(confict_window_size as usize))
         },
+        DependencyAwareV1(conflict_window_size) => {
+            info!(
+                "Using dependency aware transaction shuffling with conflict window size {}",
+                conflict_window_size
+            );
+            Arc::new(DependencyAwareShuffler::new(conflict_window_size as usize))
+        },
     }
 }
@@ -200,7 +200,7 @@ fn twins_proposer_test() {
     let num_twins = 2;
 
     // Specify round leaders
-    // Will default to the first node, if no leader specified for given round
+    // Will default to the first node without a twin, if no leader specified for given round
     let mut round_proposers: HashMap<Round, usize> = HashMap::new();
     // Leaders are n0 (and implicitly twin0) for round 1..10
     for i in 1..10 {
@@ -281,7 +281,7 @@ fn twins_commit_test() {
     let num_twins = 1;
 
     // Specify round leaders
-    // Will default to the first node, if no leader specified for given round
+    // Will default to the first node without a twin, if no leader specified for given round
     let mut round_proposers: HashMap<Round, usize> = HashMap::new();
     // Leaders are n0 and twin0 for round 1..10
     for i in 1..10 {
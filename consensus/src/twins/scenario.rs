@@ -0,0 +1,170 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Declarative Twins scenarios, as described in the Twins paper
+//! (https://arxiv.org/abs/2004.10617): for each round, a partition of the nodes (twins included)
+//! and the leader of the round. A scenario generator enumerates or samples them from a bounded
+//! number of rounds, partitions and leaders.
+
+use crate::network_tests::TwinId;
+use aptos_consensus_types::common::Round;
+use itertools::Itertools;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Groups of nodes that can only talk within their group, by node index.
+pub type Partition = Vec<Vec<usize>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundConfig {
+    /// Index of the validator leading the round. A validator with a twin leads with its twin.
+    pub leader: usize,
+    pub partition: Partition,
+}
+
+/// Nodes `0..num_nodes` are the validators and node `num_nodes + i` is the twin of validator `i`,
+/// matching the order nodes are started in by `SMRNode::start_num_nodes_with_twins`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scenario {
+    pub num_nodes: usize,
+    pub num_twins: usize,
+    /// Configuration of round `i + 1`, the network isn't partitioned after the last one.
+    pub rounds: Vec<RoundConfig>,
+}
+
+impl Scenario {
+    pub fn num_rounds(&self) -> Round {
+        self.rounds.len() as Round
+    }
+
+    /// Validators without a twin, which have to behave honestly.
+    pub fn honest_validators(&self) -> impl Iterator<Item = usize> {
+        self.num_twins..self.num_nodes
+    }
+
+    /// Leaders by round, as taken by `SMRNode::start_num_nodes_with_twins`.
+    pub fn round_proposers(&self) -> HashMap<Round, usize> {
+        self.rounds
+            .iter()
+            .enumerate()
+            .map(|(idx, config)| (idx as Round + 1, config.leader))
+            .collect()
+    }
+
+    /// Partitions by round, as taken by `NetworkPlayground::split_network_round`.
+    pub fn round_partitions(&self, twin_ids: &[TwinId]) -> HashMap<Round, Vec<Vec<TwinId>>> {
+        assert_eq!(twin_ids.len(), self.num_nodes + self.num_twins);
+        self.rounds
+            .iter()
+            .enumerate()
+            .map(|(idx, config)| {
+                let partition = config
+                    .partition
+                    .iter()
+                    .map(|group| group.iter().map(|node| twin_ids[*node]).collect())
+                    .collect();
+                (idx as Round + 1, partition)
+            })
+            .collect()
+    }
+}
+
+pub struct ScenarioGenerator {
+    num_nodes: usize,
+    num_twins: usize,
+    num_rounds: usize,
+    round_configs: Vec<RoundConfig>,
+}
+
+impl ScenarioGenerator {
+    /// Considers all partitions of the nodes into at most `max_partitions` groups in which some
+    /// group holds a quorum of validators, since a round can't complete otherwise and the
+    /// scenario would say nothing about liveness. Any validator can lead any round.
+    pub fn new(
+        num_nodes: usize,
+        num_twins: usize,
+        num_rounds: usize,
+        max_partitions: usize,
+    ) -> Self {
+        assert!(num_twins <= num_nodes);
+        let quorum = num_nodes * 2 / 3 + 1;
+        let num_validators_in =
+            |group: &Vec<usize>| group.iter().map(|node| node % num_nodes).unique().count();
+        let round_configs = set_partitions(num_nodes + num_twins, max_partitions)
+            .into_iter()
+            .filter(|partition| {
+                partition
+                    .iter()
+                    .any(|group| num_validators_in(group) >= quorum)
+            })
+            .cartesian_product(0..num_nodes)
+            .map(|(partition, leader)| RoundConfig { leader, partition })
+            .collect();
+
+        Self {
+            num_nodes,
+            num_twins,
+            num_rounds,
+            round_configs,
+        }
+    }
+
+    pub fn round_configs(&self) -> &[RoundConfig] {
+        &self.round_configs
+    }
+
+    /// All scenarios, in a deterministic order. There are `round_configs().len() ^ num_rounds`
+    /// of them, so callers usually only take a slice of them.
+    pub fn enumerate(&self) -> impl Iterator<Item = Scenario> + '_ {
+        itertools::repeat_n(self.round_configs.iter(), self.num_rounds)
+            .multi_cartesian_product()
+            .map(|rounds| self.scenario(rounds.into_iter().cloned().collect()))
+    }
+
+    /// A scenario picked uniformly at random.
+    pub fn sample(&self, rng: &mut impl Rng) -> Scenario {
+        self.scenario(
+            (0..self.num_rounds)
+                .map(|_| self.round_configs[rng.gen_range(0, self.round_configs.len())].clone())
+                .collect(),
+        )
+    }
+
+    fn scenario(&self, rounds: Vec<RoundConfig>) -> Scenario {
+        Scenario {
+            num_nodes: self.num_nodes,
+            num_twins: self.num_twins,
+            rounds,
+        }
+    }
+}
+
+/// All partitions of `0..num_elements` into at most `max_parts` non-empty groups.
+pub fn set_partitions(num_elements: usize, max_parts: usize) -> Vec<Partition> {
+    fn extend(
+        element: usize,
+        num_elements: usize,
+        max_parts: usize,
+        current: &mut Partition,
+        out: &mut Vec<Partition>,
+    ) {
+        if element == num_elements {
+            out.push(current.clone());
+            return;
+        }
+        for idx in 0..current.len() {
+            current[idx].push(element);
+            extend(element + 1, num_elements, max_parts, current, out);
+            current[idx].pop();
+        }
+        if current.len() < max_parts {
+            current.push(vec![element]);
+            extend(element + 1, num_elements, max_parts, current, out);
+            current.pop();
+        }
+    }
+
+    let mut out = vec![];
+    extend(0, num_elements, max_parts, &mut vec![], &mut out);
+    out
+}
@@ -0,0 +1,161 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_tests::NetworkPlayground,
+    test_utils::consensus_runtime,
+    twins::{scenario::Scenario, twins_node::SMRNode},
+};
+use anyhow::{ensure, Result};
+use aptos_consensus_types::{block::Block, common::Round};
+use aptos_crypto::HashValue;
+use aptos_types::on_chain_config::ProposerElectionType::RoundProposer;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    time::{Duration, Instant},
+};
+
+/// Runs Twins scenarios against a network of `SMRNode`s and checks the safety and liveness of
+/// what got committed.
+pub struct ScenarioExecutor {
+    round_initial_timeout_ms: u64,
+    /// Rounds after the scenario led by honest validators, for the network to make progress in.
+    num_liveness_rounds: Round,
+    liveness_timeout: Duration,
+}
+
+impl Default for ScenarioExecutor {
+    fn default() -> Self {
+        Self {
+            round_initial_timeout_ms: 1_000,
+            num_liveness_rounds: 10,
+            liveness_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ScenarioExecutor {
+    pub fn run(&self, scenario: &Scenario) -> Result<()> {
+        let runtime = consensus_runtime();
+        let mut playground = NetworkPlayground::new(runtime.handle().clone());
+
+        let num_rounds = scenario.num_rounds();
+        let mut round_proposers = scenario.round_proposers();
+        let honest_validators: Vec<_> = scenario.honest_validators().collect();
+        ensure!(!honest_validators.is_empty(), "every validator has a twin");
+        // Past these rounds, the first validator leads, which has no twin either.
+        for (idx, round) in (num_rounds + 1..=num_rounds + self.num_liveness_rounds).enumerate() {
+            round_proposers.insert(round, honest_validators[idx % honest_validators.len()]);
+        }
+
+        let nodes = SMRNode::start_num_nodes_with_twins_and_timeout(
+            scenario.num_nodes,
+            scenario.num_twins,
+            &mut playground,
+            RoundProposer(HashMap::new()),
+            Some(round_proposers),
+            self.round_initial_timeout_ms,
+        );
+        let twin_ids: Vec<_> = nodes.iter().map(|node| node.id).collect();
+        ensure!(
+            playground.split_network_round(&scenario.round_partitions(&twin_ids)),
+            "failed to partition the network"
+        );
+        runtime.spawn(playground.start());
+
+        // Wait for all honest validators to commit past the scenario, the network is whole again.
+        let committed_past_scenario = |node: &SMRNode| {
+            node.committed_blocks
+                .lock()
+                .last()
+                .map_or(false, |block| block.round() > num_rounds)
+        };
+        let deadline = Instant::now() + self.liveness_timeout;
+        runtime.block_on(async {
+            while Instant::now() < deadline
+                && !honest_validators
+                    .iter()
+                    .all(|idx| committed_past_scenario(&nodes[*idx]))
+            {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        });
+
+        let commit_logs: Vec<_> = nodes
+            .iter()
+            .map(|node| node.committed_blocks.lock().clone())
+            .collect();
+        drop(nodes);
+        drop(runtime);
+
+        check_safety(&commit_logs)?;
+        check_liveness(&commit_logs, &honest_validators, num_rounds)
+    }
+}
+
+/// Checks that no nodes committed conflicting blocks: there's at most one block committed per
+/// round and no two committed blocks extend the same parent. Each node commits in increasing
+/// rounds.
+pub fn check_safety(commit_logs: &[Vec<Block>]) -> Result<()> {
+    let mut block_by_round: HashMap<Round, HashValue> = HashMap::new();
+    let mut child_by_parent: HashMap<HashValue, HashValue> = HashMap::new();
+    for (node, commit_log) in commit_logs.iter().enumerate() {
+        for (prev, block) in commit_log.iter().zip(commit_log.iter().skip(1)) {
+            ensure!(
+                prev.round() < block.round(),
+                "node {} committed round {} after round {}",
+                node,
+                block.round(),
+                prev.round(),
+            );
+        }
+        for block in commit_log {
+            match block_by_round.entry(block.round()) {
+                Entry::Occupied(entry) => ensure!(
+                    *entry.get() == block.id(),
+                    "node {} committed block {} at round {}, conflicting with block {}",
+                    node,
+                    block.id(),
+                    block.round(),
+                    entry.get(),
+                ),
+                Entry::Vacant(entry) => {
+                    entry.insert(block.id());
+                },
+            }
+            match child_by_parent.entry(block.parent_id()) {
+                Entry::Occupied(entry) => ensure!(
+                    *entry.get() == block.id(),
+                    "node {} committed block {} forking from block {} extended by block {}",
+                    node,
+                    block.id(),
+                    block.parent_id(),
+                    entry.get(),
+                ),
+                Entry::Vacant(entry) => {
+                    entry.insert(block.id());
+                },
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that all honest validators committed a block after `after_round`.
+pub fn check_liveness(
+    commit_logs: &[Vec<Block>],
+    honest_validators: &[usize],
+    after_round: Round,
+) -> Result<()> {
+    for idx in honest_validators {
+        let last_round = commit_logs[*idx].last().map_or(0, |block| block.round());
+        ensure!(
+            last_round > after_round,
+            "node {} committed up to round {} only, expected progress after round {}",
+            idx,
+            last_round,
+            after_round,
+        );
+    }
+    Ok(())
+}
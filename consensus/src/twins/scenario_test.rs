@@ -0,0 +1,90 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::twins::{
+    scenario::{set_partitions, ScenarioGenerator},
+    scenario_executor::{check_liveness, check_safety, ScenarioExecutor},
+};
+use aptos_consensus_types::block::{block_test_utils::certificate_for_genesis, Block};
+use rand::{rngs::StdRng, SeedableRng};
+use std::env;
+
+#[test]
+fn test_set_partitions() {
+    // Stirling numbers of the second kind: S(4, 1) + S(4, 2)
+    assert_eq!(set_partitions(4, 2).len(), 1 + 7);
+    // Bell number B(5)
+    assert_eq!(set_partitions(5, 5).len(), 52);
+    for partition in set_partitions(5, 3) {
+        let mut nodes: Vec<_> = partition.into_iter().flatten().collect();
+        nodes.sort_unstable();
+        assert_eq!(nodes, (0..5).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_scenario_generator() {
+    let generator = ScenarioGenerator::new(4, 1, 2, 2);
+    // A validator and its twin count once towards the quorum of 3.
+    for config in generator.round_configs() {
+        assert!(config.partition.iter().any(|group| {
+            let mut validators: Vec<_> = group.iter().map(|node| node % 4).collect();
+            validators.sort_unstable();
+            validators.dedup();
+            validators.len() >= 3
+        }));
+    }
+
+    let num_round_configs = generator.round_configs().len();
+    let scenarios: Vec<_> = generator.enumerate().collect();
+    assert_eq!(scenarios.len(), num_round_configs * num_round_configs);
+    assert!(scenarios
+        .iter()
+        .all(|scenario| scenario.num_rounds() == 2 && scenario.num_twins == 1));
+    assert_ne!(scenarios[0], scenarios[1]);
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let scenario = generator.sample(&mut rng);
+    assert!(scenarios.contains(&scenario));
+}
+
+#[test]
+fn test_check_safety() {
+    let genesis_qc = certificate_for_genesis();
+    let block = Block::new_nil(1, genesis_qc.clone(), vec![]);
+    let fork = Block::new_nil(2, genesis_qc, vec![]);
+
+    assert!(check_safety(&[vec![block.clone()], vec![block.clone()], vec![]]).is_ok());
+    assert!(check_safety(&[vec![block.clone()], vec![fork.clone()]]).is_err());
+    assert!(check_safety(&[vec![fork, block.clone()]]).is_err());
+
+    assert!(check_liveness(&[vec![block.clone()], vec![]], &[0], 0).is_ok());
+    assert!(check_liveness(&[vec![block], vec![]], &[0, 1], 0).is_err());
+}
+
+#[test]
+/// Runs randomly generated scenarios of 4 validators, one of them with a twin, and checks that
+/// no conflicting blocks get committed and that the network makes progress once it's whole again.
+/// The scenarios are the same on every run unless another seed is given.
+///
+/// Run more scenarios, e.g. in a nightly job:
+/// TWINS_NUM_SCENARIOS=1000 TWINS_SEED=42 cargo xtest -p consensus twins_random_scenarios_test
+fn twins_random_scenarios_test() {
+    let num_scenarios: usize = env::var("TWINS_NUM_SCENARIOS")
+        .map_or(2, |num| num.parse().expect("invalid TWINS_NUM_SCENARIOS"));
+    let seed: u64 =
+        env::var("TWINS_SEED").map_or(0, |seed| seed.parse().expect("invalid TWINS_SEED"));
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let generator = ScenarioGenerator::new(4, 1, 4, 2);
+    let executor = ScenarioExecutor::default();
+    for _ in 0..num_scenarios {
+        let scenario = generator.sample(&mut rng);
+        if let Err(e) = executor.run(&scenario) {
+            panic!(
+                "[TwinsTest] scenario failed with TWINS_SEED={}: {}\n{:?}",
+                seed, e, scenario
+            );
+        }
+    }
+}
@@ -21,8 +21,12 @@ use aptos_config::{
     generator::{self, ValidatorSwarm},
     network_id::{NetworkId, PeerNetworkId},
 };
-use aptos_consensus_types::common::{Author, Round};
+use aptos_consensus_types::{
+    block::Block,
+    common::{Author, Round},
+};
 use aptos_event_notifications::{ReconfigNotification, ReconfigNotificationListener};
+use aptos_infallible::Mutex;
 use aptos_mempool::mocks::MockSharedMempool;
 use aptos_network::{
     application::interface::{NetworkClient, NetworkServiceEvents},
@@ -57,6 +61,8 @@ pub struct SMRNode {
     pub id: TwinId,
     pub storage: Arc<MockStorage>,
     pub commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
+    /// Blocks committed by the node, in commit order.
+    pub committed_blocks: Arc<Mutex<Vec<Block>>>,
     _runtime: Runtime,
     _shared_mempool: MockSharedMempool,
     _state_sync: mpsc::UnboundedReceiver<Vec<SignedTransaction>>,
@@ -165,10 +171,18 @@ impl SMRNode {
         runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver));
 
         let (commit_cb_sender, commit_cb_receiver) = mpsc::unbounded::<LedgerInfoWithSignatures>();
+        let committed_blocks = Arc::new(Mutex::new(vec![]));
+        let committed_blocks_clone = committed_blocks.clone();
         runtime.spawn(async move {
             loop {
                 let ordered_blocks = ordered_blocks_events.next().await.unwrap();
                 let commit = ordered_blocks.ordered_proof.clone();
+                committed_blocks_clone.lock().extend(
+                    ordered_blocks
+                        .ordered_blocks
+                        .iter()
+                        .map(|executed_block| executed_block.block().clone()),
+                );
                 state_computer
                     .commit_to_storage(ordered_blocks)
                     .await
@@ -182,6 +196,7 @@ impl SMRNode {
             id: twin_id,
             _runtime: runtime,
             commit_cb_receiver,
+            committed_blocks,
             storage,
             _shared_mempool: shared_mempool,
             _state_sync: state_sync,
@@ -195,6 +210,27 @@ impl SMRNode {
         playground: &mut NetworkPlayground,
         proposer_type: ProposerElectionType,
         round_proposers_idx: Option<HashMap<Round, usize>>,
+    ) -> Vec<Self> {
+        // Disable timeout in twins test to avoid flakiness
+        Self::start_num_nodes_with_twins_and_timeout(
+            num_nodes,
+            num_twins,
+            playground,
+            proposer_type,
+            round_proposers_idx,
+            2_000_000,
+        )
+    }
+
+    /// Starts a given number of nodes and their twins, with the given initial round timeout.
+    /// The twin of node `i` is started as node `num_nodes + i`.
+    pub fn start_num_nodes_with_twins_and_timeout(
+        num_nodes: usize,
+        num_twins: usize,
+        playground: &mut NetworkPlayground,
+        proposer_type: ProposerElectionType,
+        round_proposers_idx: Option<HashMap<Round, usize>>,
+        round_initial_timeout_ms: u64,
     ) -> Vec<Self> {
         assert!(num_nodes >= num_twins);
         let ValidatorSwarm {
@@ -216,6 +252,8 @@ impl SMRNode {
         });
 
         node_configs.sort_by_key(author_from_config);
+        // Validators with a twin come last in the validator set, so that the first validator, which
+        // leads the rounds without a proposer in `RoundProposer`, has no twin.
         let validator_set = ValidatorSet::new(
             node_configs
                 .iter()
@@ -226,7 +264,7 @@ impl SMRNode {
                         sr_test_config.author,
                         sr_test_config.consensus_key.as_ref().unwrap().public_key(),
                         1,
-                        index as u64,
+                        ((index + num_nodes - num_twins) % num_nodes) as u64,
                     )
                 })
                 .collect(),
@@ -270,8 +308,7 @@ impl SMRNode {
                 .unwrap()
                 .waypoint = Some(waypoint);
             config.base.waypoint = WaypointConfig::FromConfig(waypoint);
-            // Disable timeout in twins test to avoid flakiness
-            config.consensus.round_initial_timeout_ms = 2_000_000;
+            config.consensus.round_initial_timeout_ms = round_initial_timeout_ms;
 
             let author = author_from_config(&config);
 
@@ -0,0 +1,25 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::server::utils::CONTENT_TYPE_JSON;
+use aptos_consensus_types::equivocation_evidence::{
+    EQUIVOCATION_EVIDENCE, MAX_EVIDENCE_PER_RESPONSE,
+};
+use hyper::{Body, StatusCode};
+
+/// Handles a new equivocation evidence request
+pub fn handle_equivocation_evidence_request() -> (StatusCode, Body, String) {
+    (
+        StatusCode::OK,
+        Body::from(get_equivocation_evidence_json()),
+        CONTENT_TYPE_JSON.into(),
+    )
+}
+
+/// Returns the most recent equivocation evidence collected by consensus as a JSON string
+fn get_equivocation_evidence_json() -> String {
+    match serde_json::to_string(&EQUIVOCATION_EVIDENCE.get_latest(MAX_EVIDENCE_PER_RESPONSE)) {
+        Ok(equivocation_evidence) => equivocation_evidence,
+        Err(error) => format!("Failed to get equivocation evidence! Error: {}", error),
+    }
+}
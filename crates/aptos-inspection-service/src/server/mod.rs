@@ -17,6 +17,7 @@ use std::{
 };
 
 mod configuration;
+mod equivocation_evidence;
 mod index;
 mod json_encoder;
 mod metrics;
@@ -29,6 +30,7 @@ mod tests;
 
 // The list of endpoints offered by the inspection service
 pub const CONFIGURATION_PATH: &str = "/configuration";
+pub const EQUIVOCATION_EVIDENCE_PATH: &str = "/equivocation_evidence";
 pub const FORGE_METRICS_PATH: &str = "/forge_metrics";
 pub const INDEX_PATH: &str = "/";
 pub const JSON_METRICS_PATH: &str = "/json_metrics";
@@ -102,6 +104,11 @@ async fn serve_requests(
             // Exposes the node configuration
             configuration::handle_configuration_request(&node_config)
         },
+        EQUIVOCATION_EVIDENCE_PATH => {
+            // /equivocation_evidence
+            // Exposes the evidence of equivocating validators collected by consensus
+            equivocation_evidence::handle_equivocation_evidence_request()
+        },
         FORGE_METRICS_PATH => {
             // /forge_metrics
             // Exposes forge encoded metrics
@@ -7,8 +7,8 @@ use crate::{
         peer_information::PEER_INFO_DISABLED_MESSAGE, serve_requests,
         system_information::SYS_INFO_DISABLED_MESSAGE, utils::get_all_metrics,
     },
-    CONFIGURATION_PATH, FORGE_METRICS_PATH, INDEX_PATH, JSON_METRICS_PATH, METRICS_PATH,
-    PEER_INFORMATION_PATH, SYSTEM_INFORMATION_PATH,
+    CONFIGURATION_PATH, EQUIVOCATION_EVIDENCE_PATH, FORGE_METRICS_PATH, INDEX_PATH,
+    JSON_METRICS_PATH, METRICS_PATH, PEER_INFORMATION_PATH, SYSTEM_INFORMATION_PATH,
 };
 use aptos_config::config::NodeConfig;
 use aptos_network::application::storage::PeersAndMetadata;
@@ -53,6 +53,21 @@ async fn test_inspect_configuration() {
     assert!(response_body_string.contains("expose_configuration: true"));
 }
 
+#[tokio::test]
+async fn test_inspect_equivocation_evidence() {
+    // Create a validator config
+    let config = NodeConfig::get_default_validator_config();
+
+    // Ping the equivocation evidence endpoint
+    let mut response = send_get_request_to_path(&config, EQUIVOCATION_EVIDENCE_PATH).await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+    let response_body_string = read_to_string(response_body.as_ref()).unwrap();
+
+    // Verify that no evidence has been collected
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response_body_string, "[]");
+}
+
 #[tokio::test]
 async fn test_inspect_forge_metrics() {
     // Create a VFN config
@@ -82,6 +97,7 @@ async fn test_inspect_index() {
     // Verify that the response contains all the endpoints
     assert_eq!(response.status(), StatusCode::OK);
     assert!(response_body_string.contains(CONFIGURATION_PATH));
+    assert!(response_body_string.contains(EQUIVOCATION_EVIDENCE_PATH));
     assert!(response_body_string.contains(FORGE_METRICS_PATH));
     assert!(response_body_string.contains(JSON_METRICS_PATH));
     assert!(response_body_string.contains(METRICS_PATH));
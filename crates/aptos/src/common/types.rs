@@ -38,6 +38,7 @@ use aptos_types::{
         authenticator::AuthenticationKey, EntryFunction, MultisigTransactionPayload, Script,
         SignedTransaction, TransactionArgument, TransactionPayload, TransactionStatus,
     },
+    vm_status::VMStatus,
 };
 use async_trait::async_trait;
 use clap::{Parser, ValueEnum};
@@ -1358,6 +1359,49 @@ pub struct TransactionOptions {
     /// flamegraphs that reflect the gas usage.
     #[clap(long)]
     pub(crate) profile_gas: bool,
+
+    /// If this option is set, simulate the transaction locally using the debugger and save a
+    /// JSON trace of its execution (call frames, arguments, resource accesses and events).
+    #[clap(long, conflicts_with("profile_gas"))]
+    pub(crate) trace_execution: bool,
+}
+
+/// A transaction signed for local simulation, along with the chain state it is simulated at.
+struct LocalSimulation {
+    debugger: AptosDebugger,
+    transaction: SignedTransaction,
+    hash: aptos_crypto::HashValue,
+    version: u64,
+    gas_unit_price: u64,
+    sender_address: AccountAddress,
+}
+
+impl LocalSimulation {
+    fn summary(
+        &self,
+        vm_status: VMStatus,
+        status: &TransactionStatus,
+        gas_used: u64,
+    ) -> TransactionSummary {
+        // TODO(Gas): double check if this is correct.
+        let success = match status {
+            TransactionStatus::Keep(exec_status) => Some(exec_status.is_success()),
+            TransactionStatus::Discard(_) | TransactionStatus::Retry => None,
+        };
+
+        TransactionSummary {
+            transaction_hash: self.hash.into(),
+            gas_used: Some(gas_used),
+            gas_unit_price: Some(self.gas_unit_price),
+            pending: None,
+            sender: Some(self.sender_address),
+            sequence_number: None, // The transaction is not committed so there is no new sequence number.
+            success,
+            timestamp_us: None,
+            version: Some(self.version), // The transaction is not committed so there is no new version.
+            vm_status: Some(vm_status.to_string()),
+        }
+    }
 }
 
 impl TransactionOptions {
@@ -1512,15 +1556,11 @@ impl TransactionOptions {
         Ok(response.into_inner())
     }
 
-    /// Simulate the transaction locally using the debugger, with the gas profiler enabled.
-    pub async fn profile_gas(
+    /// Signs the transaction for a local simulation at the latest version of the chain.
+    async fn prepare_local_simulation(
         &self,
         payload: TransactionPayload,
-    ) -> CliTypedResult<TransactionSummary> {
-        println!();
-        println!("Simulating transaction locally with the gas profiler...");
-        println!("This is still experimental so results may be inaccurate.");
-
+    ) -> CliTypedResult<LocalSimulation> {
         let client = self.rest_client()?;
 
         // Fetch the chain states required for the simulation
@@ -1562,9 +1602,35 @@ impl TransactionOptions {
             sender_account.sign_with_transaction_builder(transaction_factory.payload(payload));
         let hash = transaction.clone().committed_hash();
 
+        Ok(LocalSimulation {
+            debugger: AptosDebugger::rest_client(client).unwrap(),
+            transaction,
+            hash,
+            version,
+            gas_unit_price,
+            sender_address,
+        })
+    }
+
+    /// Simulate the transaction locally using the debugger, with the gas profiler enabled.
+    pub async fn profile_gas(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<TransactionSummary> {
+        println!();
+        println!("Simulating transaction locally with the gas profiler...");
+        println!("This is still experimental so results may be inaccurate.");
+
+        let simulation = self.prepare_local_simulation(payload).await?;
+        let hash = simulation.hash;
+
         // Execute the transaction using the debugger
-        let debugger = AptosDebugger::rest_client(client).unwrap();
-        let res = debugger.execute_transaction_at_version_with_gas_profiler(version, transaction);
+        let res = simulation
+            .debugger
+            .execute_transaction_at_version_with_gas_profiler(
+                simulation.version,
+                simulation.transaction.clone(),
+            );
         let (vm_status, output, gas_log) = res.map_err(|err| {
             CliError::UnexpectedError(format!("failed to simulate txn with gas profiler: {}", err))
         })?;
@@ -1658,25 +1724,43 @@ impl TransactionOptions {
         println!();
 
         // Generate the transaction summary
+        Ok(simulation.summary(vm_status, output.status(), output.gas_used()))
+    }
 
-        // TODO(Gas): double check if this is correct.
-        let success = match output.status() {
-            TransactionStatus::Keep(exec_status) => Some(exec_status.is_success()),
-            TransactionStatus::Discard(_) | TransactionStatus::Retry => None,
-        };
+    /// Simulate the transaction locally using the debugger, and save the trace of its execution.
+    pub async fn trace_execution(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<TransactionSummary> {
+        println!();
+        println!("Simulating transaction locally with the execution tracer...");
+        println!("This is still experimental so results may be inaccurate.");
 
-        Ok(TransactionSummary {
-            transaction_hash: hash.into(),
-            gas_used: Some(output.gas_used()),
-            gas_unit_price: Some(gas_unit_price),
-            pending: None,
-            sender: Some(sender_address),
-            sequence_number: None, // The transaction is not comitted so there is no new sequence number.
-            success,
-            timestamp_us: None,
-            version: Some(version), // The transaction is not comitted so there is no new version.
-            vm_status: Some(vm_status.to_string()),
-        })
+        let simulation = self.prepare_local_simulation(payload).await?;
+
+        // Execute the transaction using the debugger
+        let res = simulation
+            .debugger
+            .execute_transaction_at_version_with_tracer(
+                simulation.version,
+                simulation.transaction.clone(),
+            );
+        let (vm_status, output, trace) = res.map_err(|err| {
+            CliError::UnexpectedError(format!("failed to simulate txn with tracer: {}", err))
+        })?;
+
+        let dir = Path::new("execution-traces");
+        create_dir_if_not_exist(dir)?;
+        let trace_file_path = Path::join(dir, format!("txn-{}.json", simulation.hash));
+        let trace_json = trace
+            .to_json()
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        write_to_file(&trace_file_path, "execution trace", trace_json.as_bytes())?;
+        println!();
+        println!("Execution trace saved to {}", trace_file_path.display());
+        println!();
+
+        Ok(simulation.summary(vm_status, output.status(), output.gas_used()))
     }
 
     pub async fn estimate_gas_price(&self) -> CliTypedResult<u64> {
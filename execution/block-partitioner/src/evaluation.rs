@@ -0,0 +1,274 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Evaluates `BlockPartitionerConfig` settings by partitioning the same blocks with each of them,
+//! e.g. blocks replayed from the history of a network.
+
+use crate::{sharded_block_partitioner::ShardedBlockPartitioner, BlockPartitionerConfig};
+use aptos_types::{
+    block_executor::partitioner::PartitionedTransactions,
+    transaction::{analyzed_transaction::AnalyzedTransaction, Transaction},
+};
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// The quality of the partitioning of a single block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartitionStats {
+    pub num_txns: usize,
+    /// Number of cross-shard dependencies, i.e. edges from a transaction to the transactions of
+    /// other shards it has to wait for.
+    pub num_cross_shard_edges: usize,
+    /// Number of rounds with at least one transaction.
+    pub num_rounds: usize,
+    /// Number of transactions of each shard.
+    pub shard_loads: Vec<usize>,
+    pub latency: Duration,
+}
+
+impl PartitionStats {
+    pub fn new(partitioned_txns: &PartitionedTransactions, latency: Duration) -> Self {
+        let mut num_cross_shard_edges = 0;
+        let mut num_rounds = 0;
+        let mut shard_loads = vec![];
+        for sub_blocks in partitioned_txns.sharded_txns() {
+            shard_loads.push(sub_blocks.num_txns());
+            num_cross_shard_edges += sub_blocks
+                .iter()
+                .map(|txn| txn.cross_shard_dependencies().num_required_edges())
+                .sum::<usize>();
+            let last_used_round = sub_blocks
+                .sub_block_iter()
+                .enumerate()
+                .filter(|(_, sub_block)| !sub_block.is_empty())
+                .map(|(round, _)| round + 1)
+                .last()
+                .unwrap_or(0);
+            num_rounds = num_rounds.max(last_used_round);
+        }
+        Self {
+            num_txns: partitioned_txns.num_txns(),
+            num_cross_shard_edges,
+            num_rounds,
+            shard_loads,
+            latency,
+        }
+    }
+
+    /// The load of the busiest shard over the average load, 1.0 being perfectly balanced.
+    pub fn load_imbalance(&self) -> f64 {
+        let max_load = self.shard_loads.iter().max().copied().unwrap_or(0);
+        if max_load == 0 {
+            return 1.0;
+        }
+        let avg_load = self.num_txns as f64 / self.shard_loads.len() as f64;
+        max_load as f64 / avg_load
+    }
+}
+
+/// The stats of all the blocks partitioned with one setting.
+pub struct SettingEvaluation {
+    name: String,
+    partitioner: ShardedBlockPartitioner,
+    stats: Vec<PartitionStats>,
+}
+
+impl SettingEvaluation {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stats(&self) -> &[PartitionStats] {
+        &self.stats
+    }
+
+    fn latency_percentile(&self, percentile: usize) -> Duration {
+        let mut latencies: Vec<_> = self.stats.iter().map(|stats| stats.latency).collect();
+        latencies.sort();
+        latencies
+            .get((latencies.len() * percentile / 100).min(latencies.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for SettingEvaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let num_blocks = self.stats.len().max(1) as f64;
+        let num_txns: usize = self.stats.iter().map(|stats| stats.num_txns).sum();
+        let num_edges: usize = self
+            .stats
+            .iter()
+            .map(|stats| stats.num_cross_shard_edges)
+            .sum();
+        let avg_rounds = self
+            .stats
+            .iter()
+            .map(|stats| stats.num_rounds)
+            .sum::<usize>() as f64
+            / num_blocks;
+        let max_rounds = self
+            .stats
+            .iter()
+            .map(|stats| stats.num_rounds)
+            .max()
+            .unwrap_or(0);
+        let avg_imbalance = self
+            .stats
+            .iter()
+            .map(|stats| stats.load_imbalance())
+            .sum::<f64>()
+            / num_blocks;
+        write!(
+            f,
+            "{}: blocks {}, txns {}, cross-shard edges {} ({:.3}/txn), rounds avg {:.2} max {}, \
+             load imbalance avg {:.3}, latency p50 {:?} p99 {:?}",
+            self.name,
+            self.stats.len(),
+            num_txns,
+            num_edges,
+            num_edges as f64 / num_txns.max(1) as f64,
+            avg_rounds,
+            max_rounds,
+            avg_imbalance,
+            self.latency_percentile(50),
+            self.latency_percentile(99),
+        )
+    }
+}
+
+/// Splits a stream of transactions into blocks, and partitions the user transactions of each
+/// block with every setting.
+pub struct PartitionerEvaluator {
+    settings: Vec<SettingEvaluation>,
+    current_block: Vec<AnalyzedTransaction>,
+    // The transactions before the first block metadata transaction may be a partial block.
+    seen_block_metadata: bool,
+}
+
+impl PartitionerEvaluator {
+    pub fn new(configs: Vec<BlockPartitionerConfig>) -> Self {
+        let settings = configs
+            .into_iter()
+            .map(|config| SettingEvaluation {
+                name: config.to_string(),
+                partitioner: config.build(),
+                stats: vec![],
+            })
+            .collect();
+        Self {
+            settings,
+            current_block: vec![],
+            seen_block_metadata: false,
+        }
+    }
+
+    /// Adds the next transaction of the history. A block metadata transaction ends the current
+    /// block and starts a new one, the transactions before the first one are skipped.
+    pub fn add_transaction(&mut self, txn: Transaction) {
+        match txn {
+            Transaction::BlockMetadata(_) => {
+                self.finish_block();
+                self.seen_block_metadata = true;
+            },
+            Transaction::UserTransaction(_) => {
+                if self.seen_block_metadata {
+                    self.current_block.push(txn.into());
+                }
+            },
+            // Only user transactions are partitioned.
+            Transaction::GenesisTransaction(_) | Transaction::StateCheckpoint(_) => {},
+        }
+    }
+
+    /// Partitions the current block, if not empty, with every setting.
+    pub fn finish_block(&mut self) {
+        if self.current_block.is_empty() {
+            return;
+        }
+        let block = std::mem::take(&mut self.current_block);
+        for setting in &mut self.settings {
+            let start_time = Instant::now();
+            let partitioned_txns = setting.partitioner.partition(block.clone());
+            let latency = start_time.elapsed();
+            setting
+                .stats
+                .push(PartitionStats::new(&partitioned_txns, latency));
+        }
+    }
+
+    pub fn num_blocks(&self) -> usize {
+        self.settings
+            .first()
+            .map_or(0, |setting| setting.stats.len())
+    }
+
+    pub fn settings(&self) -> &[SettingEvaluation] {
+        &self.settings
+    }
+}
+
+impl fmt::Display for PartitionerEvaluator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for setting in &self.settings {
+            writeln!(f, "{}", setting)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        evaluation::PartitionerEvaluator,
+        test_utils::{
+            create_non_conflicting_p2p_transaction, create_signed_p2p_transaction,
+            generate_test_account,
+        },
+        BlockPartitionerConfig,
+    };
+
+    #[test]
+    fn test_evaluate_settings() {
+        let mut evaluator = PartitionerEvaluator::new(vec![
+            BlockPartitionerConfig::default().num_shards(1),
+            BlockPartitionerConfig::default()
+                .num_shards(4)
+                .max_partitioning_rounds(2)
+                .partition_last_round(true),
+        ]);
+
+        // A block of independent transactions, and a block of transactions of a single sender.
+        for _ in 0..8 {
+            evaluator
+                .current_block
+                .push(create_non_conflicting_p2p_transaction());
+        }
+        evaluator.finish_block();
+        let mut sender = generate_test_account();
+        let receivers: Vec<_> = (0..8).map(|_| generate_test_account()).collect();
+        evaluator.current_block =
+            create_signed_p2p_transaction(&mut sender, receivers.iter().collect());
+        evaluator.finish_block();
+        // Empty blocks are skipped
+        evaluator.finish_block();
+        assert_eq!(evaluator.num_blocks(), 2);
+
+        let unsharded = &evaluator.settings()[0].stats();
+        assert!(unsharded
+            .iter()
+            .all(|stats| stats.num_cross_shard_edges == 0
+                && stats.num_rounds == 1
+                && stats.shard_loads == vec![8]));
+
+        let sharded = &evaluator.settings()[1].stats();
+        assert_eq!(sharded[0].shard_loads, vec![2, 2, 2, 2]);
+        assert_eq!(sharded[0].num_cross_shard_edges, 0);
+        assert_eq!(sharded[0].load_imbalance(), 1.0);
+        // All the transactions of a sender go to the same shard.
+        assert_eq!(sharded[1].shard_loads.iter().max(), Some(&8));
+        assert_eq!(sharded[1].load_imbalance(), 4.0);
+    }
+}
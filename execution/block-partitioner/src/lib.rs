@@ -4,10 +4,13 @@
 
 use crate::sharded_block_partitioner::ShardedBlockPartitioner;
 use aptos_types::block_executor::partitioner::RoundId;
+use std::fmt;
 
+pub mod evaluation;
 pub mod sharded_block_partitioner;
 pub mod test_utils;
 
+#[derive(Clone, Debug)]
 pub struct BlockPartitionerConfig {
     num_shards: usize,
     max_partitioning_rounds: RoundId,
@@ -60,3 +63,16 @@ impl Default for BlockPartitionerConfig {
         Self::new()
     }
 }
+
+impl fmt::Display for BlockPartitionerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "shards={},max_rounds={},avoid_threshold={},partition_last_round={}",
+            self.num_shards,
+            self.max_partitioning_rounds,
+            self.cross_shard_dep_avoid_threshold,
+            self.partition_last_round
+        )
+    }
+}
@@ -4,21 +4,28 @@ use crate::pipeline::ExecuteBlockMessage;
 use aptos_block_partitioner::{
     sharded_block_partitioner::ShardedBlockPartitioner, BlockPartitionerConfig,
 };
+use aptos_consensus::transaction_shuffler::{create_transaction_shuffler, TransactionShuffler};
 use aptos_crypto::HashValue;
 use aptos_logger::info;
 use aptos_types::{
     block_executor::partitioner::{ExecutableBlock, ExecutableTransactions},
+    on_chain_config::TransactionShufflerType,
     transaction::Transaction,
 };
-use std::time::Instant;
+use std::{sync::Arc, time::Instant};
 
 pub(crate) struct BlockPartitioningStage {
     num_blocks_processed: usize,
     maybe_partitioner: Option<ShardedBlockPartitioner>,
+    transaction_shuffler: Arc<dyn TransactionShuffler>,
 }
 
 impl BlockPartitioningStage {
-    pub fn new(num_shards: usize, partition_last_round: bool) -> Self {
+    pub fn new(
+        num_shards: usize,
+        partition_last_round: bool,
+        transaction_shuffler_type: TransactionShufflerType,
+    ) -> Self {
         let maybe_partitioner = if num_shards <= 1 {
             None
         } else {
@@ -35,16 +42,18 @@ impl BlockPartitioningStage {
         Self {
             num_blocks_processed: 0,
             maybe_partitioner,
+            transaction_shuffler: create_transaction_shuffler(transaction_shuffler_type),
         }
     }
 
-    pub fn process(&mut self, mut txns: Vec<Transaction>) -> ExecuteBlockMessage {
+    pub fn process(&mut self, txns: Vec<Transaction>) -> ExecuteBlockMessage {
         let current_block_start_time = Instant::now();
         info!(
             "In iteration {}, received {:?} transactions.",
             self.num_blocks_processed,
             txns.len()
         );
+        let mut txns = self.shuffle(txns);
         let block_id = HashValue::random();
         let block: ExecutableBlock = match &self.maybe_partitioner {
             None => (block_id, txns).into(),
@@ -63,4 +72,23 @@ impl BlockPartitioningStage {
             block,
         }
     }
+
+    /// Shuffles the user transactions of the block the way consensus would, keeping the
+    /// trailing state checkpoint in place.
+    fn shuffle(&self, txns: Vec<Transaction>) -> Vec<Transaction> {
+        let mut user_txns = vec![];
+        let mut other_txns = vec![];
+        for txn in txns {
+            match txn {
+                Transaction::UserTransaction(txn) => user_txns.push(txn),
+                txn => other_txns.push(txn),
+            }
+        }
+        self.transaction_shuffler
+            .shuffle(user_txns)
+            .into_iter()
+            .map(Transaction::UserTransaction)
+            .chain(other_txns)
+            .collect()
+    }
 }
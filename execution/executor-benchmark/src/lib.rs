@@ -39,6 +39,7 @@ use aptos_transaction_generator_lib::{
     create_txn_generator_creator, TransactionGeneratorCreator, TransactionType,
     TransactionType::NonConflictingCoinTransfer,
 };
+use aptos_types::on_chain_config::TransactionShufflerType;
 use db_reliable_submitter::DbReliableTransactionSubmitter;
 use pipeline::PipelineConfig;
 use std::{
@@ -58,7 +59,7 @@ where
         AptosDB::open(
             &config.storage.dir(),
             false, /* readonly */
-            config.storage.storage_pruner_config,
+            config.storage.storage_pruner_config.clone(),
             config.storage.rocksdb_configs,
             false,
             config.storage.buffered_state_target_items,
@@ -93,6 +94,15 @@ fn create_checkpoint(
     .expect("db checkpoint creation fails.");
 }
 
+/// The throughput of a benchmark run, and the number of speculative aborts of its parallel
+/// execution.
+#[derive(Clone, Debug)]
+pub struct BenchmarkResult {
+    pub overall_tps: f64,
+    pub vm_tps: f64,
+    pub speculative_aborts: u64,
+}
+
 /// Runs the benchmark with given parameters.
 #[allow(clippy::too_many_arguments)]
 pub fn run_benchmark<V>(
@@ -111,7 +121,8 @@ pub fn run_benchmark<V>(
     use_sharded_state_merkle_db: bool,
     skip_index_and_usage: bool,
     pipeline_config: PipelineConfig,
-) where
+) -> BenchmarkResult
+where
     V: TransactionBlockExecutor + 'static,
 {
     create_checkpoint(
@@ -170,6 +181,7 @@ pub fn run_benchmark<V>(
                 num_executor_shards: 1,
                 async_partitioning: false,
                 use_global_executor: false,
+                transaction_shuffler_type: TransactionShufflerType::NoShuffling,
             },
         )
     });
@@ -239,6 +251,7 @@ pub fn run_benchmark<V>(
     let start_commit_total = APTOS_EXECUTOR_COMMIT_BLOCKS_SECONDS.get_sample_sum();
 
     let start_vm_time = APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS.get_sample_sum();
+    let start_speculative_aborts = block_executor_counters::SPECULATIVE_ABORT_COUNT.get();
     if let Some(transaction_generator_creator) = transaction_generator_creator {
         generator.run_workload(
             block_size,
@@ -279,6 +292,9 @@ pub fn run_benchmark<V>(
         }
     );
     info!("Overall TPS: {} txn/s", delta_v / elapsed);
+    let speculative_aborts =
+        block_executor_counters::SPECULATIVE_ABORT_COUNT.get() - start_speculative_aborts;
+    info!("Speculative aborts: {}", speculative_aborts);
     info!("Overall GPS: {} gas/s", delta_gas / elapsed);
     info!(
         "Overall GPT: {} gas/txn",
@@ -323,6 +339,12 @@ pub fn run_benchmark<V>(
     if verify_sequence_numbers {
         generator.verify_sequence_numbers(db.reader);
     }
+
+    BenchmarkResult {
+        overall_tps: delta_v / elapsed,
+        vm_tps: delta_v / delta_vm_time,
+        speculative_aborts,
+    }
 }
 
 fn init_workload<V>(
@@ -544,6 +566,7 @@ mod tests {
     use aptos_executor::block_executor::TransactionBlockExecutor;
     use aptos_temppath::TempPath;
     use aptos_transaction_generator_lib::args::TransactionTypeArg;
+    use aptos_types::on_chain_config::TransactionShufflerType;
     use aptos_vm::AptosVM;
 
     fn test_generic_benchmark<E>(
@@ -579,6 +602,7 @@ mod tests {
                 num_executor_shards: 1,
                 async_partitioning: false,
                 use_global_executor: false,
+                transaction_shuffler_type: TransactionShufflerType::NoShuffling,
             },
         );
 
@@ -608,6 +632,7 @@ mod tests {
                 num_executor_shards: 1,
                 async_partitioning: false,
                 use_global_executor: false,
+                transaction_shuffler_type: TransactionShufflerType::NoShuffling,
             },
         );
     }
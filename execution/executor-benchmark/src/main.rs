@@ -10,11 +10,14 @@ use aptos_executor_benchmark::{native_executor::NativeExecutor, pipeline::Pipeli
 use aptos_metrics_core::{register_int_gauge, IntGauge};
 use aptos_push_metrics::MetricsPusher;
 use aptos_transaction_generator_lib::args::TransactionTypeArg;
+use aptos_types::on_chain_config::TransactionShufflerType;
 use aptos_vm::AptosVM;
 use clap::{Parser, Subcommand};
 use once_cell::sync::Lazy;
 use std::{
+    env,
     path::PathBuf,
+    process,
     time::{SystemTime, UNIX_EPOCH},
 };
 
@@ -64,6 +67,8 @@ impl PrunerOpt {
                 enable: self.enable_state_pruner,
                 prune_window: self.state_prune_window,
                 batch_size: self.state_pruning_batch_size,
+                prune_window_secs: None,
+                target_size_bytes: None,
             },
             epoch_snapshot_pruner_config: EpochSnapshotPrunerConfig {
                 enable: self.enable_epoch_snapshot_pruner,
@@ -75,6 +80,10 @@ impl PrunerOpt {
                 prune_window: self.ledger_prune_window,
                 batch_size: self.ledger_pruning_batch_size,
                 user_pruning_window_offset: 0,
+                prune_window_secs: None,
+                target_size_bytes: None,
+                archived_addresses: vec![],
+                archived_event_types: vec![],
             },
         }
     }
@@ -98,6 +107,17 @@ pub struct PipelineOpt {
     async_partitioning: bool,
     #[clap(long)]
     use_global_executor: bool,
+    /// Pass the write-sets estimated by the block partitioner to the block executor, to delay
+    /// likely-conflicting transactions. Only has an effect with sharded execution.
+    #[clap(long)]
+    use_estimated_write_sets: bool,
+    /// Conflict window size of the transaction shuffler applied to each block before execution,
+    /// 0 disables shuffling.
+    #[clap(long, default_value_t = 0)]
+    shuffler_conflict_window_size: u32,
+    /// Use the dependency aware transaction shuffler instead of the sender aware one.
+    #[clap(long)]
+    dependency_aware_shuffling: bool,
 }
 
 impl PipelineOpt {
@@ -111,6 +131,17 @@ impl PipelineOpt {
             num_executor_shards: self.num_executor_shards,
             async_partitioning: self.async_partitioning,
             use_global_executor: self.use_global_executor,
+            transaction_shuffler_type: self.transaction_shuffler_type(),
+        }
+    }
+
+    fn transaction_shuffler_type(&self) -> TransactionShufflerType {
+        if self.shuffler_conflict_window_size == 0 {
+            TransactionShufflerType::NoShuffling
+        } else if self.dependency_aware_shuffling {
+            TransactionShufflerType::DependencyAwareV1(self.shuffler_conflict_window_size)
+        } else {
+            TransactionShufflerType::SenderAwareV2(self.shuffler_conflict_window_size)
         }
     }
 }
@@ -218,6 +249,12 @@ enum Command {
 
         #[clap(long, value_parser)]
         checkpoint_dir: PathBuf,
+
+        /// Run the benchmark twice, without and with `--use-estimated-write-sets`, each in its
+        /// own process, and print the results of both. Requires sharded execution, as only the
+        /// partitioner estimates write-sets.
+        #[clap(long)]
+        compare_estimated_write_sets: bool,
     },
     AddAccounts {
         #[clap(long, value_parser)]
@@ -266,6 +303,7 @@ where
             module_working_set_size,
             data_dir,
             checkpoint_dir,
+            compare_estimated_write_sets,
         } => {
             let transaction_mix = if transaction_type.is_empty() {
                 None
@@ -281,7 +319,36 @@ where
                 Some(mix_per_phase[0].clone())
             };
 
-            aptos_executor_benchmark::run_benchmark::<E>(
+            if compare_estimated_write_sets {
+                assert!(
+                    !opt.use_native_executor && opt.pipeline_opt.num_executor_shards > 1,
+                    "Comparing estimated write-sets requires sharded execution with the AptosVM"
+                );
+                // The option is read once, when the sharded block executor is created, so each
+                // mode runs in a child process. Both reuse the checkpoint dir, which is recreated
+                // from the data dir on every run.
+                let args: Vec<_> = env::args_os()
+                    .skip(1)
+                    .filter(|arg| {
+                        arg != "--compare-estimated-write-sets"
+                            && arg != "--use-estimated-write-sets"
+                    })
+                    .collect();
+                for use_estimated_write_sets in [false, true] {
+                    let mut command = process::Command::new(env::current_exe().unwrap());
+                    if use_estimated_write_sets {
+                        command.arg("--use-estimated-write-sets");
+                    }
+                    let status = command
+                        .args(&args)
+                        .status()
+                        .expect("Failed to start the benchmark process");
+                    assert!(status.success(), "Benchmark process failed: {}", status);
+                }
+                return;
+            }
+
+            let result = aptos_executor_benchmark::run_benchmark::<E>(
                 opt.block_size,
                 blocks,
                 transaction_mix,
@@ -298,6 +365,17 @@ where
                 opt.skip_index_and_usage,
                 opt.pipeline_opt.pipeline_config(),
             );
+            println!(
+                "{}: overall TPS {:.0} txn/s, VM TPS {:.0} txn/s, speculative aborts {}",
+                if opt.pipeline_opt.use_estimated_write_sets {
+                    "estimated_write_sets"
+                } else {
+                    "no_estimated_write_sets"
+                },
+                result.overall_tps,
+                result.vm_tps,
+                result.speculative_aborts
+            );
         },
         Command::AddAccounts {
             data_dir,
@@ -341,6 +419,7 @@ fn main() {
 
     AptosVM::set_concurrency_level_once(opt.concurrency_level());
     AptosVM::set_num_shards_once(opt.pipeline_opt.num_executor_shards);
+    AptosVM::set_use_estimated_write_sets_once(opt.pipeline_opt.use_estimated_write_sets);
     NativeExecutor::set_concurrency_level_once(opt.concurrency_level());
 
     if opt.use_native_executor {
@@ -11,6 +11,7 @@ use aptos_executor_types::BlockExecutorTrait;
 use aptos_logger::info;
 use aptos_types::{
     block_executor::partitioner::ExecutableBlock,
+    on_chain_config::TransactionShufflerType,
     transaction::{Transaction, Version},
 };
 use std::{
@@ -33,6 +34,7 @@ pub struct PipelineConfig {
     pub num_executor_shards: usize,
     pub async_partitioning: bool,
     pub use_global_executor: bool,
+    pub transaction_shuffler_type: TransactionShufflerType,
 }
 
 pub struct Pipeline<V> {
@@ -91,8 +93,11 @@ where
 
         let mut join_handles = vec![];
 
-        let mut partitioning_stage =
-            BlockPartitioningStage::new(num_partitioner_shards, !config.use_global_executor);
+        let mut partitioning_stage = BlockPartitioningStage::new(
+            num_partitioner_shards,
+            !config.use_global_executor,
+            config.transaction_shuffler_type.clone(),
+        );
 
         let mut exe = TransactionExecutor::new(
             executor_1,
@@ -1,8 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use aptos_state_view::in_memory_state_view::InMemoryStateView;
+use crate::error::Error;
 use aptos_types::{
-    block_executor::partitioner::SubBlocksForShard,
+    block_executor::partitioner::{ShardId, SubBlocksForShard},
+    state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{analyzed_transaction::AnalyzedTransaction, TransactionOutput},
     vm_status::VMStatus,
 };
@@ -12,10 +13,12 @@ mod error;
 pub mod process_executor_service;
 mod remote_cordinator_client;
 mod remote_cross_shard_client;
-mod remote_executor_client;
+pub mod remote_executor_client;
 pub mod remote_executor_service;
-#[cfg(test)]
-mod test_utils;
+mod remote_state_view;
+mod remote_state_view_service;
+#[cfg(any(test, feature = "testing"))]
+pub mod test_utils;
 #[cfg(test)]
 mod tests;
 #[cfg(test)]
@@ -40,30 +43,56 @@ pub enum RemoteExecutionRequest {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ExecuteBlockCommand {
     pub(crate) sub_blocks: SubBlocksForShard<AnalyzedTransaction>,
-    // Currently we only support the state view backed by in-memory hashmap, which means that
-    // the controller needs to pre-read all the KV pairs from the storage and pass them to the
-    // executor service. In the future, we will support other types of state view, e.g., the
-    // state view backed by remote storage service, which will allow the executor service to read the KV pairs
-    // directly from the storage.
-    pub(crate) state_view: InMemoryStateView,
+    // The state values are not sent along with the command, the shard fetches them from the
+    // coordinator's state view on demand, see `RemoteKVRequest`.
     pub(crate) concurrency_level: usize,
     pub(crate) maybe_block_gas_limit: Option<u64>,
 }
 
 impl ExecuteBlockCommand {
-    pub fn into(
-        self,
-    ) -> (
-        SubBlocksForShard<AnalyzedTransaction>,
-        InMemoryStateView,
-        usize,
-        Option<u64>,
-    ) {
+    pub fn into(self) -> (SubBlocksForShard<AnalyzedTransaction>, usize, Option<u64>) {
         (
             self.sub_blocks,
-            self.state_view,
             self.concurrency_level,
             self.maybe_block_gas_limit,
         )
     }
 }
+
+/// A request of a shard for the values of state keys of the block being executed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RemoteKVRequest {
+    pub(crate) shard_id: ShardId,
+    // Echoed in the response, so that the shard can ignore responses to a previous block.
+    pub(crate) block_seq_num: u64,
+    pub(crate) keys: Vec<StateKey>,
+}
+
+impl RemoteKVRequest {
+    pub fn new(shard_id: ShardId, block_seq_num: u64, keys: Vec<StateKey>) -> Self {
+        Self {
+            shard_id,
+            block_seq_num,
+            keys,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RemoteKVResponse {
+    pub(crate) block_seq_num: u64,
+    // A value that failed to be read fails the execution of the block on the shard.
+    pub(crate) inner: Vec<(StateKey, Result<Option<StateValue>, Error>)>,
+}
+
+impl RemoteKVResponse {
+    pub fn new(
+        block_seq_num: u64,
+        inner: Vec<(StateKey, Result<Option<StateValue>, Error>)>,
+    ) -> Self {
+        Self {
+            block_seq_num,
+            inner,
+        }
+    }
+}
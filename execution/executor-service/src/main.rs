@@ -1,26 +1,110 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::Result;
+use aptos_executor_service::process_executor_service::{
+    ExecutorServiceConfig, ProcessExecutorService,
+};
+use aptos_logger::info;
 use clap::Parser;
+use crossbeam_channel::Sender;
+use std::{net::SocketAddr, path::PathBuf, thread};
 
 #[derive(Debug, Parser)]
 struct Args {
-    #[clap(long, default_value_t = 8080)]
-    pub server_port: u16,
+    /// Path to a YAML executor service config, instead of the options below.
+    #[clap(long, value_parser, conflicts_with_all = [
+        "shard_id",
+        "num_shards",
+        "coordinator_address",
+        "remote_executor_addresses",
+        "health_check_address",
+        "use_estimated_write_sets",
+    ])]
+    pub config: Option<PathBuf>,
+
+    #[clap(long, required_unless_present = "config")]
+    pub shard_id: Option<usize>,
+
+    #[clap(long, required_unless_present = "config")]
+    pub num_shards: Option<usize>,
 
     #[clap(long, default_value_t = 8)]
     pub num_executor_threads: usize,
+
+    #[clap(long, required_unless_present = "config")]
+    pub coordinator_address: Option<SocketAddr>,
+
+    /// The addresses of all the shards, including this one, in shard id order.
+    #[clap(long, num_args = 1.., required_unless_present = "config")]
+    pub remote_executor_addresses: Vec<SocketAddr>,
+
+    /// Serve a health report over HTTP on this address.
+    #[clap(long)]
+    pub health_check_address: Option<SocketAddr>,
+
+    /// Pass the write-sets estimated by the block partitioner to the block executor.
+    #[clap(long)]
+    pub use_estimated_write_sets: bool,
+}
+
+impl Args {
+    fn into_config(self) -> Result<ExecutorServiceConfig> {
+        if let Some(path) = self.config {
+            return ExecutorServiceConfig::load(&path);
+        }
+        // Clap ensures these are set without a config file.
+        let config = ExecutorServiceConfig {
+            shard_id: self.shard_id.unwrap(),
+            num_shards: self.num_shards.unwrap(),
+            num_executor_threads: self.num_executor_threads,
+            coordinator_address: self.coordinator_address.unwrap(),
+            remote_executor_addresses: self.remote_executor_addresses,
+            health_check_address: self.health_check_address,
+            use_estimated_write_sets: self.use_estimated_write_sets,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Stops the executor service on SIGINT or SIGTERM.
+fn spawn_signal_handler(shutdown_tx: Sender<()>) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    // Registered before returning, so that the service is never killed by an early SIGTERM.
+    #[cfg(unix)]
+    let mut terminate = {
+        let _guard = runtime.enter();
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?
+    };
+    thread::Builder::new()
+        .name("executor_service_signal_handler".to_string())
+        .spawn(move || {
+            runtime.block_on(async {
+                #[cfg(unix)]
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {},
+                    _ = terminate.recv() => {},
+                }
+                #[cfg(not(unix))]
+                let _ = tokio::signal::ctrl_c().await;
+            });
+            info!("Received shutdown signal, stopping the executor service");
+            let _ = shutdown_tx.send(());
+        })?;
+    Ok(())
 }
 
-fn main() {
-    // TODO (skedia): Uncomment this once the executor service is implemented.
-    let _args = Args::parse();
+fn main() -> Result<()> {
+    let args = Args::parse();
     aptos_logger::Logger::new().init();
 
-    // let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), args.server_port);
-    // let executor_service =
-    //     ProcessExecutorService::new(server_addr, 1000, args.num_executor_threads);
-    // executor_service.run();
+    let config = args.into_config()?;
+    let executor_service = ProcessExecutorService::new(config);
+    spawn_signal_handler(executor_service.shutdown_sender())?;
+    executor_service.run()
 }
 
 #[test]
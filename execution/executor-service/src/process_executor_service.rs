@@ -1,39 +1,179 @@
 // Copyright © Aptos Foundation
 
 use crate::remote_executor_service::ExecutorService;
-use aptos_logger::info;
+use anyhow::{ensure, Context, Result};
+use aptos_logger::{info, warn};
 use aptos_types::block_executor::partitioner::ShardId;
-use std::net::SocketAddr;
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The configuration of a standalone executor service shard.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExecutorServiceConfig {
+    pub shard_id: ShardId,
+    pub num_shards: usize,
+    pub num_executor_threads: usize,
+    pub coordinator_address: SocketAddr,
+    /// The addresses of all the shards, including this one, indexed by shard id.
+    pub remote_executor_addresses: Vec<SocketAddr>,
+    /// If set, a health report is served over HTTP on this address.
+    #[serde(default)]
+    pub health_check_address: Option<SocketAddr>,
+    /// Whether the write-sets estimated by the block partitioner are passed to the block
+    /// executor, to delay likely-conflicting transactions.
+    #[serde(default)]
+    pub use_estimated_write_sets: bool,
+}
+
+impl ExecutorServiceConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read executor service config {:?}", path))?;
+        let config: Self = serde_yaml::from_str(&contents)
+            .with_context(|| format!("Failed to parse executor service config {:?}", path))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.shard_id < self.num_shards,
+            "Shard id {} is out of range for {} shards",
+            self.shard_id,
+            self.num_shards
+        );
+        ensure!(
+            self.remote_executor_addresses.len() == self.num_shards,
+            "Expected {} remote executor addresses, got {}",
+            self.num_shards,
+            self.remote_executor_addresses.len()
+        );
+        ensure!(
+            self.num_executor_threads > 0,
+            "The number of executor threads must be positive"
+        );
+        Ok(())
+    }
+}
+
+/// The health of a standalone executor service shard, as served on the health check address.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub shard_id: ShardId,
+    pub num_shards: usize,
+    pub uptime_secs: u64,
+    pub num_executed_blocks: u64,
+}
 
 /// An implementation of the remote executor service that runs in a standalone process.
 pub struct ProcessExecutorService {
-    _executor_service: ExecutorService,
+    config: ExecutorServiceConfig,
+    executor_service: ExecutorService,
 }
 
 impl ProcessExecutorService {
-    pub fn new(
-        shard_id: ShardId,
-        num_shards: usize,
-        num_threads: usize,
-        coordinator_address: SocketAddr,
-        remote_shard_addresses: Vec<SocketAddr>,
-    ) -> Self {
-        let self_address = remote_shard_addresses[shard_id];
+    pub fn new(config: ExecutorServiceConfig) -> Self {
+        let self_address = config.remote_executor_addresses[config.shard_id];
         info!(
             "Starting process remote executor service on {}",
             self_address
         );
-        let mut executor_service = ExecutorService::new(
-            shard_id,
-            num_shards,
-            num_threads,
+        let executor_service = ExecutorService::new(
+            config.shard_id,
+            config.num_shards,
+            config.num_executor_threads,
             self_address,
-            coordinator_address,
-            remote_shard_addresses,
+            config.coordinator_address,
+            config.remote_executor_addresses.clone(),
+            config.use_estimated_write_sets,
         );
-        executor_service.start();
         Self {
-            _executor_service: executor_service,
+            config,
+            executor_service,
+        }
+    }
+
+    /// Returns a sender that stops the service once the block being executed, if any, is done.
+    pub fn shutdown_sender(&self) -> Sender<()> {
+        self.executor_service.shutdown_sender()
+    }
+
+    /// Serves the health report, if configured, and executes blocks until shutdown.
+    pub fn run(mut self) -> Result<()> {
+        if let Some(address) = self.config.health_check_address {
+            let listener = TcpListener::bind(address)
+                .with_context(|| format!("Failed to bind health check address {}", address))?;
+            let shard_id = self.config.shard_id;
+            let num_shards = self.config.num_shards;
+            let num_executed_blocks = self.executor_service.num_executed_blocks();
+            let start_time = Instant::now();
+            thread::Builder::new()
+                .name(format!("executor_service-{}_health_check", shard_id))
+                .spawn(move || {
+                    for stream in listener.incoming().flatten() {
+                        let report = HealthReport {
+                            shard_id,
+                            num_shards,
+                            uptime_secs: start_time.elapsed().as_secs(),
+                            num_executed_blocks: num_executed_blocks.load(Ordering::Relaxed),
+                        };
+                        if let Err(e) = Self::serve_health_report(stream, &report) {
+                            warn!("Failed to serve health report: {:?}", e);
+                        }
+                    }
+                })
+                .expect("Failed to spawn health check thread");
+            info!("Serving executor service health on {}", address);
         }
+        self.executor_service.start();
+        info!("Executor service shard {} stopped", self.config.shard_id);
+        Ok(())
+    }
+
+    fn serve_health_report(mut stream: TcpStream, report: &HealthReport) -> Result<()> {
+        // The request itself is ignored, any request gets the health report.
+        stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request);
+        let body = serde_json::to_string(report)?;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+        Ok(())
     }
 }
+
+/// Fetches the health report of a standalone executor service served on the given address.
+pub fn get_health_report(address: SocketAddr, timeout: Duration) -> Result<HealthReport> {
+    let mut stream = TcpStream::connect_timeout(&address, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    write!(
+        stream,
+        "GET /health HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        address
+    )?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body)
+        .context("Malformed health check response")?;
+    Ok(serde_json::from_str(body)?)
+}
@@ -1,20 +1,31 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use crate::{RemoteExecutionRequest, RemoteExecutionResult};
+use crate::{
+    remote_state_view::RemoteStateViewClient, RemoteExecutionRequest, RemoteExecutionResult,
+};
 use aptos_secure_net::network_controller::{Message, NetworkController};
-use aptos_state_view::in_memory_state_view::InMemoryStateView;
 use aptos_types::{
     block_executor::partitioner::ShardId, transaction::TransactionOutput, vm_status::VMStatus,
 };
 use aptos_vm::sharded_block_executor::{
     coordinator_client::CoordinatorClient, ExecutorShardCommand,
 };
-use crossbeam_channel::{Receiver, Sender};
-use std::{net::SocketAddr, sync::Arc};
+use crossbeam_channel::{select, Receiver, Sender};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 pub struct RemoteCoordinatorClient {
     command_rx: Receiver<Message>,
     result_tx: Sender<Message>,
+    state_view_client: Arc<RemoteStateViewClient>,
+    // Stops the shard once the block being executed, if any, is done.
+    shutdown_rx: Receiver<()>,
+    num_executed_blocks: Arc<AtomicU64>,
 }
 
 impl RemoteCoordinatorClient {
@@ -22,6 +33,9 @@ impl RemoteCoordinatorClient {
         shard_id: ShardId,
         controller: &mut NetworkController,
         coordinator_address: SocketAddr,
+        state_view_client: Arc<RemoteStateViewClient>,
+        shutdown_rx: Receiver<()>,
+        num_executed_blocks: Arc<AtomicU64>,
     ) -> Self {
         let execute_command_type = format!("execute_command_{}", shard_id);
         let execute_result_type = format!("execute_result_{}", shard_id);
@@ -32,19 +46,26 @@ impl RemoteCoordinatorClient {
         Self {
             command_rx,
             result_tx,
+            state_view_client,
+            shutdown_rx,
+            num_executed_blocks,
         }
     }
 }
 
-impl CoordinatorClient<InMemoryStateView> for RemoteCoordinatorClient {
-    fn receive_execute_command(&self) -> ExecutorShardCommand<InMemoryStateView> {
-        let message = self.command_rx.recv().unwrap();
+impl CoordinatorClient<RemoteStateViewClient> for RemoteCoordinatorClient {
+    fn receive_execute_command(&self) -> ExecutorShardCommand<RemoteStateViewClient> {
+        let message = select! {
+            recv(self.command_rx) -> message => message.unwrap(),
+            recv(self.shutdown_rx) -> _ => return ExecutorShardCommand::Stop,
+        };
         let request: RemoteExecutionRequest = bcs::from_bytes(&message.data).unwrap();
         match request {
             RemoteExecutionRequest::ExecuteBlock(command) => {
-                let (sub_blocks, state_view, concurrency, gas_limit) = command.into();
+                let (sub_blocks, concurrency, gas_limit) = command.into();
+                self.state_view_client.init_for_block(&sub_blocks);
                 ExecutorShardCommand::ExecuteSubBlocks(
-                    Arc::new(state_view),
+                    self.state_view_client.clone(),
                     sub_blocks,
                     concurrency,
                     gas_limit,
@@ -56,6 +77,7 @@ impl CoordinatorClient<InMemoryStateView> for RemoteCoordinatorClient {
     fn send_execution_result(&self, result: Result<Vec<Vec<TransactionOutput>>, VMStatus>) {
         let remote_execution_result = RemoteExecutionResult::new(result);
         let output_message = bcs::to_bytes(&remote_execution_result).unwrap();
+        self.num_executed_blocks.fetch_add(1, Ordering::Relaxed);
         self.result_tx.send(Message::new(output_message)).unwrap();
     }
 }
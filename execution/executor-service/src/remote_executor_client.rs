@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use crate::{ExecuteBlockCommand, RemoteExecutionRequest, RemoteExecutionResult};
+use crate::{
+    remote_state_view_service::RemoteStateViewService, ExecuteBlockCommand, RemoteExecutionRequest,
+    RemoteExecutionResult,
+};
 use aptos_logger::trace;
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_state_view::StateView;
@@ -13,7 +16,6 @@ use aptos_vm::sharded_block_executor::executor_client::{ExecutorClient, ShardedE
 use crossbeam_channel::{Receiver, Sender};
 use std::{
     net::SocketAddr,
-    ops::Deref,
     sync::{Arc, Mutex},
 };
 
@@ -23,10 +25,10 @@ pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
     command_txs: Arc<Vec<Mutex<Sender<Message>>>>,
     // Channels to receive execution results from the executor shards.
     result_rxs: Vec<Receiver<Message>>,
-    // Thread pool used to pre-fetch the state values for the block in parallel and create an in-memory state view.
+    // Thread pool used to send the commands and to serve the state values to the shards.
     thread_pool: Arc<rayon::ThreadPool>,
-
-    phantom: std::marker::PhantomData<S>,
+    // Serves the state values of the block being executed to the shards.
+    state_view_service: Arc<RemoteStateViewService<S>>,
 }
 
 #[allow(dead_code)]
@@ -43,6 +45,12 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
                 .build()
                 .unwrap(),
         );
+        let state_view_service = Arc::new(RemoteStateViewService::new(
+            controller,
+            remote_shard_addresses.clone(),
+            thread_pool.clone(),
+        ));
+        state_view_service.start();
         let (command_txs, result_rxs) = remote_shard_addresses
             .iter()
             .enumerate()
@@ -59,7 +67,7 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             command_txs: Arc::new(command_txs),
             result_rxs,
             thread_pool,
-            phantom: std::marker::PhantomData,
+            state_view_service,
         }
     }
 
@@ -87,6 +95,7 @@ impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorC
         concurrency_level_per_shard: usize,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<ShardedExecutionOutput, VMStatus> {
+        self.state_view_service.set_state_view(state_view);
         self.thread_pool.scope(|s| {
             let (block, global_txns) = transactions.into();
             assert!(
@@ -94,15 +103,11 @@ impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorC
                 "Global transactions are not supported yet in remote execution mode."
             );
             for (shard_id, sub_blocks) in block.into_iter().enumerate() {
-                let state_view = state_view.clone();
                 let senders = self.command_txs.clone();
                 s.spawn(move |_| {
                     let execution_request =
                         RemoteExecutionRequest::ExecuteBlock(ExecuteBlockCommand {
                             sub_blocks,
-                            // TODO(skedia): Instead of serializing the entire state view, we should
-                            // serialize only the state values needed for the shard.
-                            state_view: S::as_in_memory_state_view(state_view.deref()),
                             concurrency_level: concurrency_level_per_shard,
                             maybe_block_gas_limit,
                         });
@@ -116,7 +121,9 @@ impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorC
             }
         });
 
-        let execution_results = self.get_output_from_shards()?;
+        let execution_results = self.get_output_from_shards();
+        self.state_view_service.drop_state_view();
+        let execution_results = execution_results?;
 
         Ok(ShardedExecutionOutput::new(execution_results, vec![]))
     }
@@ -3,19 +3,24 @@
 
 use crate::{
     remote_cordinator_client::RemoteCoordinatorClient,
-    remote_cross_shard_client::RemoteCrossShardClient,
+    remote_cross_shard_client::RemoteCrossShardClient, remote_state_view::RemoteStateViewClient,
 };
 use aptos_secure_net::network_controller::NetworkController;
-use aptos_state_view::in_memory_state_view::InMemoryStateView;
 use aptos_types::block_executor::partitioner::ShardId;
 use aptos_vm::sharded_block_executor::sharded_executor_service::ShardedExecutorService;
-use std::{net::SocketAddr, sync::Arc};
+use crossbeam_channel::{unbounded, Sender};
+use std::{
+    net::SocketAddr,
+    sync::{atomic::AtomicU64, Arc},
+};
 
 /// A service that provides support for remote execution. Essentially, it reads a request from
 /// the remote executor client and executes the block locally and returns the result.
 pub struct ExecutorService {
     controller: NetworkController,
-    executor_service: Arc<ShardedExecutorService<InMemoryStateView>>,
+    executor_service: Arc<ShardedExecutorService<RemoteStateViewClient>>,
+    shutdown_tx: Sender<()>,
+    num_executed_blocks: Arc<AtomicU64>,
 }
 
 impl ExecutorService {
@@ -26,13 +31,24 @@ impl ExecutorService {
         self_address: SocketAddr,
         coordinator_address: SocketAddr,
         remote_shard_addresses: Vec<SocketAddr>,
+        use_estimated_write_sets: bool,
     ) -> Self {
         let service_name = format!("executor_service-{}", shard_id);
         let mut controller = NetworkController::new(service_name, self_address, 5000);
+        let (shutdown_tx, shutdown_rx) = unbounded();
+        let num_executed_blocks = Arc::new(AtomicU64::new(0));
+        let state_view_client = Arc::new(RemoteStateViewClient::new(
+            shard_id,
+            &mut controller,
+            coordinator_address,
+        ));
         let coordinator_client = Arc::new(RemoteCoordinatorClient::new(
             shard_id,
             &mut controller,
             coordinator_address,
+            state_view_client,
+            shutdown_rx,
+            num_executed_blocks.clone(),
         ));
         let cross_shard_client = Arc::new(RemoteCrossShardClient::new(
             &mut controller,
@@ -45,14 +61,28 @@ impl ExecutorService {
             num_threads,
             coordinator_client,
             cross_shard_client,
+            use_estimated_write_sets,
         ));
 
         Self {
             controller,
             executor_service,
+            shutdown_tx,
+            num_executed_blocks,
         }
     }
 
+    /// Returns a sender that stops the service once the block being executed, if any, is done.
+    pub fn shutdown_sender(&self) -> Sender<()> {
+        self.shutdown_tx.clone()
+    }
+
+    /// The number of blocks executed, and reported to the coordinator, so far.
+    pub fn num_executed_blocks(&self) -> Arc<AtomicU64> {
+        self.num_executed_blocks.clone()
+    }
+
+    /// Starts the network and executes the blocks received from the coordinator, until shutdown.
     pub fn start(&mut self) {
         self.controller.start();
         self.executor_service.start();
@@ -0,0 +1,194 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::Error, RemoteKVRequest, RemoteKVResponse};
+use anyhow::Result;
+use aptos_logger::trace;
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_state_view::TStateView;
+use aptos_types::{
+    block_executor::partitioner::{ShardId, SubBlocksForShard},
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    transaction::analyzed_transaction::{AnalyzedTransaction, StorageLocation},
+};
+use crossbeam_channel::{Receiver, Sender};
+use dashmap::{mapref::entry::Entry, DashMap};
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::{Arc, Condvar, Mutex, RwLock},
+    thread,
+    time::Duration,
+};
+
+// The max number of keys requested from the coordinator in a single message.
+const REMOTE_STATE_KEY_BATCH_SIZE: usize = 200;
+// The max time to wait for the coordinator to respond with a state value.
+const REMOTE_STATE_VALUE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// A state value that may still be in flight from the coordinator.
+#[derive(Clone)]
+struct RemoteStateValue {
+    value: Arc<(Mutex<Option<Result<Option<StateValue>, Error>>>, Condvar)>,
+}
+
+impl RemoteStateValue {
+    fn waiting() -> Self {
+        Self {
+            value: Arc::new((Mutex::new(None), Condvar::new())),
+        }
+    }
+
+    fn set(&self, value: Result<Option<StateValue>, Error>) {
+        let (lock, cvar) = &*self.value;
+        *lock.lock().unwrap() = Some(value);
+        cvar.notify_all();
+    }
+
+    // Fails instead of blocking the execution forever if the response is lost.
+    fn get(&self) -> Result<Option<StateValue>, Error> {
+        let (lock, cvar) = &*self.value;
+        let (value, _) = cvar
+            .wait_timeout_while(lock.lock().unwrap(), REMOTE_STATE_VALUE_TIMEOUT, |value| {
+                value.is_none()
+            })
+            .unwrap();
+        value.clone().unwrap_or_else(|| {
+            Err(Error::InternalError(format!(
+                "Timed out after {:?} waiting for the state value from the coordinator",
+                REMOTE_STATE_VALUE_TIMEOUT
+            )))
+        })
+    }
+}
+
+// The state values fetched for a single block.
+struct BlockStateCache {
+    block_seq_num: u64,
+    state_values: DashMap<StateKey, RemoteStateValue>,
+}
+
+/// A state view of a shard that fetches the state values from the coordinator on demand, and
+/// caches them for the duration of the block.
+pub struct RemoteStateViewClient {
+    shard_id: ShardId,
+    kv_tx: Arc<Mutex<Sender<Message>>>,
+    cache: Arc<RwLock<Arc<BlockStateCache>>>,
+}
+
+impl RemoteStateViewClient {
+    pub fn new(
+        shard_id: ShardId,
+        controller: &mut NetworkController,
+        coordinator_address: SocketAddr,
+    ) -> Self {
+        let kv_tx = Arc::new(Mutex::new(controller.create_outbound_channel(
+            coordinator_address,
+            "remote_kv_request".to_string(),
+        )));
+        let kv_rx = controller.create_inbound_channel("remote_kv_response".to_string());
+        let cache = Arc::new(RwLock::new(Arc::new(BlockStateCache {
+            block_seq_num: 0,
+            state_values: DashMap::new(),
+        })));
+
+        let response_cache = cache.clone();
+        thread::Builder::new()
+            .name(format!("remote_state_view-{}", shard_id))
+            .spawn(move || Self::receive_responses(kv_rx, response_cache))
+            .expect("Failed to spawn remote state view thread");
+
+        Self {
+            shard_id,
+            kv_tx,
+            cache,
+        }
+    }
+
+    /// Resets the cache for a new block, and prefetches the state keys the transactions of the
+    /// block are known to read or write.
+    pub fn init_for_block(&self, sub_blocks: &SubBlocksForShard<AnalyzedTransaction>) {
+        let mut cache = self.cache.write().unwrap();
+        *cache = Arc::new(BlockStateCache {
+            block_seq_num: cache.block_seq_num + 1,
+            state_values: DashMap::new(),
+        });
+
+        let keys: HashSet<StateKey> = sub_blocks
+            .iter()
+            .flat_map(|txn| txn.txn().read_hints().iter().chain(txn.txn().write_hints()))
+            .filter_map(|location| match location {
+                StorageLocation::Specific(key) => Some(key.clone()),
+                // Wildcards are fetched on demand.
+                StorageLocation::WildCardStruct(_) | StorageLocation::WildCardTable(_) => None,
+            })
+            .collect();
+        trace!(
+            "Shard {} prefetching {} state keys for block {}",
+            self.shard_id,
+            keys.len(),
+            cache.block_seq_num
+        );
+        for key in &keys {
+            cache
+                .state_values
+                .insert(key.clone(), RemoteStateValue::waiting());
+        }
+        let keys: Vec<_> = keys.into_iter().collect();
+        for batch in keys.chunks(REMOTE_STATE_KEY_BATCH_SIZE) {
+            self.send_request(cache.block_seq_num, batch.to_vec());
+        }
+    }
+
+    fn send_request(&self, block_seq_num: u64, keys: Vec<StateKey>) {
+        let request = RemoteKVRequest::new(self.shard_id, block_seq_num, keys);
+        self.kv_tx
+            .lock()
+            .unwrap()
+            .send(Message::new(bcs::to_bytes(&request).unwrap()))
+            .unwrap();
+    }
+
+    fn receive_responses(kv_rx: Receiver<Message>, cache: Arc<RwLock<Arc<BlockStateCache>>>) {
+        while let Ok(message) = kv_rx.recv() {
+            let response: RemoteKVResponse = bcs::from_bytes(&message.to_bytes()).unwrap();
+            let cache = cache.read().unwrap().clone();
+            // Responses to the requests of a previous block are stale.
+            if response.block_seq_num != cache.block_seq_num {
+                continue;
+            }
+            for (key, value) in response.inner {
+                if let Some(state_value) = cache.state_values.get(&key) {
+                    state_value.set(value);
+                }
+            }
+        }
+    }
+}
+
+impl TStateView for RemoteStateViewClient {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        let cache = self.cache.read().unwrap().clone();
+        let state_value = match cache.state_values.entry(state_key.clone()) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                let state_value = entry.insert(RemoteStateValue::waiting()).clone();
+                self.send_request(cache.block_seq_num, vec![state_key.clone()]);
+                state_value
+            },
+        };
+        Ok(state_value.get()?)
+    }
+
+    fn is_genesis(&self) -> bool {
+        unimplemented!("is_genesis is not implemented for RemoteStateViewClient")
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        Ok(StateStorageUsage::new_untracked())
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::Error, RemoteKVRequest, RemoteKVResponse};
+use aptos_logger::{trace, warn};
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_state_view::StateView;
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex, RwLock},
+    thread,
+};
+
+/// Serves the state values requested by the shards from the state view of the block being
+/// executed by the coordinator.
+pub struct RemoteStateViewService<S: StateView + Sync + Send + 'static> {
+    kv_rx: Receiver<Message>,
+    // Channels to send the state values to the executor shards.
+    kv_txs: Arc<Vec<Mutex<Sender<Message>>>>,
+    thread_pool: Arc<rayon::ThreadPool>,
+    state_view: Arc<RwLock<Option<Arc<S>>>>,
+}
+
+impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
+    pub fn new(
+        controller: &mut NetworkController,
+        remote_shard_addresses: Vec<SocketAddr>,
+        thread_pool: Arc<rayon::ThreadPool>,
+    ) -> Self {
+        let kv_rx = controller.create_inbound_channel("remote_kv_request".to_string());
+        let kv_txs = remote_shard_addresses
+            .iter()
+            .map(|address| {
+                Mutex::new(
+                    controller.create_outbound_channel(*address, "remote_kv_response".to_string()),
+                )
+            })
+            .collect();
+        Self {
+            kv_rx,
+            kv_txs: Arc::new(kv_txs),
+            thread_pool,
+            state_view: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Serves the requests in a separate thread.
+    pub fn start(self: &Arc<Self>) {
+        let service = self.clone();
+        thread::Builder::new()
+            .name("remote_state_view_service".to_string())
+            .spawn(move || {
+                while let Ok(message) = service.kv_rx.recv() {
+                    let service = service.clone();
+                    service
+                        .thread_pool
+                        .clone()
+                        .spawn(move || service.handle_message(message));
+                }
+            })
+            .expect("Failed to spawn remote state view service thread");
+    }
+
+    pub fn set_state_view(&self, state_view: Arc<S>) {
+        *self.state_view.write().unwrap() = Some(state_view);
+    }
+
+    pub fn drop_state_view(&self) {
+        *self.state_view.write().unwrap() = None;
+    }
+
+    fn handle_message(&self, message: Message) {
+        let request: RemoteKVRequest = match bcs::from_bytes(&message.to_bytes()) {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("Dropping malformed state value request: {}", e);
+                return;
+            },
+        };
+        let kv_tx = match self.kv_txs.get(request.shard_id) {
+            Some(kv_tx) => kv_tx,
+            None => {
+                warn!(
+                    "Dropping state value request of unknown shard {}",
+                    request.shard_id
+                );
+                return;
+            },
+        };
+        let state_view = self.state_view.read().unwrap().clone();
+        trace!(
+            "Serving {} state values to shard {}",
+            request.keys.len(),
+            request.shard_id
+        );
+        let values = request
+            .keys
+            .into_iter()
+            .map(|key| {
+                // The errors are sent back for the shard to fail the block.
+                let value = match &state_view {
+                    Some(state_view) => state_view.get_state_value(&key).map_err(|e| {
+                        warn!("Failed to read the state value of {:?}: {}", key, e);
+                        Error::InternalError(format!(
+                            "Failed to read the state value of {:?}: {}",
+                            key, e
+                        ))
+                    }),
+                    // Usually a stale request, e.g. a prefetch of keys the shard did not read
+                    // before finishing the block, which the shard ignores. Answering it anyway
+                    // keeps a shard that still waits for the values from hanging.
+                    None => Err(Error::InternalError(format!(
+                        "No block is being executed to read the state value of {:?}",
+                        key
+                    ))),
+                };
+                (key, value)
+            })
+            .collect();
+        let response = RemoteKVResponse::new(request.block_seq_num, values);
+        let message = match bcs::to_bytes(&response) {
+            Ok(bytes) => Message::new(bytes),
+            Err(e) => {
+                warn!(
+                    "Failed to serialize the state values of shard {}: {}",
+                    request.shard_id, e
+                );
+                return;
+            },
+        };
+        if let Err(e) = kv_tx.lock().unwrap().send(message) {
+            warn!(
+                "Failed to send the state values to shard {}: {}",
+                request.shard_id, e
+            );
+        }
+    }
+}
@@ -2,14 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    remote_executor_client::RemoteExecutorClient, test_utils,
+    remote_executor_client::RemoteExecutorClient, remote_state_view::RemoteStateViewClient,
+    remote_state_view_service::RemoteStateViewService, test_utils,
     thread_executor_service::ThreadExecutorService,
 };
+use anyhow::{bail, Result};
 use aptos_config::utils;
 use aptos_language_e2e_tests::data_store::FakeDataStore;
 use aptos_secure_net::network_controller::NetworkController;
+use aptos_state_view::TStateView;
+use aptos_types::{
+    block_executor::partitioner::SubBlocksForShard,
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+};
 use aptos_vm::sharded_block_executor::ShardedBlockExecutor;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
 
 pub fn create_thread_remote_executor_shards(
     num_shards: usize,
@@ -63,3 +75,81 @@ fn test_sharded_block_executor_no_conflict() {
     let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
     test_utils::test_sharded_block_executor_no_conflict(sharded_block_executor);
 }
+
+/// A state view that fails to read a given key.
+struct FailingStateView {
+    data: FakeDataStore,
+    failing_key: StateKey,
+}
+
+impl TStateView for FailingStateView {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        if *state_key == self.failing_key {
+            bail!("Failed to read {:?}", state_key);
+        }
+        self.data.get_state_value(state_key)
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.data.get_usage()
+    }
+}
+
+#[test]
+fn test_remote_state_view() {
+    let coordinator_address =
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
+    let shard_address =
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
+    let mut coordinator_controller = NetworkController::new(
+        "remote-state-view-coordinator".to_string(),
+        coordinator_address,
+        5000,
+    );
+    let mut shard_controller =
+        NetworkController::new("remote-state-view-shard".to_string(), shard_address, 5000);
+    let thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap(),
+    );
+    let service = Arc::new(RemoteStateViewService::new(
+        &mut coordinator_controller,
+        vec![shard_address],
+        thread_pool,
+    ));
+    service.start();
+    let state_view = RemoteStateViewClient::new(0, &mut shard_controller, coordinator_address);
+    coordinator_controller.start();
+    shard_controller.start();
+
+    let key = StateKey::raw(b"key".to_vec());
+    let missing_key = StateKey::raw(b"missing_key".to_vec());
+    let failing_key = StateKey::raw(b"failing_key".to_vec());
+    let value = StateValue::from(b"value".to_vec());
+
+    // Between blocks, the requests are answered with an error instead of being dropped.
+    let error = state_view.get_state_value(&key).unwrap_err();
+    assert!(error.to_string().contains("No block is being executed"));
+
+    let mut data = FakeDataStore::default();
+    data.set(key.clone(), value.clone());
+    service.set_state_view(Arc::new(FailingStateView {
+        data,
+        failing_key: failing_key.clone(),
+    }));
+    state_view.init_for_block(&SubBlocksForShard::empty(0));
+    assert_eq!(state_view.get_state_value(&key).unwrap(), Some(value));
+    assert_eq!(state_view.get_state_value(&missing_key).unwrap(), None);
+    // The read errors of the coordinator are sent back to the shard.
+    let error = state_view.get_state_value(&failing_key).unwrap_err();
+    assert!(error.to_string().contains("Failed to read the state value"));
+    service.drop_state_view();
+}
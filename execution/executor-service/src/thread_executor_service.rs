@@ -27,6 +27,7 @@ impl ThreadExecutorService {
             self_address,
             coordinator_address,
             remote_shard_addresses,
+            false,
         );
 
         let thread_name = format!("ThreadExecutorService-{}", shard_id);
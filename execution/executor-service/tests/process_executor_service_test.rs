@@ -0,0 +1,119 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::utils;
+use aptos_executor_service::{
+    process_executor_service::get_health_report, remote_executor_client::RemoteExecutorClient,
+    test_utils,
+};
+use aptos_language_e2e_tests::data_store::FakeDataStore;
+use aptos_secure_net::network_controller::NetworkController;
+use aptos_vm::sharded_block_executor::ShardedBlockExecutor;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    process::{Child, Command},
+    thread,
+    time::{Duration, Instant},
+};
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn local_address() -> SocketAddr {
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port())
+}
+
+/// Kills the shard process if the test fails before it is stopped.
+struct ShardProcess {
+    child: Child,
+    health_check_address: SocketAddr,
+}
+
+impl Drop for ShardProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn spawn_shard(
+    shard_id: usize,
+    coordinator_address: SocketAddr,
+    remote_shard_addresses: &[SocketAddr],
+) -> ShardProcess {
+    let health_check_address = local_address();
+    let child = Command::new(env!("CARGO_BIN_EXE_aptos-executor-service"))
+        .arg("--shard-id")
+        .arg(shard_id.to_string())
+        .arg("--num-shards")
+        .arg(remote_shard_addresses.len().to_string())
+        .arg("--num-executor-threads")
+        .arg("2")
+        .arg("--coordinator-address")
+        .arg(coordinator_address.to_string())
+        .arg("--remote-executor-addresses")
+        .args(
+            remote_shard_addresses
+                .iter()
+                .map(|address| address.to_string()),
+        )
+        .arg("--health-check-address")
+        .arg(health_check_address.to_string())
+        .spawn()
+        .expect("Failed to spawn the executor service");
+    ShardProcess {
+        child,
+        health_check_address,
+    }
+}
+
+fn wait_until_healthy(shard: &ShardProcess) {
+    let start = Instant::now();
+    while get_health_report(shard.health_check_address, Duration::from_secs(1)).is_err() {
+        assert!(
+            start.elapsed() < STARTUP_TIMEOUT,
+            "Executor service did not become healthy"
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn test_process_executor_service_no_conflict() {
+    let num_shards = 3;
+    let coordinator_address = local_address();
+    let remote_shard_addresses: Vec<_> = (0..num_shards).map(|_| local_address()).collect();
+    let mut shards: Vec<_> = (0..num_shards)
+        .map(|shard_id| spawn_shard(shard_id, coordinator_address, &remote_shard_addresses))
+        .collect();
+    shards.iter().for_each(wait_until_healthy);
+
+    let mut controller = NetworkController::new(
+        "remote-executor-coordinator".to_string(),
+        coordinator_address,
+        5000,
+    );
+    let executor_client =
+        RemoteExecutorClient::<FakeDataStore>::new(remote_shard_addresses, &mut controller, None);
+    controller.start();
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
+    test_utils::test_sharded_block_executor_no_conflict(sharded_block_executor);
+
+    for (shard_id, shard) in shards.iter().enumerate() {
+        let report = get_health_report(shard.health_check_address, Duration::from_secs(5)).unwrap();
+        assert_eq!(report.shard_id, shard_id);
+        assert_eq!(report.num_shards, num_shards);
+        assert_eq!(report.num_executed_blocks, 1);
+    }
+
+    // Shards stop gracefully on SIGTERM.
+    #[cfg(unix)]
+    for shard in shards.iter_mut() {
+        let status = Command::new("kill")
+            .arg("-TERM")
+            .arg(shard.child.id().to_string())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert!(shard.child.wait().unwrap().success());
+    }
+}
@@ -24,6 +24,7 @@ use aptos_vm::{
         local_executor_shard::{LocalExecutorClient, LocalExecutorService},
         ShardedBlockExecutor,
     },
+    state_key_prefetch::state_keys_to_prefetch,
     AptosVM, VMExecutor,
 };
 use fail::fail_point;
@@ -34,7 +35,11 @@ use std::{ops::Deref, sync::Arc, time::Duration};
 pub static SHARDED_BLOCK_EXECUTOR: Lazy<
     Arc<Mutex<ShardedBlockExecutor<CachedStateView, LocalExecutorClient<CachedStateView>>>>,
 > = Lazy::new(|| {
-    let client = LocalExecutorService::setup_local_executor_shards(AptosVM::get_num_shards(), None);
+    let client = LocalExecutorService::setup_local_executor_shards(
+        AptosVM::get_num_shards(),
+        None,
+        AptosVM::get_use_estimated_write_sets(),
+    );
     Arc::new(Mutex::new(ShardedBlockExecutor::new(client)))
 });
 
@@ -55,6 +60,10 @@ impl ChunkOutput {
         state_view: CachedStateView,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Self> {
+        if AptosVM::get_prefetch_state_keys() {
+            Self::prefetch_state_keys(&transactions, &state_view);
+        }
+
         match transactions {
             ExecutableTransactions::Unsharded(txns) => {
                 Self::by_transaction_execution_unsharded::<V>(
@@ -69,6 +78,25 @@ impl ChunkOutput {
         }
     }
 
+    /// Warms the state view with the state the transactions are likely to read, fetched in
+    /// parallel, so that execution (sequential or parallel) does not wait on the reads one at a
+    /// time.
+    fn prefetch_state_keys(transactions: &ExecutableTransactions, state_view: &CachedStateView) {
+        let _timer = metrics::APTOS_EXECUTOR_OTHER_TIMERS_SECONDS
+            .with_label_values(&["prefetch_state_keys"])
+            .start_timer();
+        let keys = match transactions {
+            ExecutableTransactions::Unsharded(txns) => state_keys_to_prefetch(txns),
+            ExecutableTransactions::Sharded(txns) => state_keys_to_prefetch(
+                txns.sharded_txns()
+                    .iter()
+                    .flat_map(|sub_blocks| sub_blocks.iter())
+                    .map(|txn_with_deps| txn_with_deps.txn().transaction()),
+            ),
+        };
+        state_view.prefetch(&keys);
+    }
+
     fn by_transaction_execution_unsharded<V: VMExecutor>(
         transactions: Vec<Transaction>,
         state_view: CachedStateView,
@@ -17,6 +17,7 @@
 //! where a block is a length prefixed array of bytes.
 
 pub mod network_controller;
+pub mod noise;
 
 use aptos_logger::{info, trace, warn, Schema};
 use aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
@@ -136,14 +137,22 @@ pub enum Error {
     AlreadyShutdown,
     #[error("Found data that is too large to decode: {0}")]
     DataTooLarge(usize),
+    #[error("Invalid handshake: {0}")]
+    InvalidHandshake(String),
     #[error("Internal network error:")]
     NetworkError(#[from] std::io::Error),
     #[error("No active stream")]
     NoActiveStream,
+    #[error("Noise error: {0}")]
+    NoiseError(#[from] aptos_crypto::noise::NoiseError),
     #[error("Overflow error: {0}")]
     OverflowError(String),
     #[error("Remote stream cleanly closed")]
     RemoteStreamClosed,
+    #[error("Request did not complete within {0} ms: {1}")]
+    RequestTimeout(u64, String),
+    #[error("Peer is not allowed to connect: {0}")]
+    UnauthorizedPeer(String),
 }
 
 pub struct NetworkClient {
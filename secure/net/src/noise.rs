@@ -0,0 +1,597 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Noise IK authenticated and encrypted counterparts of `NetworkClient` and `NetworkServer`, for
+//! services that must only talk to known peers, e.g., a remote safety rules signer.
+//!
+//! A connection starts with the Noise IK handshake of `aptos_crypto::noise`, as in
+//! `network/src/noise`: the client knows the static public key of the server up front and the
+//! server only accepts clients whose static public key is allowlisted. The handshake payload of
+//! the client is a timestamp that must increase across its handshakes, so that recorded
+//! handshakes can't be replayed. Each message is then sent as a single block of Noise messages,
+//! as a message may not fit within the maximum size of a Noise message.
+
+use crate::{
+    increment_counter, Error, LogEvent, Method, MethodResult, NetworkMode, NetworkStream,
+    SecureNetLogSchema,
+};
+use aptos_crypto::{
+    noise::{self, NoiseConfig, NoiseError, NoiseSession, AES_GCM_TAGLEN, MAX_SIZE_NOISE_MSG},
+    x25519,
+};
+use aptos_logger::{info, warn};
+use rand::rngs::OsRng;
+use std::{
+    collections::{HashMap, HashSet},
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Size of the handshake payload of the client, a timestamp in microseconds.
+const TIMESTAMP_SIZE: usize = 8;
+/// Largest plaintext that fits in a single Noise message.
+const MAX_CHUNK_SIZE: usize = MAX_SIZE_NOISE_MSG - AES_GCM_TAGLEN;
+/// Delay between two attempts of a client to complete a request.
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An established connection, after the handshake.
+struct NoiseConnection {
+    stream: NetworkStream,
+    session: NoiseSession,
+}
+
+impl NoiseConnection {
+    fn read(&mut self) -> Result<Vec<u8>, Error> {
+        let mut data = self.stream.read()?;
+        if data.is_empty() {
+            return Err(NoiseError::MsgTooShort.into());
+        }
+
+        let mut plaintext = Vec::with_capacity(data.len());
+        for chunk in data.chunks_mut(MAX_SIZE_NOISE_MSG) {
+            plaintext.extend_from_slice(self.session.read_message_in_place(chunk)?);
+        }
+        Ok(plaintext)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        let num_chunks = std::cmp::max(1, (data.len() + MAX_CHUNK_SIZE - 1) / MAX_CHUNK_SIZE);
+        let mut ciphertext = Vec::with_capacity(data.len() + num_chunks * AES_GCM_TAGLEN);
+        // An empty message is still sent as a Noise message, authenticated by its tag.
+        for idx in 0..num_chunks {
+            let start = idx * MAX_CHUNK_SIZE;
+            let end = std::cmp::min(data.len(), start + MAX_CHUNK_SIZE);
+            let mut chunk = data[start..end].to_vec();
+            let tag = self.session.write_message_in_place(&mut chunk)?;
+            ciphertext.extend(chunk);
+            ciphertext.extend(tag);
+        }
+        self.stream.write(&ciphertext)
+    }
+}
+
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time is before the UNIX epoch")
+        .as_micros() as u64
+}
+
+pub struct NoiseNetworkClient {
+    service: String,
+    server: SocketAddr,
+    noise_config: NoiseConfig,
+    server_public_key: x25519::PublicKey,
+    connection: Option<NoiseConnection>,
+    /// Read, Write, Connect timeout in milliseconds.
+    timeout_ms: u64,
+    /// Time in milliseconds after which a request that couldn't complete fails.
+    request_timeout_ms: u64,
+}
+
+impl NoiseNetworkClient {
+    pub fn new(
+        service: String,
+        server: SocketAddr,
+        private_key: x25519::PrivateKey,
+        server_public_key: x25519::PublicKey,
+        timeout_ms: u64,
+        request_timeout_ms: u64,
+    ) -> Self {
+        Self {
+            service,
+            server,
+            noise_config: NoiseConfig::new(private_key),
+            server_public_key,
+            connection: None,
+            timeout_ms,
+            request_timeout_ms,
+        }
+    }
+
+    fn increment_counter(&self, method: Method, result: MethodResult) {
+        increment_counter(&self.service, NetworkMode::Client, method, result)
+    }
+
+    /// Sends a request and blocks until its response is received. On failure, the connection is
+    /// dropped and the request retried on a new one, until the request timeout expires.
+    pub fn request(&mut self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let deadline = Instant::now() + Duration::from_millis(self.request_timeout_ms);
+        loop {
+            let err = match self.try_request(data) {
+                Ok(response) => return Ok(response),
+                Err(err) => err,
+            };
+            self.connection = None;
+            if Instant::now() + RETRY_INTERVAL >= deadline {
+                return Err(Error::RequestTimeout(
+                    self.request_timeout_ms,
+                    err.to_string(),
+                ));
+            }
+            thread::sleep(RETRY_INTERVAL);
+        }
+    }
+
+    /// Shutdown the internal network stream
+    pub fn shutdown(&mut self) -> Result<(), Error> {
+        info!(SecureNetLogSchema::new(
+            &self.service,
+            NetworkMode::Client,
+            LogEvent::Shutdown,
+        ));
+
+        let connection = self.connection.take().ok_or(Error::NoActiveStream)?;
+        connection.stream.shutdown()?;
+        Ok(())
+    }
+
+    fn try_request(&mut self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        self.increment_counter(Method::Write, MethodResult::Query);
+        let result = self.server()?.write(data);
+        if let Err(err) = &result {
+            self.increment_counter(Method::Write, MethodResult::Failure);
+            warn!(SecureNetLogSchema::new(
+                &self.service,
+                NetworkMode::Client,
+                LogEvent::DisconnectedPeerOnWrite,
+            )
+            .error(err)
+            .remote_peer(&self.server));
+        } else {
+            self.increment_counter(Method::Write, MethodResult::Success);
+        }
+        result?;
+
+        self.increment_counter(Method::Read, MethodResult::Query);
+        let result = self.server()?.read();
+        if let Err(err) = &result {
+            self.increment_counter(Method::Read, MethodResult::Failure);
+            warn!(SecureNetLogSchema::new(
+                &self.service,
+                NetworkMode::Client,
+                LogEvent::DisconnectedPeerOnRead,
+            )
+            .error(err)
+            .remote_peer(&self.server));
+        } else {
+            self.increment_counter(Method::Read, MethodResult::Success);
+        }
+        result
+    }
+
+    fn server(&mut self) -> Result<&mut NoiseConnection, Error> {
+        if self.connection.is_none() {
+            self.increment_counter(Method::Connect, MethodResult::Query);
+            info!(SecureNetLogSchema::new(
+                &self.service,
+                NetworkMode::Client,
+                LogEvent::ConnectionAttempt,
+            )
+            .remote_peer(&self.server));
+
+            match self.connect() {
+                Ok(connection) => {
+                    self.connection = Some(connection);
+                    self.increment_counter(Method::Connect, MethodResult::Success);
+                    info!(SecureNetLogSchema::new(
+                        &self.service,
+                        NetworkMode::Client,
+                        LogEvent::ConnectionSuccessful,
+                    )
+                    .remote_peer(&self.server));
+                },
+                Err(err) => {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        &self.service,
+                        NetworkMode::Client,
+                        LogEvent::ConnectionFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&self.server));
+                    return Err(err);
+                },
+            }
+        }
+
+        self.connection.as_mut().ok_or(Error::NoActiveStream)
+    }
+
+    fn connect(&self) -> Result<NoiseConnection, Error> {
+        let timeout = Duration::from_millis(self.timeout_ms);
+        let stream = TcpStream::connect_timeout(&self.server, timeout)?;
+        stream.set_nodelay(true)?;
+        let mut stream = NetworkStream::new(stream, self.server, self.timeout_ms);
+
+        let timestamp = now_micros().to_le_bytes();
+        let mut init_message = vec![0; noise::handshake_init_msg_len(TIMESTAMP_SIZE)];
+        let handshake_state = self.noise_config.initiate_connection(
+            &mut OsRng,
+            self.service.as_bytes(),
+            self.server_public_key,
+            Some(&timestamp),
+            &mut init_message,
+        )?;
+        stream.write(&init_message)?;
+
+        let response = stream.read()?;
+        let (_, session) = self
+            .noise_config
+            .finalize_connection(handshake_state, &response)?;
+        Ok(NoiseConnection { stream, session })
+    }
+}
+
+pub struct NoiseNetworkServer {
+    service: String,
+    listener: Option<TcpListener>,
+    noise_config: NoiseConfig,
+    allowed_client_keys: HashSet<x25519::PublicKey>,
+    /// Timestamp of the last handshake of each client, to reject replayed handshakes.
+    handshake_timestamps: HashMap<x25519::PublicKey, u64>,
+    connection: Option<(NoiseConnection, SocketAddr)>,
+    /// Read, Write, Connect timeout in milliseconds.
+    timeout_ms: u64,
+}
+
+impl NoiseNetworkServer {
+    pub fn new(
+        service: String,
+        listen: SocketAddr,
+        private_key: x25519::PrivateKey,
+        allowed_client_keys: HashSet<x25519::PublicKey>,
+        timeout_ms: u64,
+    ) -> Self {
+        let listener = TcpListener::bind(listen);
+        Self {
+            service,
+            listener: Some(listener.unwrap()),
+            noise_config: NoiseConfig::new(private_key),
+            allowed_client_keys,
+            handshake_timestamps: HashMap::new(),
+            connection: None,
+            timeout_ms,
+        }
+    }
+
+    pub fn public_key(&self) -> x25519::PublicKey {
+        self.noise_config.public_key()
+    }
+
+    fn increment_counter(&self, method: Method, result: MethodResult) {
+        increment_counter(&self.service, NetworkMode::Server, method, result)
+    }
+
+    /// If there isn't already an authenticated client, it accepts one. Otherwise it blocks until
+    /// able to successfully read an entire message.
+    pub fn read(&mut self) -> Result<Vec<u8>, Error> {
+        self.increment_counter(Method::Read, MethodResult::Query);
+
+        let result = {
+            let (connection, remote) = self.client()?;
+            connection.read().map_err(|e| (*remote, e))
+        };
+
+        if let Err((remote, err)) = &result {
+            self.increment_counter(Method::Read, MethodResult::Failure);
+            warn!(SecureNetLogSchema::new(
+                &self.service,
+                NetworkMode::Server,
+                LogEvent::DisconnectedPeerOnRead,
+            )
+            .error(err)
+            .remote_peer(remote));
+
+            self.connection = None;
+        } else {
+            self.increment_counter(Method::Read, MethodResult::Success);
+        }
+
+        result.map_err(|err| err.1)
+    }
+
+    /// Shutdown the internal network stream
+    pub fn shutdown(&mut self) -> Result<(), Error> {
+        info!(SecureNetLogSchema::new(
+            &self.service,
+            NetworkMode::Server,
+            LogEvent::Shutdown,
+        ));
+
+        self.listener.take().ok_or(Error::AlreadyShutdown)?;
+        let (connection, _) = self.connection.take().ok_or(Error::NoActiveStream)?;
+        connection.stream.shutdown()?;
+        Ok(())
+    }
+
+    /// If there isn't already an authenticated client, it accepts one. Otherwise it blocks until
+    /// it is able to successfully send an entire message.
+    pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.increment_counter(Method::Write, MethodResult::Query);
+
+        let result = {
+            let (connection, remote) = self.client()?;
+            connection.write(data).map_err(|e| (*remote, e))
+        };
+
+        if let Err((remote, err)) = &result {
+            self.increment_counter(Method::Write, MethodResult::Failure);
+            warn!(SecureNetLogSchema::new(
+                &self.service,
+                NetworkMode::Server,
+                LogEvent::DisconnectedPeerOnWrite,
+            )
+            .error(err)
+            .remote_peer(remote));
+
+            self.connection = None;
+        } else {
+            self.increment_counter(Method::Write, MethodResult::Success);
+        }
+
+        result.map_err(|err| err.1)
+    }
+
+    fn client(&mut self) -> Result<&mut (NoiseConnection, SocketAddr), Error> {
+        if self.connection.is_none() {
+            self.increment_counter(Method::Connect, MethodResult::Query);
+            info!(SecureNetLogSchema::new(
+                &self.service,
+                NetworkMode::Server,
+                LogEvent::ConnectionAttempt,
+            ));
+
+            let listener = self.listener.as_mut().ok_or(Error::AlreadyShutdown)?;
+            let (stream, remote) = match listener.accept() {
+                Ok(ok) => ok,
+                Err(err) => {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    let err = err.into();
+                    warn!(SecureNetLogSchema::new(
+                        &self.service,
+                        NetworkMode::Server,
+                        LogEvent::ConnectionFailed,
+                    )
+                    .error(&err));
+                    return Err(err);
+                },
+            };
+
+            stream.set_nodelay(true)?;
+            let stream = NetworkStream::new(stream, remote, self.timeout_ms);
+            let connection = match self.accept(stream) {
+                Ok(connection) => connection,
+                Err(err) => {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        &self.service,
+                        NetworkMode::Server,
+                        LogEvent::ConnectionFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&remote));
+                    return Err(err);
+                },
+            };
+
+            self.increment_counter(Method::Connect, MethodResult::Success);
+            info!(SecureNetLogSchema::new(
+                &self.service,
+                NetworkMode::Server,
+                LogEvent::ConnectionSuccessful,
+            )
+            .remote_peer(&remote));
+            self.connection = Some((connection, remote));
+        }
+
+        self.connection.as_mut().ok_or(Error::NoActiveStream)
+    }
+
+    /// Runs the responder side of the handshake on a newly accepted stream.
+    fn accept(&mut self, mut stream: NetworkStream) -> Result<NoiseConnection, Error> {
+        let init_message = stream.read()?;
+        let (client_key, handshake_state, payload) = self
+            .noise_config
+            .parse_client_init_message(self.service.as_bytes(), &init_message)?;
+
+        if !self.allowed_client_keys.contains(&client_key) {
+            return Err(Error::UnauthorizedPeer(client_key.to_string()));
+        }
+        let timestamp: [u8; TIMESTAMP_SIZE] = payload.as_slice().try_into().map_err(|_| {
+            Error::InvalidHandshake(format!("unexpected payload size {}", payload.len()))
+        })?;
+        let timestamp = u64::from_le_bytes(timestamp);
+        if let Some(last_timestamp) = self.handshake_timestamps.get(&client_key) {
+            if timestamp <= *last_timestamp {
+                return Err(Error::InvalidHandshake(format!(
+                    "replayed handshake of {} with timestamp {}",
+                    client_key, timestamp
+                )));
+            }
+        }
+
+        let mut response = vec![0; noise::handshake_resp_msg_len(0)];
+        let session = self.noise_config.respond_to_client(
+            &mut OsRng,
+            handshake_state,
+            None,
+            &mut response,
+        )?;
+        stream.write(&response)?;
+        self.handshake_timestamps.insert(client_key, timestamp);
+        Ok(NoiseConnection { stream, session })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_config::utils;
+    use aptos_crypto::Uniform;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    /// Read, Write, Connect timeout in milliseconds.
+    const TIMEOUT: u64 = 5_000;
+    /// Request timeout in milliseconds.
+    const REQUEST_TIMEOUT: u64 = 1_000;
+
+    fn keys(seed: u8) -> (x25519::PrivateKey, x25519::PublicKey) {
+        let private_key = x25519::PrivateKey::generate(&mut StdRng::from_seed([seed; 32]));
+        let public_key = private_key.public_key();
+        (private_key, public_key)
+    }
+
+    /// Starts a server echoing every request back to its client.
+    fn start_echo_server(allowed_client_keys: HashSet<x25519::PublicKey>) -> SocketAddr {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let (server_key, _) = keys(0);
+        let mut server = NoiseNetworkServer::new(
+            "test".to_string(),
+            server_addr,
+            server_key,
+            allowed_client_keys,
+            TIMEOUT,
+        );
+        thread::spawn(move || loop {
+            if let Ok(request) = server.read() {
+                let _ = server.write(&request);
+            }
+        });
+        server_addr
+    }
+
+    #[test]
+    fn test_request() {
+        let (client_key, client_public_key) = keys(1);
+        let server_addr = start_echo_server(vec![client_public_key].into_iter().collect());
+        let mut client = NoiseNetworkClient::new(
+            "test".to_string(),
+            server_addr,
+            client_key,
+            keys(0).1,
+            TIMEOUT,
+            REQUEST_TIMEOUT,
+        );
+
+        for data in [
+            vec![],
+            vec![0, 1, 2, 3],
+            vec![4; MAX_CHUNK_SIZE],
+            vec![5; 3 * MAX_SIZE_NOISE_MSG + 1],
+        ] {
+            assert_eq!(client.request(&data).unwrap(), data);
+        }
+
+        // A new connection resumes operations.
+        client.shutdown().unwrap();
+        assert_eq!(client.request(&[6, 7]).unwrap(), vec![6, 7]);
+    }
+
+    #[test]
+    fn test_unauthorized_client() {
+        let (_, client_public_key) = keys(1);
+        let server_addr = start_echo_server(vec![client_public_key].into_iter().collect());
+
+        let (other_key, _) = keys(2);
+        let mut client = NoiseNetworkClient::new(
+            "test".to_string(),
+            server_addr,
+            other_key,
+            keys(0).1,
+            TIMEOUT,
+            REQUEST_TIMEOUT,
+        );
+        assert!(matches!(
+            client.request(&[0, 1, 2, 3]),
+            Err(Error::RequestTimeout(REQUEST_TIMEOUT, _))
+        ));
+    }
+
+    #[test]
+    fn test_unexpected_server_key() {
+        let (client_key, client_public_key) = keys(1);
+        let server_addr = start_echo_server(vec![client_public_key].into_iter().collect());
+
+        let mut client = NoiseNetworkClient::new(
+            "test".to_string(),
+            server_addr,
+            client_key,
+            keys(2).1,
+            TIMEOUT,
+            REQUEST_TIMEOUT,
+        );
+        assert!(matches!(
+            client.request(&[0, 1, 2, 3]),
+            Err(Error::RequestTimeout(REQUEST_TIMEOUT, _))
+        ));
+    }
+
+    #[test]
+    fn test_replayed_handshake() {
+        let (client_key, client_public_key) = keys(1);
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let (server_key, server_public_key) = keys(0);
+        let mut server = NoiseNetworkServer::new(
+            "test".to_string(),
+            server_addr,
+            server_key,
+            vec![client_public_key].into_iter().collect(),
+            TIMEOUT,
+        );
+
+        // Record the handshake of a client and play it again on a new connection.
+        let noise_config = NoiseConfig::new(client_key);
+        let mut init_message = vec![0; noise::handshake_init_msg_len(TIMESTAMP_SIZE)];
+        noise_config
+            .initiate_connection(
+                &mut OsRng,
+                b"test",
+                server_public_key,
+                Some(&now_micros().to_le_bytes()),
+                &mut init_message,
+            )
+            .unwrap();
+        let replay = |init_message: Vec<u8>| {
+            thread::spawn(move || {
+                let stream = TcpStream::connect(server_addr).unwrap();
+                let mut stream = NetworkStream::new(stream, server_addr, TIMEOUT);
+                stream.write(&init_message).unwrap();
+                stream.read()
+            })
+        };
+
+        let handle = replay(init_message.clone());
+        // The client hangs up once it gets the response of the server.
+        assert!(server.read().is_err());
+        assert!(handle.join().unwrap().is_ok());
+
+        let handle = replay(init_message);
+        assert!(matches!(server.read(), Err(Error::InvalidHandshake(_))));
+        assert!(handle.join().unwrap().is_err());
+    }
+}
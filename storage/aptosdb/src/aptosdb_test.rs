@@ -91,10 +91,13 @@ fn test_pruner_config() {
     for enable in [false, true] {
         let state_merkle_pruner = StateMerklePrunerManager::<StaleNodeIndexSchema>::new(
             Arc::clone(&aptos_db.state_merkle_db),
+            Arc::clone(&aptos_db.ledger_db),
             StateMerklePrunerConfig {
                 enable,
                 prune_window: 20,
                 batch_size: 1,
+                prune_window_secs: None,
+                target_size_bytes: None,
             },
         );
         assert_eq!(state_merkle_pruner.is_pruner_enabled(), enable);
@@ -106,6 +109,10 @@ fn test_pruner_config() {
                 prune_window: 100,
                 batch_size: 1,
                 user_pruning_window_offset: 0,
+                prune_window_secs: None,
+                target_size_bytes: None,
+                archived_addresses: vec![],
+                archived_event_types: vec![],
             });
         assert_eq!(ledger_pruner.is_pruner_enabled(), enable);
         assert_eq!(ledger_pruner.get_prune_window(), 100);
@@ -187,11 +194,17 @@ pub fn test_state_merkle_pruning_impl(
                 prune_window: 10,
                 batch_size: 1,
                 user_pruning_window_offset: 0,
+                prune_window_secs: None,
+                target_size_bytes: None,
+                archived_addresses: vec![],
+                archived_event_types: vec![],
             },
             state_merkle_pruner_config: StateMerklePrunerConfig {
                 enable: true,
                 prune_window: 5,
                 batch_size: 1,
+                prune_window_secs: None,
+                target_size_bytes: None,
             },
             epoch_snapshot_pruner_config: EpochSnapshotPrunerConfig {
                 enable: true,
@@ -0,0 +1,355 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a background thread that keeps verifying randomly sampled versions in
+//! the DB while the node is running, so that corruptions are discovered without having to stop
+//! the node and run the db-debugger.
+
+use crate::{
+    event_store::EventStore,
+    ledger_store::LedgerStore,
+    metrics::{CONSISTENCY_CHECKS, CONSISTENCY_CHECK_FAILURES, OTHER_TIMERS_SECONDS},
+    pruner::{LedgerPrunerManager, PrunerManager},
+    schema::state_value::StateValueSchema,
+    state_store::StateDb,
+    transaction_store::TransactionStore,
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_config::config::ConsistencyCheckerConfig;
+use aptos_crypto::hash::{CryptoHash, EventAccumulatorHasher};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    ledger_info::LedgerInfo, proof::accumulator::InMemoryAccumulator,
+    state_store::state_value::StateValue, transaction::Version,
+};
+use rand::Rng;
+use std::{
+    sync::{mpsc, Arc},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug)]
+pub(crate) struct ConsistencyChecker {
+    sender: Mutex<mpsc::Sender<()>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl ConsistencyChecker {
+    pub fn new(version_checker: VersionChecker, config: ConsistencyCheckerConfig) -> Self {
+        let (send, recv) = mpsc::channel();
+        let join_handle = Some(
+            thread::Builder::new()
+                .name("db_consistency_checker".into())
+                .spawn(move || loop {
+                    if let Err(e) = version_checker.run_round(config.versions_per_round) {
+                        warn!(
+                            error = ?e,
+                            "DB consistency check round failed."
+                        );
+                    }
+
+                    match recv.recv_timeout(Duration::from_millis(config.round_interval_ms)) {
+                        Ok(_) => break,
+                        Err(mpsc::RecvTimeoutError::Timeout) => (),
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                })
+                .expect("Failed to spawn DB consistency checker thread."),
+        );
+        Self {
+            sender: Mutex::new(send),
+            join_handle,
+        }
+    }
+}
+
+impl Drop for ConsistencyChecker {
+    fn drop(&mut self) {
+        // Notify the checker thread to exit
+        self.sender.lock().send(()).unwrap();
+        self.join_handle
+            .take()
+            .expect("DB consistency checker thread must exist.")
+            .join()
+            .expect("DB consistency checker thread should join peacefully.");
+    }
+}
+
+/// Runs the individual checks on a version. Data below the min readable version of the
+/// corresponding pruner is not checked.
+pub(crate) struct VersionChecker {
+    ledger_store: LedgerStore,
+    transaction_store: TransactionStore,
+    event_store: EventStore,
+    state_db: Arc<StateDb>,
+    ledger_pruner: Arc<LedgerPrunerManager>,
+    skip_index_and_usage: bool,
+}
+
+impl VersionChecker {
+    pub fn new(
+        state_db: Arc<StateDb>,
+        ledger_pruner: Arc<LedgerPrunerManager>,
+        skip_index_and_usage: bool,
+    ) -> Self {
+        let ledger_db = &state_db.ledger_db;
+        Self {
+            ledger_store: LedgerStore::new(Arc::clone(ledger_db)),
+            transaction_store: TransactionStore::new(Arc::clone(ledger_db)),
+            event_store: EventStore::new(ledger_db.event_db_arc()),
+            state_db,
+            ledger_pruner,
+            skip_index_and_usage,
+        }
+    }
+
+    fn run_round(&self, num_versions: usize) -> Result<()> {
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["consistency_check_round"])
+            .start_timer();
+
+        let ledger_info_with_sigs = match self.ledger_store.get_latest_ledger_info_option() {
+            Some(ledger_info_with_sigs) => ledger_info_with_sigs,
+            None => return Ok(()),
+        };
+        let ledger_info = ledger_info_with_sigs.ledger_info();
+        let latest_version = ledger_info.version();
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..num_versions {
+            let ledger_min_readable_version = self.ledger_min_readable_version();
+            if ledger_min_readable_version <= latest_version {
+                let version = rng.gen_range(ledger_min_readable_version..=latest_version);
+                self.run_check(
+                    "transaction_accumulator",
+                    version,
+                    || self.ledger_min_readable_version(),
+                    || self.check_transaction_accumulator(version, ledger_info),
+                );
+                self.run_check(
+                    "event_accumulator",
+                    version,
+                    || self.ledger_min_readable_version(),
+                    || self.check_event_accumulator(version),
+                );
+            }
+
+            let state_min_readable_version = self.state_min_readable_version();
+            if state_min_readable_version <= latest_version {
+                let version = rng.gen_range(state_min_readable_version..=latest_version);
+                self.run_check(
+                    "write_set",
+                    version,
+                    || self.state_min_readable_version(),
+                    || self.check_write_set(version),
+                );
+                self.run_check(
+                    "state_merkle",
+                    version,
+                    || self.state_min_readable_version(),
+                    || self.check_state_merkle(version),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run_check(
+        &self,
+        check: &'static str,
+        version: Version,
+        min_readable_version: impl FnOnce() -> Version,
+        f: impl FnOnce() -> Result<()>,
+    ) {
+        CONSISTENCY_CHECKS.with_label_values(&[check]).inc();
+        if let Err(err) = f() {
+            // The pruner can delete the data while it's being checked, which is not a corruption.
+            if min_readable_version() > version {
+                return;
+            }
+            CONSISTENCY_CHECK_FAILURES.with_label_values(&[check]).inc();
+            error!(
+                check = check,
+                version = version,
+                error = ?err,
+                "DB inconsistency found."
+            );
+        }
+    }
+
+    fn ledger_min_readable_version(&self) -> Version {
+        self.ledger_pruner.get_min_readable_version()
+    }
+
+    /// Checking the state involves the ledger, the state KV and the state merkle DBs.
+    fn state_min_readable_version(&self) -> Version {
+        let state_merkle_pruner = &self.state_db.state_merkle_pruner;
+        // The min readable version of the state merkle pruner is only updated after a batch is
+        // pruned, so the ones within the window of the latest version are the safe ones to read.
+        let state_merkle_min_readable_version = if state_merkle_pruner.is_pruner_enabled() {
+            let latest_version = self
+                .ledger_store
+                .get_latest_ledger_info_option()
+                .map_or(0, |li| li.ledger_info().version());
+            std::cmp::max(
+                state_merkle_pruner.get_min_readable_version(),
+                latest_version.saturating_sub(state_merkle_pruner.get_prune_window()),
+            )
+        } else {
+            state_merkle_pruner.get_min_readable_version()
+        };
+
+        [
+            self.ledger_min_readable_version(),
+            self.state_db.state_kv_pruner.get_min_readable_version(),
+            state_merkle_min_readable_version,
+        ]
+        .into_iter()
+        .max()
+        .unwrap()
+    }
+
+    /// Verifies the TransactionInfo at `version` against the root of the transaction accumulator
+    /// in the latest LedgerInfo.
+    fn check_transaction_accumulator(
+        &self,
+        version: Version,
+        ledger_info: &LedgerInfo,
+    ) -> Result<()> {
+        self.ledger_store
+            .get_transaction_info_with_proof(version, ledger_info.version())?
+            .verify(ledger_info, version)
+    }
+
+    /// Verifies the events at `version` against the event root hash in the TransactionInfo, as
+    /// well as against the persisted event accumulator.
+    fn check_event_accumulator(&self, version: Version) -> Result<()> {
+        let txn_info = self.ledger_store.get_transaction_info(version)?;
+        let events = self.event_store.get_events_by_version(version)?;
+        let event_hashes: Vec<_> = events.iter().map(CryptoHash::hash).collect();
+        let root_hash =
+            InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes).root_hash();
+        ensure!(
+            root_hash == txn_info.event_root_hash(),
+            "Event root hash mismatch at version {}, calculated: {}, in TransactionInfo: {}",
+            version,
+            root_hash,
+            txn_info.event_root_hash(),
+        );
+
+        // The event accumulator is not persisted together with the indices.
+        if !self.skip_index_and_usage && !events.is_empty() {
+            let persisted_root_hash = self
+                .event_store
+                .get_event_root_hash(version, events.len() as u64)?;
+            ensure!(
+                persisted_root_hash == root_hash,
+                "Persisted event accumulator mismatch at version {}, persisted: {}, expected: {}",
+                version,
+                persisted_root_hash,
+                root_hash,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the write set at `version` against the TransactionInfo and the state KV entries
+    /// written at the same version.
+    fn check_write_set(&self, version: Version) -> Result<()> {
+        let txn_info = self.ledger_store.get_transaction_info(version)?;
+        let write_set = self.transaction_store.get_write_set(version)?;
+        let write_set_hash = CryptoHash::hash(&write_set);
+        ensure!(
+            write_set_hash == txn_info.state_change_hash(),
+            "Write set hash mismatch at version {}, calculated: {}, in TransactionInfo: {}",
+            version,
+            write_set_hash,
+            txn_info.state_change_hash(),
+        );
+
+        for (state_key, write_op) in write_set.iter() {
+            let value = self
+                .state_db
+                .state_kv_db
+                .db_shard(state_key.get_shard_id())
+                .get::<StateValueSchema>(&(state_key.clone(), version))?
+                .ok_or_else(|| {
+                    format_err!(
+                        "State KV entry missing for key {:?} at version {}",
+                        state_key,
+                        version
+                    )
+                })?;
+            ensure!(
+                value.as_ref().map(StateValue::bytes) == write_op.bytes(),
+                "State KV entry for key {:?} at version {} doesn't match the write set.",
+                state_key,
+                version,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the state tree leaves of the keys written at `version`, in the latest persisted
+    /// snapshot at or before it, against the state KV entries and the state checkpoint hash in
+    /// the TransactionInfo of the snapshot version.
+    fn check_state_merkle(&self, version: Version) -> Result<()> {
+        let snapshot_version = match self
+            .state_db
+            .state_merkle_db
+            .get_state_snapshot_version_before(version + 1)?
+        {
+            Some(snapshot_version) => snapshot_version,
+            None => return Ok(()),
+        };
+        if snapshot_version > version || snapshot_version < self.state_min_readable_version() {
+            return Ok(());
+        }
+
+        let root_hash = self
+            .state_db
+            .state_merkle_db
+            .get_root_hash(snapshot_version)?;
+        let txn_info = self.ledger_store.get_transaction_info(snapshot_version)?;
+        ensure!(
+            txn_info.state_checkpoint_hash() == Some(root_hash),
+            "State tree root hash mismatch at version {}, in tree: {}, in TransactionInfo: {:?}",
+            snapshot_version,
+            root_hash,
+            txn_info.state_checkpoint_hash(),
+        );
+
+        for (state_key, _write_op) in self.transaction_store.get_write_set(version)?.iter() {
+            let (leaf, proof) = self
+                .state_db
+                .state_merkle_db
+                .get_with_proof_ext(state_key, snapshot_version)?;
+            let leaf_value_hash = leaf.map(|(value_hash, _)| value_hash);
+            proof.verify_by_hash(root_hash, CryptoHash::hash(state_key), leaf_value_hash)?;
+
+            let value_hash = self
+                .state_db
+                .get_state_value_by_version(state_key, snapshot_version)?
+                .map(|value| CryptoHash::hash(&value));
+            ensure!(
+                leaf_value_hash == value_hash,
+                "State tree leaf of key {:?} at version {} doesn't match state KV, leaf: {:?}, KV: {:?}",
+                state_key,
+                snapshot_version,
+                leaf_value_hash,
+                value_hash,
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::VersionChecker;
+use crate::{
+    schema::write_set::WriteSetSchema,
+    test_helper::{arb_blocks_to_commit, update_in_memory_state},
+    AptosDB,
+};
+use aptos_storage_interface::DbWriter;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{TransactionToCommit, Version},
+    write_set::WriteSet,
+};
+use proptest::prelude::*;
+use std::sync::Arc;
+
+fn commit_blocks(
+    db: &AptosDB,
+    input: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)],
+) -> Version {
+    let mut in_memory_state = db
+        .state_store
+        .buffered_state()
+        .lock()
+        .current_state()
+        .clone();
+    let mut cur_ver: Version = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in input {
+        update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+        db.save_transactions(
+            txns_to_commit,
+            cur_ver,                /* first_version */
+            cur_ver.checked_sub(1), /* base_state_version */
+            Some(ledger_info_with_sigs),
+            true, /* sync_commit */
+            in_memory_state.clone(),
+        )
+        .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    cur_ver - 1
+}
+
+fn version_checker(db: &AptosDB) -> VersionChecker {
+    VersionChecker::new(
+        Arc::clone(&db.state_store.state_db),
+        Arc::clone(&db.ledger_pruner),
+        db.skip_index_and_usage,
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_consistent_db(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        let latest_version = commit_blocks(&db, &input);
+        let ledger_info_with_sigs = db.ledger_store.get_latest_ledger_info().unwrap();
+
+        let checker = version_checker(&db);
+        for version in 0..=latest_version {
+            checker
+                .check_transaction_accumulator(version, ledger_info_with_sigs.ledger_info())
+                .unwrap();
+            checker.check_event_accumulator(version).unwrap();
+            checker.check_write_set(version).unwrap();
+            checker.check_state_merkle(version).unwrap();
+        }
+        checker.run_round(10).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_write_set(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        let latest_version = commit_blocks(&db, &input);
+
+        let checker = version_checker(&db);
+        let version = (0..=latest_version)
+            .find(|v| !db.transaction_store.get_write_set(*v).unwrap().is_empty());
+        if let Some(version) = version {
+            db.ledger_db
+                .write_set_db()
+                .put::<WriteSetSchema>(&version, &WriteSet::default())
+                .unwrap();
+            prop_assert!(checker.check_write_set(version).is_err());
+        }
+    }
+}
@@ -303,6 +303,19 @@ impl EventStore {
         Ok((first_version, payload))
     }
 
+    /// Returns the root hash of the persisted event accumulator of the transaction at `version`,
+    /// which emitted `num_events` events.
+    pub fn get_event_root_hash(&self, version: Version, num_events: u64) -> Result<HashValue> {
+        ensure!(
+            num_events > 0,
+            "Empty event accumulators are not persisted."
+        );
+        MerkleAccumulator::<EventHashReader, EventAccumulatorHasher>::get_root_hash(
+            &EventHashReader::new(self, version),
+            num_events,
+        )
+    }
+
     /// Save contract events yielded by the transaction at `version` and return root hash of the
     /// event accumulator formed by these events.
     pub fn put_events(
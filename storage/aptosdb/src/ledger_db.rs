@@ -13,6 +13,7 @@ use crate::{
         transaction_info_db_column_families, write_set_db_column_families,
     },
     schema::db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+    utils::get_live_sst_files_size,
 };
 use anyhow::Result;
 use aptos_config::config::{RocksdbConfig, RocksdbConfigs};
@@ -211,6 +212,34 @@ impl LedgerDb {
         )
     }
 
+    /// Estimated on-disk size of all ledger data.
+    pub(crate) fn approximate_size_bytes(&self) -> Result<u64> {
+        if Arc::ptr_eq(&self.ledger_metadata_db, &self.event_db) {
+            // Ledger db is not split, everything lives in the same db.
+            return get_live_sst_files_size(&self.ledger_metadata_db, &ledger_db_column_families());
+        }
+        [
+            (
+                &self.ledger_metadata_db,
+                ledger_metadata_db_column_families(),
+            ),
+            (&self.event_db, event_db_column_families()),
+            (
+                &self.transaction_accumulator_db,
+                transaction_accumulator_db_column_families(),
+            ),
+            (&self.transaction_db, transaction_db_column_families()),
+            (
+                &self.transaction_info_db,
+                transaction_info_db_column_families(),
+            ),
+            (&self.write_set_db, write_set_db_column_families()),
+        ]
+        .iter()
+        .map(|(db, cf_names)| get_live_sst_files_size(db, cf_names))
+        .sum()
+    }
+
     pub fn metadata_db(&self) -> &DB {
         &self.ledger_metadata_db
     }
@@ -23,6 +23,7 @@ pub mod schema;
 pub mod state_restore;
 pub mod utils;
 
+mod consistency_checker;
 mod db_options;
 mod event_store;
 mod ledger_db;
@@ -43,6 +44,7 @@ pub mod db_debugger;
 
 use crate::{
     backup::{backup_handler::BackupHandler, restore_handler::RestoreHandler, restore_utils},
+    consistency_checker::{ConsistencyChecker, VersionChecker},
     db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
     db_options::{ledger_db_column_families, state_merkle_db_column_families},
     errors::AptosDbError,
@@ -64,7 +66,8 @@ use crate::{
 };
 use anyhow::{bail, ensure, Result};
 use aptos_config::config::{
-    PrunerConfig, RocksdbConfig, RocksdbConfigs, NO_OP_STORAGE_PRUNER_CONFIG,
+    ConsistencyCheckerConfig, PrunerConfig, RocksdbConfig, RocksdbConfigs,
+    NO_OP_STORAGE_PRUNER_CONFIG,
 };
 #[cfg(any(test, feature = "fuzzing"))]
 use aptos_config::config::{
@@ -277,8 +280,9 @@ pub struct AptosDB {
     ledger_store: Arc<LedgerStore>,
     state_store: Arc<StateStore>,
     transaction_store: Arc<TransactionStore>,
-    ledger_pruner: LedgerPrunerManager,
+    ledger_pruner: Arc<LedgerPrunerManager>,
     _rocksdb_property_reporter: RocksdbPropertyReporter,
+    consistency_checker: Option<ConsistencyChecker>,
     ledger_commit_lock: std::sync::Mutex<()>,
     indexer: Option<Indexer>,
     skip_index_and_usage: bool,
@@ -300,14 +304,18 @@ impl AptosDB {
         let state_kv_db = Arc::new(state_kv_db);
         let state_merkle_pruner = StateMerklePrunerManager::new(
             Arc::clone(&state_merkle_db),
+            Arc::clone(&ledger_db),
             pruner_config.state_merkle_pruner_config,
         );
         let epoch_snapshot_pruner = StateMerklePrunerManager::new(
             Arc::clone(&state_merkle_db),
+            Arc::clone(&ledger_db),
             pruner_config.epoch_snapshot_pruner_config.into(),
         );
-        let state_kv_pruner =
-            StateKvPrunerManager::new(Arc::clone(&state_kv_db), pruner_config.ledger_pruner_config);
+        let state_kv_pruner = StateKvPrunerManager::new(
+            Arc::clone(&state_kv_db),
+            pruner_config.ledger_pruner_config.clone(),
+        );
         let state_store = Arc::new(StateStore::new(
             Arc::clone(&ledger_db),
             Arc::clone(&state_merkle_db),
@@ -321,8 +329,10 @@ impl AptosDB {
             skip_index_and_usage,
         ));
 
-        let ledger_pruner =
-            LedgerPrunerManager::new(Arc::clone(&ledger_db), pruner_config.ledger_pruner_config);
+        let ledger_pruner = Arc::new(LedgerPrunerManager::new(
+            Arc::clone(&ledger_db),
+            pruner_config.ledger_pruner_config,
+        ));
 
         AptosDB {
             ledger_db: Arc::clone(&ledger_db),
@@ -338,6 +348,7 @@ impl AptosDB {
                 ledger_db.metadata_db_arc(),
                 Arc::clone(&state_merkle_db),
             ),
+            consistency_checker: None,
             ledger_commit_lock: std::sync::Mutex::new(()),
             indexer: None,
             skip_index_and_usage,
@@ -449,6 +460,22 @@ impl AptosDB {
         Ok((ledger_db, state_merkle_db, state_kv_db))
     }
 
+    /// Starts verifying randomly sampled versions in the background, if enabled in the config.
+    pub fn start_consistency_checker(&mut self, config: ConsistencyCheckerConfig) {
+        if !config.enable {
+            return;
+        }
+        info!(config = ?config, "Starting DB consistency checker.");
+        self.consistency_checker = Some(ConsistencyChecker::new(
+            VersionChecker::new(
+                Arc::clone(&self.state_store.state_db),
+                Arc::clone(&self.ledger_pruner),
+                self.skip_index_and_usage,
+            ),
+            config,
+        ));
+    }
+
     fn open_indexer(
         &mut self,
         db_root_path: impl AsRef<Path>,
@@ -648,7 +675,7 @@ impl AptosDB {
         ledger_version: Version,
         fetch_events: bool,
     ) -> Result<TransactionWithProof> {
-        self.error_if_ledger_pruned("Transaction", version)?;
+        self.error_if_ledger_pruned_and_not_archived("Transaction", version)?;
 
         let proof = self
             .ledger_store
@@ -766,6 +793,7 @@ impl AptosDB {
         let mut events_with_version = event_indices
             .into_iter()
             .map(|(seq, ver, idx)| {
+                self.error_if_ledger_pruned_and_not_archived("Event", ver)?;
                 let event = self.event_store.get_event_by_version_and_index(ver, idx)?;
                 ensure!(
                     seq == event.sequence_number(),
@@ -1265,6 +1293,39 @@ impl AptosDB {
         Ok(())
     }
 
+    /// Like `error_if_ledger_pruned`, but lets through versions kept by the archival filter of
+    /// the ledger pruner.
+    fn error_if_ledger_pruned_and_not_archived(
+        &self,
+        data_type: &str,
+        version: Version,
+    ) -> Result<()> {
+        if version >= self.ledger_pruner.get_min_readable_version()
+            || self.ledger_pruner.is_archived(version)?
+        {
+            return Ok(());
+        }
+        self.error_if_ledger_pruned(data_type, version)
+    }
+
+    /// Like `error_if_ledger_pruned_and_not_archived`, for all the versions in
+    /// [start_version, start_version + limit).
+    fn error_if_ledger_range_pruned_and_not_archived(
+        &self,
+        data_type: &str,
+        start_version: Version,
+        limit: u64,
+    ) -> Result<()> {
+        let end_version = std::cmp::min(
+            start_version.saturating_add(limit),
+            self.ledger_pruner.get_min_readable_version(),
+        );
+        for version in start_version..end_version {
+            self.error_if_ledger_pruned_and_not_archived(data_type, version)?;
+        }
+        Ok(())
+    }
+
     fn error_if_state_merkle_pruned(&self, data_type: &str, version: Version) -> Result<()> {
         let min_readable_version = self
             .state_store
@@ -1442,9 +1503,12 @@ impl DbReader for AptosDB {
             if start_version > ledger_version || limit == 0 {
                 return Ok(TransactionListWithProof::new_empty());
             }
-            self.error_if_ledger_pruned("Transaction", start_version)?;
-
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
+            self.error_if_ledger_range_pruned_and_not_archived(
+                "Transaction",
+                start_version,
+                limit,
+            )?;
 
             let txns = (start_version..start_version + limit)
                 .map(|version| self.transaction_store.get_transaction(version))
@@ -1519,9 +1583,12 @@ impl DbReader for AptosDB {
                 return Ok(TransactionOutputListWithProof::new_empty());
             }
 
-            self.error_if_ledger_pruned("Transaction", start_version)?;
-
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
+            self.error_if_ledger_range_pruned_and_not_archived(
+                "Transaction",
+                start_version,
+                limit,
+            )?;
 
             let (txn_infos, txns_and_outputs) = (start_version..start_version + limit)
                 .map(|version| {
@@ -1577,7 +1644,11 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<Transaction>> + '_>> {
         gauged_api("get_transaction_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_range_pruned_and_not_archived(
+                "Transaction",
+                start_version,
+                limit,
+            )?;
 
             let iter = self
                 .transaction_store
@@ -1593,7 +1664,11 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<TransactionInfo>> + '_>> {
         gauged_api("get_transaction_info_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_range_pruned_and_not_archived(
+                "Transaction",
+                start_version,
+                limit,
+            )?;
 
             let iter = self
                 .ledger_store
@@ -1609,7 +1684,11 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<Vec<ContractEvent>>> + '_>> {
         gauged_api("get_events_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_range_pruned_and_not_archived(
+                "Transaction",
+                start_version,
+                limit,
+            )?;
 
             let iter = self
                 .event_store
@@ -1628,7 +1707,11 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<WriteSet>> + '_>> {
         gauged_api("get_write_set_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_range_pruned_and_not_archived(
+                "Transaction",
+                start_version,
+                limit,
+            )?;
 
             let iter = self
                 .transaction_store
@@ -1644,7 +1727,11 @@ impl DbReader for AptosDB {
         ledger_version: Version,
     ) -> Result<TransactionAccumulatorRangeProof> {
         gauged_api("get_transaction_accumulator_range_proof", || {
-            self.error_if_ledger_pruned("Transaction", first_version)?;
+            self.error_if_ledger_range_pruned_and_not_archived(
+                "Transaction",
+                first_version,
+                limit,
+            )?;
 
             self.ledger_store.get_transaction_range_proof(
                 Some(first_version),
@@ -1721,6 +1808,19 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_values_with_version_by_version(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<Option<(Version, StateValue)>>> {
+        gauged_api("get_state_values_with_version_by_version", || {
+            self.error_if_state_kv_pruned("StateValue", version)?;
+
+            self.state_store
+                .get_state_values_with_version_by_version(state_keys, version)
+        })
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
@@ -1888,7 +1988,7 @@ impl DbReader for AptosDB {
 
     fn get_accumulator_root_hash(&self, version: Version) -> Result<HashValue> {
         gauged_api("get_accumulator_root_hash", || {
-            self.error_if_ledger_pruned("Transaction accumulator", version)?;
+            self.error_if_ledger_pruned_and_not_archived("Transaction accumulator", version)?;
             self.ledger_store.get_root_hash(version)
         })
     }
@@ -1899,7 +1999,7 @@ impl DbReader for AptosDB {
         ledger_version: Version,
     ) -> Result<AccumulatorConsistencyProof> {
         gauged_api("get_accumulator_consistency_proof", || {
-            self.error_if_ledger_pruned(
+            self.error_if_ledger_pruned_and_not_archived(
                 "Transaction accumulator",
                 client_known_version.unwrap_or(0),
             )?;
@@ -3,8 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_counter, register_int_gauge,
-    register_int_gauge_vec, HistogramVec, IntCounter, IntGauge, IntGaugeVec,
+    exponential_buckets, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge, register_int_gauge_vec, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -76,6 +77,19 @@ pub static PRUNER_WINDOW: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// The window (in versions) each pruning policy translates to, as well as the effective one.
+pub static PRUNER_DYNAMIC_WINDOW: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "aptos_storage_pruner_dynamic_window",
+        // metric description
+        "Aptos storage prune window in versions, by pruning policy",
+        // metric labels (dimensions)
+        &["pruner_name", "policy"]
+    )
+    .unwrap()
+});
+
 /// DB pruner least readable versions
 pub static PRUNER_VERSIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -89,6 +103,32 @@ pub static PRUNER_VERSIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of checks done by the background consistency checker, by kind of check.
+pub static CONSISTENCY_CHECKS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "aptos_storage_consistency_checks",
+        // metric description
+        "Aptos storage consistency checks done",
+        // metric labels (dimensions)
+        &["check"]
+    )
+    .unwrap()
+});
+
+/// Number of inconsistencies found by the background consistency checker, by kind of check.
+pub static CONSISTENCY_CHECK_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "aptos_storage_consistency_check_failures",
+        // metric description
+        "Aptos storage inconsistencies found",
+        // metric labels (dimensions)
+        &["check"]
+    )
+    .unwrap()
+});
+
 /// Pruner batch size. For ledger pruner, this means the number of versions to be pruned at a time.
 /// For state store pruner, this means the number of stale nodes to be pruned at a time.
 pub static PRUNER_BATCH_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
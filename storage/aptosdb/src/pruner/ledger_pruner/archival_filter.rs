@@ -0,0 +1,200 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    ledger_db::LedgerDb,
+    schema::{event::EventSchema, transaction::TransactionSchema, write_set::WriteSetSchema},
+};
+use anyhow::{Context, Result};
+use aptos_config::config::LedgerPrunerConfig;
+use aptos_schemadb::ReadOptions;
+use aptos_types::{
+    account_address::AccountAddress,
+    contract_event::ContractEvent,
+    state_store::state_key::{StateKey, StateKeyInner},
+    transaction::{Transaction, Version},
+};
+use move_core_types::language_storage::TypeTag;
+use std::{collections::BTreeSet, ops::Range, str::FromStr, sync::Arc};
+
+/// Decides which versions the ledger pruner keeps regardless of the prune window, because they
+/// touch one of the configured archived accounts or event types.
+///
+/// The decision for a version is made by looking at whatever of its transaction, events and
+/// write set is still in the DB. Since nothing of an archived version is ever deleted, the same
+/// decision is reached when catching up a sub pruner that was interrupted mid batch.
+#[derive(Debug)]
+pub(crate) struct ArchivalFilter {
+    ledger_db: Arc<LedgerDb>,
+    addresses: BTreeSet<AccountAddress>,
+    event_types: BTreeSet<TypeTag>,
+}
+
+impl ArchivalFilter {
+    pub fn new(ledger_db: Arc<LedgerDb>, config: &LedgerPrunerConfig) -> Result<Self> {
+        let event_types = config
+            .archived_event_types
+            .iter()
+            .map(|s| {
+                TypeTag::from_str(s).with_context(|| format!("Invalid archived event type: {}", s))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            ledger_db,
+            addresses: config.archived_addresses.iter().cloned().collect(),
+            event_types,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.addresses.is_empty() || !self.event_types.is_empty()
+    }
+
+    pub fn is_archived(&self, version: Version) -> Result<bool> {
+        if !self.is_enabled() {
+            return Ok(false);
+        }
+
+        // Nothing of an archived version is deleted, so a version without its transaction has
+        // been pruned. This is the common case for reads below the prune window, answered by a
+        // single point lookup.
+        let txn = match self
+            .ledger_db
+            .transaction_db()
+            .get::<TransactionSchema>(&version)?
+        {
+            Some(txn) => txn,
+            None => return Ok(false),
+        };
+        if self.is_archived_transaction(&txn) {
+            return Ok(true);
+        }
+
+        if !self.addresses.is_empty() {
+            if let Some(write_set) = self
+                .ledger_db
+                .write_set_db()
+                .get::<WriteSetSchema>(&version)?
+            {
+                if write_set
+                    .iter()
+                    .any(|(state_key, _)| self.is_archived_state_key(state_key))
+                {
+                    return Ok(true);
+                }
+            }
+        }
+
+        let mut iter = self
+            .ledger_db
+            .event_db()
+            .iter::<EventSchema>(ReadOptions::default())?;
+        iter.seek(&(version, 0))?;
+        for item in iter {
+            let ((event_version, _index), event) = item?;
+            if event_version != version {
+                break;
+            }
+            if self.is_archived_event(&event) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Splits [begin, end) into the ranges of versions that are allowed to be pruned.
+    pub fn ranges_to_prune(&self, begin: Version, end: Version) -> Result<Vec<Range<Version>>> {
+        if !self.is_enabled() {
+            return Ok(if begin < end {
+                vec![begin..end]
+            } else {
+                vec![]
+            });
+        }
+
+        let mut ranges = Vec::new();
+        let mut range_begin = begin;
+        for version in self.archived_versions(begin, end)? {
+            if range_begin < version {
+                ranges.push(range_begin..version);
+            }
+            range_begin = version + 1;
+        }
+        if range_begin < end {
+            ranges.push(range_begin..end);
+        }
+        Ok(ranges)
+    }
+
+    fn archived_versions(&self, begin: Version, end: Version) -> Result<BTreeSet<Version>> {
+        let mut versions = BTreeSet::new();
+
+        let mut iter = self
+            .ledger_db
+            .transaction_db()
+            .iter::<TransactionSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for item in iter {
+            let (version, txn) = item?;
+            if version >= end {
+                break;
+            }
+            if self.is_archived_transaction(&txn) {
+                versions.insert(version);
+            }
+        }
+
+        let mut iter = self
+            .ledger_db
+            .event_db()
+            .iter::<EventSchema>(ReadOptions::default())?;
+        iter.seek(&(begin, 0))?;
+        for item in iter {
+            let ((version, _index), event) = item?;
+            if version >= end {
+                break;
+            }
+            if self.is_archived_event(&event) {
+                versions.insert(version);
+            }
+        }
+
+        let mut iter = self
+            .ledger_db
+            .write_set_db()
+            .iter::<WriteSetSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for item in iter {
+            let (version, write_set) = item?;
+            if version >= end {
+                break;
+            }
+            if write_set
+                .iter()
+                .any(|(state_key, _)| self.is_archived_state_key(state_key))
+            {
+                versions.insert(version);
+            }
+        }
+
+        Ok(versions)
+    }
+
+    fn is_archived_transaction(&self, txn: &Transaction) -> bool {
+        txn.try_as_signed_user_txn()
+            .map_or(false, |txn| self.addresses.contains(&txn.sender()))
+    }
+
+    fn is_archived_event(&self, event: &ContractEvent) -> bool {
+        self.addresses.contains(&event.key().get_creator_address())
+            || self.event_types.contains(event.type_tag())
+    }
+
+    fn is_archived_state_key(&self, state_key: &StateKey) -> bool {
+        match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => self.addresses.contains(&access_path.address),
+            StateKeyInner::TableItem { .. } | StateKeyInner::Raw(_) => false,
+        }
+    }
+}
@@ -77,6 +77,10 @@ fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
         prune_window: 0,
         batch_size: 1,
         user_pruning_window_offset: 0,
+        prune_window_secs: None,
+        target_size_bytes: None,
+        archived_addresses: vec![],
+        archived_event_types: vec![],
     });
     // start pruning events batches of size 2 and verify transactions have been pruned from DB
     for i in (0..=num_versions).step_by(2) {
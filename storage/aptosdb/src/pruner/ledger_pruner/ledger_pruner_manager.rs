@@ -3,9 +3,12 @@
 
 use crate::{
     ledger_db::LedgerDb,
-    metrics::{PRUNER_BATCH_SIZE, PRUNER_VERSIONS, PRUNER_WINDOW},
+    metrics::{PRUNER_BATCH_SIZE, PRUNER_VERSIONS},
     pruner::{
-        ledger_pruner::LedgerPruner, pruner_manager::PrunerManager, pruner_utils,
+        ledger_pruner::{archival_filter::ArchivalFilter, LedgerPruner},
+        prune_window::{self, PruneWindow},
+        pruner_manager::PrunerManager,
+        pruner_utils,
         pruner_worker::PrunerWorker,
     },
 };
@@ -20,7 +23,7 @@ pub(crate) struct LedgerPrunerManager {
     ledger_db: Arc<LedgerDb>,
     /// DB version window, which dictates how many version of other stores like transaction, ledger
     /// info, events etc to keep.
-    prune_window: Version,
+    prune_window: PruneWindow,
     /// It is None iff the pruner is not enabled.
     pruner_worker: Option<PrunerWorker>,
     /// Ideal batch size of the versions to be sent to the ledger pruner
@@ -31,6 +34,8 @@ pub(crate) struct LedgerPrunerManager {
     user_pruning_window_offset: u64,
     /// The minimal readable version for the ledger data.
     min_readable_version: AtomicVersion,
+    /// Decides which versions below the min readable version are kept nevertheless.
+    archival_filter: Arc<ArchivalFilter>,
 }
 
 impl PrunerManager for LedgerPrunerManager {
@@ -41,7 +46,7 @@ impl PrunerManager for LedgerPrunerManager {
     }
 
     fn get_prune_window(&self) -> Version {
-        self.prune_window
+        self.prune_window.get()
     }
 
     fn get_min_readable_version(&self) -> Version {
@@ -52,7 +57,7 @@ impl PrunerManager for LedgerPrunerManager {
         let min_version = self.get_min_readable_version();
         if self.is_pruner_enabled() {
             let adjusted_window = self
-                .prune_window
+                .get_prune_window()
                 .saturating_sub(self.user_pruning_window_offset);
             let adjusted_cutoff = self.latest_version.lock().saturating_sub(adjusted_window);
             std::cmp::max(min_version, adjusted_cutoff)
@@ -66,11 +71,17 @@ impl PrunerManager for LedgerPrunerManager {
         *self.latest_version.lock() = latest_version;
 
         let min_readable_version = self.get_min_readable_version();
+        if self.is_pruner_enabled() {
+            self.prune_window
+                .maybe_refresh(latest_version, min_readable_version);
+        }
         // Only wake up the ledger pruner if there are `ledger_pruner_pruning_batch_size` pending
         // versions.
         if self.is_pruner_enabled()
             && latest_version
-                >= min_readable_version + self.pruning_batch_size as u64 + self.prune_window
+                >= min_readable_version
+                    .saturating_add(self.pruning_batch_size as u64)
+                    .saturating_add(self.get_prune_window())
         {
             self.set_pruner_target_db_version(latest_version);
         }
@@ -105,10 +116,15 @@ impl PrunerManager for LedgerPrunerManager {
 impl LedgerPrunerManager {
     /// Creates a worker thread that waits on a channel for pruning commands.
     pub fn new(ledger_db: Arc<LedgerDb>, ledger_pruner_config: LedgerPrunerConfig) -> Self {
+        let archival_filter = Arc::new(
+            ArchivalFilter::new(Arc::clone(&ledger_db), &ledger_pruner_config)
+                .expect("Archived event types are validated by the config sanitizer."),
+        );
         let pruner_worker = if ledger_pruner_config.enable {
             Some(Self::init_pruner(
                 Arc::clone(&ledger_db),
-                ledger_pruner_config,
+                Arc::clone(&archival_filter),
+                &ledger_pruner_config,
             ))
         } else {
             None
@@ -121,27 +137,45 @@ impl LedgerPrunerManager {
             .with_label_values(&["ledger_pruner", "min_readable"])
             .set(min_readable_version as i64);
 
+        let prune_window = PruneWindow::new(
+            "ledger_pruner",
+            ledger_pruner_config.prune_window,
+            ledger_pruner_config.prune_window_secs,
+            ledger_pruner_config.target_size_bytes,
+            Arc::clone(&ledger_db),
+            {
+                let ledger_db = Arc::clone(&ledger_db);
+                Box::new(move || ledger_db.approximate_size_bytes())
+            },
+            prune_window::REFRESH_INTERVAL,
+        );
+
         Self {
             ledger_db,
-            prune_window: ledger_pruner_config.prune_window,
+            prune_window,
             pruner_worker,
             pruning_batch_size: ledger_pruner_config.batch_size,
             latest_version: Arc::new(Mutex::new(min_readable_version)),
             user_pruning_window_offset: ledger_pruner_config.user_pruning_window_offset,
             min_readable_version: AtomicVersion::new(min_readable_version),
+            archival_filter,
         }
     }
 
+    /// Whether the data at `version` is kept by the archival filter even though it's older than
+    /// the min readable version.
+    pub fn is_archived(&self, version: Version) -> Result<bool> {
+        self.archival_filter.is_archived(version)
+    }
+
     fn init_pruner(
         ledger_db: Arc<LedgerDb>,
-        ledger_pruner_config: LedgerPrunerConfig,
+        archival_filter: Arc<ArchivalFilter>,
+        ledger_pruner_config: &LedgerPrunerConfig,
     ) -> PrunerWorker {
-        let pruner =
-            Arc::new(LedgerPruner::new(ledger_db).expect("Failed to create ledger pruner."));
-
-        PRUNER_WINDOW
-            .with_label_values(&["ledger_pruner"])
-            .set(ledger_pruner_config.prune_window as i64);
+        let pruner = Arc::new(
+            LedgerPruner::new(ledger_db, archival_filter).expect("Failed to create ledger pruner."),
+        );
 
         PRUNER_BATCH_SIZE
             .with_label_values(&["ledger_pruner"])
@@ -152,7 +186,7 @@ impl LedgerPrunerManager {
 
     fn set_pruner_target_db_version(&self, latest_version: Version) {
         assert!(self.pruner_worker.is_some());
-        let min_readable_version = latest_version.saturating_sub(self.prune_window);
+        let min_readable_version = latest_version.saturating_sub(self.get_prune_window());
         self.min_readable_version
             .store(min_readable_version, Ordering::SeqCst);
         self.pruner_worker
@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod archival_filter;
 mod event_store_pruner;
 mod ledger_metadata_pruner;
 pub(crate) mod ledger_pruner_manager;
@@ -16,7 +17,8 @@ use crate::{
         db_pruner::DBPruner,
         db_sub_pruner::DBSubPruner,
         ledger_pruner::{
-            event_store_pruner::EventStorePruner, ledger_metadata_pruner::LedgerMetadataPruner,
+            archival_filter::ArchivalFilter, event_store_pruner::EventStorePruner,
+            ledger_metadata_pruner::LedgerMetadataPruner,
             transaction_accumulator_pruner::TransactionAccumulatorPruner,
             transaction_info_pruner::TransactionInfoPruner, transaction_pruner::TransactionPruner,
             write_set_pruner::WriteSetPruner,
@@ -29,6 +31,7 @@ use aptos_logger::info;
 use aptos_types::transaction::{AtomicVersion, Version};
 use std::{
     cmp::min,
+    ops::Range,
     sync::{atomic::Ordering, Arc},
 };
 
@@ -43,7 +46,16 @@ pub(crate) struct LedgerPruner {
 
     ledger_metadata_pruner: Box<LedgerMetadataPruner>,
 
-    sub_pruners: Vec<Box<dyn DBSubPruner + Send + Sync>>,
+    archival_filter: Arc<ArchivalFilter>,
+
+    sub_pruners: Vec<Box<dyn LedgerSubPruner + Send + Sync>>,
+}
+
+/// A sub pruner of the ledger pruner.
+pub(crate) trait LedgerSubPruner: DBSubPruner {
+    /// Like `DBSubPruner::prune`, but only deletes the versions in `ranges`, which are the ones
+    /// in [current_progress, target_version) not kept by the `ArchivalFilter`.
+    fn prune_ranges(&self, ranges: &[Range<Version>], target_version: Version) -> Result<()>;
 }
 
 impl DBPruner for LedgerPruner {
@@ -64,13 +76,18 @@ impl DBPruner for LedgerPruner {
                 target_version = current_batch_target_version,
                 "Pruning ledger data."
             );
+            // Decided once for all sub pruners, before any of them deletes anything.
+            let ranges = self
+                .archival_filter
+                .ranges_to_prune(progress, current_batch_target_version)?;
+
             self.ledger_metadata_pruner
                 .prune(progress, current_batch_target_version)?;
 
             // NOTE: If necessary, this can be done in parallel.
-            self.sub_pruners
-                .iter()
-                .try_for_each(|pruner| pruner.prune(progress, current_batch_target_version))?;
+            self.sub_pruners.iter().try_for_each(|pruner| {
+                pruner.prune_ranges(&ranges, current_batch_target_version)
+            })?;
 
             progress = current_batch_target_version;
             self.record_progress(progress);
@@ -104,7 +121,7 @@ impl DBPruner for LedgerPruner {
 }
 
 impl LedgerPruner {
-    pub fn new(ledger_db: Arc<LedgerDb>) -> Result<Self> {
+    pub fn new(ledger_db: Arc<LedgerDb>, archival_filter: Arc<ArchivalFilter>) -> Result<Self> {
         info!(name = LEDGER_PRUNER_NAME, "Initializing...");
 
         let ledger_metadata_pruner = Box::new(
@@ -124,26 +141,31 @@ impl LedgerPruner {
         let event_store_pruner = Box::new(EventStorePruner::new(
             Arc::new(EventStore::new(ledger_db.event_db_arc())),
             ledger_db.event_db_arc(),
+            Arc::clone(&archival_filter),
             metadata_progress,
         )?);
         let transaction_accumulator_pruner = Box::new(TransactionAccumulatorPruner::new(
             Arc::clone(&transaction_store),
             ledger_db.transaction_accumulator_db_arc(),
+            Arc::clone(&archival_filter),
             metadata_progress,
         )?);
         let transaction_info_pruner = Box::new(TransactionInfoPruner::new(
             Arc::clone(&transaction_store),
             ledger_db.transaction_info_db_arc(),
+            Arc::clone(&archival_filter),
             metadata_progress,
         )?);
         let transaction_pruner = Box::new(TransactionPruner::new(
             Arc::clone(&transaction_store),
             ledger_db.transaction_db_arc(),
+            Arc::clone(&archival_filter),
             metadata_progress,
         )?);
         let write_set_pruner = Box::new(WriteSetPruner::new(
             Arc::clone(&transaction_store),
             ledger_db.write_set_db_arc(),
+            Arc::clone(&archival_filter),
             metadata_progress,
         )?);
 
@@ -151,6 +173,7 @@ impl LedgerPruner {
             target_version: AtomicVersion::new(metadata_progress),
             progress: AtomicVersion::new(metadata_progress),
             ledger_metadata_pruner,
+            archival_filter,
             sub_pruners: vec![
                 event_store_pruner,
                 transaction_accumulator_pruner,
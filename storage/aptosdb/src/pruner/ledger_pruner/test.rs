@@ -5,21 +5,28 @@ use crate::{
     schema::version_data::VersionDataSchema, AptosDB, LedgerPrunerManager, LedgerStore,
     PrunerManager, TransactionStore,
 };
+use anyhow::Result;
 use aptos_accumulator::HashReader;
-use aptos_config::config::LedgerPrunerConfig;
+use aptos_config::config::{
+    LedgerPrunerConfig, PrunerConfig, RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+};
 use aptos_schemadb::SchemaBatch;
-use aptos_storage_interface::DbReader;
+use aptos_storage_interface::{DbReader, Order};
 use aptos_temppath::TempPath;
 use aptos_types::{
     account_address::AccountAddress,
     block_metadata::BlockMetadata,
+    contract_event::ContractEvent,
+    event::EventKey,
     proof::position::Position,
     state_merkle_pruner::state_storage_usage::StateStorageUsage,
     transaction::{SignedTransaction, Transaction, TransactionInfo, Version},
     write_set::WriteSet,
 };
+use move_core_types::language_storage::TypeTag;
 use proptest::{collection::vec, prelude::*, proptest};
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
@@ -36,6 +43,31 @@ proptest! {
         verify_txn_store_pruner(txns, txn_infos, step_size)
     }
 
+    #[test]
+    fn test_txn_store_pruner_with_archived_addresses(
+        txns in vec(any::<SignedTransaction>().prop_map(Transaction::UserTransaction), 2..50),
+        txn_infos in vec(any::<TransactionInfo>(), 50),
+    ) {
+        verify_txn_store_pruner_with_archived_addresses(txns, txn_infos)
+    }
+
+    #[test]
+    fn test_ledger_pruner_with_archived_event_types(
+        txns in vec(any::<SignedTransaction>().prop_map(Transaction::UserTransaction), 2..50),
+        txn_infos in vec(any::<TransactionInfo>(), 50),
+        write_sets in vec(any::<WriteSet>(), 50),
+    ) {
+        verify_ledger_pruner_with_archived_event_types(txns, txn_infos, write_sets)
+    }
+
+    #[test]
+    fn test_read_archived_event_types(
+        txns in vec(any::<SignedTransaction>().prop_map(Transaction::UserTransaction), 2..50),
+        txn_infos in vec(any::<TransactionInfo>(), 50),
+    ) {
+        verify_read_archived_event_types(txns, txn_infos)
+    }
+
      #[test]
     fn test_write_set_pruner(
         write_set in vec(any::<WriteSet>(), 100),
@@ -55,6 +87,10 @@ fn verify_write_set_pruner(write_sets: Vec<WriteSet>) {
         prune_window: 0,
         batch_size: 1,
         user_pruning_window_offset: 0,
+        prune_window_secs: None,
+        target_size_bytes: None,
+        archived_addresses: vec![],
+        archived_event_types: vec![],
     });
 
     // write sets
@@ -128,6 +164,10 @@ fn verify_txn_store_pruner(
                 prune_window: 0,
                 batch_size: 1,
                 user_pruning_window_offset: 0,
+                prune_window_secs: None,
+                target_size_bytes: None,
+                archived_addresses: vec![],
+                archived_event_types: vec![],
             });
         pruner
             .wake_and_wait_pruner(i as u64 /* latest_version */)
@@ -163,6 +203,291 @@ fn verify_txn_store_pruner(
     }
 }
 
+fn verify_txn_store_pruner_with_archived_addresses(
+    txns: Vec<Transaction>,
+    txn_infos: Vec<TransactionInfo>,
+) {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let transaction_store = &aptos_db.transaction_store;
+    let ledger_store = LedgerStore::new(Arc::clone(&aptos_db.ledger_db));
+    let num_transaction = txns.len();
+
+    let ledger_version = num_transaction as Version - 1;
+    put_txn_in_store(
+        &aptos_db,
+        transaction_store,
+        &ledger_store,
+        &txn_infos,
+        &txns,
+    );
+
+    let archived_address = txns[0].try_as_signed_user_txn().unwrap().sender();
+    let pruner = LedgerPrunerManager::new(Arc::clone(&aptos_db.ledger_db), LedgerPrunerConfig {
+        enable: true,
+        prune_window: 0,
+        batch_size: 1,
+        user_pruning_window_offset: 0,
+        prune_window_secs: None,
+        target_size_bytes: None,
+        archived_addresses: vec![archived_address],
+        archived_event_types: vec![],
+    });
+    pruner
+        .wake_and_wait_pruner(num_transaction as u64 /* latest_version */)
+        .unwrap();
+
+    // Transactions sent by the archived account, and their proofs, survive the pruning.
+    for (version, txn) in txns.iter().enumerate() {
+        let version = version as Version;
+        if txn.try_as_signed_user_txn().unwrap().sender() == archived_address {
+            assert!(pruner.is_archived(version).unwrap());
+            verify_txn_in_store(
+                transaction_store,
+                &ledger_store,
+                &txns,
+                version,
+                ledger_version,
+            );
+        } else {
+            assert!(!pruner.is_archived(version).unwrap());
+            verify_txn_not_in_store(transaction_store, &txns, version, ledger_version);
+        }
+    }
+}
+
+fn verify_ledger_pruner_with_archived_event_types(
+    txns: Vec<Transaction>,
+    txn_infos: Vec<TransactionInfo>,
+    write_sets: Vec<WriteSet>,
+) {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let transaction_store = &aptos_db.transaction_store;
+    let event_store = &aptos_db.event_store;
+    let ledger_store = LedgerStore::new(Arc::clone(&aptos_db.ledger_db));
+    let num_transaction = txns.len();
+
+    let ledger_version = num_transaction as Version - 1;
+    put_txn_in_store(
+        &aptos_db,
+        transaction_store,
+        &ledger_store,
+        &txn_infos,
+        &txns,
+    );
+
+    // Every third version emits an event of the archived type, the others one of another type.
+    let archived_type = "0x1::archived::Event";
+    let is_archived = |version: Version| version % 3 == 0;
+    let events: Vec<_> = (0..num_transaction as Version)
+        .map(|version| {
+            let type_tag = if is_archived(version) {
+                archived_type
+            } else {
+                "0x1::other::Event"
+            };
+            ContractEvent::new(
+                EventKey::new(0, AccountAddress::random()),
+                0,
+                TypeTag::from_str(type_tag).unwrap(),
+                vec![],
+            )
+        })
+        .collect();
+    let event_batch = SchemaBatch::new();
+    let write_set_batch = SchemaBatch::new();
+    for version in 0..num_transaction {
+        event_store
+            .put_events(
+                version as Version,
+                &events[version..=version],
+                /*skip_index=*/ false,
+                &event_batch,
+            )
+            .unwrap();
+        transaction_store
+            .put_write_set(version as Version, &write_sets[version], &write_set_batch)
+            .unwrap();
+    }
+    aptos_db
+        .ledger_db
+        .event_db()
+        .write_schemas(event_batch)
+        .unwrap();
+    aptos_db
+        .ledger_db
+        .write_set_db()
+        .write_schemas(write_set_batch)
+        .unwrap();
+
+    let pruner = LedgerPrunerManager::new(Arc::clone(&aptos_db.ledger_db), LedgerPrunerConfig {
+        enable: true,
+        prune_window: 0,
+        batch_size: 1,
+        user_pruning_window_offset: 0,
+        prune_window_secs: None,
+        target_size_bytes: None,
+        archived_addresses: vec![],
+        archived_event_types: vec![archived_type.to_string()],
+    });
+    pruner
+        .wake_and_wait_pruner(num_transaction as u64 /* latest_version */)
+        .unwrap();
+
+    // The transactions, events and write sets of versions emitting an archived event type
+    // survive the pruning.
+    for version in 0..num_transaction as Version {
+        if is_archived(version) {
+            assert!(pruner.is_archived(version).unwrap());
+            verify_txn_in_store(
+                transaction_store,
+                &ledger_store,
+                &txns,
+                version,
+                ledger_version,
+            );
+            assert_eq!(event_store.get_events_by_version(version).unwrap(), vec![
+                events[version as usize].clone()
+            ]);
+            assert_eq!(
+                transaction_store.get_write_set(version).unwrap(),
+                write_sets[version as usize]
+            );
+        } else {
+            assert!(!pruner.is_archived(version).unwrap());
+            verify_txn_not_in_store(transaction_store, &txns, version, ledger_version);
+            assert!(event_store
+                .get_events_by_version(version)
+                .unwrap()
+                .is_empty());
+            assert!(transaction_store.get_write_set(version).is_err());
+        }
+    }
+}
+
+fn verify_read_archived_event_types(txns: Vec<Transaction>, txn_infos: Vec<TransactionInfo>) {
+    let archived_type = "0x1::archived::Event";
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::open(
+        &tmp_dir,
+        false, /* is_read_only */
+        PrunerConfig {
+            ledger_pruner_config: LedgerPrunerConfig {
+                enable: true,
+                prune_window: 0,
+                batch_size: 1,
+                user_pruning_window_offset: 0,
+                prune_window_secs: None,
+                target_size_bytes: None,
+                archived_addresses: vec![],
+                archived_event_types: vec![archived_type.to_string()],
+            },
+            ..Default::default()
+        },
+        RocksdbConfigs::default(),
+        false, /* enable_indexer */
+        BUFFERED_STATE_TARGET_ITEMS,
+        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    )
+    .unwrap();
+    let transaction_store = &aptos_db.transaction_store;
+    let event_store = &aptos_db.event_store;
+    let ledger_store = LedgerStore::new(Arc::clone(&aptos_db.ledger_db));
+    let num_transaction = txns.len();
+    let ledger_version = num_transaction as Version - 1;
+    put_txn_in_store(
+        &aptos_db,
+        transaction_store,
+        &ledger_store,
+        &txn_infos,
+        &txns,
+    );
+
+    // Every third version emits an event of the archived type, the others one of another type,
+    // each type on its own event stream.
+    let archived_key = EventKey::new(0, AccountAddress::random());
+    let other_key = EventKey::new(1, AccountAddress::random());
+    let is_archived = |version: Version| version % 3 == 0;
+    let mut archived_events = vec![];
+    let event_batch = SchemaBatch::new();
+    for version in 0..num_transaction as Version {
+        let event = if is_archived(version) {
+            let event = ContractEvent::new(
+                archived_key,
+                archived_events.len() as u64,
+                TypeTag::from_str(archived_type).unwrap(),
+                vec![],
+            );
+            archived_events.push((version, event.clone()));
+            event
+        } else {
+            ContractEvent::new(
+                other_key,
+                version - version / 3 - 1,
+                TypeTag::from_str("0x1::other::Event").unwrap(),
+                vec![],
+            )
+        };
+        event_store
+            .put_events(version, &[event], /*skip_index=*/ false, &event_batch)
+            .unwrap();
+    }
+    aptos_db
+        .ledger_db
+        .event_db()
+        .write_schemas(event_batch)
+        .unwrap();
+
+    aptos_db
+        .ledger_pruner
+        .wake_and_wait_pruner(num_transaction as u64 /* latest_version */)
+        .unwrap();
+
+    // The events of the archived type are still served, by event key and by version.
+    let events = aptos_db
+        .get_events(
+            &archived_key,
+            0,
+            Order::Ascending,
+            archived_events.len() as u64,
+            ledger_version,
+        )
+        .unwrap();
+    assert_eq!(
+        events
+            .into_iter()
+            .map(|event| (event.transaction_version, event.event))
+            .collect::<Vec<_>>(),
+        archived_events
+    );
+    let txn = aptos_db
+        .get_transaction_by_version(0, ledger_version, /*fetch_events=*/ true)
+        .unwrap();
+    assert_eq!(txn.transaction, txns[0]);
+    assert_eq!(txn.events, Some(vec![archived_events[0].1.clone()]));
+    assert_eq!(
+        aptos_db
+            .get_events_iterator(0, 1)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+        vec![vec![archived_events[0].1.clone()]]
+    );
+
+    // The other versions are pruned, also when read as part of a range.
+    assert!(aptos_db
+        .get_transaction_by_version(1, ledger_version, /*fetch_events=*/ true)
+        .is_err());
+    assert!(aptos_db.get_events_iterator(0, 2).is_err());
+    assert!(aptos_db
+        .get_transactions(0, 2, ledger_version, /*fetch_events=*/ true)
+        .is_err());
+    assert!(aptos_db
+        .get_events(&other_key, 0, Order::Ascending, 1, ledger_version)
+        .map_or(true, |events| events.is_empty()));
+}
+
 fn verify_txn_not_in_store(
     transaction_store: &TransactionStore,
     txns: &[Transaction],
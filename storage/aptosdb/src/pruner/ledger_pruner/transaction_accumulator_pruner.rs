@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    pruner::{db_sub_pruner::DBSubPruner, pruner_utils::get_or_initialize_subpruner_progress},
+    pruner::{
+        db_sub_pruner::DBSubPruner,
+        ledger_pruner::{archival_filter::ArchivalFilter, LedgerSubPruner},
+        pruner_utils::get_or_initialize_subpruner_progress,
+    },
     schema::db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
     TransactionStore,
 };
@@ -10,22 +14,40 @@ use anyhow::Result;
 use aptos_logger::info;
 use aptos_schemadb::{SchemaBatch, DB};
 use aptos_types::transaction::Version;
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 #[derive(Debug)]
 pub struct TransactionAccumulatorPruner {
     transaction_store: Arc<TransactionStore>,
     transaction_accumulator_db: Arc<DB>,
+    archival_filter: Arc<ArchivalFilter>,
 }
 
 impl DBSubPruner for TransactionAccumulatorPruner {
     fn prune(&self, current_progress: Version, target_version: Version) -> Result<()> {
-        let batch = SchemaBatch::new();
-        self.transaction_store.prune_transaction_accumulator(
-            current_progress,
+        self.prune_ranges(
+            &self
+                .archival_filter
+                .ranges_to_prune(current_progress, target_version)?,
             target_version,
-            &batch,
-        )?;
+        )
+    }
+}
+
+impl LedgerSubPruner for TransactionAccumulatorPruner {
+    fn prune_ranges(&self, ranges: &[Range<Version>], target_version: Version) -> Result<()> {
+        let batch = SchemaBatch::new();
+        // Proofs of archived transactions need the accumulator nodes along their paths to the
+        // root, which is almost all of the accumulator, so it's kept entirely in that case.
+        if !self.archival_filter.is_enabled() {
+            for range in ranges {
+                self.transaction_store.prune_transaction_accumulator(
+                    range.start,
+                    range.end,
+                    &batch,
+                )?;
+            }
+        }
         batch.put::<DbMetadataSchema>(
             &DbMetadataKey::TransactionAccumulatorPrunerProgress,
             &DbMetadataValue::Version(target_version),
@@ -38,6 +60,7 @@ impl TransactionAccumulatorPruner {
     pub(in crate::pruner) fn new(
         transaction_store: Arc<TransactionStore>,
         transaction_accumulator_db: Arc<DB>,
+        archival_filter: Arc<ArchivalFilter>,
         metadata_progress: Version,
     ) -> Result<Self> {
         let progress = get_or_initialize_subpruner_progress(
@@ -49,6 +72,7 @@ impl TransactionAccumulatorPruner {
         let myself = TransactionAccumulatorPruner {
             transaction_store,
             transaction_accumulator_db,
+            archival_filter,
         };
 
         info!(
@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    pruner::{db_sub_pruner::DBSubPruner, pruner_utils::get_or_initialize_subpruner_progress},
+    pruner::{
+        db_sub_pruner::DBSubPruner,
+        ledger_pruner::{archival_filter::ArchivalFilter, LedgerSubPruner},
+        pruner_utils::get_or_initialize_subpruner_progress,
+    },
     schema::{
         db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
         transaction::TransactionSchema,
@@ -13,28 +17,39 @@ use anyhow::{ensure, Result};
 use aptos_logger::info;
 use aptos_schemadb::{ReadOptions, SchemaBatch, DB};
 use aptos_types::transaction::{Transaction, Version};
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 #[derive(Debug)]
 pub struct TransactionPruner {
     transaction_store: Arc<TransactionStore>,
     transaction_db: Arc<DB>,
+    archival_filter: Arc<ArchivalFilter>,
 }
 
 impl DBSubPruner for TransactionPruner {
     fn prune(&self, current_progress: Version, target_version: Version) -> Result<()> {
-        let batch = SchemaBatch::new();
-        let candidate_transactions =
-            self.get_pruning_candidate_transactions(current_progress, target_version)?;
-        self.transaction_store
-            .prune_transaction_by_hash(&candidate_transactions, &batch)?;
-        self.transaction_store
-            .prune_transaction_by_account(&candidate_transactions, &batch)?;
-        self.transaction_store.prune_transaction_schema(
-            current_progress,
+        self.prune_ranges(
+            &self
+                .archival_filter
+                .ranges_to_prune(current_progress, target_version)?,
             target_version,
-            &batch,
-        )?;
+        )
+    }
+}
+
+impl LedgerSubPruner for TransactionPruner {
+    fn prune_ranges(&self, ranges: &[Range<Version>], target_version: Version) -> Result<()> {
+        let batch = SchemaBatch::new();
+        for range in ranges {
+            let candidate_transactions =
+                self.get_pruning_candidate_transactions(range.start, range.end)?;
+            self.transaction_store
+                .prune_transaction_by_hash(&candidate_transactions, &batch)?;
+            self.transaction_store
+                .prune_transaction_by_account(&candidate_transactions, &batch)?;
+            self.transaction_store
+                .prune_transaction_schema(range.start, range.end, &batch)?;
+        }
         batch.put::<DbMetadataSchema>(
             &DbMetadataKey::TransactionPrunerProgress,
             &DbMetadataValue::Version(target_version),
@@ -47,6 +62,7 @@ impl TransactionPruner {
     pub(in crate::pruner) fn new(
         transaction_store: Arc<TransactionStore>,
         transaction_db: Arc<DB>,
+        archival_filter: Arc<ArchivalFilter>,
         metadata_progress: Version,
     ) -> Result<Self> {
         let progress = get_or_initialize_subpruner_progress(
@@ -58,6 +74,7 @@ impl TransactionPruner {
         let myself = TransactionPruner {
             transaction_store,
             transaction_db,
+            archival_filter,
         };
 
         info!(
@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    pruner::{db_sub_pruner::DBSubPruner, pruner_utils::get_or_initialize_subpruner_progress},
+    pruner::{
+        db_sub_pruner::DBSubPruner,
+        ledger_pruner::{archival_filter::ArchivalFilter, LedgerSubPruner},
+        pruner_utils::get_or_initialize_subpruner_progress,
+    },
     schema::db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
     TransactionStore,
 };
@@ -10,19 +14,33 @@ use anyhow::Result;
 use aptos_logger::info;
 use aptos_schemadb::{SchemaBatch, DB};
 use aptos_types::transaction::Version;
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 #[derive(Debug)]
 pub struct WriteSetPruner {
     transaction_store: Arc<TransactionStore>,
     write_set_db: Arc<DB>,
+    archival_filter: Arc<ArchivalFilter>,
 }
 
 impl DBSubPruner for WriteSetPruner {
     fn prune(&self, current_progress: Version, target_version: Version) -> Result<()> {
+        self.prune_ranges(
+            &self
+                .archival_filter
+                .ranges_to_prune(current_progress, target_version)?,
+            target_version,
+        )
+    }
+}
+
+impl LedgerSubPruner for WriteSetPruner {
+    fn prune_ranges(&self, ranges: &[Range<Version>], target_version: Version) -> Result<()> {
         let batch = SchemaBatch::new();
-        self.transaction_store
-            .prune_write_set(current_progress, target_version, &batch)?;
+        for range in ranges {
+            self.transaction_store
+                .prune_write_set(range.start, range.end, &batch)?;
+        }
         batch.put::<DbMetadataSchema>(
             &DbMetadataKey::WriteSetPrunerProgress,
             &DbMetadataValue::Version(target_version),
@@ -35,6 +53,7 @@ impl WriteSetPruner {
     pub(in crate::pruner) fn new(
         transaction_store: Arc<TransactionStore>,
         write_set_db: Arc<DB>,
+        archival_filter: Arc<ArchivalFilter>,
         metadata_progress: Version,
     ) -> Result<Self> {
         let progress = get_or_initialize_subpruner_progress(
@@ -46,6 +65,7 @@ impl WriteSetPruner {
         let myself = WriteSetPruner {
             transaction_store,
             write_set_db,
+            archival_filter,
         };
 
         info!(
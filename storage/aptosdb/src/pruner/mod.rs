@@ -5,6 +5,7 @@
 mod db_pruner;
 mod db_sub_pruner;
 mod ledger_pruner;
+mod prune_window;
 mod pruner_manager;
 mod pruner_utils;
 mod pruner_worker;
@@ -0,0 +1,199 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module translates the time and disk size based pruning policies into a prune window in
+//! versions, which is what the pruner managers work with.
+
+use crate::{
+    event_store::EventStore,
+    ledger_db::LedgerDb,
+    metrics::{PRUNER_DYNAMIC_WINDOW, PRUNER_WINDOW},
+    pruner::pruner_utils,
+};
+use anyhow::Result;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_types::transaction::{AtomicVersion, Version};
+use std::{
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant},
+};
+
+/// Looking up block timestamps and RocksDB properties is too expensive to do on every commit.
+pub(crate) const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Returns the estimated on-disk size in bytes of the data a pruner is responsible for.
+pub(crate) type SizeFn = Box<dyn Fn() -> Result<u64> + Send + Sync>;
+
+/// The prune window of a pruner. Without a time or size based policy configured it's simply the
+/// configured `prune_window`, otherwise it's recalculated every `refresh_interval` as the smallest
+/// window satisfying the configured policies, and the configured `prune_window` is only used until
+/// the policies can be calculated.
+pub(crate) struct PruneWindow {
+    pruner_name: &'static str,
+    prune_window: Version,
+    window_secs: Option<u64>,
+    target_size_bytes: Option<u64>,
+    ledger_db: Arc<LedgerDb>,
+    size_fn: SizeFn,
+    refresh_interval: Duration,
+    effective_window: AtomicVersion,
+    last_refreshed: Mutex<Option<Instant>>,
+}
+
+impl PruneWindow {
+    pub fn new(
+        pruner_name: &'static str,
+        prune_window: Version,
+        window_secs: Option<u64>,
+        target_size_bytes: Option<u64>,
+        ledger_db: Arc<LedgerDb>,
+        size_fn: SizeFn,
+        refresh_interval: Duration,
+    ) -> Self {
+        PRUNER_WINDOW
+            .with_label_values(&[pruner_name])
+            .set(prune_window as i64);
+
+        Self {
+            pruner_name,
+            prune_window,
+            window_secs,
+            target_size_bytes,
+            ledger_db,
+            size_fn,
+            refresh_interval,
+            effective_window: AtomicVersion::new(prune_window),
+            last_refreshed: Mutex::new(None),
+        }
+    }
+
+    pub fn get(&self) -> Version {
+        self.effective_window.load(Ordering::SeqCst)
+    }
+
+    fn is_dynamic(&self) -> bool {
+        self.window_secs.is_some() || self.target_size_bytes.is_some()
+    }
+
+    /// Recalculates the window if a policy is configured and the last calculation is stale.
+    pub fn maybe_refresh(&self, latest_version: Version, min_readable_version: Version) {
+        if !self.is_dynamic() {
+            return;
+        }
+        {
+            let mut last_refreshed = self.last_refreshed.lock();
+            if last_refreshed.map_or(false, |t| t.elapsed() < self.refresh_interval) {
+                return;
+            }
+            *last_refreshed = Some(Instant::now());
+        }
+
+        let window_by_time = self.window_secs.and_then(|secs| {
+            self.window_by_time(latest_version, min_readable_version, secs)
+                .map_err(|err| {
+                    warn!(
+                        pruner_name = self.pruner_name,
+                        error = ?err,
+                        "Failed to calculate prune window by time."
+                    )
+                })
+                .ok()
+        });
+        let window_by_size = self.target_size_bytes.and_then(|target_size_bytes| {
+            self.window_by_size(latest_version, min_readable_version, target_size_bytes)
+                .map_err(|err| {
+                    warn!(
+                        pruner_name = self.pruner_name,
+                        error = ?err,
+                        "Failed to calculate prune window by size."
+                    )
+                })
+                .ok()
+                .flatten()
+        });
+
+        let window = match (window_by_time, window_by_size) {
+            (None, None) => self.get(),
+            (Some(w), None) | (None, Some(w)) => w,
+            (Some(w1), Some(w2)) => std::cmp::min(w1, w2),
+        };
+        for (policy, window) in [("time", window_by_time), ("size", window_by_size)] {
+            if let Some(window) = window {
+                PRUNER_DYNAMIC_WINDOW
+                    .with_label_values(&[self.pruner_name, policy])
+                    .set(window.min(i64::MAX as Version) as i64);
+            }
+        }
+
+        let old_window = self.effective_window.swap(window, Ordering::SeqCst);
+        if old_window != window {
+            info!(
+                pruner_name = self.pruner_name,
+                old_window = old_window,
+                window = window,
+                "Prune window updated."
+            );
+        }
+        PRUNER_WINDOW
+            .with_label_values(&[self.pruner_name])
+            .set(window.min(i64::MAX as Version) as i64);
+    }
+
+    /// Number of versions committed in the `window_secs` seconds before the latest block.
+    fn window_by_time(
+        &self,
+        latest_version: Version,
+        min_readable_version: Version,
+        window_secs: u64,
+    ) -> Result<Version> {
+        let event_store = EventStore::new(self.ledger_db.event_db_arc());
+        let block_timestamp = |version| {
+            event_store
+                .get_block_metadata(version)
+                .map(|(_, block)| block.proposed_time())
+        };
+        let cutoff_usecs =
+            block_timestamp(latest_version)?.saturating_sub(window_secs.saturating_mul(1_000_000));
+
+        // Binary search for the first version in a block no older than the cutoff. Block events
+        // of versions already pruned can't be found, those are treated as older than the cutoff.
+        let ledger_min_readable_version =
+            pruner_utils::get_ledger_pruner_progress(&self.ledger_db)?;
+        let mut low = std::cmp::max(min_readable_version, ledger_min_readable_version);
+        let mut high = latest_version;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if block_timestamp(mid).map_or(true, |ts| ts < cutoff_usecs) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(latest_version - low)
+    }
+
+    /// Number of versions expected to fit in `target_size_bytes`, extrapolated from the current
+    /// size of the data in the window. `None` if there is nothing on disk to extrapolate from yet,
+    /// which is normal while the data is only in memtables.
+    fn window_by_size(
+        &self,
+        latest_version: Version,
+        min_readable_version: Version,
+        target_size_bytes: u64,
+    ) -> Result<Option<Version>> {
+        let size_bytes = (self.size_fn)()?;
+        if size_bytes == 0 {
+            return Ok(None);
+        }
+        let num_versions = latest_version.saturating_sub(min_readable_version) + 1;
+        let bytes_per_version = size_bytes as f64 / num_versions as f64;
+        // Float to int casts saturate, so a tiny size can't overflow the window.
+        Ok(Some(
+            (target_size_bytes as f64 / bytes_per_version) as Version,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test;
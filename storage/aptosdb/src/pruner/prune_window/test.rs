@@ -0,0 +1,182 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{pruner::prune_window::PruneWindow, AptosDB};
+use aptos_schemadb::SchemaBatch;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{new_block_event_key, NewBlockEvent},
+    contract_event::ContractEvent,
+    transaction::Version,
+};
+use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+const VERSIONS_PER_BLOCK: Version = 10;
+const SECS_PER_BLOCK: u64 = 100;
+
+/// Saves a block every `VERSIONS_PER_BLOCK` versions in [0, `num_versions`), proposed every
+/// `SECS_PER_BLOCK` seconds.
+fn save_blocks(db: &AptosDB, num_versions: Version) {
+    let batch = SchemaBatch::new();
+    for (seq, version) in (0..num_versions)
+        .step_by(VERSIONS_PER_BLOCK as usize)
+        .enumerate()
+    {
+        let seq = seq as u64;
+        let new_block_event = NewBlockEvent::new(
+            AccountAddress::random(),
+            0,   // epoch
+            seq, // round
+            seq, // height
+            vec![],
+            AccountAddress::random(),
+            vec![],
+            seq * SECS_PER_BLOCK * 1_000_000,
+        );
+        let event = ContractEvent::new(
+            new_block_event_key(),
+            seq,
+            TypeTag::Struct(Box::new(NewBlockEvent::struct_tag())),
+            bcs::to_bytes(&new_block_event).unwrap(),
+        );
+        db.event_store
+            .put_events(version, &[event], /*skip_index=*/ false, &batch)
+            .unwrap();
+    }
+    db.ledger_db.event_db().write_schemas(batch).unwrap();
+}
+
+fn prune_window(
+    db: &AptosDB,
+    versions: Version,
+    window_secs: Option<u64>,
+    target_size_bytes: Option<u64>,
+    size_bytes: Arc<AtomicU64>,
+) -> PruneWindow {
+    PruneWindow::new(
+        "test_pruner",
+        versions,
+        window_secs,
+        target_size_bytes,
+        Arc::clone(&db.ledger_db),
+        Box::new(move || Ok(size_bytes.load(Ordering::SeqCst))),
+        Duration::ZERO,
+    )
+}
+
+#[test]
+fn test_window_by_time() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    save_blocks(&db, 100);
+
+    // The latest block is proposed at 900s, so the cutoff is at 650s and the first block no
+    // older than that is the one at 700s, starting at version 70.
+    let window = prune_window(&db, 0, Some(250), None, Arc::new(AtomicU64::new(0)));
+    assert_eq!(window.get(), 0);
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), 29);
+
+    // The configured window is only used until the window by time is calculated, it doesn't
+    // keep more versions.
+    let window = prune_window(&db, 50, Some(250), None, Arc::new(AtomicU64::new(0)));
+    assert_eq!(window.get(), 50);
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), 29);
+
+    // Versions already pruned are treated as older than the cutoff.
+    let window = prune_window(&db, 0, Some(250), None, Arc::new(AtomicU64::new(0)));
+    window.maybe_refresh(99, 80);
+    assert_eq!(window.get(), 19);
+}
+
+#[test]
+fn test_window_by_size() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let size_bytes = Arc::new(AtomicU64::new(0));
+    let window = prune_window(&db, 10, None, Some(300), Arc::clone(&size_bytes));
+
+    // Nothing on disk yet, e.g. everything is still in memtables: the window is kept.
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), 10);
+
+    // 10 bytes per version.
+    size_bytes.store(1000, Ordering::SeqCst);
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), 30);
+
+    // Back to nothing on disk (e.g. after a compaction of everything pruned): the window is kept
+    // rather than growing unbounded.
+    size_bytes.store(0, Ordering::SeqCst);
+    window.maybe_refresh(199, 100);
+    assert_eq!(window.get(), 30);
+
+    // A tiny size extrapolates to a huge window, which must not overflow.
+    size_bytes.store(1, Ordering::SeqCst);
+    let window = prune_window(&db, 10, None, Some(u64::MAX), Arc::clone(&size_bytes));
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), Version::MAX);
+}
+
+#[test]
+fn test_window_by_time_and_size() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    save_blocks(&db, 100);
+    let size_bytes = Arc::new(AtomicU64::new(1000));
+
+    // The smaller of the two windows wins: 29 versions by time vs 40 by size, and vice versa.
+    let window = prune_window(&db, 0, Some(250), Some(400), Arc::clone(&size_bytes));
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), 29);
+
+    let window = prune_window(&db, 0, Some(250), Some(200), size_bytes);
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), 20);
+}
+
+#[test]
+fn test_static_window() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    save_blocks(&db, 100);
+
+    let window = prune_window(&db, 42, None, None, Arc::new(AtomicU64::new(1000)));
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), 42);
+}
+
+#[test]
+fn test_refresh_interval() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let size_bytes = Arc::new(AtomicU64::new(1000));
+    let window = PruneWindow::new(
+        "test_pruner",
+        0,
+        None,
+        Some(300),
+        Arc::clone(&db.ledger_db),
+        {
+            let size_bytes = Arc::clone(&size_bytes);
+            Box::new(move || Ok(size_bytes.load(Ordering::SeqCst)))
+        },
+        Duration::from_secs(3600),
+    );
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), 30);
+
+    // Not recalculated before the refresh interval elapses.
+    size_bytes.store(3000, Ordering::SeqCst);
+    window.maybe_refresh(99, 0);
+    assert_eq!(window.get(), 30);
+}
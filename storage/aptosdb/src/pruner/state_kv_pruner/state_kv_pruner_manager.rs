@@ -85,7 +85,7 @@ impl StateKvPrunerManager {
         let pruner_worker = if state_kv_pruner_config.enable {
             Some(Self::init_pruner(
                 Arc::clone(&state_kv_db),
-                state_kv_pruner_config,
+                &state_kv_pruner_config,
             ))
         } else {
             None
@@ -109,7 +109,7 @@ impl StateKvPrunerManager {
 
     fn init_pruner(
         state_kv_db: Arc<StateKvDb>,
-        state_kv_pruner_config: LedgerPrunerConfig,
+        state_kv_pruner_config: &LedgerPrunerConfig,
     ) -> PrunerWorker {
         let pruner =
             Arc::new(StateKvPruner::new(state_kv_db).expect("Failed to create state kv pruner."));
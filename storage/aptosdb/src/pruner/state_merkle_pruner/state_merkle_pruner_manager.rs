@@ -5,8 +5,10 @@
 //! meant to be triggered by other threads as they commit new data to the DB.
 
 use crate::{
-    metrics::{PRUNER_BATCH_SIZE, PRUNER_VERSIONS, PRUNER_WINDOW},
+    ledger_db::LedgerDb,
+    metrics::{PRUNER_BATCH_SIZE, PRUNER_VERSIONS},
     pruner::{
+        prune_window::{self, PruneWindow},
         pruner_manager::PrunerManager,
         pruner_utils,
         pruner_worker::PrunerWorker,
@@ -36,7 +38,7 @@ where
 {
     state_merkle_db: Arc<StateMerkleDb>,
     /// DB version window, which dictates how many versions of state merkle data to keep.
-    prune_window: Version,
+    prune_window: PruneWindow,
     /// It is None iff the pruner is not enabled.
     pruner_worker: Option<PrunerWorker>,
     /// The minimal readable version for the state merkle data.
@@ -56,7 +58,7 @@ where
     }
 
     fn get_prune_window(&self) -> Version {
-        self.prune_window
+        self.prune_window.get()
     }
 
     fn get_min_readable_version(&self) -> Version {
@@ -67,6 +69,12 @@ where
     fn maybe_set_pruner_target_db_version(&self, latest_version: Version) {
         // Always wake up the state pruner.
         if self.is_pruner_enabled() {
+            // The in-memory min readable version is not updated as the worker makes progress.
+            let min_readable_version =
+                pruner_utils::get_state_merkle_pruner_progress::<S>(&self.state_merkle_db)
+                    .unwrap_or_else(|_| self.get_min_readable_version());
+            self.prune_window
+                .maybe_refresh(latest_version, min_readable_version);
             self.set_pruner_target_db_version(latest_version);
         }
     }
@@ -105,6 +113,7 @@ where
     /// Creates a worker thread that waits on a channel for pruning commands.
     pub fn new(
         state_merkle_db: Arc<StateMerkleDb>,
+        ledger_db: Arc<LedgerDb>,
         state_merkle_pruner_config: StateMerklePrunerConfig,
     ) -> Self {
         let pruner_worker = if state_merkle_pruner_config.enable {
@@ -123,9 +132,22 @@ where
             .with_label_values(&[S::name(), "min_readable"])
             .set(min_readable_version as i64);
 
+        let prune_window = PruneWindow::new(
+            S::name(),
+            state_merkle_pruner_config.prune_window,
+            state_merkle_pruner_config.prune_window_secs,
+            state_merkle_pruner_config.target_size_bytes,
+            ledger_db,
+            {
+                let state_merkle_db = Arc::clone(&state_merkle_db);
+                Box::new(move || state_merkle_db.approximate_size_bytes())
+            },
+            prune_window::REFRESH_INTERVAL,
+        );
+
         Self {
             state_merkle_db,
-            prune_window: state_merkle_pruner_config.prune_window,
+            prune_window,
             pruner_worker,
             min_readable_version: AtomicVersion::new(min_readable_version),
             _phantom: PhantomData,
@@ -141,10 +163,6 @@ where
                 .expect("Failed to create state merkle pruner."),
         );
 
-        PRUNER_WINDOW
-            .with_label_values(&[S::name()])
-            .set(state_merkle_pruner_config.prune_window as i64);
-
         PRUNER_BATCH_SIZE
             .with_label_values(&[S::name()])
             .set(state_merkle_pruner_config.batch_size as i64);
@@ -161,6 +179,6 @@ where
         self.pruner_worker
             .as_ref()
             .unwrap()
-            .set_target_db_version(latest_version.saturating_sub(self.prune_window));
+            .set_target_db_version(latest_version.saturating_sub(self.get_prune_window()));
     }
 }
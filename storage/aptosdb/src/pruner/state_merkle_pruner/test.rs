@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    ledger_db::LedgerDb,
     new_sharded_kv_schema_batch,
     stale_node_index::StaleNodeIndexSchema,
     stale_state_value_index::StaleStateValueIndexSchema,
@@ -94,13 +95,20 @@ fn verify_state_in_store(
 
 fn create_state_merkle_pruner_manager(
     state_merkle_db: &Arc<StateMerkleDb>,
+    ledger_db: &Arc<LedgerDb>,
     prune_batch_size: usize,
 ) -> StateMerklePrunerManager<StaleNodeIndexSchema> {
-    StateMerklePrunerManager::new(Arc::clone(state_merkle_db), StateMerklePrunerConfig {
-        enable: true,
-        prune_window: 0,
-        batch_size: prune_batch_size,
-    })
+    StateMerklePrunerManager::new(
+        Arc::clone(state_merkle_db),
+        Arc::clone(ledger_db),
+        StateMerklePrunerConfig {
+            enable: true,
+            prune_window: 0,
+            batch_size: prune_batch_size,
+            prune_window_secs: None,
+            target_size_bytes: None,
+        },
+    )
 }
 
 #[test]
@@ -127,8 +135,11 @@ fn test_state_store_pruner() {
     // Prune till version=0. This should basically be a no-op. Create a new pruner everytime to
     // test the min_readable_version initialization logic.
     {
-        let pruner =
-            create_state_merkle_pruner_manager(&aptos_db.state_merkle_db, prune_batch_size);
+        let pruner = create_state_merkle_pruner_manager(
+            &aptos_db.state_merkle_db,
+            &aptos_db.ledger_db,
+            prune_batch_size,
+        );
         pruner.wake_and_wait_pruner(0 /* latest_version */).unwrap();
         for i in 0..num_versions {
             verify_state_in_store(
@@ -144,8 +155,11 @@ fn test_state_store_pruner() {
     // we expect versions 0 to 9 to be pruned. Create a new pruner everytime to test the
     // min_readable_version initialization logic.
     {
-        let pruner =
-            create_state_merkle_pruner_manager(&aptos_db.state_merkle_db, prune_batch_size);
+        let pruner = create_state_merkle_pruner_manager(
+            &aptos_db.state_merkle_db,
+            &aptos_db.ledger_db,
+            prune_batch_size,
+        );
         pruner
             .wake_and_wait_pruner(prune_batch_size as u64 /* latest_version */)
             .unwrap();
@@ -219,8 +233,11 @@ fn test_state_store_pruner_partial_version() {
     // Prune till version=0. This should basically be a no-op. Create a new pruner every time
     // to test the min_readable_version initialization logic.
     {
-        let pruner =
-            create_state_merkle_pruner_manager(&aptos_db.state_merkle_db, prune_batch_size);
+        let pruner = create_state_merkle_pruner_manager(
+            &aptos_db.state_merkle_db,
+            &aptos_db.ledger_db,
+            prune_batch_size,
+        );
         pruner.wake_and_wait_pruner(0 /* latest_version */).unwrap();
         verify_state_in_store(state_store, key1.clone(), Some(&value1), 1);
         verify_state_in_store(state_store, key2.clone(), Some(&value2_update), 1);
@@ -231,8 +248,11 @@ fn test_state_store_pruner_partial_version() {
     // should prune 1 stale node with the version 0. Create a new pruner everytime to test the
     // min_readable_version initialization logic.
     {
-        let pruner =
-            create_state_merkle_pruner_manager(&aptos_db.state_merkle_db, prune_batch_size);
+        let pruner = create_state_merkle_pruner_manager(
+            &aptos_db.state_merkle_db,
+            &aptos_db.ledger_db,
+            prune_batch_size,
+        );
         assert!(pruner.wake_and_wait_pruner(1 /* latest_version */,).is_ok());
         assert!(state_store
             .get_state_value_with_proof_by_version(&key1, 0_u64)
@@ -245,8 +265,11 @@ fn test_state_store_pruner_partial_version() {
     // Prune 3 more times. All version 0 and 1 stale nodes should be gone. Create a new pruner
     // everytime to test the min_readable_version initialization logic.
     {
-        let pruner =
-            create_state_merkle_pruner_manager(&aptos_db.state_merkle_db, prune_batch_size);
+        let pruner = create_state_merkle_pruner_manager(
+            &aptos_db.state_merkle_db,
+            &aptos_db.ledger_db,
+            prune_batch_size,
+        );
         assert!(pruner.wake_and_wait_pruner(2 /* latest_version */,).is_ok());
         assert!(pruner.wake_and_wait_pruner(2 /* latest_version */,).is_ok());
 
@@ -352,6 +375,10 @@ fn verify_state_value_pruner(inputs: Vec<Vec<(StateKey, Option<StateValue>)>>) {
         prune_window: 0,
         batch_size: 1,
         user_pruning_window_offset: 0,
+        prune_window_secs: None,
+        target_size_bytes: None,
+        archived_addresses: vec![],
+        archived_event_types: vec![],
     });
     for batch in inputs {
         update_store(store, batch.clone().into_iter(), version);
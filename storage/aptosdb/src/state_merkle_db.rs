@@ -9,7 +9,10 @@ use crate::{
     schema::jellyfish_merkle_node::JellyfishMerkleNodeSchema,
     stale_node_index::StaleNodeIndexSchema,
     stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
-    utils::truncation_helper::{get_state_merkle_commit_progress, truncate_state_merkle_db_shards},
+    utils::{
+        get_live_sst_files_size,
+        truncation_helper::{get_state_merkle_commit_progress, truncate_state_merkle_db_shards},
+    },
     versioned_node_cache::VersionedNodeCache,
     NUM_STATE_SHARDS, OTHER_TIMERS_SECONDS,
 };
@@ -181,6 +184,18 @@ impl StateMerkleDb {
         Ok(())
     }
 
+    /// Estimated on-disk size of all tree nodes and indices.
+    pub(crate) fn approximate_size_bytes(&self) -> Result<u64> {
+        let cf_names = state_merkle_db_column_families();
+        let mut size = get_live_sst_files_size(&self.state_merkle_metadata_db, &cf_names)?;
+        if self.enable_sharding {
+            for db_shard in self.state_merkle_db_shards.iter() {
+                size += get_live_sst_files_size(db_shard, &cf_names)?;
+            }
+        }
+        Ok(size)
+    }
+
     pub(crate) fn metadata_db(&self) -> &DB {
         &self.state_merkle_metadata_db
     }
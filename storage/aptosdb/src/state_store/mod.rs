@@ -164,6 +164,41 @@ impl DbReader for StateDb {
             .and_then(|((_, version), value_opt)| value_opt.map(|value| (version, value))))
     }
 
+    /// Gets the latest state values and their corresponding versions of the given keys up to the
+    /// given version, seeking the keys of each shard with a single iterator.
+    fn get_state_values_with_version_by_version(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<Option<(Version, StateValue)>>> {
+        let mut key_indices_by_shard = vec![vec![]; self.state_kv_db.num_shards() as usize];
+        for (index, state_key) in state_keys.iter().enumerate() {
+            key_indices_by_shard[state_key.get_shard_id() as usize].push(index);
+        }
+
+        let mut results = vec![None; state_keys.len()];
+        for (shard_id, key_indices) in key_indices_by_shard.into_iter().enumerate() {
+            if key_indices.is_empty() {
+                continue;
+            }
+            let mut read_opts = ReadOptions::default();
+            // We want `None` if the state_key changes in iteration.
+            read_opts.set_prefix_same_as_start(true);
+            let mut iter = self
+                .state_kv_db
+                .db_shard(shard_id as u8)
+                .iter::<StateValueSchema>(read_opts)?;
+            for index in key_indices {
+                iter.seek(&(state_keys[index].clone(), version))?;
+                results[index] = iter
+                    .next()
+                    .transpose()?
+                    .and_then(|((_, version), value_opt)| value_opt.map(|value| (version, value)));
+            }
+        }
+        Ok(results)
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
@@ -259,6 +294,17 @@ impl DbReader for StateStore {
             .get_state_value_with_version_by_version(state_key, version)
     }
 
+    /// Gets the latest state values and their corresponding versions of the given keys up to the
+    /// given version.
+    fn get_state_values_with_version_by_version(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<Option<(Version, StateValue)>>> {
+        self.deref()
+            .get_state_values_with_version_by_version(state_keys, version)
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
@@ -437,10 +483,12 @@ impl StateStore {
 
         let state_merkle_pruner = StateMerklePrunerManager::new(
             Arc::clone(&state_merkle_db),
+            Arc::clone(&ledger_db),
             NO_OP_STORAGE_PRUNER_CONFIG.state_merkle_pruner_config,
         );
         let epoch_snapshot_pruner = StateMerklePrunerManager::new(
             Arc::clone(&state_merkle_db),
+            Arc::clone(&ledger_db),
             NO_OP_STORAGE_PRUNER_CONFIG.state_merkle_pruner_config,
         );
         let state_kv_pruner = StateKvPrunerManager::new(
@@ -14,6 +14,8 @@ use aptos_jellyfish_merkle::{
     node_type::{Node, NodeKey},
     TreeReader,
 };
+use aptos_scratchpad::SparseMerkleTree;
+use aptos_state_view::TStateView;
 use aptos_storage_interface::{
     jmt_update_refs, jmt_updates, DbReader, DbWriter, StateSnapshotReceiver,
 };
@@ -23,7 +25,10 @@ use aptos_types::{
     state_store::state_key::StateKeyTag,
 };
 use proptest::{collection::hash_map, prelude::*};
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 fn put_value_set(
     state_store: &StateStore,
@@ -338,6 +343,165 @@ pub fn test_get_state_snapshot_before() {
     );
 }
 
+#[test]
+fn test_get_state_values_with_version_by_version() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let keys: Vec<_> = (0..20)
+        .map(|i| StateKey::raw(format!("test_key{}", i).into_bytes()))
+        .collect();
+
+    put_value_set(
+        store,
+        keys[..10]
+            .iter()
+            .map(|key| (key.clone(), StateValue::from(b"value0".to_vec())))
+            .collect(),
+        0, /* version */
+        None,
+    );
+    put_value_set(
+        store,
+        keys[5..15]
+            .iter()
+            .map(|key| (key.clone(), StateValue::from(b"value1".to_vec())))
+            .collect(),
+        1, /* version */
+        Some(0),
+    );
+
+    for version in 0..2 {
+        let expected: Vec<_> = keys
+            .iter()
+            .map(|key| {
+                store
+                    .get_state_value_with_version_by_version(key, version)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            store
+                .get_state_values_with_version_by_version(&keys, version)
+                .unwrap(),
+            expected
+        );
+    }
+    assert_eq!(
+        store
+            .get_state_values_with_version_by_version(&keys[..0], 1)
+            .unwrap(),
+        vec![]
+    );
+}
+
+/// Counts the state value reads, to tell the reads served from the cache of a `CachedStateView`
+/// from the ones going to the DB.
+struct CountingReader {
+    store: Arc<StateStore>,
+    num_keys_read: AtomicUsize,
+}
+
+impl DbReader for CountingReader {
+    fn get_state_snapshot_before(
+        &self,
+        next_version: Version,
+    ) -> Result<Option<(Version, HashValue)>> {
+        self.store.get_state_snapshot_before(next_version)
+    }
+
+    fn get_state_value_with_version_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<(Version, StateValue)>> {
+        self.num_keys_read.fetch_add(1, Ordering::SeqCst);
+        self.store
+            .get_state_value_with_version_by_version(state_key, version)
+    }
+
+    fn get_state_values_with_version_by_version(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<Option<(Version, StateValue)>>> {
+        self.num_keys_read
+            .fetch_add(state_keys.len(), Ordering::SeqCst);
+        self.store
+            .get_state_values_with_version_by_version(state_keys, version)
+    }
+
+    fn get_state_proof_by_version_ext(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<SparseMerkleProofExt> {
+        self.store
+            .get_state_proof_by_version_ext(state_key, version)
+    }
+}
+
+#[test]
+fn test_cached_state_view_prefetch() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let keys: Vec<_> = (0..20)
+        .map(|i| StateKey::raw(format!("test_key{}", i).into_bytes()))
+        .collect();
+    let value = StateValue::from(b"value".to_vec());
+    let root = put_value_set(
+        &db.state_store,
+        keys[..10]
+            .iter()
+            .map(|key| (key.clone(), value.clone()))
+            .collect(),
+        0, /* version */
+        None,
+    );
+
+    let reader = Arc::new(CountingReader {
+        store: db.state_store.clone(),
+        num_keys_read: AtomicUsize::new(0),
+    });
+    let view = CachedStateView::new(
+        StateViewId::Miscellaneous,
+        reader.clone(),
+        1, /* next_version */
+        SparseMerkleTree::new(root, StateStorageUsage::new_untracked()),
+        Arc::new(AsyncProofFetcher::new(reader.clone())),
+    )
+    .unwrap();
+
+    // Existing and missing keys are all fetched, and duplicates only once.
+    view.prefetch(keys[5..15].iter().chain(&keys[5..10]));
+    assert_eq!(reader.num_keys_read.load(Ordering::SeqCst), 10);
+
+    // Prefetched keys are hits, both for values and for missing keys, and are not fetched again.
+    assert_eq!(view.get_state_value(&keys[5]).unwrap(), Some(value.clone()));
+    assert_eq!(view.get_state_value(&keys[14]).unwrap(), None);
+    view.prefetch(&keys[5..15]);
+    assert_eq!(reader.num_keys_read.load(Ordering::SeqCst), 10);
+
+    // Other keys are misses.
+    assert_eq!(view.get_state_value(&keys[0]).unwrap(), Some(value.clone()));
+    assert_eq!(reader.num_keys_read.load(Ordering::SeqCst), 11);
+    view.prefetch(&keys[..5]);
+    assert_eq!(reader.num_keys_read.load(Ordering::SeqCst), 15);
+
+    let state_cache = view.into_state_cache();
+    for (i, key) in keys.iter().enumerate() {
+        let cached = state_cache.sharded_state_cache[key.get_shard_id() as usize]
+            .get(key)
+            .map(|entry| entry.value().clone());
+        let expected = match i {
+            0..=9 => Some((Some(0), Some(value.clone()))),
+            10..=14 => Some((None, None)),
+            _ => None,
+        };
+        assert_eq!(cached, expected);
+    }
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
 
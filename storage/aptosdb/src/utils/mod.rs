@@ -6,7 +6,7 @@ pub(crate) mod truncation_helper;
 
 use crate::schema::db_metadata::{DbMetadataKey, DbMetadataSchema};
 use anyhow::Result;
-use aptos_schemadb::DB;
+use aptos_schemadb::{ColumnFamilyName, DB};
 use aptos_types::transaction::Version;
 
 pub(crate) fn get_progress(db: &DB, progress_key: &DbMetadataKey) -> Result<Option<Version>> {
@@ -14,3 +14,12 @@ pub(crate) fn get_progress(db: &DB, progress_key: &DbMetadataKey) -> Result<Opti
         .get::<DbMetadataSchema>(progress_key)?
         .map(|v| v.expect_version()))
 }
+
+/// Sum of the live SST file sizes of the column families, a cheap estimate of how much disk the
+/// data in them takes.
+pub(crate) fn get_live_sst_files_size(db: &DB, cf_names: &[ColumnFamilyName]) -> Result<u64> {
+    cf_names
+        .iter()
+        .map(|cf_name| db.get_property(cf_name, "rocksdb.live-sst-files-size"))
+        .sum()
+}
@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod backup;
+pub mod query;
+pub mod read_transactions;
 pub mod replay_verify;
 pub mod restore;
 pub mod verify;
@@ -0,0 +1,585 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::{EpochHistory, EpochHistoryRestoreController},
+        state_snapshot::{manifest::StateSnapshotBackup, restore::StateSnapshotRestoreController},
+        transaction::{manifest::TransactionBackup, restore::LoadedChunk},
+    },
+    metadata,
+    metadata::{cache::MetadataCacheOpt, StateSnapshotBackupMeta},
+    storage::BackupStorage,
+    utils::{
+        storage_ext::BackupStorageExt, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_db::state_restore::StateSnapshotRestoreMode;
+use aptos_logger::prelude::*;
+use aptos_storage_interface::StateSnapshotReceiver;
+use aptos_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+    write_set::TransactionWrite,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// What to look up in the backup.
+pub enum BackupQuery {
+    /// The value of a state key (e.g. a resource) as of the queried version.
+    StateValue(StateKey),
+    /// The events emitted by the transaction at the queried version.
+    Events,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackupQueryResult {
+    StateValue {
+        version: Version,
+        state_key: StateKey,
+        /// Hex encoded raw bytes of the value, `None` if the key doesn't exist at `version`.
+        value: Option<String>,
+        /// Version of the last write to the key at or before `version`, or the version of the
+        /// state snapshot it's read from if it's not written to since.
+        last_modified_at: Version,
+        /// Version of the epoch-ending-verified ledger info that the proof was checked against.
+        proven_by_ledger_info_at: Version,
+    },
+    Events {
+        version: Version,
+        events: Vec<ContractEvent>,
+        proven_by_ledger_info_at: Version,
+    },
+}
+
+/// Answers point-in-time queries from the backup storage without restoring a DB.
+///
+/// Only the backup files needed are downloaded: for a state value, the transaction chunks
+/// between the nearest state snapshot and the target version are scanned backwards for the last
+/// write to the key, and only if there is none the state snapshot chunks up to the one covering
+/// the key are streamed. Everything read is verified against ledger infos that are in turn
+/// verified by the epoch ending backups (and trusted waypoints).
+pub struct QueryBackupCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    concurrent_downloads: usize,
+    version: Version,
+    query: BackupQuery,
+}
+
+impl QueryBackupCoordinator {
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        metadata_cache_opt: MetadataCacheOpt,
+        trusted_waypoints_opt: TrustedWaypointOpt,
+        concurrent_downloads: usize,
+        version: Version,
+        query: BackupQuery,
+    ) -> Self {
+        Self {
+            storage,
+            metadata_cache_opt,
+            trusted_waypoints_opt,
+            concurrent_downloads,
+            version,
+            query,
+        }
+    }
+
+    pub async fn run(self) -> Result<BackupQueryResult> {
+        info!(version = self.version, "Query backup coordinator started.");
+        let ret = self.run_impl().await;
+        if let Err(e) = &ret {
+            error!(error = ?e, "Query backup coordinator failed.");
+        } else {
+            info!("Query backup coordinator exiting with success.");
+        }
+        ret
+    }
+
+    async fn run_impl(self) -> Result<BackupQueryResult> {
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+        let max_txn_ver = metadata_view
+            .max_transaction_version()?
+            .ok_or_else(|| anyhow!("No transaction backup found."))?;
+        ensure!(
+            self.version <= max_txn_ver,
+            "Version {} is newer than the latest transaction in the backup ({}).",
+            self.version,
+            max_txn_ver,
+        );
+
+        let global_opt = GlobalRestoreOptions {
+            target_version: self.version,
+            trusted_waypoints: Arc::new(self.trusted_waypoints_opt.verify()?),
+            run_mode: Arc::new(RestoreRunMode::Verify),
+            concurrent_downloads: self.concurrent_downloads,
+            replay_concurrency_level: 0, // won't replay, doesn't matter
+        };
+        let epoch_history = Arc::new(
+            EpochHistoryRestoreController::new(
+                metadata_view
+                    .select_epoch_ending_backups(Version::MAX)?
+                    .into_iter()
+                    .map(|backup| backup.manifest)
+                    .collect(),
+                global_opt,
+                Arc::clone(&self.storage),
+            )
+            .run()
+            .await?,
+        );
+
+        match &self.query {
+            BackupQuery::Events => {
+                let chunk = self
+                    .load_chunk_at(&metadata_view, &epoch_history, self.version)
+                    .await?;
+                let proven_by_ledger_info_at = chunk.ledger_info.ledger_info().version();
+                let idx = (self.version - chunk.manifest.first_version) as usize;
+                let events = chunk
+                    .event_vecs
+                    .into_iter()
+                    .nth(idx)
+                    .expect("Chunk covers the version.");
+                Ok(BackupQueryResult::Events {
+                    version: self.version,
+                    events,
+                    proven_by_ledger_info_at,
+                })
+            },
+            BackupQuery::StateValue(state_key) => {
+                let snapshot = metadata_view.select_state_snapshot(self.version)?;
+                let first_txn_version = snapshot.as_ref().map_or(0, |s| s.version + 1);
+                if let Some(res) = self
+                    .find_last_write(&metadata_view, &epoch_history, state_key, first_txn_version)
+                    .await?
+                {
+                    return Ok(res);
+                }
+                let snapshot = snapshot.ok_or_else(|| {
+                    anyhow!(
+                        "Key not written to in transactions [0, {}] and no state snapshot available.",
+                        self.version,
+                    )
+                })?;
+                self.read_from_snapshot(&epoch_history, state_key, snapshot)
+                    .await
+            },
+        }
+    }
+
+    /// Scans the transactions in [`first_version`, `self.version`] backwards for the last write
+    /// to `state_key`, verifying the write set of each transaction scanned.
+    async fn find_last_write(
+        &self,
+        metadata_view: &metadata::view::MetadataView,
+        epoch_history: &Arc<EpochHistory>,
+        state_key: &StateKey,
+        first_version: Version,
+    ) -> Result<Option<BackupQueryResult>> {
+        if first_version > self.version {
+            return Ok(None);
+        }
+        let backups = metadata_view.select_transaction_backups(first_version, self.version)?;
+        for backup in backups.into_iter().rev() {
+            let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+            manifest.verify()?;
+            for chunk_manifest in manifest.chunks.into_iter().rev() {
+                if chunk_manifest.first_version > self.version
+                    || chunk_manifest.last_version < first_version
+                {
+                    continue;
+                }
+                let chunk =
+                    LoadedChunk::load(chunk_manifest, &self.storage, Some(epoch_history)).await?;
+                Self::ensure_ledger_info_verifiable(epoch_history, &chunk.ledger_info)?;
+                let chunk_first_version = chunk.manifest.first_version;
+                let proven_by_ledger_info_at = chunk.ledger_info.ledger_info().version();
+                for (idx, (txn_info, write_set)) in chunk
+                    .txn_infos
+                    .iter()
+                    .zip(chunk.write_sets.iter())
+                    .enumerate()
+                    .rev()
+                {
+                    let version = chunk_first_version + idx as Version;
+                    if version > self.version || version < first_version {
+                        continue;
+                    }
+                    // Every write set scanned is verified, not only the one with the write: a
+                    // write dropped from a later write set would otherwise go unnoticed.
+                    ensure!(
+                        CryptoHash::hash(write_set) == txn_info.state_change_hash(),
+                        "Write set hash mismatch at version {}.",
+                        version,
+                    );
+                    if let Some(write_op) = write_set.get(state_key) {
+                        return Ok(Some(BackupQueryResult::StateValue {
+                            version: self.version,
+                            state_key: state_key.clone(),
+                            value: write_op.as_state_value().map(|v| hex::encode(v.bytes())),
+                            last_modified_at: version,
+                            proven_by_ledger_info_at,
+                        }));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Streams the snapshot chunks up to the one covering `state_key`, verifying each with its
+    /// range proof against the snapshot root hash.
+    async fn read_from_snapshot(
+        &self,
+        epoch_history: &Arc<EpochHistory>,
+        state_key: &StateKey,
+        snapshot: StateSnapshotBackupMeta,
+    ) -> Result<BackupQueryResult> {
+        info!(
+            epoch = snapshot.epoch,
+            version = snapshot.version,
+            "Reading state snapshot."
+        );
+        let manifest: StateSnapshotBackup = self.storage.load_json_file(&snapshot.manifest).await?;
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_bcs_file(&manifest.proof).await?;
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+        let state_root_hash = txn_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        ensure!(
+            state_root_hash == manifest.root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            manifest.root_hash,
+            state_root_hash,
+        );
+        Self::ensure_ledger_info_verifiable(epoch_history, &li)?;
+        epoch_history.verify_ledger_info(&li)?;
+
+        let key_hash: HashValue = CryptoHash::hash(state_key);
+        let total_chunks = manifest.chunks.len();
+        let num_chunks = manifest
+            .chunks
+            .iter()
+            .position(|chunk| chunk.last_key >= key_hash)
+            .map_or(manifest.chunks.len(), |idx| idx + 1);
+        let mut receiver = RestoreRunMode::Verify.get_state_restore_receiver(
+            manifest.version,
+            manifest.root_hash,
+            StateSnapshotRestoreMode::TreeOnly,
+        )?;
+
+        let mut value: Option<StateValue> = None;
+        for chunk in manifest.chunks.into_iter().take(num_chunks) {
+            let blobs =
+                StateSnapshotRestoreController::read_state_value(&self.storage, chunk.blobs)
+                    .await?;
+            let proof = self.storage.load_bcs_file(&chunk.proof).await?;
+            if chunk.last_key >= key_hash {
+                value = blobs
+                    .iter()
+                    .find(|(k, _v)| k == state_key)
+                    .map(|(_k, v)| v.clone());
+            }
+            receiver = tokio::task::spawn_blocking(move || {
+                receiver.add_chunk(blobs, proof)?;
+                Result::<_>::Ok(receiver)
+            })
+            .await??;
+        }
+        if num_chunks == total_chunks {
+            // The key can be beyond the last chunk, in which case its absence is only proven once
+            // the whole tree adds up to the root hash.
+            tokio::task::spawn_blocking(move || receiver.finish()).await??;
+        }
+
+        Ok(BackupQueryResult::StateValue {
+            version: self.version,
+            state_key: state_key.clone(),
+            value: value.map(|v| hex::encode(v.bytes())),
+            last_modified_at: manifest.version,
+            proven_by_ledger_info_at: li.ledger_info().version(),
+        })
+    }
+
+    async fn load_chunk_at(
+        &self,
+        metadata_view: &metadata::view::MetadataView,
+        epoch_history: &Arc<EpochHistory>,
+        version: Version,
+    ) -> Result<LoadedChunk> {
+        let backup = metadata_view
+            .select_transaction_backups(version, version)?
+            .into_iter()
+            .find(|b| b.first_version <= version && b.last_version >= version)
+            .ok_or_else(|| anyhow!("No transaction backup covers version {}.", version))?;
+        let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+        manifest.verify()?;
+        let chunk_manifest = manifest
+            .chunks
+            .into_iter()
+            .find(|c| c.first_version <= version && c.last_version >= version)
+            .ok_or_else(|| anyhow!("No transaction chunk covers version {}.", version))?;
+        let chunk = LoadedChunk::load(chunk_manifest, &self.storage, Some(epoch_history)).await?;
+        Self::ensure_ledger_info_verifiable(epoch_history, &chunk.ledger_info)?;
+        Ok(chunk)
+    }
+
+    /// `EpochHistory::verify_ledger_info` lets ledger infos newer than the known epochs pass with
+    /// a warning, which is not acceptable for answering a query.
+    fn ensure_ledger_info_verifiable(
+        epoch_history: &EpochHistory,
+        li: &LedgerInfoWithSignatures,
+    ) -> Result<()> {
+        ensure!(
+            li.ledger_info().epoch() <= epoch_history.epoch_endings.len() as u64,
+            "Ledger info at version {} is in epoch {}, which is not covered by the epoch ending \
+            backups (until epoch {}).",
+            li.ledger_info().version(),
+            li.ledger_info().epoch(),
+            epoch_history.epoch_endings.len(),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backup_types::{
+            epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+            transaction::{
+                backup::{TransactionBackupController, TransactionBackupOpt},
+                manifest::TransactionBackup,
+            },
+        },
+        coordinators::query::{BackupQuery, BackupQueryResult, QueryBackupCoordinator},
+        metadata::cache::MetadataCacheOpt,
+        storage::{local_fs::LocalFs, BackupStorage, FileHandle},
+        utils::{
+            backup_service_client::BackupServiceClient,
+            storage_ext::BackupStorageExt,
+            test_utils::{start_local_backup_service, tmp_db_with_random_content},
+            GlobalBackupOpt, TrustedWaypointOpt,
+        },
+    };
+    use anyhow::Result;
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        contract_event::ContractEvent,
+        state_store::state_key::StateKey,
+        transaction::{Transaction, TransactionInfo, Version},
+        write_set::{TransactionWrite, WriteSet},
+    };
+    use std::{convert::TryInto, mem::size_of, path::PathBuf, sync::Arc};
+    use tokio::{runtime::Runtime, time::Duration};
+
+    struct TestBackup {
+        rt: Runtime,
+        backup_dir: TempPath,
+        storage: Arc<dyn BackupStorage>,
+        transaction_manifest: FileHandle,
+        write_sets: Vec<WriteSet>,
+    }
+
+    /// Backs up the epoch endings and all the transactions of a DB with random content.
+    fn backup_db_with_random_content() -> TestBackup {
+        let (_src_db_dir, src_db, blocks) = tmp_db_with_random_content();
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let storage: Arc<dyn BackupStorage> =
+            Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+        let (rt, port) = start_local_backup_service(src_db);
+        let client = Arc::new(BackupServiceClient::new(format!(
+            "http://localhost:{}",
+            port
+        )));
+
+        let txns_to_commit: Vec<_> = blocks.iter().flat_map(|(txns, _li)| txns).collect();
+        let max_chunk_size = txns_to_commit
+            .iter()
+            .map(|t| bcs::to_bytes(t.transaction()).unwrap().len())
+            .max()
+            .unwrap() // biggest txn
+            + 115 // size of a serialized TransactionInfo
+            + size_of::<u32>(); // record len header
+        let latest_epoch = blocks.last().unwrap().1.ledger_info().next_block_epoch();
+        rt.block_on(
+            EpochEndingBackupController::new(
+                EpochEndingBackupOpt {
+                    start_epoch: 0,
+                    end_epoch: latest_epoch,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 1024,
+                },
+                Arc::clone(&client),
+                Arc::clone(&storage),
+            )
+            .run(),
+        )
+        .unwrap();
+        let transaction_manifest = rt
+            .block_on(
+                TransactionBackupController::new(
+                    TransactionBackupOpt {
+                        start_version: 0,
+                        num_transactions: txns_to_commit.len(),
+                    },
+                    GlobalBackupOpt { max_chunk_size },
+                    client,
+                    Arc::clone(&storage),
+                )
+                .run(),
+            )
+            .unwrap();
+
+        TestBackup {
+            rt,
+            backup_dir,
+            storage,
+            transaction_manifest,
+            write_sets: txns_to_commit
+                .into_iter()
+                .map(|t| t.write_set().clone())
+                .collect(),
+        }
+    }
+
+    impl TestBackup {
+        fn latest_version(&self) -> Version {
+            self.write_sets.len() as Version - 1
+        }
+
+        /// A key written to by a transaction in the middle of the backup, with the version of its
+        /// last write.
+        fn key_with_last_write(&self) -> (StateKey, Version) {
+            let state_key = self.write_sets[self.write_sets.len() / 2..]
+                .iter()
+                .find_map(|write_set| write_set.iter().next())
+                .map(|(state_key, _write_op)| state_key.clone())
+                .expect("Random content has non-empty write sets.");
+            let last_write = (0..=self.latest_version())
+                .rev()
+                .find(|v| self.write_sets[*v as usize].get(&state_key).is_some())
+                .unwrap();
+            (state_key, last_write)
+        }
+
+        fn query(&self, version: Version, state_key: &StateKey) -> Result<BackupQueryResult> {
+            self.rt.block_on(
+                QueryBackupCoordinator::new(
+                    Arc::clone(&self.storage),
+                    MetadataCacheOpt::new(None::<PathBuf>),
+                    TrustedWaypointOpt::default(),
+                    4,
+                    version,
+                    BackupQuery::StateValue(state_key.clone()),
+                )
+                .run(),
+            )
+        }
+
+        /// Removes `state_key` from the write set at `version` in the backup, leaving the
+        /// transaction infos (and hence the proofs of the chunk) intact.
+        fn drop_write(&self, version: Version, state_key: &StateKey) {
+            let manifest: TransactionBackup = self
+                .rt
+                .block_on(self.storage.load_json_file(&self.transaction_manifest))
+                .unwrap();
+            let chunk = manifest
+                .chunks
+                .into_iter()
+                .find(|c| c.first_version <= version && c.last_version >= version)
+                .unwrap();
+            let path = self.backup_dir.path().join(&chunk.transactions);
+
+            let bytes = std::fs::read(&path).unwrap();
+            let mut records = vec![];
+            let mut rest = bytes.as_slice();
+            while !rest.is_empty() {
+                let (len, tail) = rest.split_at(size_of::<u32>());
+                let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+                records.push(tail[..len].to_vec());
+                rest = &tail[len..];
+            }
+            let idx = (version - chunk.first_version) as usize;
+            let (txn, txn_info, events, write_set): (
+                Transaction,
+                TransactionInfo,
+                Vec<ContractEvent>,
+                WriteSet,
+            ) = bcs::from_bytes(&records[idx]).unwrap();
+            let mut write_set = write_set.into_mut();
+            write_set.as_inner_mut().remove(state_key).unwrap();
+            records[idx] =
+                bcs::to_bytes(&(txn, txn_info, events, write_set.freeze().unwrap())).unwrap();
+
+            let mut tampered = vec![];
+            for record in records {
+                tampered.extend((record.len() as u32).to_be_bytes());
+                tampered.extend(record);
+            }
+            std::fs::write(path, tampered).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_query_state_value() {
+        let backup = backup_db_with_random_content();
+        let (state_key, last_write) = backup.key_with_last_write();
+        let expected_value = backup.write_sets[last_write as usize]
+            .get(&state_key)
+            .unwrap()
+            .as_state_value()
+            .map(|v| hex::encode(v.bytes()));
+
+        match backup.query(backup.latest_version(), &state_key).unwrap() {
+            BackupQueryResult::StateValue {
+                value,
+                last_modified_at,
+                ..
+            } => {
+                assert_eq!(value, expected_value);
+                assert_eq!(last_modified_at, last_write);
+            },
+            BackupQueryResult::Events { .. } => panic!("Expected a state value."),
+        }
+        backup.rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_query_state_value_detects_dropped_write() {
+        let backup = backup_db_with_random_content();
+        let (state_key, last_write) = backup.key_with_last_write();
+        backup.drop_write(last_write, &state_key);
+
+        let err = backup
+            .query(backup.latest_version(), &state_key)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains(&format!(
+                "Write set hash mismatch at version {}",
+                last_write
+            )),
+            "{:?}",
+            err,
+        );
+        backup.rt.shutdown_timeout(Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::EpochHistoryRestoreController,
+        transaction::{manifest::TransactionBackup, restore::LoadedChunk},
+    },
+    metadata,
+    metadata::cache::MetadataCacheOpt,
+    storage::BackupStorage,
+    utils::{
+        storage_ext::BackupStorageExt, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::hash::CryptoHash;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    contract_event::ContractEvent,
+    transaction::{Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
+};
+use itertools::multizip;
+use std::sync::Arc;
+
+/// A transaction read from the backup, together with its outputs.
+pub struct TransactionRecord {
+    pub version: Version,
+    pub transaction: Transaction,
+    pub info: TransactionInfo,
+    pub events: Vec<ContractEvent>,
+    pub write_set: WriteSet,
+}
+
+/// Reads the transactions in [`start_version`, `end_version`] from the backup in order, verifying
+/// them against the epoch ending ledger infos, and hands them to the caller one by one.
+pub struct ReadTransactionsCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    concurrent_downloads: usize,
+    start_version: Version,
+    end_version: Version,
+}
+
+impl ReadTransactionsCoordinator {
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        metadata_cache_opt: MetadataCacheOpt,
+        trusted_waypoints_opt: TrustedWaypointOpt,
+        concurrent_downloads: usize,
+        start_version: Version,
+        end_version: Version,
+    ) -> Self {
+        Self {
+            storage,
+            metadata_cache_opt,
+            trusted_waypoints_opt,
+            concurrent_downloads,
+            start_version,
+            end_version,
+        }
+    }
+
+    pub async fn run(self, f: impl FnMut(TransactionRecord) -> Result<()>) -> Result<()> {
+        info!(
+            start_version = self.start_version,
+            end_version = self.end_version,
+            "Read transactions coordinator started."
+        );
+        let ret = self.run_impl(f).await;
+        if let Err(e) = &ret {
+            error!(error = ?e, "Read transactions coordinator failed.");
+        } else {
+            info!("Read transactions coordinator exiting with success.");
+        }
+        ret
+    }
+
+    async fn run_impl(self, mut f: impl FnMut(TransactionRecord) -> Result<()>) -> Result<()> {
+        ensure!(
+            self.start_version <= self.end_version,
+            "start_version {} is larger than end_version {}.",
+            self.start_version,
+            self.end_version,
+        );
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+        let max_txn_ver = metadata_view
+            .max_transaction_version()?
+            .ok_or_else(|| anyhow!("No transaction backup found."))?;
+        let end_version = std::cmp::min(self.end_version, max_txn_ver);
+        ensure!(
+            self.start_version <= end_version,
+            "start_version {} is newer than the latest transaction in the backup ({}).",
+            self.start_version,
+            max_txn_ver,
+        );
+
+        let global_opt = GlobalRestoreOptions {
+            target_version: end_version,
+            trusted_waypoints: Arc::new(self.trusted_waypoints_opt.verify()?),
+            run_mode: Arc::new(RestoreRunMode::Verify),
+            concurrent_downloads: self.concurrent_downloads,
+            replay_concurrency_level: 0, // won't replay, doesn't matter
+        };
+        let epoch_history = Arc::new(
+            EpochHistoryRestoreController::new(
+                metadata_view
+                    .select_epoch_ending_backups(Version::MAX)?
+                    .into_iter()
+                    .map(|backup| backup.manifest)
+                    .collect(),
+                global_opt,
+                Arc::clone(&self.storage),
+            )
+            .run()
+            .await?,
+        );
+
+        let mut next_version = self.start_version;
+        for backup in metadata_view.select_transaction_backups(self.start_version, end_version)? {
+            let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+            manifest.verify()?;
+            for chunk_manifest in manifest.chunks {
+                if chunk_manifest.last_version < next_version
+                    || chunk_manifest.first_version > end_version
+                {
+                    continue;
+                }
+                ensure!(
+                    chunk_manifest.first_version <= next_version,
+                    "Transactions [{}, {}) missing in the backup.",
+                    next_version,
+                    chunk_manifest.first_version,
+                );
+                let chunk =
+                    LoadedChunk::load(chunk_manifest, &self.storage, Some(&epoch_history)).await?;
+                let chunk_first_version = chunk.manifest.first_version;
+                for (idx, (transaction, info, events, write_set)) in multizip((
+                    chunk.txns,
+                    chunk.txn_infos,
+                    chunk.event_vecs,
+                    chunk.write_sets,
+                ))
+                .enumerate()
+                {
+                    let version = chunk_first_version + idx as Version;
+                    if version < next_version {
+                        continue;
+                    }
+                    if version > end_version {
+                        break;
+                    }
+                    ensure!(
+                        CryptoHash::hash(&write_set) == info.state_change_hash(),
+                        "Write set hash mismatch at version {}.",
+                        version,
+                    );
+                    f(TransactionRecord {
+                        version,
+                        transaction,
+                        info,
+                        events,
+                        write_set,
+                    })?;
+                    next_version = version + 1;
+                }
+            }
+        }
+        ensure!(
+            next_version > end_version,
+            "Transactions [{}, {}] missing in the backup.",
+            next_version,
+            end_version,
+        );
+
+        Ok(())
+    }
+}
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
+#[cfg(feature = "quorum-store-debugger")]
+use aptos_consensus::quorum_store_db_debugger;
 use aptos_db::db_debugger::{checkpoint, ledger, state_tree, truncate};
 use clap::Parser;
 
@@ -14,6 +16,9 @@ pub enum Command {
     #[clap(subcommand)]
     Ledger(ledger::Cmd),
     Truncate(truncate::Cmd),
+    #[cfg(feature = "quorum-store-debugger")]
+    #[clap(subcommand)]
+    QuorumStore(quorum_store_db_debugger::Cmd),
 }
 
 impl Command {
@@ -23,6 +28,8 @@ impl Command {
             Command::Checkpoint(cmd) => cmd.run(),
             Command::Ledger(cmd) => cmd.run(),
             Command::Truncate(cmd) => cmd.run(),
+            #[cfg(feature = "quorum-store-debugger")]
+            Command::QuorumStore(cmd) => cmd.run(),
         }
     }
 }
@@ -0,0 +1,206 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod writer;
+
+use crate::export::writer::ParquetExporter;
+use anyhow::{ensure, Result};
+use aptos_backup_cli::{
+    coordinators::read_transactions::ReadTransactionsCoordinator,
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::{ConcurrentDownloadsOpt, TrustedWaypointOpt},
+};
+use aptos_config::config::{
+    RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_storage_interface::{state_view::DbStateView, DbReader};
+use aptos_types::transaction::Version;
+use aptos_vm::data_cache::AsMoveResolver;
+use clap::{Parser, Subcommand};
+use itertools::multizip;
+use std::{path::PathBuf, sync::Arc};
+
+const BATCH_SIZE: u64 = 10_000;
+
+/// Export transactions, transaction infos, events and write sets over a version range to
+/// partitioned Parquet files.
+#[derive(Subcommand)]
+pub enum Command {
+    #[clap(about = "Export from a DB. Resources and events are also decoded into JSON.")]
+    Db(ExportDbOpt),
+    #[clap(
+        about = "Export from the backup files, verified against the epoch ending ledger infos. \
+        Only the raw bytes of resources and events are exported, since the Move modules to \
+        decode them with are not available."
+    )]
+    Backup(ExportBackupOpt),
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            Command::Db(opt) => opt.run(),
+            Command::Backup(opt) => opt.run().await,
+        }
+    }
+}
+
+#[derive(Parser)]
+pub struct ExportOpt {
+    #[clap(long, value_parser, help = "Directory to write the Parquet files to.")]
+    output_dir: PathBuf,
+    #[clap(long, default_value_t = 0, help = "The first version to export.")]
+    start_version: Version,
+    #[clap(
+        long,
+        help = "The last version to export. [Defaults to the latest version available]"
+    )]
+    end_version: Option<Version>,
+    #[clap(
+        long,
+        default_value_t = 1_000_000,
+        help = "Number of versions covered by each file. Files are aligned to multiples of it."
+    )]
+    versions_per_file: u64,
+}
+
+impl ExportOpt {
+    fn ensure_valid(&self) -> Result<()> {
+        ensure!(
+            self.versions_per_file > 0,
+            "versions_per_file must be positive."
+        );
+        Ok(())
+    }
+}
+
+#[derive(Parser)]
+pub struct ExportDbOpt {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+    #[clap(flatten)]
+    export_opt: ExportOpt,
+    #[clap(long, help = "Skip decoding resources and events into JSON.")]
+    skip_decoding: bool,
+}
+
+impl ExportDbOpt {
+    pub fn run(self) -> Result<()> {
+        let db = AptosDB::open(
+            &self.db_dir,
+            true, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs::default(),
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+        export_db(Arc::new(db), self.export_opt, self.skip_decoding)
+    }
+}
+
+pub(crate) fn export_db(
+    db: Arc<dyn DbReader>,
+    export_opt: ExportOpt,
+    skip_decoding: bool,
+) -> Result<()> {
+    export_opt.ensure_valid()?;
+    let latest_version = db.get_latest_version()?;
+    let end_version = export_opt
+        .end_version
+        .map_or(latest_version, |v| std::cmp::min(v, latest_version));
+    ensure!(
+        export_opt.start_version <= end_version,
+        "start_version {} is newer than end_version {}.",
+        export_opt.start_version,
+        end_version,
+    );
+
+    // Values are decoded with the modules at the latest version, which can read the values
+    // written by earlier versions of the same modules since upgrades are compatible.
+    let state_view = DbStateView {
+        db: Arc::clone(&db),
+        version: Some(latest_version),
+    };
+    let resolver = state_view.as_move_resolver();
+    let annotator = AptosValueAnnotator::new(&resolver);
+    let mut exporter = ParquetExporter::new(
+        export_opt.output_dir,
+        export_opt.versions_per_file,
+        (!skip_decoding).then_some(&annotator),
+    )?;
+
+    let mut start_version = export_opt.start_version;
+    while start_version <= end_version {
+        let limit = std::cmp::min(BATCH_SIZE, end_version - start_version + 1);
+        for (idx, (txn, txn_info, events, write_set)) in multizip((
+            db.get_transaction_iterator(start_version, limit)?,
+            db.get_transaction_info_iterator(start_version, limit)?,
+            db.get_events_iterator(start_version, limit)?,
+            db.get_write_set_iterator(start_version, limit)?,
+        ))
+        .enumerate()
+        {
+            exporter.add(
+                start_version + idx as Version,
+                &txn?,
+                &txn_info?,
+                &events?,
+                &write_set?,
+            )?;
+        }
+        start_version += limit;
+    }
+
+    exporter.finish()
+}
+
+#[derive(Parser)]
+pub struct ExportBackupOpt {
+    #[clap(flatten)]
+    metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    #[clap(flatten)]
+    storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[clap(flatten)]
+    export_opt: ExportOpt,
+}
+
+impl ExportBackupOpt {
+    pub async fn run(self) -> Result<()> {
+        self.export_opt.ensure_valid()?;
+        let mut exporter = ParquetExporter::new(
+            self.export_opt.output_dir,
+            self.export_opt.versions_per_file,
+            None, /* annotator */
+        )?;
+
+        ReadTransactionsCoordinator::new(
+            self.storage.init_storage().await?,
+            self.metadata_cache_opt,
+            self.trusted_waypoints_opt,
+            self.concurrent_downloads.get(),
+            self.export_opt.start_version,
+            self.export_opt.end_version.unwrap_or(Version::MAX),
+        )
+        .run(|record| {
+            exporter.add(
+                record.version,
+                &record.transaction,
+                &record.info,
+                &record.events,
+                &record.write_set,
+            )
+        })
+        .await?;
+
+        exporter.finish()
+    }
+}
@@ -0,0 +1,475 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Buffers the exported rows column by column and writes them out as one Parquet file per table
+//! per partition. Partitions are aligned to multiples of `versions_per_file`, and the files are
+//! laid out as `<output_dir>/<table>/<first_version>-<last_version>.parquet`.
+//!
+//! The schemas below are part of the interface of the export and should only be extended by
+//! appending nullable columns.
+
+use anyhow::Result;
+use aptos_logger::info;
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_storage_interface::state_view::DbStateView;
+use aptos_types::{
+    contract_event::ContractEvent,
+    state_store::state_key::StateKeyInner,
+    transaction::{ExecutionStatus, Transaction, TransactionInfo, TransactionPayload, Version},
+    write_set::{WriteOp, WriteSet},
+};
+use aptos_vm::data_cache::StorageAdapter;
+use arrow::{
+    array::{ArrayRef, BinaryArray, BooleanArray, StringArray, UInt64Array},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    record_batch::RecordBatch,
+};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub(crate) type Annotator<'a> = AptosValueAnnotator<'a, StorageAdapter<'a, DbStateView>>;
+
+pub(crate) struct ParquetExporter<'a> {
+    output_dir: PathBuf,
+    versions_per_file: u64,
+    /// Decodes resources and events into JSON, if the Move modules are available.
+    annotator: Option<&'a Annotator<'a>>,
+    /// First and last version buffered in the current partition.
+    buffered_range: Option<(Version, Version)>,
+    transactions: TransactionColumns,
+    transaction_infos: TransactionInfoColumns,
+    events: EventColumns,
+    write_set_changes: WriteSetChangeColumns,
+}
+
+impl<'a> ParquetExporter<'a> {
+    pub fn new(
+        output_dir: PathBuf,
+        versions_per_file: u64,
+        annotator: Option<&'a Annotator<'a>>,
+    ) -> Result<Self> {
+        for table in [
+            TransactionColumns::TABLE,
+            TransactionInfoColumns::TABLE,
+            EventColumns::TABLE,
+            WriteSetChangeColumns::TABLE,
+        ] {
+            std::fs::create_dir_all(output_dir.join(table))?;
+        }
+
+        Ok(Self {
+            output_dir,
+            versions_per_file,
+            annotator,
+            buffered_range: None,
+            transactions: TransactionColumns::default(),
+            transaction_infos: TransactionInfoColumns::default(),
+            events: EventColumns::default(),
+            write_set_changes: WriteSetChangeColumns::default(),
+        })
+    }
+
+    pub fn add(
+        &mut self,
+        version: Version,
+        txn: &Transaction,
+        txn_info: &TransactionInfo,
+        events: &[ContractEvent],
+        write_set: &WriteSet,
+    ) -> Result<()> {
+        if let Some((first_version, _)) = self.buffered_range {
+            if first_version / self.versions_per_file != version / self.versions_per_file {
+                self.flush()?;
+            }
+        }
+        self.buffered_range = Some(match self.buffered_range {
+            Some((first_version, _)) => (first_version, version),
+            None => (version, version),
+        });
+
+        self.transactions.push(version, txn)?;
+        self.transaction_infos.push(version, txn_info);
+        self.events.push(version, events, self.annotator)?;
+        self.write_set_changes
+            .push(version, write_set, self.annotator)?;
+        Ok(())
+    }
+
+    /// Writes out whatever is buffered. Must be called after the last `add()`.
+    pub fn finish(mut self) -> Result<()> {
+        self.flush()
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        let (first_version, last_version) = match self.buffered_range.take() {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+        let file_name = format!("{}-{}.parquet", first_version, last_version);
+
+        self.write_table(
+            TransactionColumns::TABLE,
+            &file_name,
+            self.transactions.take_batch()?,
+        )?;
+        self.write_table(
+            TransactionInfoColumns::TABLE,
+            &file_name,
+            self.transaction_infos.take_batch()?,
+        )?;
+        self.write_table(EventColumns::TABLE, &file_name, self.events.take_batch()?)?;
+        self.write_table(
+            WriteSetChangeColumns::TABLE,
+            &file_name,
+            self.write_set_changes.take_batch()?,
+        )?;
+
+        info!(
+            first_version = first_version,
+            last_version = last_version,
+            output_dir = ?self.output_dir,
+            "Exported versions."
+        );
+        Ok(())
+    }
+
+    fn write_table(&self, table: &str, file_name: &str, batch: RecordBatch) -> Result<()> {
+        write_parquet(&self.output_dir.join(table).join(file_name), batch)
+    }
+}
+
+fn write_parquet(path: &Path, batch: RecordBatch) -> Result<()> {
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[derive(Default)]
+struct TransactionColumns {
+    version: Vec<u64>,
+    transaction_type: Vec<String>,
+    sender: Vec<Option<String>>,
+    sequence_number: Vec<Option<u64>>,
+    max_gas_amount: Vec<Option<u64>>,
+    gas_unit_price: Vec<Option<u64>>,
+    expiration_timestamp_secs: Vec<Option<u64>>,
+    payload_type: Vec<Option<String>>,
+    entry_function: Vec<Option<String>>,
+    bcs: Vec<Vec<u8>>,
+}
+
+impl TransactionColumns {
+    const TABLE: &'static str = "transactions";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("version", DataType::UInt64, false),
+            Field::new("transaction_type", DataType::Utf8, false),
+            Field::new("sender", DataType::Utf8, true),
+            Field::new("sequence_number", DataType::UInt64, true),
+            Field::new("max_gas_amount", DataType::UInt64, true),
+            Field::new("gas_unit_price", DataType::UInt64, true),
+            Field::new("expiration_timestamp_secs", DataType::UInt64, true),
+            Field::new("payload_type", DataType::Utf8, true),
+            Field::new("entry_function", DataType::Utf8, true),
+            Field::new("bcs", DataType::Binary, false),
+        ]))
+    }
+
+    fn push(&mut self, version: Version, txn: &Transaction) -> Result<()> {
+        let transaction_type = match txn {
+            Transaction::UserTransaction(_) => "user",
+            Transaction::GenesisTransaction(_) => "genesis",
+            Transaction::BlockMetadata(_) => "block_metadata",
+            Transaction::StateCheckpoint(_) => "state_checkpoint",
+        };
+        let user_txn = txn.try_as_signed_user_txn();
+        let (payload_type, entry_function) = match user_txn.map(|txn| txn.payload()) {
+            Some(TransactionPayload::Script(_)) => (Some("script"), None),
+            Some(TransactionPayload::ModuleBundle(_)) => (Some("module_bundle"), None),
+            Some(TransactionPayload::EntryFunction(entry_function)) => (
+                Some("entry_function"),
+                Some(format!(
+                    "{}::{}",
+                    entry_function.module().short_str_lossless(),
+                    entry_function.function()
+                )),
+            ),
+            Some(TransactionPayload::Multisig(_)) => (Some("multisig"), None),
+            None => (None, None),
+        };
+
+        self.version.push(version);
+        self.transaction_type.push(transaction_type.to_string());
+        self.sender
+            .push(user_txn.map(|txn| txn.sender().to_hex_literal()));
+        self.sequence_number
+            .push(user_txn.map(|txn| txn.sequence_number()));
+        self.max_gas_amount
+            .push(user_txn.map(|txn| txn.max_gas_amount()));
+        self.gas_unit_price
+            .push(user_txn.map(|txn| txn.gas_unit_price()));
+        self.expiration_timestamp_secs
+            .push(user_txn.map(|txn| txn.expiration_timestamp_secs()));
+        self.payload_type
+            .push(payload_type.map(ToString::to_string));
+        self.entry_function.push(entry_function);
+        self.bcs.push(bcs::to_bytes(txn)?);
+        Ok(())
+    }
+
+    fn take_batch(&mut self) -> Result<RecordBatch> {
+        let columns = std::mem::take(self);
+        Ok(RecordBatch::try_new(Self::schema(), vec![
+            Arc::new(UInt64Array::from(columns.version)) as ArrayRef,
+            Arc::new(StringArray::from(columns.transaction_type)),
+            Arc::new(StringArray::from(columns.sender)),
+            Arc::new(UInt64Array::from(columns.sequence_number)),
+            Arc::new(UInt64Array::from(columns.max_gas_amount)),
+            Arc::new(UInt64Array::from(columns.gas_unit_price)),
+            Arc::new(UInt64Array::from(columns.expiration_timestamp_secs)),
+            Arc::new(StringArray::from(columns.payload_type)),
+            Arc::new(StringArray::from(columns.entry_function)),
+            Arc::new(BinaryArray::from_iter_values(columns.bcs)),
+        ])?)
+    }
+}
+
+#[derive(Default)]
+struct TransactionInfoColumns {
+    version: Vec<u64>,
+    transaction_hash: Vec<String>,
+    state_change_hash: Vec<String>,
+    event_root_hash: Vec<String>,
+    state_checkpoint_hash: Vec<Option<String>>,
+    gas_used: Vec<u64>,
+    success: Vec<bool>,
+    vm_status: Vec<String>,
+}
+
+impl TransactionInfoColumns {
+    const TABLE: &'static str = "transaction_infos";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("version", DataType::UInt64, false),
+            Field::new("transaction_hash", DataType::Utf8, false),
+            Field::new("state_change_hash", DataType::Utf8, false),
+            Field::new("event_root_hash", DataType::Utf8, false),
+            Field::new("state_checkpoint_hash", DataType::Utf8, true),
+            Field::new("gas_used", DataType::UInt64, false),
+            Field::new("success", DataType::Boolean, false),
+            Field::new("vm_status", DataType::Utf8, false),
+        ]))
+    }
+
+    fn push(&mut self, version: Version, txn_info: &TransactionInfo) {
+        self.version.push(version);
+        self.transaction_hash
+            .push(txn_info.transaction_hash().to_hex_literal());
+        self.state_change_hash
+            .push(txn_info.state_change_hash().to_hex_literal());
+        self.event_root_hash
+            .push(txn_info.event_root_hash().to_hex_literal());
+        self.state_checkpoint_hash.push(
+            txn_info
+                .state_checkpoint_hash()
+                .map(|hash| hash.to_hex_literal()),
+        );
+        self.gas_used.push(txn_info.gas_used());
+        self.success
+            .push(matches!(txn_info.status(), ExecutionStatus::Success));
+        self.vm_status.push(format!("{:?}", txn_info.status()));
+    }
+
+    fn take_batch(&mut self) -> Result<RecordBatch> {
+        let columns = std::mem::take(self);
+        Ok(RecordBatch::try_new(Self::schema(), vec![
+            Arc::new(UInt64Array::from(columns.version)) as ArrayRef,
+            Arc::new(StringArray::from(columns.transaction_hash)),
+            Arc::new(StringArray::from(columns.state_change_hash)),
+            Arc::new(StringArray::from(columns.event_root_hash)),
+            Arc::new(StringArray::from(columns.state_checkpoint_hash)),
+            Arc::new(UInt64Array::from(columns.gas_used)),
+            Arc::new(BooleanArray::from(columns.success)),
+            Arc::new(StringArray::from(columns.vm_status)),
+        ])?)
+    }
+}
+
+#[derive(Default)]
+struct EventColumns {
+    version: Vec<u64>,
+    event_index: Vec<u64>,
+    creator_address: Vec<String>,
+    creation_number: Vec<u64>,
+    sequence_number: Vec<u64>,
+    type_tag: Vec<String>,
+    data: Vec<Vec<u8>>,
+    data_json: Vec<Option<String>>,
+}
+
+impl EventColumns {
+    const TABLE: &'static str = "events";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("version", DataType::UInt64, false),
+            Field::new("event_index", DataType::UInt64, false),
+            Field::new("creator_address", DataType::Utf8, false),
+            Field::new("creation_number", DataType::UInt64, false),
+            Field::new("sequence_number", DataType::UInt64, false),
+            Field::new("type_tag", DataType::Utf8, false),
+            Field::new("data", DataType::Binary, false),
+            Field::new("data_json", DataType::Utf8, true),
+        ]))
+    }
+
+    fn push(
+        &mut self,
+        version: Version,
+        events: &[ContractEvent],
+        annotator: Option<&Annotator>,
+    ) -> Result<()> {
+        for (idx, event) in events.iter().enumerate() {
+            self.version.push(version);
+            self.event_index.push(idx as u64);
+            self.creator_address
+                .push(event.key().get_creator_address().to_hex_literal());
+            self.creation_number.push(event.key().get_creation_number());
+            self.sequence_number.push(event.sequence_number());
+            self.type_tag.push(event.type_tag().to_string());
+            self.data.push(event.event_data().to_vec());
+            // Leave the JSON empty if the type can't be resolved, the raw bytes are still there.
+            let data_json = match annotator.and_then(|a| a.view_contract_event(event).ok()) {
+                Some(value) => Some(serde_json::to_string(&value)?),
+                None => None,
+            };
+            self.data_json.push(data_json);
+        }
+        Ok(())
+    }
+
+    fn take_batch(&mut self) -> Result<RecordBatch> {
+        let columns = std::mem::take(self);
+        Ok(RecordBatch::try_new(Self::schema(), vec![
+            Arc::new(UInt64Array::from(columns.version)) as ArrayRef,
+            Arc::new(UInt64Array::from(columns.event_index)),
+            Arc::new(StringArray::from(columns.creator_address)),
+            Arc::new(UInt64Array::from(columns.creation_number)),
+            Arc::new(UInt64Array::from(columns.sequence_number)),
+            Arc::new(StringArray::from(columns.type_tag)),
+            Arc::new(BinaryArray::from_iter_values(columns.data)),
+            Arc::new(StringArray::from(columns.data_json)),
+        ])?)
+    }
+}
+
+#[derive(Default)]
+struct WriteSetChangeColumns {
+    version: Vec<u64>,
+    change_index: Vec<u64>,
+    change_type: Vec<String>,
+    state_key_type: Vec<String>,
+    address: Vec<Option<String>>,
+    resource_type: Vec<Option<String>>,
+    table_handle: Vec<Option<String>>,
+    state_key_bcs: Vec<Vec<u8>>,
+    value: Vec<Option<Vec<u8>>>,
+    value_json: Vec<Option<String>>,
+}
+
+impl WriteSetChangeColumns {
+    const TABLE: &'static str = "write_set_changes";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("version", DataType::UInt64, false),
+            Field::new("change_index", DataType::UInt64, false),
+            Field::new("change_type", DataType::Utf8, false),
+            Field::new("state_key_type", DataType::Utf8, false),
+            Field::new("address", DataType::Utf8, true),
+            Field::new("resource_type", DataType::Utf8, true),
+            Field::new("table_handle", DataType::Utf8, true),
+            Field::new("state_key_bcs", DataType::Binary, false),
+            Field::new("value", DataType::Binary, true),
+            Field::new("value_json", DataType::Utf8, true),
+        ]))
+    }
+
+    fn push(
+        &mut self,
+        version: Version,
+        write_set: &WriteSet,
+        annotator: Option<&Annotator>,
+    ) -> Result<()> {
+        for (idx, (state_key, write_op)) in write_set.iter().enumerate() {
+            let change_type = match write_op {
+                WriteOp::Creation(_) | WriteOp::CreationWithMetadata { .. } => "creation",
+                WriteOp::Modification(_) | WriteOp::ModificationWithMetadata { .. } => {
+                    "modification"
+                },
+                WriteOp::Deletion | WriteOp::DeletionWithMetadata { .. } => "deletion",
+            };
+            let (state_key_type, address, resource_type, table_handle) = match state_key.inner() {
+                StateKeyInner::AccessPath(access_path) => (
+                    "access_path",
+                    Some(access_path.address.to_hex_literal()),
+                    access_path.get_struct_tag().map(|tag| tag.to_string()),
+                    None,
+                ),
+                StateKeyInner::TableItem { handle, .. } => {
+                    ("table_item", None, None, Some(handle.0.to_hex_literal()))
+                },
+                StateKeyInner::Raw(_) => ("raw", None, None, None),
+            };
+            let value_json = match (state_key.inner(), write_op.bytes(), annotator) {
+                (StateKeyInner::AccessPath(access_path), Some(bytes), Some(annotator))
+                    if access_path.get_struct_tag().is_some() =>
+                {
+                    match annotator.view_access_path(access_path.clone(), bytes) {
+                        Ok(value) => Some(serde_json::to_string(&value)?),
+                        Err(_) => None,
+                    }
+                },
+                _ => None,
+            };
+
+            self.version.push(version);
+            self.change_index.push(idx as u64);
+            self.change_type.push(change_type.to_string());
+            self.state_key_type.push(state_key_type.to_string());
+            self.address.push(address);
+            self.resource_type.push(resource_type);
+            self.table_handle.push(table_handle);
+            self.state_key_bcs.push(bcs::to_bytes(state_key)?);
+            self.value.push(write_op.bytes().map(<[u8]>::to_vec));
+            self.value_json.push(value_json);
+        }
+        Ok(())
+    }
+
+    fn take_batch(&mut self) -> Result<RecordBatch> {
+        let columns = std::mem::take(self);
+        Ok(RecordBatch::try_new(Self::schema(), vec![
+            Arc::new(UInt64Array::from(columns.version)) as ArrayRef,
+            Arc::new(UInt64Array::from(columns.change_index)),
+            Arc::new(StringArray::from(columns.change_type)),
+            Arc::new(StringArray::from(columns.state_key_type)),
+            Arc::new(StringArray::from(columns.address)),
+            Arc::new(StringArray::from(columns.resource_type)),
+            Arc::new(StringArray::from(columns.table_handle)),
+            Arc::new(BinaryArray::from_iter_values(columns.state_key_bcs)),
+            Arc::new(columns.value.into_iter().collect::<BinaryArray>()),
+            Arc::new(StringArray::from(columns.value_json)),
+        ])?)
+    }
+}
@@ -6,6 +6,9 @@ extern crate core;
 mod backup;
 mod backup_maintenance;
 mod debugger;
+mod export;
+mod partitioner_replay;
+mod query_backup;
 mod replay_verify;
 pub mod restore;
 #[cfg(test)]
@@ -23,10 +26,15 @@ pub enum DBTool {
     #[clap(subcommand)]
     Restore(restore::Command),
     ReplayVerify(replay_verify::Opt),
+    QueryBackup(query_backup::Opt),
     #[clap(subcommand)]
     Debug(debugger::Command),
     #[clap(subcommand)]
     BackupMaintenance(backup_maintenance::Command),
+    #[clap(subcommand)]
+    Export(export::Command),
+    #[clap(subcommand)]
+    PartitionerReplay(partitioner_replay::Command),
 }
 
 impl DBTool {
@@ -35,8 +43,11 @@ impl DBTool {
             DBTool::Backup(cmd) => cmd.run().await,
             DBTool::Restore(cmd) => cmd.run().await,
             DBTool::ReplayVerify(cmd) => cmd.run().await,
+            DBTool::QueryBackup(cmd) => cmd.run().await,
             DBTool::BackupMaintenance(cmd) => cmd.run().await,
             DBTool::Debug(cmd) => cmd.run(),
+            DBTool::Export(cmd) => cmd.run().await,
+            DBTool::PartitionerReplay(cmd) => cmd.run().await,
         }
     }
 }
@@ -0,0 +1,199 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use aptos_backup_cli::{
+    coordinators::read_transactions::ReadTransactionsCoordinator,
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::{ConcurrentDownloadsOpt, TrustedWaypointOpt},
+};
+use aptos_block_partitioner::{evaluation::PartitionerEvaluator, BlockPartitionerConfig};
+use aptos_config::config::{
+    RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_storage_interface::DbReader;
+use aptos_types::{block_executor::partitioner::RoundId, transaction::Version};
+use clap::{Parser, Subcommand};
+use itertools::iproduct;
+use std::{path::PathBuf, sync::Arc};
+
+const BATCH_SIZE: u64 = 10_000;
+
+/// Replay the blocks of a version range through the block partitioner with each combination of
+/// the given settings, and report the cross-shard edges, rounds used, shard load balance and
+/// partitioning latency of each. Only user transactions are partitioned, and a block cut by the
+/// version range is skipped.
+#[derive(Subcommand)]
+pub enum Command {
+    #[clap(about = "Replay the blocks from a DB.")]
+    Db(ReplayDbOpt),
+    #[clap(
+        about = "Replay the blocks from the backup files, verified against the epoch ending \
+        ledger infos."
+    )]
+    Backup(ReplayBackupOpt),
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            Command::Db(opt) => opt.run(),
+            Command::Backup(opt) => opt.run().await,
+        }
+    }
+}
+
+#[derive(Parser)]
+pub struct PartitionerReplayOpt {
+    #[clap(long, default_value_t = 0, help = "The first version to replay.")]
+    start_version: Version,
+    #[clap(
+        long,
+        help = "The last version to replay. [Defaults to the latest version available]"
+    )]
+    end_version: Option<Version>,
+    #[clap(
+        long,
+        num_args = 1..,
+        default_values_t = [4, 8, 16],
+        help = "Numbers of shards to evaluate."
+    )]
+    num_shards: Vec<usize>,
+    #[clap(
+        long,
+        num_args = 1..,
+        default_values_t = [2, 4],
+        help = "Max partitioning rounds to evaluate."
+    )]
+    max_partitioning_rounds: Vec<RoundId>,
+    #[clap(
+        long,
+        num_args = 1..,
+        default_values_t = [0.9],
+        help = "Cross-shard dependency avoid thresholds to evaluate."
+    )]
+    cross_shard_dep_avoid_threshold: Vec<f32>,
+    #[clap(
+        long,
+        num_args = 1..,
+        default_values_t = [true],
+        help = "Whether to partition the last round, values to evaluate."
+    )]
+    partition_last_round: Vec<bool>,
+}
+
+impl PartitionerReplayOpt {
+    fn evaluator(&self) -> Result<PartitionerEvaluator> {
+        ensure!(
+            self.max_partitioning_rounds
+                .iter()
+                .all(|rounds| *rounds > 0),
+            "max_partitioning_rounds must be positive."
+        );
+        let configs = iproduct!(
+            self.num_shards.iter(),
+            self.max_partitioning_rounds.iter(),
+            self.cross_shard_dep_avoid_threshold.iter(),
+            self.partition_last_round.iter()
+        )
+        .map(|(num_shards, rounds, threshold, partition_last_round)| {
+            BlockPartitionerConfig::default()
+                .num_shards(*num_shards)
+                .max_partitioning_rounds(*rounds)
+                .cross_shard_dep_avoid_threshold(*threshold)
+                .partition_last_round(*partition_last_round)
+        })
+        .collect();
+        Ok(PartitionerEvaluator::new(configs))
+    }
+}
+
+#[derive(Parser)]
+pub struct ReplayDbOpt {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+    #[clap(flatten)]
+    replay_opt: PartitionerReplayOpt,
+}
+
+impl ReplayDbOpt {
+    pub fn run(self) -> Result<()> {
+        let db = AptosDB::open(
+            &self.db_dir,
+            true, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs::default(),
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+        let evaluator = replay_db(Arc::new(db), &self.replay_opt)?;
+        print!("{}", evaluator);
+        Ok(())
+    }
+}
+
+pub(crate) fn replay_db(
+    db: Arc<dyn DbReader>,
+    replay_opt: &PartitionerReplayOpt,
+) -> Result<PartitionerEvaluator> {
+    let mut evaluator = replay_opt.evaluator()?;
+    let latest_version = db.get_latest_version()?;
+    let end_version = replay_opt
+        .end_version
+        .map_or(latest_version, |v| std::cmp::min(v, latest_version));
+    ensure!(
+        replay_opt.start_version <= end_version,
+        "start_version {} is newer than end_version {}.",
+        replay_opt.start_version,
+        end_version,
+    );
+
+    let mut start_version = replay_opt.start_version;
+    while start_version <= end_version {
+        let limit = std::cmp::min(BATCH_SIZE, end_version - start_version + 1);
+        for txn in db.get_transaction_iterator(start_version, limit)? {
+            evaluator.add_transaction(txn?);
+        }
+        start_version += limit;
+    }
+    Ok(evaluator)
+}
+
+#[derive(Parser)]
+pub struct ReplayBackupOpt {
+    #[clap(flatten)]
+    metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    #[clap(flatten)]
+    storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[clap(flatten)]
+    replay_opt: PartitionerReplayOpt,
+}
+
+impl ReplayBackupOpt {
+    pub async fn run(self) -> Result<()> {
+        let mut evaluator = self.replay_opt.evaluator()?;
+        ReadTransactionsCoordinator::new(
+            self.storage.init_storage().await?,
+            self.metadata_cache_opt,
+            self.trusted_waypoints_opt,
+            self.concurrent_downloads.get(),
+            self.replay_opt.start_version,
+            self.replay_opt.end_version.unwrap_or(Version::MAX),
+        )
+        .run(|record| {
+            evaluator.add_transaction(record.transaction);
+            Ok(())
+        })
+        .await?;
+        print!("{}", evaluator);
+        Ok(())
+    }
+}
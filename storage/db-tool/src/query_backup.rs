@@ -0,0 +1,83 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use aptos_backup_cli::{
+    coordinators::query::{BackupQuery, QueryBackupCoordinator},
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::{ConcurrentDownloadsOpt, TrustedWaypointOpt},
+};
+use aptos_types::{
+    access_path::AccessPath, account_address::AccountAddress, state_store::state_key::StateKey,
+    transaction::Version,
+};
+use clap::Parser;
+use move_core_types::language_storage::StructTag;
+
+/// Look up a resource or the events of a transaction at a version straight from the backup,
+/// without restoring a DB. Results are verified against the epoch ending ledger infos in the
+/// backup and printed as JSON.
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(flatten)]
+    metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    #[clap(flatten)]
+    storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[clap(long, help = "The version to query at.")]
+    version: Version,
+    #[clap(long, requires = "resource", help = "Account address of the resource.")]
+    address: Option<AccountAddress>,
+    #[clap(
+        long,
+        requires = "address",
+        help = "Struct tag of the resource, e.g. 0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+    )]
+    resource: Option<StructTag>,
+    #[clap(
+        long,
+        conflicts_with_all = ["address", "resource", "events"],
+        help = "Hex encoded BCS bytes of an arbitrary state key."
+    )]
+    state_key_hex: Option<String>,
+    #[clap(
+        long,
+        conflicts_with_all = ["address", "resource"],
+        help = "Query the events emitted by the transaction at the version instead."
+    )]
+    events: bool,
+}
+
+impl Opt {
+    pub async fn run(self) -> Result<()> {
+        let query = if self.events {
+            BackupQuery::Events
+        } else if let Some(hex_str) = &self.state_key_hex {
+            BackupQuery::StateValue(bcs::from_bytes(&hex::decode(hex_str)?)?)
+        } else if let (Some(address), Some(resource)) = (self.address, self.resource) {
+            BackupQuery::StateValue(StateKey::access_path(AccessPath::resource_access_path(
+                address, resource,
+            )?))
+        } else {
+            bail!("Specify one of --events, --state-key-hex or --address with --resource.");
+        };
+
+        let res = QueryBackupCoordinator::new(
+            self.storage.init_storage().await?,
+            self.metadata_cache_opt,
+            self.trusted_waypoints_opt,
+            self.concurrent_downloads.get(),
+            self.version,
+            query,
+        )
+        .run()
+        .await?;
+        println!("{}", serde_json::to_string_pretty(&res)?);
+
+        Ok(())
+    }
+}
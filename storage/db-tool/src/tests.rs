@@ -64,6 +64,71 @@ fn test_various_cmd_parsing() {
         "--start-version",
         "Max",
     ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "query-backup",
+        "--local-fs-dir",
+        ".",
+        "--version",
+        "100",
+        "--address",
+        "0x1",
+        "--resource",
+        "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "query-backup",
+        "--local-fs-dir",
+        ".",
+        "--version",
+        "100",
+        "--events",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "export",
+        "db",
+        "--db-dir",
+        ".",
+        "--output-dir",
+        ".",
+        "--start-version",
+        "100",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "export",
+        "backup",
+        "--local-fs-dir",
+        ".",
+        "--output-dir",
+        ".",
+        "--versions-per-file",
+        "1000",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "partitioner-replay",
+        "db",
+        "--db-dir",
+        ".",
+        "--num-shards",
+        "4",
+        "8",
+        "--cross-shard-dep-avoid-threshold",
+        "0.8",
+        "0.9",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "partitioner-replay",
+        "backup",
+        "--local-fs-dir",
+        ".",
+        "--end-version",
+        "1000",
+    ]);
 }
 
 fn run_cmd(args: &[&str]) {
@@ -547,3 +612,123 @@ mod compaction_tests {
         rt.shutdown_timeout(Duration::from_secs(1));
     }
 }
+
+#[cfg(test)]
+mod export_tests {
+    use crate::export::{export_db, ExportOpt};
+    use aptos_executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+    use aptos_storage_interface::DbReader;
+    use aptos_temppath::TempPath;
+    use clap::Parser;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::{fs::File, path::Path};
+
+    fn num_rows(table_dir: &Path) -> (usize, i64) {
+        let mut num_files = 0;
+        let mut num_rows = 0;
+        for entry in std::fs::read_dir(table_dir).unwrap() {
+            let reader =
+                SerializedFileReader::new(File::open(entry.unwrap().path()).unwrap()).unwrap();
+            num_files += 1;
+            num_rows += reader.metadata().file_metadata().num_rows();
+        }
+        (num_files, num_rows)
+    }
+
+    #[test]
+    fn test_export_db() {
+        let db = test_execution_with_storage_impl();
+        let latest_version = db.get_latest_version().unwrap();
+        let output_dir = TempPath::new();
+
+        let export_opt = ExportOpt::try_parse_from([
+            "export",
+            "--output-dir",
+            output_dir.path().to_str().unwrap(),
+            "--versions-per-file",
+            "10",
+        ])
+        .unwrap();
+        export_db(db.clone(), export_opt, false /* skip_decoding */).unwrap();
+
+        let num_versions = latest_version + 1;
+        let (num_files, num_txns) = num_rows(&output_dir.path().join("transactions"));
+        assert_eq!(num_files as u64, (num_versions + 9) / 10);
+        assert_eq!(num_txns as u64, num_versions);
+        let (_, num_txn_infos) = num_rows(&output_dir.path().join("transaction_infos"));
+        assert_eq!(num_txn_infos as u64, num_versions);
+
+        let num_events: usize = db
+            .get_events_iterator(0, num_versions)
+            .unwrap()
+            .map(|events| events.unwrap().len())
+            .sum();
+        let (_, num_event_rows) = num_rows(&output_dir.path().join("events"));
+        assert_eq!(num_event_rows as usize, num_events);
+
+        let num_changes: usize = db
+            .get_write_set_iterator(0, num_versions)
+            .unwrap()
+            .map(|write_set| write_set.unwrap().iter().count())
+            .sum();
+        let (_, num_change_rows) = num_rows(&output_dir.path().join("write_set_changes"));
+        assert_eq!(num_change_rows as usize, num_changes);
+    }
+}
+
+#[cfg(test)]
+mod partitioner_replay_tests {
+    use crate::partitioner_replay::{replay_db, PartitionerReplayOpt};
+    use aptos_executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+    use aptos_storage_interface::DbReader;
+    use aptos_types::transaction::Transaction;
+    use clap::Parser;
+
+    #[test]
+    fn test_replay_db() {
+        let db = test_execution_with_storage_impl();
+        let latest_version = db.get_latest_version().unwrap();
+        let replay_opt = PartitionerReplayOpt::try_parse_from([
+            "partitioner-replay",
+            "--num-shards",
+            "1",
+            "4",
+            "--max-partitioning-rounds",
+            "2",
+        ])
+        .unwrap();
+        let evaluator = replay_db(db.clone(), &replay_opt).unwrap();
+
+        // The user transactions of the blocks ended by a block metadata transaction.
+        let mut expected_block_sizes = vec![];
+        let mut current_block_size = None;
+        for txn in db.get_transaction_iterator(0, latest_version + 1).unwrap() {
+            match txn.unwrap() {
+                Transaction::BlockMetadata(_) => {
+                    if let Some(size) = current_block_size.filter(|size| *size > 0) {
+                        expected_block_sizes.push(size);
+                    }
+                    current_block_size = Some(0);
+                },
+                Transaction::UserTransaction(_) => {
+                    if let Some(size) = current_block_size.as_mut() {
+                        *size += 1;
+                    }
+                },
+                _ => {},
+            }
+        }
+        assert!(!expected_block_sizes.is_empty());
+
+        assert_eq!(evaluator.settings().len(), 2);
+        for setting in evaluator.settings() {
+            let block_sizes: Vec<_> = setting.stats().iter().map(|stats| stats.num_txns).collect();
+            assert_eq!(block_sizes, expected_block_sizes);
+        }
+        // A single shard never has cross-shard dependencies.
+        assert!(evaluator.settings()[0]
+            .stats()
+            .iter()
+            .all(|stats| stats.num_cross_shard_edges == 0));
+    }
+}
@@ -78,6 +78,32 @@ impl AsyncProofFetcher {
         Ok(version_and_value_opt)
     }
 
+    pub fn fetch_state_values_with_version_and_schedule_proof_reads(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+        root_hash: Option<HashValue>,
+    ) -> Result<Vec<Option<(Version, StateValue)>>> {
+        let _timer = TIMER
+            .with_label_values(&["async_proof_fetcher_fetch_batch"])
+            .start_timer();
+        let version_and_value_opts = self
+            .reader
+            .get_state_values_with_version_by_version(state_keys, version)?;
+        for (state_key, version_and_value_opt) in state_keys.iter().zip(&version_and_value_opts) {
+            self.schedule_proof_read(
+                state_key.clone(),
+                version,
+                root_hash,
+                version_and_value_opt.as_ref().map(|v| {
+                    let state_value = &v.1;
+                    state_value.hash()
+                }),
+            );
+        }
+        Ok(version_and_value_opts)
+    }
+
     pub fn get_proof_cache(&self) -> HashMap<HashValue, SparseMerkleProofExt> {
         self.wait()
     }
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    async_proof_fetcher::AsyncProofFetcher, metrics::TIMER, state_view::DbStateView, DbReader,
+    async_proof_fetcher::AsyncProofFetcher,
+    metrics::{STATE_CACHE_LOOKUPS, STATE_CACHE_PREFETCHED_KEYS, TIMER},
+    state_view::DbStateView,
+    DbReader,
 };
 use anyhow::Result;
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -150,6 +153,41 @@ impl CachedStateView {
         Ok(())
     }
 
+    /// Fetches the values of the keys that are not cached yet, e.g. to warm the cache with the
+    /// state a block is likely to read before executing it. The keys are grouped by shard and each
+    /// shard is read as one batch, with the shards read in parallel. Unlike
+    /// prime_cache_by_write_set, read errors are ignored, as the reads are only hints and will
+    /// be retried (and fail) in execution if needed.
+    pub fn prefetch<'a, T: IntoIterator<Item = &'a StateKey> + Send>(&self, keys: T) {
+        let _timer = TIMER.with_label_values(&["prefetch"]).start_timer();
+        let mut num_cached = 0;
+        let mut num_fetched = 0;
+        let mut keys_by_shard: [Vec<StateKey>; 16] = arr![Vec::new(); 16];
+        for key in keys.into_iter().collect::<HashSet<_>>() {
+            let shard_id = key.get_shard_id() as usize;
+            if self.sharded_state_cache[shard_id].contains_key(key) {
+                num_cached += 1;
+            } else {
+                num_fetched += 1;
+                keys_by_shard[shard_id].push(key.clone());
+            }
+        }
+        STATE_CACHE_PREFETCHED_KEYS
+            .with_label_values(&["cached"])
+            .inc_by(num_cached);
+        STATE_CACHE_PREFETCHED_KEYS
+            .with_label_values(&["fetched"])
+            .inc_by(num_fetched);
+
+        IO_POOL.scope(|s| {
+            for keys in keys_by_shard.iter().filter(|keys| !keys.is_empty()) {
+                s.spawn(move |_| {
+                    let _ = self.fetch_and_cache_batch(keys);
+                });
+            }
+        });
+    }
+
     pub fn into_state_cache(self) -> StateCache {
         StateCache {
             frozen_base: self.speculative_state,
@@ -158,6 +196,60 @@ impl CachedStateView {
         }
     }
 
+    fn fetch_and_cache(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        let version_and_state_value_option =
+            self.get_version_and_state_value_internal(state_key)?;
+        // Update the cache if still empty
+        let new_version_and_value = self.sharded_state_cache[state_key.get_shard_id() as usize]
+            .entry(state_key.clone())
+            .or_insert(version_and_state_value_option);
+        let value_opt = &new_version_and_value.1;
+        Ok(value_opt.clone())
+    }
+
+    /// Same as `fetch_and_cache` for a batch of keys, reading the keys that are not in the
+    /// speculative state from the DB in one batch.
+    fn fetch_and_cache_batch(&self, state_keys: &[StateKey]) -> Result<()> {
+        let mut results = Vec::with_capacity(state_keys.len());
+        let mut db_keys = Vec::new();
+        for state_key in state_keys {
+            match self.speculative_state.get(state_key.hash()) {
+                StateStoreStatus::ExistsInScratchPad(value) => {
+                    results.push((state_key, (None, Some(value))))
+                },
+                StateStoreStatus::DoesNotExist => results.push((state_key, (None, None))),
+                StateStoreStatus::ExistsInDB | StateStoreStatus::Unknown => {
+                    db_keys.push(state_key.clone())
+                },
+            }
+        }
+        if let Some((version, root_hash)) = self.snapshot {
+            let version_and_value_opts = self
+                .proof_fetcher
+                .fetch_state_values_with_version_and_schedule_proof_reads(
+                    &db_keys,
+                    version,
+                    Some(root_hash),
+                )?;
+            results.extend(db_keys.iter().zip(version_and_value_opts).map(
+                |(state_key, version_and_value_opt)| match version_and_value_opt {
+                    Some((version, value)) => (state_key, (Some(version), Some(value))),
+                    None => (state_key, (None, None)),
+                },
+            ));
+        } else {
+            results.extend(db_keys.iter().map(|state_key| (state_key, (None, None))));
+        }
+
+        for (state_key, version_and_state_value_option) in results {
+            // Update the cache if still empty
+            self.sharded_state_cache[state_key.get_shard_id() as usize]
+                .entry(state_key.clone())
+                .or_insert(version_and_state_value_option);
+        }
+        Ok(())
+    }
+
     fn get_version_and_state_value_internal(
         &self,
         state_key: &StateKey,
@@ -208,18 +300,13 @@ impl TStateView for CachedStateView {
         if let Some(version_and_value_opt) =
             self.sharded_state_cache[state_key.get_shard_id() as usize].get(state_key)
         {
+            STATE_CACHE_LOOKUPS.with_label_values(&["hit"]).inc();
             // This can return None, which means the value has been deleted from the DB.
             let value_opt = &version_and_value_opt.1;
             return Ok(value_opt.clone());
         }
-        let version_and_state_value_option =
-            self.get_version_and_state_value_internal(state_key)?;
-        // Update the cache if still empty
-        let new_version_and_value = self.sharded_state_cache[state_key.get_shard_id() as usize]
-            .entry(state_key.clone())
-            .or_insert(version_and_state_value_option);
-        let value_opt = &new_version_and_value.1;
-        Ok(value_opt.clone())
+        STATE_CACHE_LOOKUPS.with_label_values(&["miss"]).inc();
+        self.fetch_and_cache(state_key)
     }
 
     fn is_genesis(&self) -> bool {
@@ -399,6 +399,20 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Same as `get_state_value_with_version_by_version` for a batch of keys, with the results in
+    /// the order of the keys. Implementations can read the batch more efficiently than one key at
+    /// a time.
+    fn get_state_values_with_version_by_version(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<Option<(Version, StateValue)>>> {
+        state_keys
+            .iter()
+            .map(|state_key| self.get_state_value_with_version_by_version(state_key, version))
+            .collect()
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
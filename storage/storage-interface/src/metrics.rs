@@ -3,7 +3,10 @@
 
 #![forbid(unsafe_code)]
 
-use aptos_metrics_core::{exponential_buckets, register_histogram_vec, HistogramVec};
+use aptos_metrics_core::{
+    exponential_buckets, register_histogram_vec, register_int_counter_vec, HistogramVec,
+    IntCounterVec,
+};
 use once_cell::sync::Lazy;
 
 pub static TIMER: Lazy<HistogramVec> = Lazy::new(|| {
@@ -15,3 +18,22 @@ pub static TIMER: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static STATE_CACHE_LOOKUPS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_storage_interface_state_cache_lookups",
+        "Number of state value lookups in CachedStateView, by whether they hit the cache.",
+        &["result"]
+    )
+    .unwrap()
+});
+
+pub static STATE_CACHE_PREFETCHED_KEYS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_storage_interface_state_cache_prefetched_keys",
+        "Number of keys prefetched into CachedStateView, by whether they were fetched or already \
+         cached.",
+        &["result"]
+    )
+    .unwrap()
+});